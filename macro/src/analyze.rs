@@ -1,13 +1,20 @@
 use std::collections::HashMap;
 
+use proc_macro2::Span;
 use proc_macro_error::abort;
-use syn::parse::Parser;
+use quote::format_ident;
+use syn::parse::{ParseStream, Parser};
+use syn::punctuated::Punctuated;
 use syn::{
-    parse_quote, Attribute, AttributeArgs, ExprCall, Field, FnArg, Generics, Ident, ImplItem,
-    ImplItemMethod, ItemImpl, Lit, Meta, MetaList, NestedMeta, Pat, PatType, Path, Receiver, Type,
-    Visibility,
+    parse_quote, Attribute, AttributeArgs, Block, Expr, ExprCall, Field, FnArg, GenericArgument,
+    GenericParam, Generics, Ident, ImplItem, ImplItemMethod, ItemImpl, Lifetime, Lit, LitFloat,
+    LitStr, Meta, MetaList, NestedMeta, Pat, PatType, Path, PathArguments, Receiver, ReturnType,
+    Token, Type, Visibility,
 };
 
+use crate::visitors::{EventVisitor, GenericParamVisitor, TransitionVisitor};
+use crate::SUPERSTATE_LIFETIME;
+
 /// Model of the state machine.
 #[cfg_attr(test, derive(Debug, Eq, PartialEq))]
 pub struct Model {
@@ -21,6 +28,8 @@ pub struct Model {
     pub superstates: HashMap<Ident, Superstate>,
     /// The actions of the state machine.
     pub actions: HashMap<Ident, Action>,
+    /// Pairs of states with structurally identical handlers, if `merge_hints` is set.
+    pub mergeable_states: Vec<(Ident, Ident)>,
 }
 
 /// General information regarding the state machine.
@@ -28,30 +37,134 @@ pub struct Model {
 pub struct StateMachine {
     /// The inital state of the state machine.
     pub initial_state: ExprCall,
+    /// The initial states that are declared for the regions of the parallel states, by the
+    /// name of the field that holds the region (e.g. `initial(left = "left::State::idle()")`).
+    pub region_initial_states: Vec<(Ident, ExprCall)>,
     /// The type on which the state machine is implemented.
     pub shared_storage_type: Type,
     /// The path of the shared storage.
     pub shared_storage_path: Path,
+    /// Optional field of the shared storage type that holds the storage the handlers are
+    /// implemented on, when it is embedded in a larger struct (e.g. `inner`).
+    pub shared_storage_field: Option<Ident>,
     /// The generics associated with the shared storage type.
     pub shared_storage_generics: Generics,
+    /// Optional lifetime of the shared storage that is always part of the generics of the
+    /// state enum, even when it is only used by local storage (e.g. `'sm`).
+    pub lifetime: Option<Lifetime>,
+    /// The lifetime of the superstate enum, which is `'sub` unless the shared storage
+    /// already declares a lifetime with that name (e.g. `'sub0`).
+    pub superstate_lifetime: Lifetime,
     /// The name for the state type.
     pub state_ident: Ident,
+    /// The name for the state kind type, which is the name of the state type followed by
+    /// `Kind` unless it is set explicitly.
+    pub state_kind_ident: Ident,
     /// Derives that will be applied on the state type.
     pub state_derives: Vec<Path>,
+    /// Representation of the state type (e.g. `C` or `C, u8`).
+    pub state_repr: Vec<Ident>,
     /// The name of the superstate type.
     pub superstate_ident: Ident,
+    /// The name of the superstate kind type, which is the name of the superstate type
+    /// followed by `Kind` unless it is set explicitly.
+    pub superstate_kind_ident: Ident,
     /// Derives that will be applied to the superstate type.
     pub superstate_derives: Vec<Path>,
     /// The identifier that is used for the event argument.
     pub event_ident: Ident,
+    /// Whether events are passed to the handlers by reference.
+    pub event_ref: bool,
     /// The identifier that is used for the context argument.
     pub context_ident: Ident,
+    /// Optional name for a public alias of the response type (e.g. `Response`).
+    pub response_ident: Option<Ident>,
+    /// Optional name for a trait that the state machines implement to handle events, so
+    /// consumers can depend on it instead of on the concrete type (e.g. `Driver`).
+    pub facade_ident: Option<Ident>,
     /// The visibility of the derived types.
     pub visibility: Visibility,
     /// Optional `on_transition` callback.
     pub on_transition: Option<Path>,
+    /// Hooks that are called on a transition between a specific pair of states.
+    pub transition_hooks: Vec<TransitionHook>,
     /// Optional `on_dispatch` callback.
     pub on_dispatch: Option<Path>,
+    /// Optional event that is handled right after initialization.
+    pub startup_event: Option<Expr>,
+    /// Optional allocator that the state stack is allocated with.
+    pub stack_allocator: Option<Expr>,
+    /// Optional field of the shared storage that tracks the previous state kind.
+    pub previous_state_kind: Option<Ident>,
+    /// Optional field of the shared storage that holds the origin of the current event.
+    pub origin: Option<Ident>,
+    /// Optional field of the shared storage that holds the sequence number of the current
+    /// event.
+    pub sequence: Option<Ident>,
+    /// Optional field of the shared storage that observes the lifecycle events.
+    pub observer: Option<Ident>,
+    /// Optional field of the shared storage that counts the entries and exits of states.
+    pub entry_exit_counts: Option<Ident>,
+    /// Optional field of the shared storage that holds the timeout of the current state.
+    pub timeout: Option<Ident>,
+    /// Optional field of the shared storage that records the actions that fired.
+    pub fired_actions: Option<Ident>,
+    /// Optional field of the shared storage that holds the error of a fallible handler.
+    pub fallible: Option<Ident>,
+    /// Whether a `validate` method should be generated.
+    pub validate: bool,
+    /// Whether a JSON description of the state machine should be generated.
+    pub json: bool,
+    /// Whether a `describe` method should be generated.
+    pub describe: bool,
+    /// Whether a `graphviz` function describing the state hierarchy should be generated.
+    pub export_graph: bool,
+    /// Whether a `diff` method should be generated on the state enum.
+    pub diff: bool,
+    /// Whether to assert at compile time that the state machine is `Send` and `Sync`.
+    pub send_sync: bool,
+    /// Whether the states should convert into their `u32` index.
+    pub state_index: bool,
+    /// Whether `apply_event_log` methods should be generated on the shared storage type.
+    pub apply_event_log: bool,
+    /// Whether `replay` methods should be generated on the shared storage type.
+    pub replay: bool,
+    /// Whether a `transition_to` method should be generated for the handlers.
+    pub transition_to: bool,
+    /// Optional mode (`blocking` or `awaitable`) that overrides the detected mode.
+    pub mode: Option<LitStr>,
+    /// Whether all optional introspection should be left out of the generated code.
+    pub minimal: bool,
+    /// Whether the state machine should give mutable access to the shared storage.
+    pub deref_storage: bool,
+    /// Whether dropping the state machine without shutting it down should panic in debug
+    /// builds.
+    pub drop_guard: bool,
+    /// Whether every handler call should be wrapped in a `tracing` span.
+    pub tracing: bool,
+    /// Whether all generics of the shared storage should be part of the state generics,
+    /// including the ones that are not used by the states.
+    pub generics_passthrough: bool,
+    /// Whether to hint at states with identical handlers that could be merged.
+    pub merge_hints: bool,
+    /// Optional configuration predicate that all generated items are gated on.
+    pub cfg: Option<Meta>,
+    /// Set when the awaitable state machine should be usable behind a `dyn` trait that
+    /// returns boxed futures.
+    pub box_futures: Option<Path>,
+    /// Set when the path of the state should be serializable as one byte per kind.
+    pub serialize_path: Option<Path>,
+}
+
+/// Hook declared with `#[transition(from = "..", to = "..", call = "..")]` on the impl block.
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub struct TransitionHook {
+    /// Name of the state that is transitioned from.
+    pub from: Ident,
+    /// Name of the state that is transitioned to.
+    pub to: Ident,
+    /// Name of the method that is called on the shared storage.
+    pub call: Ident,
 }
 
 /// Information regarding a state.
@@ -61,10 +174,10 @@ pub struct State {
     pub handler_name: Ident,
     /// Optional superstate.
     pub superstate: Option<Ident>,
-    /// Optional entry action.
-    pub entry_action: Option<Ident>,
-    /// Optional exit action.
-    pub exit_action: Option<Ident>,
+    /// Entry actions, in the order they are declared in.
+    pub entry_actions: Vec<Ident>,
+    /// Exit actions, in the order they are declared in.
+    pub exit_actions: Vec<Ident>,
     /// Local storage,
     pub local_storage: Vec<Field>,
     /// Inputs required by the state handler.
@@ -73,10 +186,44 @@ pub struct State {
     pub shared_storage_input: Option<Receiver>,
     /// Inputs provided by the state-local storage.
     pub state_inputs: Vec<PatType>,
+    /// Inputs borrowed from the fields of the shared storage.
+    pub shared_storage_fields: Vec<PatType>,
     /// Event that is submitted to the state machine.
     pub event_arg: Option<PatType>,
     /// Context that is submitted to the state machine.
     pub context_arg: Option<PatType>,
+    /// The states that are targeted by transitions in the handler body.
+    pub transitions: Vec<Ident>,
+    /// Whether the handler body contains transitions to states created at runtime.
+    pub dynamic_transitions: bool,
+    /// Weights of the transitions out of the state, declared with
+    /// `#[transition(to = "..", weight = ..)]` for use by external simulators.
+    pub transition_weights: Vec<(Ident, LitFloat)>,
+    /// Whether the handler returns a `ControlFlow` instead of a `Response`.
+    pub control_flow: bool,
+    /// The type of the error when the handler returns a `Result`.
+    pub error_type: Option<Type>,
+    /// The names of the event variants that are matched on in the handler body.
+    pub handled_events: Vec<String>,
+    /// Whether the fields of the state-local storage are regions that run in parallel.
+    pub parallel: bool,
+    /// Whether the state is meant to not take the event.
+    pub allow_missing_event: bool,
+    /// Whether inputs of the state-local storage that are not references are copied into
+    /// the handler.
+    pub local_storage_by_value: bool,
+    /// Whether the state machine can be dropped in this state without shutting it down.
+    pub terminal: bool,
+    /// Whether the state is final, meaning its handler is not allowed to transition.
+    pub is_final: bool,
+    /// The priority of the state when it is the current state of a parallel region, if it
+    /// is declared.
+    pub priority: Option<i32>,
+    /// Alternative names the kind of the state can be parsed from.
+    pub aliases: Vec<LitStr>,
+    /// Attributes that are added to the variant of the state enum, e.g. the helper
+    /// attributes of a derive (`serde(rename = "idle")`).
+    pub variant_attrs: Vec<Meta>,
     /// Whether the function is async or not.
     pub is_async: bool,
 }
@@ -88,12 +235,21 @@ pub struct Superstate {
     pub handler_name: Ident,
     /// Optional superstate.
     pub superstate: Option<Ident>,
-    /// Optional entry action.
-    pub entry_action: Option<Ident>,
-    /// Optional exit action.
-    pub exit_action: Option<Ident>,
+    /// Optional initial state that is targeted when transitioning into the superstate.
+    pub initial_state: Option<ExprCall>,
+    /// Entry actions, in the order they are declared in.
+    pub entry_actions: Vec<Ident>,
+    /// Exit actions, in the order they are declared in.
+    pub exit_actions: Vec<Ident>,
+    /// Optional action that filters events before they reach the children.
+    pub pre_dispatch: Option<Ident>,
     /// Local storage,
     pub local_storage: Vec<Field>,
+    /// Attributes that are added to the variant of the superstate enum.
+    pub variant_attrs: Vec<Meta>,
+    /// Storage owned by the superstate together with its initial value, which is kept
+    /// while transitioning between the states nested within it.
+    pub owned_storage: Vec<(Field, Expr)>,
     /// Inputs required by the superstate handler.
     pub inputs: Vec<FnArg>,
     /// Optional receiver input for the state handler (e.g. `&mut self`).
@@ -104,6 +260,14 @@ pub struct Superstate {
     pub event_arg: Option<PatType>,
     /// Context that is submitted to the state machine.
     pub context_arg: Option<PatType>,
+    /// The states that are targeted by transitions in the handler body.
+    pub transitions: Vec<Ident>,
+    /// Whether the handler body contains transitions to states created at runtime.
+    pub dynamic_transitions: bool,
+    /// Whether the handler returns a `ControlFlow` instead of a `Response`.
+    pub control_flow: bool,
+    /// The type of the error when the handler returns a `Result`.
+    pub error_type: Option<Type>,
     /// Whether the function is async or not.
     pub is_async: bool,
 }
@@ -115,14 +279,37 @@ pub struct Action {
     pub handler_name: Ident,
     /// Inputs required by the action handler.
     pub inputs: Vec<FnArg>,
+    /// Whether the action returns a `Result` that reports its failure.
+    pub is_fallible: bool,
     /// Whether the function is async or not.
     pub is_async: bool,
 }
 
 /// Analyze the impl block and create a model.
-pub fn analyze(attribute_args: AttributeArgs, item_impl: ItemImpl) -> Model {
+pub fn analyze(attribute_args: AttributeArgs, mut item_impl: ItemImpl) -> Model {
     let state_machine = analyze_state_machine(&attribute_args, &item_impl);
 
+    // The transition hooks are not real attributes, so they must be removed from the impl block.
+    item_impl
+        .attrs
+        .retain(|attr| !attr.path.is_ident("transition"));
+
+    // Add the actions that are declared inline to the impl block.
+    for action in analyze_inline_actions(&attribute_args) {
+        let collision = item_impl.items.iter().any(|item| match item {
+            ImplItem::Method(method) => method.sig.ident == action.sig.ident,
+            _ => false,
+        });
+        if collision {
+            abort!(
+                action.sig.ident,
+                "action `{}` conflicts with a method of the same name",
+                action.sig.ident
+            );
+        }
+        item_impl.items.push(ImplItem::Method(action));
+    }
+
     let mut states = HashMap::new();
     let mut superstates = HashMap::new();
     let mut actions = HashMap::new();
@@ -157,34 +344,163 @@ pub fn analyze(attribute_args: AttributeArgs, item_impl: ItemImpl) -> Model {
         }
     }
 
+    // The transition weights are not real attributes either, so they are removed from the
+    // state handlers.
+    for item in &mut item_impl.items {
+        let ImplItem::Method(method) = item else {
+            continue;
+        };
+        if let Some(attr) = method
+            .attrs
+            .iter()
+            .find(|attr| attr.path.is_ident("transition"))
+        {
+            if !states.contains_key(&method.sig.ident) {
+                abort!(
+                    attr,
+                    "transition weights can only be declared on states";
+                    help = "declare the weight on the state the transition starts from"
+                );
+            }
+        }
+        method
+            .attrs
+            .retain(|attr| !attr.path.is_ident("transition"));
+    }
+
+    // The initial states of regions can only be declared for the fields of parallel states.
+    for (region, _) in &state_machine.region_initial_states {
+        if !states.values().any(|state| state.parallel) {
+            abort!(
+                region,
+                "only state machines with parallel regions can have more than one initial state";
+                help = "mark the state that holds the regions with `#[state(parallel)]`"
+            );
+        }
+        if !states.values().any(|state| has_region(state, region)) {
+            abort!(
+                region,
+                "`{}` is not a region of a parallel state", region;
+                help = "the regions are the fields of the state-local storage of a `#[state(parallel)]` state"
+            );
+        }
+    }
+
+    let mergeable_states = match state_machine.merge_hints {
+        true => find_mergeable_states(&item_impl),
+        false => Vec::new(),
+    };
+
     Model {
         item_impl,
         state_machine,
         states,
         superstates,
         actions,
+        mergeable_states,
+    }
+}
+
+/// Check whether the state is a parallel state with a region of the given name. The regions
+/// are the fields of its state-local storage.
+fn has_region(state: &State, region: &Ident) -> bool {
+    let is_input =
+        |pat_type: &PatType| matches!(&*pat_type.pat, Pat::Ident(pat) if pat.ident == *region);
+    state.parallel
+        && (state.state_inputs.iter().any(is_input)
+            || state
+                .local_storage
+                .iter()
+                .any(|field| field.ident.as_ref() == Some(region)))
+}
+
+/// Find the pairs of states whose handlers have the same inputs and a structurally
+/// identical body, which could be merged or share a handler. This is a best-effort
+/// comparison of the syntax trees, so bodies that only look the same (e.g. because they
+/// call a macro) can also be reported.
+fn find_mergeable_states(item_impl: &ItemImpl) -> Vec<(Ident, Ident)> {
+    let state_methods: Vec<&ImplItemMethod> = item_impl
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(method)
+                if method.attrs.iter().any(|attr| attr.path.is_ident("state")) =>
+            {
+                Some(method)
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut mergeable_states = Vec::new();
+    for (index, first) in state_methods.iter().enumerate() {
+        for second in &state_methods[index + 1..] {
+            if first.sig.inputs == second.sig.inputs && first.block == second.block {
+                mergeable_states.push((first.sig.ident.clone(), second.sig.ident.clone()));
+            }
+        }
     }
+    mergeable_states
 }
 
 /// Retrieve the top level settings of the state machine.
 pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImpl) -> StateMachine {
-    let shared_storage_type = item_impl.self_ty.as_ref().clone();
+    let mut shared_storage_type = item_impl.self_ty.as_ref().clone();
     let shared_storage_generics = item_impl.generics.clone();
     let shared_storage_path = get_shared_storage_path(&shared_storage_type);
+    let mut shared_storage_field = None;
+    let mut lifetime: Option<Lifetime> = None;
 
     let mut initial_state: Option<ExprCall> = None;
+    let mut region_initial_states: Vec<(Ident, ExprCall)> = Vec::new();
 
     let mut state_ident = parse_quote!(State);
+    let mut state_kind_ident = None;
     let mut state_derives = Vec::new();
+    let mut state_repr = Vec::new();
     let mut superstate_ident = parse_quote!(Superstate);
+    let mut superstate_kind_ident = None;
     let mut superstate_derives = Vec::new();
 
     let mut on_transition = None;
     let mut on_dispatch = None;
+    let mut startup_event = None;
+    let mut stack_allocator = None;
+    let mut previous_state_kind = None;
+    let mut origin = None;
+    let mut sequence = None;
+    let mut observer = None;
+    let mut entry_exit_counts = None;
+    let mut timeout = None;
+    let mut fired_actions = None;
+    let mut fallible = None;
+    let mut validate = false;
+    let mut json = false;
+    let mut describe = false;
+    let mut export_graph = false;
+    let mut diff = false;
+    let mut send_sync = false;
+    let mut state_index = false;
+    let mut apply_event_log = false;
+    let mut replay = false;
+    let mut transition_to = false;
+    let mut mode = None;
+    let mut minimal: Option<&Path> = None;
+    let mut deref_storage = false;
+    let mut drop_guard = false;
+    let mut tracing = false;
+    let mut generics_passthrough = false;
+    let mut merge_hints = false;
+    let mut cfg = None;
+    let mut box_futures = None;
+    let mut serialize_path = None;
 
     let mut visibility = parse_quote!(pub);
     let mut event_ident = parse_quote!(event);
+    let mut event_ref = true;
     let mut context_ident = parse_quote!(context);
+    let mut response_ident = None;
+    let mut facade_ident = None;
 
     let mut state_meta: MetaList = parse_quote!(state());
     let mut superstate_meta: MetaList = parse_quote!(superstate());
@@ -195,11 +511,46 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
             NestedMeta::Meta(Meta::NameValue(name_value))
                 if name_value.path.is_ident("initial") =>
             {
+                if initial_state.is_some() {
+                    abort!(
+                        name_value,
+                        "a state machine can only have one initial state";
+                        help = "declare the initial states of the regions of a `#[state(parallel)]` state with `initial(region = \"region::State::initial_state()\")`"
+                    );
+                }
                 initial_state = match &name_value.lit {
                     Lit::Str(input_pat) => input_pat.parse().ok(),
                     _ => abort!(name_value, "must be a string literal"),
                 }
             }
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("initial") => {
+                for nested in &list.nested {
+                    let NestedMeta::Meta(Meta::NameValue(name_value)) = nested else {
+                        abort!(
+                            nested,
+                            "expected the name of a region and its initial state";
+                            help = "for example `initial(left = \"left::State::idle()\")`"
+                        );
+                    };
+                    let Some(region) = name_value.path.get_ident() else {
+                        abort!(name_value.path, "expected the name of a region")
+                    };
+                    if region_initial_states
+                        .iter()
+                        .any(|(other, _)| other == region)
+                    {
+                        abort!(region, "region `{}` already has an initial state", region);
+                    }
+                    let initial = match &name_value.lit {
+                        Lit::Str(input_pat) => match input_pat.parse() {
+                            Ok(initial) => initial,
+                            Err(_) => abort!(input_pat, "expected a call to a state constructor"),
+                        },
+                        _ => abort!(name_value, "must be a string literal"),
+                    };
+                    region_initial_states.push((region.clone(), initial));
+                }
+            }
             NestedMeta::Meta(Meta::NameValue(name_value))
                 if name_value.path.is_ident("event_identifier") =>
             {
@@ -208,6 +559,14 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                     _ => abort!(name_value, "must be a string literal"),
                 }
             }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("event_ref") =>
+            {
+                event_ref = match &name_value.lit {
+                    Lit::Bool(event_ref) => event_ref.value,
+                    _ => abort!(name_value, "must be a boolean literal"),
+                }
+            }
             NestedMeta::Meta(Meta::NameValue(name_value))
                 if name_value.path.is_ident("context_identifier") =>
             {
@@ -216,6 +575,23 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                     _ => abort!(name_value, "must be a string literal"),
                 }
             }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("response_identifier") =>
+            {
+                response_ident = match &name_value.lit {
+                    Lit::Str(response_ident) => Some(response_ident.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("facade") => {
+                facade_ident = match &name_value.lit {
+                    Lit::Str(facade_ident) => match facade_ident.parse() {
+                        Ok(facade_ident) => Some(facade_ident),
+                        Err(_) => abort!(facade_ident, "must be an identifier"),
+                    },
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
             NestedMeta::Meta(Meta::NameValue(name_value))
                 if name_value.path.is_ident("on_transition") =>
             {
@@ -232,6 +608,137 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                     _ => abort!(name_value, "must be a string literal"),
                 }
             }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("startup_event") =>
+            {
+                startup_event = match &name_value.lit {
+                    Lit::Str(input_pat) => match input_pat.parse() {
+                        Ok(startup_event) => Some(startup_event),
+                        Err(_) => abort!(
+                            input_pat,
+                            "expected an expression that creates an event";
+                            help = "for example `#[state_machine(startup_event = \"Event::Start\")]`"
+                        ),
+                    },
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("stack_allocator") =>
+            {
+                stack_allocator = match &name_value.lit {
+                    Lit::Str(input_pat) => match input_pat.parse() {
+                        Ok(stack_allocator) => Some(stack_allocator),
+                        Err(_) => abort!(
+                            input_pat,
+                            "expected an expression that refers to an allocator";
+                            help = "for example `#[state_machine(stack_allocator = \"ALLOCATOR\")]`"
+                        ),
+                    },
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("previous_state_kind") =>
+            {
+                previous_state_kind = match &name_value.lit {
+                    Lit::Str(field) => Some(field.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("origin") => {
+                origin = match &name_value.lit {
+                    Lit::Str(field) => Some(field.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("sequence") =>
+            {
+                sequence = match &name_value.lit {
+                    Lit::Str(field) => Some(field.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("observer") =>
+            {
+                observer = match &name_value.lit {
+                    Lit::Str(field) => Some(field.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("entry_exit_counts") =>
+            {
+                entry_exit_counts = match &name_value.lit {
+                    Lit::Str(field) => Some(field.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("timeout") =>
+            {
+                timeout = match &name_value.lit {
+                    Lit::Str(field) => Some(field.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("fired_actions") =>
+            {
+                fired_actions = match &name_value.lit {
+                    Lit::Str(field) => Some(field.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("fallible") =>
+            {
+                fallible = match &name_value.lit {
+                    Lit::Str(field) => Some(field.parse().unwrap()),
+                    _ => abort!(
+                        name_value,
+                        "must be a string literal";
+                        help = "pass the name of the field that holds the error, e.g. `fallible = \"error\"`"
+                    ),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("shared_storage") =>
+            {
+                let value = match &name_value.lit {
+                    Lit::Str(value) => value,
+                    _ => abort!(name_value, "must be a string literal"),
+                };
+                match value.value().split_once('.') {
+                    Some((ty, field)) => {
+                        shared_storage_type = syn::parse_str(ty.trim()).unwrap_or_else(|_| {
+                            abort!(value, "`{}` is not a valid type", ty.trim())
+                        });
+                        shared_storage_field =
+                            Some(syn::parse_str(field.trim()).unwrap_or_else(|_| {
+                                abort!(value, "`{}` is not a valid field name", field.trim())
+                            }));
+                    }
+                    None => abort!(
+                        value,
+                        "shared storage must be a type followed by the field that holds the storage";
+                        help = "consider changing it to `\"Outer.{}\"`", value.value()
+                    ),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("lifetime") =>
+            {
+                lifetime = match &name_value.lit {
+                    Lit::Str(value) => match value.parse() {
+                        Ok(lifetime) => Some(lifetime),
+                        Err(_) => abort!(value, "must be a lifetime (e.g. `\"'sm\"`)"),
+                    },
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
             NestedMeta::Meta(Meta::NameValue(name_value))
                 if name_value.path.is_ident("visibility") =>
             {
@@ -240,17 +747,153 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                     _ => abort!(name_value, "must be a string literal"),
                 }
             }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("mode") => {
+                mode = match &name_value.lit {
+                    Lit::Str(value)
+                        if value.value() == "blocking" || value.value() == "awaitable" =>
+                    {
+                        Some(value.clone())
+                    }
+                    Lit::Str(value) => abort!(
+                        value,
+                        "unknown mode";
+                        help = "expected `blocking` or `awaitable`"
+                    ),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("validate") => {
+                validate = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("json") => {
+                json = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("describe") => {
+                describe = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("export_graph") => {
+                export_graph = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("diff") => {
+                diff = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("send_sync") => {
+                send_sync = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("state_index") => {
+                state_index = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("apply_event_log") => {
+                apply_event_log = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("replay") => {
+                replay = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("transition_to") => {
+                transition_to = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("minimal") => {
+                minimal = Some(path);
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("deref_storage") => {
+                deref_storage = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("drop_guard") => {
+                drop_guard = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("tracing") => {
+                tracing = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("generics_passthrough") => {
+                generics_passthrough = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("merge_hints") => {
+                merge_hints = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("box_futures") => {
+                box_futures = Some(path.clone());
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("serialize_path") => {
+                serialize_path = Some(path.clone());
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("cfg") => {
+                cfg = match &name_value.lit {
+                    Lit::Str(predicate) => match predicate.parse() {
+                        Ok(predicate) => Some(predicate),
+                        Err(_) => abort!(predicate, "must be a configuration predicate"),
+                    },
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
             NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("state") => {
                 state_meta = list.clone();
             }
             NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("superstate") => {
                 superstate_meta = list.clone();
             }
+            // Inline actions are handled by `analyze_inline_actions`.
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("actions") => {}
 
             _ => abort!(arg, "argument not recognized"),
         }
     }
 
+    // Check that no introspection or helpers are requested when they should be left out,
+    // including the options that rely on the kinds of the states.
+    if let Some(minimal) = minimal {
+        let options = [
+            ("json", json),
+            ("validate", validate),
+            ("diff", diff),
+            ("serialize_path", serialize_path.is_some()),
+            ("state_index", state_index),
+            ("transition_to", transition_to),
+            ("replay", replay),
+            ("apply_event_log", apply_event_log),
+            ("previous_state_kind", previous_state_kind.is_some()),
+            ("entry_exit_counts", entry_exit_counts.is_some()),
+            ("timeout", timeout.is_some()),
+        ];
+        if let Some((option, _)) = options.iter().find(|(_, requested)| *requested) {
+            abort!(
+                minimal,
+                "`minimal` can not be combined with `{}`", option;
+                help = "remove `minimal` or `{}`", option
+            );
+        }
+    }
+    let minimal = minimal.is_some();
+
+    // Check that the machine-wide lifetime is declared on the impl block.
+    if let Some(lifetime) = &lifetime {
+        if !shared_storage_generics
+            .lifetimes()
+            .any(|lifetime_def| lifetime_def.lifetime == *lifetime)
+        {
+            abort!(
+                lifetime,
+                "lifetime `{}` is not declared on the impl block", lifetime;
+                help = "declare it on the impl block (e.g. `impl<{}> Machine<{}>`)", lifetime, lifetime
+            );
+        }
+    }
+
+    // Rename the lifetime of the superstate enum when the shared storage already declares a
+    // lifetime with the same name, so the generated generics do not clash.
+    let is_declared = |name: &str| {
+        shared_storage_generics
+            .lifetimes()
+            .any(|lifetime_def| lifetime_def.lifetime.to_string() == name)
+    };
+    let superstate_lifetime = match is_declared(SUPERSTATE_LIFETIME) {
+        false => Lifetime::new(SUPERSTATE_LIFETIME, Span::call_site()),
+        true => (0..)
+            .map(|index| format!("{SUPERSTATE_LIFETIME}{index}"))
+            .find(|name| !is_declared(name))
+            .map(|name| Lifetime::new(&name, Span::call_site()))
+            .unwrap(),
+    };
+
     // Check if there is an initial state given.
     let Some(initial_state) = initial_state else {
         abort!(
@@ -278,6 +921,14 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                 }
             }
 
+            // Get the custom name for the state kind enum.
+            Meta::NameValue(name_value) if name_value.path.is_ident("kind") => {
+                state_kind_ident = match &name_value.lit {
+                    Lit::Str(str_lit) => Some(str_lit.parse().unwrap()),
+                    _ => abort!(name_value, "expected string literal"),
+                }
+            }
+
             // Get the derives for the state enum.
             Meta::List(meta_list) if meta_list.path.is_ident("derive") => {
                 for nested_meta in &meta_list.nested {
@@ -290,6 +941,18 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                 }
             }
 
+            // Get the representation of the state enum.
+            Meta::NameValue(name_value) if name_value.path.is_ident("repr") => {
+                state_repr = match &name_value.lit {
+                    Lit::Str(str_lit) => str_lit
+                        .parse_with(Punctuated::<Ident, Token![,]>::parse_terminated)
+                        .unwrap_or_else(|_| abort!(str_lit, "expected list of representations"))
+                        .into_iter()
+                        .collect(),
+                    _ => abort!(name_value, "expected string literal"),
+                }
+            }
+
             // Other attributes are not recognized.
             _ => abort!(meta, "unknown attribute"),
         }
@@ -313,6 +976,14 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                 }
             }
 
+            // Get the custom name for the superstate kind enum.
+            Meta::NameValue(name_value) if name_value.path.is_ident("kind") => {
+                superstate_kind_ident = match &name_value.lit {
+                    Lit::Str(str_lit) => Some(str_lit.parse().unwrap()),
+                    _ => abort!(name_value, "expected string literal"),
+                }
+            }
+
             // Get the derives of the superstate enum.
             Meta::List(meta_list) if meta_list.path.is_ident("derive") => {
                 for nested_meta in &meta_list.nested {
@@ -330,41 +1001,265 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
         }
     }
 
+    let state_kind_ident = state_kind_ident.unwrap_or_else(|| format_ident!("{}Kind", state_ident));
+    let superstate_kind_ident =
+        superstate_kind_ident.unwrap_or_else(|| format_ident!("{}Kind", superstate_ident));
+
+    let transition_hooks = analyze_transition_hooks(&item_impl.attrs);
+
     StateMachine {
         initial_state,
+        region_initial_states,
         shared_storage_type,
         shared_storage_path,
+        shared_storage_field,
         shared_storage_generics,
+        lifetime,
+        superstate_lifetime,
         state_ident,
+        state_kind_ident,
         state_derives,
+        state_repr,
         superstate_ident,
+        superstate_kind_ident,
         superstate_derives,
         on_dispatch,
         on_transition,
+        transition_hooks,
+        startup_event,
+        stack_allocator,
+        previous_state_kind,
+        origin,
+        sequence,
+        observer,
+        entry_exit_counts,
+        timeout,
+        fired_actions,
+        fallible,
+        validate,
+        json,
+        describe,
+        export_graph,
+        diff,
+        send_sync,
+        transition_to,
+        replay,
+        apply_event_log,
+        state_index,
+        mode,
+        minimal,
+        deref_storage,
+        drop_guard,
+        tracing,
+        generics_passthrough,
+        merge_hints,
+        cfg,
+        box_futures,
+        serialize_path,
         event_ident,
+        event_ref,
         context_ident,
+        response_ident,
+        facade_ident,
         visibility,
     }
 }
 
+/// Retrieve the hooks declared with `#[transition(from = "..", to = "..", call = "..")]`
+/// attributes on the impl block.
+pub fn analyze_transition_hooks(attrs: &[Attribute]) -> Vec<TransitionHook> {
+    let mut transition_hooks = Vec::new();
+
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("transition")) {
+        let nested = match attr.parse_meta() {
+            Ok(Meta::List(list)) => list.nested,
+            _ => abort!(
+                attr,
+                "expected a list of arguments";
+                help = "for example `#[transition(from = \"connecting\", to = \"connected\", call = \"on_connected\")]`"
+            ),
+        };
+
+        let mut from = None;
+        let mut to = None;
+        let mut call = None;
+        for nested_meta in &nested {
+            let (slot, name_value) = match nested_meta {
+                NestedMeta::Meta(Meta::NameValue(name_value))
+                    if name_value.path.is_ident("from") =>
+                {
+                    (&mut from, name_value)
+                }
+                NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("to") => {
+                    (&mut to, name_value)
+                }
+                NestedMeta::Meta(Meta::NameValue(name_value))
+                    if name_value.path.is_ident("call") =>
+                {
+                    (&mut call, name_value)
+                }
+                _ => abort!(nested_meta, "argument not recognized"),
+            };
+            *slot = match &name_value.lit {
+                Lit::Str(ident) => match ident.parse::<Ident>() {
+                    Ok(ident) => Some(ident),
+                    Err(_) => abort!(ident, "must be an identifier"),
+                },
+                _ => abort!(name_value, "must be a string literal"),
+            };
+        }
+
+        match (from, to, call) {
+            (Some(from), Some(to), Some(call)) => {
+                transition_hooks.push(TransitionHook { from, to, call })
+            }
+            _ => abort!(
+                attr,
+                "transition hook requires `from`, `to` and `call`";
+                help = "for example `#[transition(from = \"connecting\", to = \"connected\", call = \"on_connected\")]`"
+            ),
+        }
+    }
+
+    transition_hooks
+}
+
+/// Retrieve the weights declared with `#[transition(to = "..", weight = ..)]` attributes on a
+/// state handler. The weights are only metadata, so they are not checked to sum to one.
+pub fn analyze_transition_weights(attrs: &[Attribute]) -> Vec<(Ident, LitFloat)> {
+    let mut transition_weights = Vec::new();
+
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("transition")) {
+        let nested = match attr.parse_meta() {
+            Ok(Meta::List(list)) => list.nested,
+            _ => abort!(
+                attr,
+                "expected a list of arguments";
+                help = "for example `#[transition(to = \"error\", weight = 0.01)]`"
+            ),
+        };
+
+        let mut to = None;
+        let mut weight = None;
+        for nested_meta in &nested {
+            match nested_meta {
+                NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("to") => {
+                    to = match &name_value.lit {
+                        Lit::Str(ident) => match ident.parse::<Ident>() {
+                            Ok(ident) => Some(ident),
+                            Err(_) => abort!(ident, "must be an identifier"),
+                        },
+                        _ => abort!(name_value, "must be a string literal"),
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(name_value))
+                    if name_value.path.is_ident("weight") =>
+                {
+                    weight = match &name_value.lit {
+                        Lit::Float(value) => Some(value.clone()),
+                        _ => abort!(
+                            name_value,
+                            "must be a floating point literal";
+                            help = "for example `weight = 1.0`"
+                        ),
+                    }
+                }
+                _ => abort!(nested_meta, "argument not recognized"),
+            }
+        }
+
+        match (to, weight) {
+            (Some(to), Some(weight)) => transition_weights.push((to, weight)),
+            _ => abort!(
+                attr,
+                "transition weight requires `to` and `weight`";
+                help = "for example `#[transition(to = \"error\", weight = 0.01)]`"
+            ),
+        }
+    }
+
+    transition_weights
+}
+
+/// Create the methods for the actions that are declared inline in the `#[state_machine]`
+/// attribute (e.g. `actions(reset_led = "self.led = false")`).
+pub fn analyze_inline_actions(attribute_args: &AttributeArgs) -> Vec<ImplItemMethod> {
+    let mut actions = Vec::new();
+
+    let lists = attribute_args.iter().filter_map(|arg| match arg {
+        NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("actions") => Some(list),
+        _ => None,
+    });
+
+    for nested_meta in lists.flat_map(|list| &list.nested) {
+        match nested_meta {
+            NestedMeta::Meta(Meta::NameValue(name_value)) => {
+                let Some(name) = name_value.path.get_ident() else {
+                    abort!(name_value.path, "expected an identifier")
+                };
+                let stmts = match &name_value.lit {
+                    Lit::Str(body) => match body.parse_with(Block::parse_within) {
+                        Ok(stmts) => stmts,
+                        Err(error) => abort!(body, "invalid action body: {}", error),
+                    },
+                    _ => abort!(name_value, "must be a string literal"),
+                };
+                if actions
+                    .iter()
+                    .any(|action: &ImplItemMethod| &action.sig.ident == name)
+                {
+                    abort!(name, "action `{}` is declared more than once", name);
+                }
+                actions.push(parse_quote!(
+                    #[action]
+                    fn #name(&mut self) {
+                        #(#stmts)*
+                    }
+                ));
+            }
+            _ => abort!(
+                nested_meta,
+                "expected an inline action";
+                help = "for example `actions(reset_led = \"self.led = false\")`"
+            ),
+        }
+    }
+
+    actions
+}
+
 /// Retrieve information regarding the state.
 pub fn analyze_state(method: &ImplItemMethod, state_machine: &StateMachine) -> State {
     let handler_name = method.sig.ident.clone();
     let inputs = method.sig.inputs.iter().cloned().collect();
 
     let mut superstate = None;
-    let mut entry_action = None;
-    let mut exit_action = None;
+    let mut entry_actions = Vec::new();
+    let mut exit_actions = Vec::new();
     let mut local_storage = Vec::new();
+    let mut parallel = false;
+    let mut allow_missing_event = false;
+    let mut local_storage_by_value = false;
+    let mut terminal = false;
+    let mut is_final = false;
+    let mut priority = None;
+    let mut aliases = Vec::new();
+    let mut variant_attrs = Vec::new();
     let mut shared_storage_input = None;
     let mut state_inputs = Vec::new();
+    let mut shared_storage_field_names: Vec<Ident> = Vec::new();
+    let mut shared_storage_fields = Vec::new();
     let mut event_arg = None;
     let mut context_arg = None;
 
+    // Only lifetimes for the event and context that are passed in per call are allowed.
     let generic_params = &method.sig.generics.params;
-    if !generic_params.is_empty() {
+    if let Some(generic_param) = generic_params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
         abort!(
-            generic_params,
+            generic_param,
             "state handlers can not define their generics themselves";
             help = "consider declaring the generics on the impl block"
         )
@@ -372,6 +1267,21 @@ pub fn analyze_state(method: &ImplItemMethod, state_machine: &StateMachine) -> S
 
     let is_async = method.sig.asyncness.is_some();
 
+    // Find the states that are targeted by the handler.
+    let mut transition_visitor = TransitionVisitor::new(&state_machine.state_ident);
+    transition_visitor.search(&method.block);
+    let dynamic_transitions = transition_visitor.has_dynamic();
+    let control_flow = returns_control_flow(&method.sig.output);
+    let error_type = returned_error_type(&method.sig.output);
+    check_response_state(&method.sig.output, &state_machine.state_ident);
+    let transitions = transition_visitor.finish();
+    let transition_weights = analyze_transition_weights(&method.attrs);
+
+    // Find the events that are matched on by the handler.
+    let mut event_visitor = EventVisitor::new(&state_machine.event_ident);
+    event_visitor.search(&method.block);
+    let handled_events = event_visitor.finish();
+
     // Iterate over the inputs of the state handler.
     for input in &method.sig.inputs {
         match input {
@@ -412,14 +1322,20 @@ pub fn analyze_state(method: &ImplItemMethod, state_machine: &StateMachine) -> S
             }
             Meta::NameValue(name_value) if name_value.path.is_ident("entry_action") => {
                 if let Lit::Str(value) = name_value.lit {
-                    entry_action = Some(Ident::new(&value.value(), value.span()));
+                    entry_actions.push(Ident::new(&value.value(), value.span()));
                 }
             }
             Meta::NameValue(name_value) if name_value.path.is_ident("exit_action") => {
                 if let Lit::Str(value) = name_value.lit {
-                    exit_action = Some(Ident::new(&value.value(), value.span()));
+                    exit_actions.push(Ident::new(&value.value(), value.span()));
                 }
             }
+            Meta::List(list) if list.path.is_ident("entry_actions") => {
+                entry_actions.extend(analyze_action_list(list));
+            }
+            Meta::List(list) if list.path.is_ident("exit_actions") => {
+                exit_actions.extend(analyze_action_list(list));
+            }
             Meta::List(list) if list.path.is_ident("local_storage") => {
                 for item in list.nested {
                     if let NestedMeta::Lit(Lit::Str(value)) = item {
@@ -428,21 +1344,136 @@ pub fn analyze_state(method: &ImplItemMethod, state_machine: &StateMachine) -> S
                     }
                 }
             }
+            Meta::NameValue(name_value) if name_value.path.is_ident("alias") => {
+                match name_value.lit {
+                    Lit::Str(value) => aliases.push(value),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            Meta::Path(path) if path.is_ident("parallel") => {
+                parallel = true;
+            }
+            Meta::Path(path) if path.is_ident("allow_missing_event") => {
+                allow_missing_event = true;
+            }
+            Meta::Path(path) if path.is_ident("local_storage_by_value") => {
+                local_storage_by_value = true;
+            }
+            Meta::Path(path) if path.is_ident("terminal") => {
+                terminal = true;
+            }
+            Meta::Path(path) if path.is_ident("final") => {
+                is_final = true;
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("priority") => {
+                match &name_value.lit {
+                    Lit::Int(value) => match value.base10_parse() {
+                        Ok(value) => priority = Some(value),
+                        Err(error) => abort!(value, "{}", error),
+                    },
+                    _ => abort!(name_value, "must be an integer literal"),
+                }
+            }
+            Meta::List(list) if list.path.is_ident("shared_storage_fields") => {
+                for item in list.nested {
+                    match item {
+                        NestedMeta::Lit(Lit::Str(value)) => {
+                            let field = Ident::new(&value.value(), value.span());
+                            if shared_storage_field_names.contains(&field) {
+                                abort!(
+                                    value,
+                                    "field `{}` of the shared storage is borrowed more than once",
+                                    field
+                                );
+                            }
+                            shared_storage_field_names.push(field);
+                        }
+                        _ => abort!(item, "must be a string literal"),
+                    }
+                }
+            }
+            Meta::List(list) if list.path.is_ident("variant_attrs") => {
+                for item in list.nested {
+                    match item {
+                        NestedMeta::Meta(meta) => variant_attrs.push(meta),
+                        _ => abort!(
+                            item,
+                            "expected an attribute";
+                            help = "for example `variant_attrs(serde(rename = \"idle\"))`"
+                        ),
+                    }
+                }
+            }
             _ => abort!(meta, "unknown attribute"),
         }
     }
 
+    // Move the inputs that borrow from the shared storage out of the state inputs.
+    for field in &shared_storage_field_names {
+        let position = state_inputs
+            .iter()
+            .position(|pat_type| match &*pat_type.pat {
+                Pat::Ident(pat) => pat.ident.eq(field),
+                _ => false,
+            });
+        match position {
+            Some(position) => shared_storage_fields.push(state_inputs.remove(position)),
+            None => abort!(field, "no input named `{}` on the state handler", field),
+        }
+    }
+
+    if let (Some(receiver), false) = (&shared_storage_input, shared_storage_fields.is_empty()) {
+        abort!(
+            receiver,
+            "can not borrow the shared storage while also borrowing its fields";
+            help = "consider passing in the required fields of the shared storage instead"
+        )
+    }
+
+    if let (true, Some(event_arg)) = (allow_missing_event, &event_arg) {
+        abort!(
+            event_arg,
+            "state is marked `allow_missing_event` but takes the event";
+            help = "consider removing the event input or the `allow_missing_event` attribute"
+        )
+    }
+
+    if let (true, Some(target)) = (is_final, transitions.first()) {
+        abort!(
+            target,
+            "final state `{}` can not transition", handler_name;
+            help = "consider removing the transition or the `final` attribute"
+        )
+    }
+
+    check_call_lifetimes(method, &state_inputs);
+
     State {
         handler_name,
         superstate,
-        entry_action,
-        exit_action,
+        entry_actions,
+        exit_actions,
         local_storage,
         inputs,
         shared_storage_input,
         state_inputs,
+        shared_storage_fields,
         event_arg,
         context_arg,
+        transitions,
+        dynamic_transitions,
+        transition_weights,
+        control_flow,
+        error_type,
+        handled_events,
+        parallel,
+        allow_missing_event,
+        local_storage_by_value,
+        terminal,
+        is_final,
+        priority,
+        aliases,
+        variant_attrs,
         is_async,
     }
 }
@@ -453,18 +1484,26 @@ pub fn analyze_superstate(method: &ImplItemMethod, state_machine: &StateMachine)
     let inputs = method.sig.inputs.iter().cloned().collect();
 
     let mut superstate = None;
-    let mut entry_action = None;
-    let mut exit_action = None;
+    let mut initial_state = None;
+    let mut entry_actions = Vec::new();
+    let mut exit_actions = Vec::new();
+    let mut pre_dispatch = None;
     let mut local_storage = Vec::new();
+    let mut owned_storage = Vec::new();
+    let mut variant_attrs = Vec::new();
     let mut shared_storage_input = None;
     let mut state_inputs = Vec::new();
     let mut event_arg = None;
     let mut context_arg = None;
 
+    // Only lifetimes for the event and context that are passed in per call are allowed.
     let generic_params = &method.sig.generics.params;
-    if !generic_params.is_empty() {
+    if let Some(generic_param) = generic_params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
         abort!(
-            generic_params,
+            generic_param,
             "superstate handlers can not define their generics themselves";
             help = "consider declaring the generics on the impl block"
         )
@@ -472,6 +1511,15 @@ pub fn analyze_superstate(method: &ImplItemMethod, state_machine: &StateMachine)
 
     let is_async = method.sig.asyncness.is_some();
 
+    // Find the states that are targeted by the handler.
+    let mut transition_visitor = TransitionVisitor::new(&state_machine.state_ident);
+    transition_visitor.search(&method.block);
+    let dynamic_transitions = transition_visitor.has_dynamic();
+    let control_flow = returns_control_flow(&method.sig.output);
+    let error_type = returned_error_type(&method.sig.output);
+    check_response_state(&method.sig.output, &state_machine.state_ident);
+    let transitions = transition_visitor.finish();
+
     // Iterate over the inputs of the superstate handler.
     for input in &method.sig.inputs {
         match input {
@@ -510,14 +1558,38 @@ pub fn analyze_superstate(method: &ImplItemMethod, state_machine: &StateMachine)
                     superstate = Some(Ident::new(&value.value(), value.span()));
                 }
             }
+            Meta::NameValue(name_value) if name_value.path.is_ident("initial") => {
+                initial_state = match &name_value.lit {
+                    Lit::Str(input_pat) => match input_pat.parse() {
+                        Ok(initial_state) => Some(initial_state),
+                        Err(_) => abort!(
+                            input_pat,
+                            "expected a call to a state constructor";
+                            help = "for example `#[superstate(initial = \"State::led_on()\")]`"
+                        ),
+                    },
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
             Meta::NameValue(name_value) if name_value.path.is_ident("entry_action") => {
                 if let Lit::Str(value) = name_value.lit {
-                    entry_action = Some(Ident::new(&value.value(), value.span()));
+                    entry_actions.push(Ident::new(&value.value(), value.span()));
                 }
             }
             Meta::NameValue(name_value) if name_value.path.is_ident("exit_action") => {
                 if let Lit::Str(value) = name_value.lit {
-                    exit_action = Some(Ident::new(&value.value(), value.span()));
+                    exit_actions.push(Ident::new(&value.value(), value.span()));
+                }
+            }
+            Meta::List(list) if list.path.is_ident("entry_actions") => {
+                entry_actions.extend(analyze_action_list(list));
+            }
+            Meta::List(list) if list.path.is_ident("exit_actions") => {
+                exit_actions.extend(analyze_action_list(list));
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("pre_dispatch") => {
+                if let Lit::Str(value) = name_value.lit {
+                    pre_dispatch = Some(Ident::new(&value.value(), value.span()));
                 }
             }
             Meta::List(list) if list.path.is_ident("local_storage") => {
@@ -528,35 +1600,175 @@ pub fn analyze_superstate(method: &ImplItemMethod, state_machine: &StateMachine)
                     }
                 }
             }
+            Meta::List(list) if list.path.is_ident("owned_storage") => {
+                for item in list.nested {
+                    if let NestedMeta::Lit(Lit::Str(value)) = item {
+                        match parse_owned_storage.parse_str(&value.value()) {
+                            Ok(field) => owned_storage.push(field),
+                            Err(_) => abort!(
+                                value,
+                                "owned storage must be declared as `field_name: FieldType = initial_value`"
+                            ),
+                        }
+                    }
+                }
+            }
+            Meta::List(list) if list.path.is_ident("variant_attrs") => {
+                for item in list.nested {
+                    match item {
+                        NestedMeta::Meta(meta) => variant_attrs.push(meta),
+                        _ => abort!(
+                            item,
+                            "expected an attribute";
+                            help = "for example `variant_attrs(serde(rename = \"idle\"))`"
+                        ),
+                    }
+                }
+            }
             _ => abort!(meta, "unknown attribute"),
         }
     }
 
+    check_call_lifetimes(method, &state_inputs);
+
     Superstate {
         handler_name,
         superstate,
-        entry_action,
-        exit_action,
+        initial_state,
+        entry_actions,
+        exit_actions,
+        pre_dispatch,
         local_storage,
+        variant_attrs,
+        owned_storage,
         inputs,
         shared_storage_input,
         state_inputs,
         event_arg,
         context_arg,
+        transitions,
+        dynamic_transitions,
+        control_flow,
+        error_type,
         is_async,
     }
 }
 
+/// Check whether a handler returns a `ControlFlow` (e.g. `ControlFlow<State>` or
+/// `core::ops::ControlFlow<State>`).
+fn returns_control_flow(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .map_or(false, |segment| segment.ident == "ControlFlow"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+/// Get the error type of a handler that returns a `Result` (e.g. `Result<Response<State>,
+/// Error>`).
+fn returned_error_type(output: &ReturnType) -> Option<Type> {
+    let segment = match output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path.path.segments.last()?,
+            _ => return None,
+        },
+        ReturnType::Default => return None,
+    };
+    if segment.ident != "Result" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => arguments
+            .args
+            .iter()
+            .filter_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty.clone()),
+                _ => None,
+            })
+            .nth(1),
+        _ => None,
+    }
+}
+
+/// Check that a handler that names the generic parameter of its return type (e.g.
+/// `Response<State>` or `ControlFlow<State>`) uses the state enum. The bare form (e.g. a
+/// `Response` alias) is not checked.
+fn check_response_state(output: &ReturnType, state_ident: &Ident) {
+    let (response_ident, state_ty) = match response_state_type(output) {
+        Some(response_state) => response_state,
+        None => return,
+    };
+    let is_state = match state_ty {
+        Type::Path(state_path) if state_path.qself.is_none() => {
+            let segments = &state_path.path.segments;
+            match segments.len() {
+                1 => segments[0].ident == *state_ident,
+                // `Self::State` refers to the state of the `IntoStateMachine` impl.
+                2 => segments[0].ident == "Self" && segments[1].ident == "State",
+                _ => false,
+            }
+        }
+        _ => false,
+    };
+    if !is_state {
+        abort!(
+            state_ty,
+            "`{}` must be generic over the state enum `{}`", response_ident, state_ident;
+            help = "consider returning `{}<{}>`", response_ident, state_ident
+        )
+    }
+}
+
+/// Get the name of the response type and its first type argument, if the return type is
+/// a parameterized `Response` or `ControlFlow`.
+fn response_state_type(output: &ReturnType) -> Option<(&Ident, &Type)> {
+    let segment = match output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path.path.segments.last()?,
+            _ => return None,
+        },
+        ReturnType::Default => return None,
+    };
+    if segment.ident != "Response" && segment.ident != "ControlFlow" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => {
+            arguments.args.iter().find_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some((&segment.ident, ty)),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
 /// Retrieve the information regarding the action.
 pub fn analyze_action(method: &ImplItemMethod) -> Action {
     let handler_name = method.sig.ident.clone();
     let inputs = method.sig.inputs.clone().into_iter().collect();
     let is_async = method.sig.asyncness.is_some();
+    let is_fallible = match &method.sig.output {
+        ReturnType::Default => false,
+        ReturnType::Type(_, ty) => {
+            !matches!(ty.as_ref(), Type::Tuple(tuple) if tuple.elems.is_empty())
+        }
+    };
 
+    // Only lifetimes for the event and context that are passed in per call are allowed.
     let generic_params = &method.sig.generics.params;
-    if !generic_params.is_empty() {
+    if let Some(generic_param) = generic_params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
         abort!(
-            generic_params,
+            generic_param,
             "action handlers can not define their generics themselves";
             help = "consider declaring the generics on the impl block"
         )
@@ -565,10 +1777,61 @@ pub fn analyze_action(method: &ImplItemMethod) -> Action {
     Action {
         handler_name,
         inputs,
+        is_fallible,
         is_async,
     }
 }
 
+/// Parse a field of the owned storage of a superstate (e.g. `count: usize = 0`).
+fn parse_owned_storage(input: ParseStream) -> syn::Result<(Field, Expr)> {
+    let field = Field::parse_named(input)?;
+    input.parse::<Token![=]>()?;
+    let initial_value = input.parse()?;
+    Ok((field, initial_value))
+}
+
+/// Parse a list of actions (e.g. `entry_actions(init_a, "init_b")`), keeping the order in
+/// which they are declared.
+fn analyze_action_list(list: MetaList) -> Vec<Ident> {
+    list.nested
+        .into_iter()
+        .map(|item| match item {
+            NestedMeta::Lit(Lit::Str(value)) => Ident::new(&value.value(), value.span()),
+            NestedMeta::Meta(Meta::Path(path)) if path.get_ident().is_some() => {
+                path.get_ident().unwrap().clone()
+            }
+            _ => abort!(
+                item,
+                "expected the name of an action";
+                help = "for example `entry_actions(init_a, init_b)`"
+            ),
+        })
+        .collect()
+}
+
+/// Lifetimes declared on a handler are only valid for a single call, so they can not be
+/// part of the inputs that are stored in the state-local storage.
+fn check_call_lifetimes(method: &ImplItemMethod, state_inputs: &[PatType]) {
+    let mut visitor = GenericParamVisitor::new(&method.sig.generics);
+    visitor.search(state_inputs);
+    let found = visitor.finish();
+
+    for lifetime_def in method.sig.generics.lifetimes() {
+        let is_stored = found.iter().any(|param| match param {
+            GenericParam::Lifetime(found) => found.lifetime == lifetime_def.lifetime,
+            _ => false,
+        });
+        if is_stored {
+            abort!(
+                lifetime_def,
+                "lifetime `{}` is only valid for a single call and can not be used in state-local storage",
+                lifetime_def.lifetime;
+                help = "consider only using it for the event or context"
+            );
+        }
+    }
+}
+
 /// Parse the attributes as a meta item.
 pub fn get_meta(attrs: &[Attribute], name: &str) -> Vec<Meta> {
     attrs
@@ -638,58 +1901,158 @@ fn valid_state_analyze() {
 
     let shared_storage_type = parse_quote!(Blinky);
     let shared_storage_path = parse_quote!(Blinky);
+    let shared_storage_field = None;
     let shared_storage_generics = parse_quote!();
+    let lifetime = None;
+    let superstate_lifetime = parse_quote!('sub);
 
     let state_ident = parse_quote!(State);
+    let state_kind_ident = parse_quote!(StateKind);
     let state_derives = vec![parse_quote!(Copy), parse_quote!(Clone)];
+    let state_repr = vec![];
     let superstate_ident = parse_quote!(Superstate);
+    let superstate_kind_ident = parse_quote!(SuperstateKind);
     let superstate_derives = vec![parse_quote!(Copy), parse_quote!(Clone)];
     let on_transition = None;
+    let transition_hooks = vec![];
     let on_dispatch = None;
+    let startup_event = None;
+    let stack_allocator = None;
+    let previous_state_kind = None;
+    let origin = None;
+    let sequence = None;
+    let observer = None;
+    let entry_exit_counts = None;
+    let timeout = None;
+    let fired_actions = None;
+    let fallible = None;
+    let validate = false;
+    let json = false;
+    let describe = false;
+    let export_graph = false;
+    let diff = false;
+    let send_sync = false;
+    let transition_to = false;
+    let replay = false;
+    let apply_event_log = false;
+    let state_index = false;
+    let mode = None;
+    let minimal = false;
+    let deref_storage = false;
+    let drop_guard = false;
+    let tracing = false;
+    let generics_passthrough = false;
+    let merge_hints = false;
+    let cfg = None;
+    let box_futures = None;
+    let serialize_path = None;
     let event_ident = parse_quote!(event);
+    let event_ref = true;
     let context_ident = parse_quote!(context);
+    let response_ident = None;
+    let facade_ident = None;
     let visibility = parse_quote!(pub);
 
     let state_machine = StateMachine {
         initial_state,
+        region_initial_states: Vec::new(),
         shared_storage_type,
         shared_storage_path,
+        shared_storage_field,
         shared_storage_generics,
+        lifetime,
+        superstate_lifetime,
         state_ident,
+        state_kind_ident,
         state_derives,
+        state_repr,
         superstate_ident,
+        superstate_kind_ident,
         superstate_derives,
         on_transition,
+        transition_hooks,
         on_dispatch,
+        startup_event,
+        stack_allocator,
+        previous_state_kind,
+        origin,
+        sequence,
+        observer,
+        entry_exit_counts,
+        timeout,
+        fired_actions,
+        fallible,
+        validate,
+        json,
+        describe,
+        export_graph,
+        diff,
+        send_sync,
+        transition_to,
+        replay,
+        apply_event_log,
+        state_index,
+        mode,
+        minimal,
+        deref_storage,
+        drop_guard,
+        tracing,
+        generics_passthrough,
+        merge_hints,
+        cfg,
+        box_futures,
+        serialize_path,
         event_ident,
+        event_ref,
         context_ident,
+        response_ident,
+        facade_ident,
         visibility,
     };
 
     let state = State {
         handler_name: parse_quote!(on),
         superstate: parse_quote!(playing),
-        entry_action: parse_quote!(enter_on),
-        exit_action: parse_quote!(enter_off),
+        entry_actions: vec![parse_quote!(enter_on)],
+        exit_actions: vec![parse_quote!(enter_off)],
         local_storage: vec![],
         inputs: vec![parse_quote!(&mut self), parse_quote!(event: &Event)],
         shared_storage_input: Some(parse_quote!(&mut self)),
         state_inputs: vec![],
+        shared_storage_fields: vec![],
         event_arg: Some(if let FnArg::Typed(event) = parse_quote!(event: &Event) {
             event
         } else {
             return;
         }),
         context_arg: None,
+        transitions: vec![],
+        dynamic_transitions: false,
+        transition_weights: vec![],
+        control_flow: false,
+        error_type: None,
+        handled_events: vec![],
+        parallel: false,
+        allow_missing_event: false,
+        local_storage_by_value: false,
+        terminal: false,
+        is_final: false,
+        priority: None,
+        aliases: vec![],
+        variant_attrs: vec![],
         is_async: false,
     };
 
     let superstate = Superstate {
         handler_name: parse_quote!(playing),
         superstate: None,
-        entry_action: None,
-        exit_action: None,
+        initial_state: None,
+        entry_actions: Vec::new(),
+        exit_actions: Vec::new(),
+        pre_dispatch: None,
         local_storage: vec![],
+        variant_attrs: vec![],
+        owned_storage: vec![],
         inputs: vec![parse_quote!(&mut self), parse_quote!(event: &Event)],
         shared_storage_input: Some(parse_quote!(&mut self)),
         state_inputs: vec![],
@@ -699,18 +2062,24 @@ fn valid_state_analyze() {
             return;
         }),
         context_arg: None,
+        transitions: vec![],
+        dynamic_transitions: false,
+        control_flow: false,
+        error_type: None,
         is_async: false,
     };
 
     let entry_action = Action {
         handler_name: parse_quote!(enter_on),
         inputs: vec![parse_quote!(&mut self)],
+        is_fallible: false,
         is_async: false,
     };
 
     let exit_action = Action {
         handler_name: parse_quote!(enter_off),
         inputs: vec![parse_quote!(&mut self)],
+        is_fallible: false,
         is_async: false,
     };
 
@@ -729,7 +2098,301 @@ fn valid_state_analyze() {
         states,
         superstates,
         actions,
+        mergeable_states: vec![],
     };
 
     assert_eq!(actual, expected);
 }
+
+#[test]
+#[should_panic]
+fn overlapping_shared_storage_fields() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(shared_storage_fields("led", "led"))]
+            fn on(led: &mut bool) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+#[should_panic]
+fn inline_action_conflicts_with_method() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::on()"),
+        parse_quote!(actions(enter_on = "self.led = true")),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(entry_action = "enter_on")]
+            fn on() -> Response<State> {
+                Response::Handled
+            }
+
+            #[action]
+            fn enter_on(&mut self) {}
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+#[should_panic]
+fn call_lifetime_in_state_local_storage() {
+    let attribute_args = vec![parse_quote!(initial = "State::on(0)")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on<'call>(counter: &mut Counter<'call>) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+#[should_panic]
+fn allow_missing_event_with_event_input() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(allow_missing_event)]
+            fn on(event: &Event) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+fn bare_and_parameterized_response() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on() -> Response {
+                Response::Handled
+            }
+
+            #[state]
+            fn off() -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate]
+            fn blinking() -> statig::Response<Self::State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze(attribute_args, item_impl);
+    assert_eq!(model.states.len(), 2);
+    assert_eq!(model.superstates.len(), 1);
+}
+
+#[test]
+#[should_panic]
+fn response_with_wrong_state() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on() -> Response<Wrong> {
+                Response::Handled
+            }
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+fn identical_state_handlers_are_mergeable() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::on()"),
+        parse_quote!(merge_hints),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                    _ => Super,
+                }
+            }
+
+            #[state]
+            fn dimmed(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                    _ => Super,
+                }
+            }
+
+            #[state]
+            fn off(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::on()),
+                    _ => Super,
+                }
+            }
+        }
+    );
+
+    let model = analyze(attribute_args, item_impl);
+    assert_eq!(
+        model.mergeable_states,
+        vec![(quote::format_ident!("on"), quote::format_ident!("dimmed"))]
+    );
+}
+
+#[test]
+#[should_panic]
+fn final_state_with_transition() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(final)]
+            fn on(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                    _ => Handled,
+                }
+            }
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+fn superstate_lifetime_is_renamed_on_collision() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl<'sub, 'sub0> Blinky<'sub, 'sub0> {
+            #[state]
+            fn on() -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    let model = analyze(attribute_args, item_impl);
+    let expected: Lifetime = parse_quote!('sub1);
+    assert_eq!(model.state_machine.superstate_lifetime, expected);
+}
+
+#[test]
+#[should_panic]
+fn multiple_initial_states() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::on()"),
+        parse_quote!(initial = "State::off()"),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on() -> Response<State> {
+                Handled
+            }
+
+            #[state]
+            fn off() -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+#[should_panic]
+fn minimal_with_state_index() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::on()"),
+        parse_quote!(minimal),
+        parse_quote!(state_index),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on() -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+#[should_panic]
+fn region_initial_state_without_parallel_state() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::on()"),
+        parse_quote!(initial(left = "left::State::a()")),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on() -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+#[should_panic]
+fn region_initial_state_of_unknown_region() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::running(StateMachine::new(Left))"),
+        parse_quote!(initial(right = "right::State::x()")),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Device {
+            #[state(parallel)]
+            fn running(left: &mut StateMachine<Left>, event: &Event) -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    analyze(attribute_args, item_impl);
+}
+
+#[test]
+fn region_initial_states() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::running(StateMachine::new(Left))"),
+        parse_quote!(initial(left = "left::State::b()")),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Device {
+            #[state(parallel)]
+            fn running(left: &mut StateMachine<Left>, event: &Event) -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    let model = analyze(attribute_args, item_impl);
+    let expected: Vec<(Ident, ExprCall)> =
+        vec![(parse_quote!(left), parse_quote!(left::State::b()))];
+    assert_eq!(model.state_machine.region_initial_states, expected);
+}
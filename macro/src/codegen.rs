@@ -1,11 +1,14 @@
 use proc_macro2::{Span, TokenStream};
-use quote::quote;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
 use syn::{
-    parse_quote, Arm, GenericParam, ItemEnum, ItemFn, ItemImpl, Lifetime, LifetimeDef, Variant,
+    parse_quote, Arm, Attribute, Expr, File, GenericParam, Generics, Ident, ImplItemMethod, Item,
+    ItemEnum, ItemFn, ItemImpl, ItemType, Lifetime, LifetimeDef, LitStr, Meta, Pat, Type, Variant,
+    WhereClause,
 };
 
 use crate::lower::{Ir, Mode};
-use crate::{CONTEXT_LIFETIME, EVENT_LIFETIME, SUPERSTATE_LIFETIME};
+use crate::{CONTEXT_LIFETIME, EVENT_LIFETIME};
 
 pub fn codegen(ir: Ir) -> TokenStream {
     let item_impl = &ir.item_impl;
@@ -14,11 +17,37 @@ pub fn codegen(ir: Ir) -> TokenStream {
 
     let state_enum = codegen_state(&ir);
     let state_impl = codegen_state_impl(&ir);
+    let state_kind_enum = codegen_state_kind(&ir);
+    let state_kind_impl = codegen_state_kind_impl(&ir);
+    let state_impl_from = codegen_state_impl_from(&ir);
+    let state_kind_impl_from = codegen_state_kind_impl_from(&ir);
+    let state_impl_try_from = codegen_state_impl_try_from(&ir);
     let state_impl_state = codegen_state_impl_state(&ir);
+    let response_alias = codegen_response_alias(&ir);
     let superstate_enum = codegen_superstate(&ir);
+    let superstate_name_impl = codegen_superstate_impl(&ir);
+    let superstate_kind_enum = codegen_superstate_kind(&ir);
     let superstate_impl = codegen_superstate_impl_superstate(&ir);
+    let validate_impl = codegen_validate(&ir);
+    let json_impl = codegen_json(&ir);
+    let diff_impl = codegen_diff(&ir);
+    let describe_impl = codegen_describe(&ir);
+    let graphviz_impl = codegen_graphviz(&ir);
+    let transition_weights_impl = codegen_transition_weights(&ir);
+    let facade = codegen_facade(&ir);
+    let send_sync_assertions = codegen_send_sync(&ir);
+    let replay_impl = codegen_replay(&ir);
+    let apply_event_log_impl = codegen_apply_event_log(&ir);
+    let deref_storage_impl = codegen_deref_storage(&ir);
+    let box_futures_impl = codegen_box_futures(&ir);
+    let serialize_path_impl = codegen_serialize_path(&ir);
+    let fired_actions_impl = codegen_fired_actions(&ir);
+    let handler_error_impl = codegen_handler_error(&ir);
+    let merge_hints = codegen_merge_hints(&ir);
+    let transition_hook_hints = codegen_transition_hook_hints(&ir);
+    let transition_to_impl = codegen_transition_to(&ir);
 
-    quote!(
+    let rust = quote!(
         // Import the proc_macro attributes so they can be used to tag functions.
         use statig::{state, superstate, action};
 
@@ -30,12 +59,102 @@ pub fn codegen(ir: Ir) -> TokenStream {
 
         #state_impl
 
+        #state_kind_enum
+
+        #state_kind_impl
+
+        #state_impl_from
+
+        #state_kind_impl_from
+
+        #state_impl_try_from
+
         #state_impl_state
 
+        #response_alias
+
         #superstate_enum
 
+        #superstate_name_impl
+
+        #superstate_kind_enum
+
         #superstate_impl
-    )
+
+        #validate_impl
+
+        #json_impl
+
+        #diff_impl
+
+        #describe_impl
+
+        #graphviz_impl
+
+        #transition_weights_impl
+
+        #facade
+
+        #send_sync_assertions
+
+        #replay_impl
+
+        #apply_event_log_impl
+
+        #deref_storage_impl
+
+        #box_futures_impl
+
+        #serialize_path_impl
+
+        #fired_actions_impl
+
+        #handler_error_impl
+
+        #(#merge_hints)*
+
+        #(#transition_hook_hints)*
+
+        #transition_to_impl
+    );
+
+    match &ir.state_machine.cfg {
+        Some(cfg) => codegen_cfg(rust, cfg),
+        None => rust,
+    }
+}
+
+/// Gate every generated item, including the impl block itself, on the configuration
+/// predicate.
+fn codegen_cfg(rust: TokenStream, cfg: &Meta) -> TokenStream {
+    let mut file: File = match syn::parse2(rust) {
+        Ok(file) => file,
+        Err(error) => return error.to_compile_error(),
+    };
+    let attr: Attribute = parse_quote!(#[cfg(#cfg)]);
+    for item in &mut file.items {
+        let attrs = match item {
+            Item::Const(item) => &mut item.attrs,
+            Item::Enum(item) => &mut item.attrs,
+            Item::ExternCrate(item) => &mut item.attrs,
+            Item::Fn(item) => &mut item.attrs,
+            Item::ForeignMod(item) => &mut item.attrs,
+            Item::Impl(item) => &mut item.attrs,
+            Item::Macro(item) => &mut item.attrs,
+            Item::Macro2(item) => &mut item.attrs,
+            Item::Mod(item) => &mut item.attrs,
+            Item::Static(item) => &mut item.attrs,
+            Item::Struct(item) => &mut item.attrs,
+            Item::Trait(item) => &mut item.attrs,
+            Item::TraitAlias(item) => &mut item.attrs,
+            Item::Type(item) => &mut item.attrs,
+            Item::Union(item) => &mut item.attrs,
+            Item::Use(item) => &mut item.attrs,
+            _ => continue,
+        };
+        attrs.insert(0, attr.clone());
+    }
+    quote!(#file)
 }
 
 fn codegen_state_machine_impl(ir: &Ir) -> ItemImpl {
@@ -48,7 +167,7 @@ fn codegen_state_machine_impl(ir: &Ir) -> ItemImpl {
     let (_, state_generics, _) = &ir.state_machine.state_generics.split_for_impl();
     let superstate_ident = &ir.state_machine.superstate_ident;
     let (_, superstate_generics, _) = &ir.state_machine.superstate_generics.split_for_impl();
-    let superstate_lifetime = Lifetime::new(SUPERSTATE_LIFETIME, Span::call_site());
+    let superstate_lifetime = &ir.state_machine.superstate_lifetime;
     let event_lifetime = Lifetime::new(EVENT_LIFETIME, Span::call_site());
     let context_lifetime = Lifetime::new(CONTEXT_LIFETIME, Span::call_site());
 
@@ -59,20 +178,182 @@ fn codegen_state_machine_impl(ir: &Ir) -> ItemImpl {
         Mode::Awaitable => quote!(awaitable),
     };
 
-    let on_transition = match &ir.state_machine.on_transition {
-        None => quote!(),
-        Some(on_transition) => quote!(
+    let observer = &ir.state_machine.observer;
+
+    let transition_hook_calls: Vec<TokenStream> = ir
+        .state_machine
+        .transition_hooks
+        .iter()
+        .map(|hook| {
+            let source_variant = &hook.source_variant;
+            let target_variant = &hook.target_variant;
+            let handler_call = &hook.handler_call;
+            quote!(
+                if matches!(source, #state_ident::#source_variant { .. })
+                    && matches!(target, #state_ident::#target_variant { .. })
+                {
+                    #handler_call;
+                }
+            )
+        })
+        .collect();
+
+    let on_transition = match (
+        &ir.state_machine.on_transition,
+        observer,
+        transition_hook_calls.is_empty(),
+    ) {
+        (None, None, true) => quote!(),
+        (Some(on_transition), None, true) => quote!(
             const ON_TRANSITION: fn(&mut Self, &Self::State, &Self::State) = #on_transition;
         ),
+        // Notify the observer before calling the `on_transition` callback, if any, followed
+        // by the hooks of the specific transition.
+        (on_transition, observer, _) => {
+            let observer_call = observer.as_ref().map(|observer| {
+                quote!(statig::Observer::<Self>::on_transition(&mut shared_storage.#observer, source, target);)
+            });
+            let on_transition_call = on_transition
+                .as_ref()
+                .map(|on_transition| quote!(#on_transition(shared_storage, source, target);));
+            quote!(
+                const ON_TRANSITION: fn(&mut Self, &Self::State, &Self::State) =
+                    |shared_storage, source, target| {
+                        #observer_call
+                        #on_transition_call
+                        #(#transition_hook_calls)*
+                    };
+            )
+        }
     };
 
-    let on_dispatch = match &ir.state_machine.on_dispatch {
-        None => quote!(),
-        Some(on_dispatch) => quote!(
+    let on_dispatch = match (
+        &ir.state_machine.on_dispatch,
+        &ir.state_machine.previous_state_kind,
+        observer,
+    ) {
+        (None, None, None) => quote!(),
+        (Some(on_dispatch), None, None) => quote!(
             const ON_DISPATCH: fn(&mut Self, StateOrSuperstate<'_, '_, Self>, &Self::Event<'_>) = #on_dispatch;
         ),
+        // Record the kind of the state and notify the observer before calling the
+        // `on_dispatch` callback, if any.
+        (on_dispatch, previous_state_kind, observer) => {
+            let record_call = previous_state_kind.as_ref().map(|field| {
+                quote!(
+                    if let statig::StateOrSuperstate::State(state) = &state_or_superstate {
+                        shared_storage.#field.record(state.kind());
+                    }
+                )
+            });
+            let observer_call = observer.as_ref().map(|observer| {
+                quote!(statig::Observer::<Self>::on_dispatch(&mut shared_storage.#observer, state_or_superstate, _event);)
+            });
+            let on_dispatch_call = on_dispatch.as_ref().map(
+                |on_dispatch| quote!(#on_dispatch(shared_storage, state_or_superstate, _event);),
+            );
+            quote!(
+                const ON_DISPATCH: fn(&mut Self, StateOrSuperstate<'_, '_, Self>, &Self::Event<'_>) =
+                    |shared_storage, state_or_superstate, _event| {
+                        #record_call
+                        #observer_call
+                        #on_dispatch_call
+                    };
+            )
+        }
+    };
+
+    // Advance the sequence number and clear the error of the previous event, if any.
+    let on_handle = match (&ir.state_machine.sequence, &ir.state_machine.handler_error) {
+        (None, None) => quote!(),
+        (sequence, handler_error) => {
+            let sequence_call = sequence.as_ref().map(
+                |field| quote!(shared_storage.#field = shared_storage.#field.wrapping_add(1);),
+            );
+            let handler_error_call = handler_error
+                .as_ref()
+                .map(|(field, _)| quote!(shared_storage.#field = None;));
+            quote!(
+                const ON_HANDLE: fn(&mut Self) = |shared_storage| {
+                    #sequence_call
+                    #handler_error_call
+                };
+            )
+        }
+    };
+
+    let on_origin = ir.state_machine.origin.as_ref().map(|field| {
+        quote!(
+            const ON_ORIGIN: fn(&mut Self, &'static str) =
+                |shared_storage, origin| shared_storage.#field = origin;
+        )
+    });
+
+    // Only states that are marked as terminal can be dropped without shutting down.
+    let drop_guard = ir.state_machine.drop_guard.then(|| {
+        let terminal_pats: Vec<Pat> = ir
+            .states
+            .values()
+            .filter(|state| state.terminal)
+            .map(|state| {
+                let variant_ident = &state.variant.ident;
+                parse_quote!(#state_ident::#variant_ident { .. })
+            })
+            .collect();
+        let is_terminal = (!terminal_pats.is_empty()).then(|| {
+            quote!(
+                const IS_TERMINAL: fn(&Self::State) -> bool =
+                    |state| matches!(state, #(#terminal_pats)|*);
+            )
+        });
+        quote!(
+            const DROP_GUARD: bool = true;
+
+            #is_terminal
+        )
+    });
+
+    let startup_event = match &ir.state_machine.startup_event {
+        None => quote!(),
+        Some(startup_event) => quote!(
+            const STARTUP_EVENT: Option<Self::Event<'static>> = Some(#startup_event);
+        ),
+    };
+
+    let stack_allocator = match &ir.state_machine.stack_allocator {
+        None => quote!(),
+        Some(stack_allocator) => quote!(
+            const STACK_ALLOCATOR: statig::StackAllocator = &#stack_allocator;
+        ),
     };
 
+    // The priorities decide the dispatch order of regions, so they are kept in minimal
+    // state machines.
+    let state_priority = codegen_priority_arms(ir).map(|priority_arms| {
+        quote!(
+            const STATE_PRIORITY: fn(&Self::State) -> i32 = |state| match state {
+                #(#priority_arms,)*
+            };
+        )
+    });
+
+    // Without the name tables of a minimal state machine, the states are left unnamed.
+    let state_name = (!ir.state_machine.minimal).then(|| {
+        quote!(
+            const STATE_NAME: fn(&Self::State) -> &'static str = |state| state.name();
+        )
+    });
+
+    // A minimal state machine is debugged without its shared storage.
+    let debug_storage = (!ir.state_machine.minimal).then(|| {
+        quote!(
+            const DEBUG_STORAGE: fn(&Self) -> Option<&dyn core::fmt::Debug> = |shared_storage| {
+                use statig::debug_storage::{ViaDebug as _, ViaFallback as _};
+                (&statig::debug_storage::Wrap(shared_storage)).debug_storage()
+            };
+        )
+    });
+
     parse_quote!(
         impl #impl_generics statig::#mode::IntoStateMachine for #shared_storage_type #where_clause
         {
@@ -85,6 +366,22 @@ fn codegen_state_machine_impl(ir: &Ir) -> ItemImpl {
             #on_transition
 
             #on_dispatch
+
+            #on_handle
+
+            #on_origin
+
+            #startup_event
+
+            #stack_allocator
+
+            #drop_guard
+
+            #state_name
+
+            #state_priority
+
+            #debug_storage
         }
     )
 }
@@ -93,16 +390,21 @@ fn codegen_state(ir: &Ir) -> ItemEnum {
     let state_ident = &ir.state_machine.state_ident;
     let (state_generics, _, _) = &ir.state_machine.state_generics.split_for_impl();
     let state_derives = &ir.state_machine.state_derives;
+    let state_repr = &ir.state_machine.state_repr;
+    let repr = (!state_repr.is_empty()).then(|| quote!(#[repr(#(#state_repr),*)]));
 
-    let variants: Vec<Variant> = ir
+    // The variants are sorted by name, so their discriminants match the state kinds.
+    let mut variants: Vec<Variant> = ir
         .states
         .values()
         .map(|state| state.variant.clone())
         .collect();
+    variants.sort_by(|a, b| a.ident.cmp(&b.ident));
     let visibility = &ir.state_machine.visibility;
 
     parse_quote!(
         #[derive(#(#state_derives),*)]
+        #repr
         # visibility enum #state_ident #state_generics {
             #(#variants),*
         }
@@ -113,20 +415,438 @@ fn codegen_state_impl(ir: &Ir) -> ItemImpl {
     let state_ident = &ir.state_machine.state_ident;
     let (impl_generics, state_generics, _) = &ir.state_machine.state_generics.split_for_impl();
 
-    let constructors: Vec<ItemFn> = ir
+    let state_kind_ident = &ir.state_machine.state_kind_ident;
+    let visibility = &ir.state_machine.visibility;
+
+    let mut constructors: Vec<ItemFn> = ir
         .states
         .values()
         .map(|state| &state.constructor)
         .cloned()
         .collect();
 
+    constructors.extend(
+        ir.superstates
+            .values()
+            .filter_map(|superstate| superstate.initial_state_constructor.clone()),
+    );
+
+    // The kinds and the depth are left out when a minimal state machine is requested.
+    let kind = (!ir.state_machine.minimal).then(|| {
+        let kind_arms: Vec<Arm> = ir
+            .states
+            .values()
+            .map(|state| {
+                let variant_ident = &state.variant.ident;
+                parse_quote!(#state_ident::#variant_ident { .. } => #state_kind_ident::#variant_ident)
+            })
+            .collect();
+
+        // The path of a state holds its superstates followed by the state itself.
+        let max_depth = ir
+            .states
+            .values()
+            .map(|state| state.path.len().saturating_sub(1))
+            .max()
+            .unwrap_or(0);
+
+        quote!(
+            /// The number of superstates of the most deeply nested state, which bounds the
+            /// number of ancestors of any state (e.g. for a `[SuperstateKind; MAX_DEPTH]`).
+            #visibility const MAX_DEPTH: usize = #max_depth;
+
+            /// Get the kind of the state.
+            #visibility const fn kind(&self) -> #state_kind_ident {
+                match self {
+                    #(#kind_arms,)*
+                }
+            }
+        )
+    });
+
+    // Introspection is left out when a minimal state machine is requested.
+    let mut introspection: Vec<ItemFn> = Vec::new();
+
+    if !ir.state_machine.minimal {
+        let name_arms: Vec<Arm> = ir
+            .states
+            .values()
+            .map(|state| {
+                let variant_ident = &state.variant.ident;
+                let name = variant_ident.to_string();
+                parse_quote!(#state_ident::#variant_ident { .. } => #name)
+            })
+            .collect();
+
+        introspection.push(parse_quote!(
+            /// Get the name of the variant of the state (e.g. `"LedOn"`), which is cheaper
+            /// than formatting the state with `Debug`.
+            #visibility const fn name(&self) -> &'static str {
+                match self {
+                    #(#name_arms,)*
+                }
+            }
+        ));
+
+        if let Some(priority_arms) = codegen_priority_arms(ir) {
+            introspection.push(parse_quote!(
+                /// Get the priority of the state, which decides the order in which the
+                /// regions of a parallel state handle events when this is their current state.
+                #visibility const fn priority(&self) -> i32 {
+                    match self {
+                        #(#priority_arms,)*
+                    }
+                }
+            ));
+        }
+
+        let state_path_arms: Vec<Arm> = ir
+            .states
+            .values()
+            .map(|state| {
+                let variant_ident = &state.variant.ident;
+                let path = state.path.iter().map(|ident| ident.to_string());
+                parse_quote!(#state_ident::#variant_ident { .. } => &[#(#path),*])
+            })
+            .collect();
+
+        introspection.push(parse_quote!(
+            /// Get the names of the superstates of the state and the state itself, ordered
+            /// from the outermost superstate to the state.
+            #visibility fn state_path(&self) -> &'static [&'static str] {
+                match self {
+                    #(#state_path_arms,)*
+                }
+            }
+        ));
+
+        let superstate_kind_ident = &ir.state_machine.superstate_kind_ident;
+        let ancestors_arms: Vec<Arm> = ir
+            .states
+            .values()
+            .map(|state| {
+                let variant_ident = &state.variant.ident;
+                let ancestors = state
+                    .path
+                    .iter()
+                    .rev()
+                    .skip(1)
+                    .map(|ancestor| &ir.superstates[ancestor].variant.ident);
+                parse_quote!(
+                    #state_ident::#variant_ident { .. } => &[#(#superstate_kind_ident::#ancestors),*]
+                )
+            })
+            .collect();
+
+        introspection.push(parse_quote!(
+            /// Get the kinds of the superstates of the state, ordered from the direct
+            /// superstate of the state to the outermost superstate.
+            #visibility fn ancestors(&self) -> impl Iterator<Item = #superstate_kind_ident> {
+                let ancestors: &'static [#superstate_kind_ident] = match self {
+                    #(#ancestors_arms,)*
+                };
+                ancestors.iter().copied()
+            }
+        ));
+
+        // The constructor of every state is named after its handler.
+        let handler_name_arms: Vec<Arm> = ir
+            .states
+            .values()
+            .map(|state| {
+                let variant_ident = &state.variant.ident;
+                let handler_name = state.constructor.sig.ident.to_string();
+                parse_quote!(#state_ident::#variant_ident { .. } => #handler_name)
+            })
+            .collect();
+
+        introspection.push(parse_quote!(
+            /// Get the name of the handler function of the state, as it was written before
+            /// it was converted to the name of the variant.
+            #visibility fn handler_name(&self) -> &'static str {
+                match self {
+                    #(#handler_name_arms,)*
+                }
+            }
+        ));
+
+        let handled_events_arms: Vec<Arm> = ir
+            .states
+            .values()
+            .map(|state| {
+                let variant_ident = &state.variant.ident;
+                let events = &state.handled_events;
+                parse_quote!(#state_kind_ident::#variant_ident => &[#(#events),*])
+            })
+            .collect();
+
+        introspection.push(parse_quote!(
+            /// Get the names of the event variants that are matched on by the handler of the
+            /// given kind of state. Wildcards and bindings are reported as `"_"`.
+            #visibility fn handled_events(kind: #state_kind_ident) -> &'static [&'static str] {
+                match kind {
+                    #(#handled_events_arms,)*
+                }
+            }
+        ));
+
+        let reachable_states = &ir.state_machine.reachable_states;
+
+        introspection.push(parse_quote!(
+            /// Get the names of the states that can be reached from the initial state by
+            /// following the transitions in the handler bodies, in breadth-first order.
+            /// Only transitions written as `Transition(State::state_name(..))` can be
+            /// followed, so states that are only targeted by transitions that are created
+            /// at runtime are missing.
+            #visibility fn reachable_states() -> &'static [&'static str] {
+                &[#(#reachable_states),*]
+            }
+        ));
+    }
+
     parse_quote!(
         impl #impl_generics #state_ident #state_generics {
             #(#constructors)*
+
+            #kind
+
+            #(#introspection)*
         }
     )
 }
 
+/// Create the arms that match every state to its priority, if any of the states declares one.
+fn codegen_priority_arms(ir: &Ir) -> Option<Vec<Arm>> {
+    let state_ident = &ir.state_machine.state_ident;
+    ir.states
+        .values()
+        .any(|state| state.priority.is_some())
+        .then(|| {
+            ir.states
+                .values()
+                .map(|state| {
+                    let variant_ident = &state.variant.ident;
+                    let priority = state.priority.unwrap_or(0);
+                    parse_quote!(#state_ident::#variant_ident { .. } => #priority)
+                })
+                .collect()
+        })
+}
+
+fn codegen_state_kind(ir: &Ir) -> Option<ItemEnum> {
+    // The kinds are left out when a minimal state machine is requested.
+    if ir.state_machine.minimal {
+        return None;
+    }
+
+    let state_kind_ident = &ir.state_machine.state_kind_ident;
+    let visibility = &ir.state_machine.visibility;
+
+    // The variants are sorted by name so their indices and ordering are stable.
+    let mut variants: Vec<&Ident> = ir
+        .states
+        .values()
+        .map(|state| &state.variant.ident)
+        .collect();
+    variants.sort();
+
+    Some(parse_quote!(
+        /// The kind of a state, without its state-local storage. Kinds are ordered by the
+        /// names of their states.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #visibility enum #state_kind_ident {
+            #(#variants),*
+        }
+    ))
+}
+
+fn codegen_state_kind_impl(ir: &Ir) -> Option<ItemImpl> {
+    // Parsing kinds from their names is left out when a minimal state machine is requested.
+    if ir.state_machine.minimal {
+        return None;
+    }
+
+    let state_kind_ident = &ir.state_machine.state_kind_ident;
+    let visibility = &ir.state_machine.visibility;
+
+    let from_name_arms: Vec<Arm> = ir
+        .states
+        .values()
+        .map(|state| {
+            let variant_ident = &state.variant.ident;
+            let names = &state.names;
+            parse_quote!(#(#names)|* => Some(#state_kind_ident::#variant_ident))
+        })
+        .collect();
+    let from_name_ignore_case_checks: Vec<Expr> = ir
+        .states
+        .values()
+        .map(|state| {
+            let variant_ident = &state.variant.ident;
+            let names = &state.names;
+            parse_quote!(
+                if #(name.eq_ignore_ascii_case(#names))||* {
+                    return Some(#state_kind_ident::#variant_ident);
+                }
+            )
+        })
+        .collect();
+
+    let state_count = ir.states.len();
+    let adjacency_rows = ir
+        .state_machine
+        .adjacency
+        .iter()
+        .map(|row| quote!([#(#row),*]));
+    let transitions_into_rows = ir
+        .state_machine
+        .transitions_into
+        .iter()
+        .map(|row| quote!(&[#(#row),*]));
+
+    Some(parse_quote!(
+        impl #state_kind_ident {
+            /// The number of states.
+            #visibility const STATE_COUNT: usize = #state_count;
+
+            /// Whether a state can transition to another state in one step, indexed by
+            /// `source as usize` and `target as usize`. Only transitions written as
+            /// `Transition(State::state_name(..))` are known, so a state whose handler (or one
+            /// of its superstates' handlers) transitions to a state created at runtime is
+            /// conservatively marked as adjacent to every state.
+            #visibility const ADJACENCY: [[bool; Self::STATE_COUNT]; Self::STATE_COUNT] =
+                [#(#adjacency_rows),*];
+
+            /// Whether a state of this kind can transition to a state of the `target` kind in
+            /// one step. See [`ADJACENCY`](Self::ADJACENCY).
+            #visibility const fn can_transition_to(self, target: Self) -> bool {
+                Self::ADJACENCY[self as usize][target as usize]
+            }
+
+            /// The names of the states that can transition into a state of this kind in one
+            /// step (e.g. to find out which states can put the state machine in an error
+            /// state), sorted by name. The initial state is entered from `"<start>"`, which
+            /// comes first. States that transition to states created at runtime can reach any
+            /// state, so they collapse into a single `"<dynamic>"` entry at the end.
+            #visibility const fn transitions_into(self) -> &'static [&'static str] {
+                const TRANSITIONS_INTO: [&[&str]; #state_count] = [#(#transitions_into_rows),*];
+                TRANSITIONS_INTO[self as usize]
+            }
+
+            /// Whether `from` to `to` is a declared transition of the state machine (e.g. to
+            /// check transitions that are driven manually). Transitions from a state that can
+            /// transition to states created at runtime can not be checked and are always
+            /// considered valid.
+            #visibility const fn is_valid_transition(from: Self, to: Self) -> bool {
+                from.can_transition_to(to)
+            }
+
+            /// Panic in debug builds when `from` to `to` is not a declared transition of the
+            /// state machine. See [`is_valid_transition`](Self::is_valid_transition).
+            #[track_caller]
+            #visibility fn assert_valid_transition(from: Self, to: Self) {
+                debug_assert!(
+                    Self::is_valid_transition(from, to),
+                    "invalid transition from {:?} to {:?}",
+                    from,
+                    to
+                );
+            }
+
+            /// Get the kind of the state with the given name or alias (e.g. for selecting
+            /// the initial state from a configuration file).
+            #visibility fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#from_name_arms,)*
+                    _ => None,
+                }
+            }
+
+            /// Get the kind of the state with the given name or alias, ignoring the ASCII
+            /// case of the name.
+            #visibility fn from_name_ignore_case(name: &str) -> Option<Self> {
+                #(#from_name_ignore_case_checks)*
+                None
+            }
+        }
+    ))
+}
+
+fn codegen_state_impl_from(ir: &Ir) -> Option<ItemImpl> {
+    if !ir.state_machine.state_index {
+        return None;
+    }
+
+    let state_ident = &ir.state_machine.state_ident;
+    let (impl_generics, state_generics, _) = &ir.state_machine.state_generics.split_for_impl();
+
+    Some(parse_quote!(
+        impl #impl_generics From<&#state_ident #state_generics> for u32 {
+            /// Get the index of the state, based on the sorted names of the states.
+            fn from(state: &#state_ident #state_generics) -> Self {
+                state.kind() as u32
+            }
+        }
+    ))
+}
+
+fn codegen_state_kind_impl_from(ir: &Ir) -> Option<ItemImpl> {
+    if ir.state_machine.minimal {
+        return None;
+    }
+
+    let state_ident = &ir.state_machine.state_ident;
+    let state_kind_ident = &ir.state_machine.state_kind_ident;
+    let (impl_generics, state_generics, _) = &ir.state_machine.state_generics.split_for_impl();
+
+    Some(parse_quote!(
+        impl #impl_generics From<&#state_ident #state_generics> for #state_kind_ident {
+            /// Get the kind of the state.
+            fn from(state: &#state_ident #state_generics) -> Self {
+                state.kind()
+            }
+        }
+    ))
+}
+
+fn codegen_state_impl_try_from(ir: &Ir) -> Option<ItemImpl> {
+    if ir.state_machine.minimal {
+        return None;
+    }
+
+    let state_ident = &ir.state_machine.state_ident;
+    let (impl_generics, state_generics, _) = &ir.state_machine.state_generics.split_for_impl();
+    let state_kind_ident = &ir.state_machine.state_kind_ident;
+
+    // Only states without state-local storage can be created from their kind.
+    let mut arms: Vec<Arm> = ir
+        .states
+        .values()
+        .filter(|state| state.variant.fields.is_empty())
+        .map(|state| {
+            let variant_ident = &state.variant.ident;
+            parse_quote!(#state_kind_ident::#variant_ident => Ok(#state_ident::#variant_ident {}))
+        })
+        .collect();
+
+    if arms.len() < ir.states.len() {
+        arms.push(parse_quote!(kind => Err(kind)));
+    }
+
+    Some(parse_quote!(
+        impl #impl_generics core::convert::TryFrom<#state_kind_ident> for #state_ident #state_generics {
+            type Error = #state_kind_ident;
+
+            /// Create the state of the given kind, if it has no state-local storage.
+            fn try_from(kind: #state_kind_ident) -> Result<Self, Self::Error> {
+                match kind {
+                    #(#arms,)*
+                }
+            }
+        }
+    ))
+}
+
 fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
     let shared_storage_type = &ir.state_machine.shared_storage_type;
     let (impl_generics, _, where_clause) =
@@ -139,6 +859,7 @@ fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
     let mut constructors: Vec<ItemFn> = Vec::new();
     let mut call_handler_arms: Vec<Arm> = Vec::new();
     let mut call_entry_action_arms: Vec<Arm> = Vec::new();
+    let mut try_call_entry_action_arms: Vec<Arm> = Vec::new();
     let mut call_exit_action_arms: Vec<Arm> = Vec::new();
     let mut superstate_arms: Vec<Arm> = Vec::new();
     let mut same_state_arms: Vec<Arm> = Vec::new();
@@ -153,16 +874,32 @@ fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
         constructors.push(state.constructor.clone());
         call_handler_arms.push(parse_quote!(#pat => #handler_call));
         call_entry_action_arms.push(parse_quote!(#pat => #entry_action_call));
+        match &state.try_entry_action_call {
+            Some(try_entry_action_call) => {
+                try_call_entry_action_arms.push(parse_quote!(#pat => #try_entry_action_call))
+            }
+            None => {
+                try_call_entry_action_arms.push(parse_quote!(#pat => { #entry_action_call; true }))
+            }
+        }
         call_exit_action_arms.push(parse_quote!(#pat => #exit_action_call));
         superstate_arms.push(parse_quote!(#pat => #superstate_pat));
     }
 
     call_handler_arms.push(parse_quote!(_ => statig::Response::Super));
     call_entry_action_arms.push(parse_quote!(_ => {}));
+    try_call_entry_action_arms.push(parse_quote!(_ => true));
     call_exit_action_arms.push(parse_quote!(_ => {}));
     superstate_arms.push(parse_quote!(_ => None));
     same_state_arms.push(parse_quote!(_ => false));
 
+    let try_call_entry_action = ir
+        .states
+        .values()
+        .any(|state| state.try_entry_action_call.is_some())
+        .then(|| codegen_try_call_entry_action(ir, &try_call_entry_action_arms));
+    let carry_over_storage = codegen_carry_over_storage(ir);
+
     match ir.state_machine.mode {
         Mode::Blocking => {
             parse_quote!(
@@ -190,6 +927,8 @@ fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
                         }
                     }
 
+                    #try_call_entry_action
+
                     fn call_exit_action(
                         &mut self,
                         shared_storage: &mut #shared_storage_type,
@@ -205,6 +944,8 @@ fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
                             #(#superstate_arms),*
                         }
                     }
+
+                    #carry_over_storage
                 }
             )
         }
@@ -237,6 +978,8 @@ fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
                     })
                 }
 
+                #try_call_entry_action
+
                 fn call_exit_action<'fut>(
                     &'fut mut self,
                     shared_storage: &'fut mut #shared_storage_type,
@@ -254,11 +997,132 @@ fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
                         #(#superstate_arms),*
                     }
                 }
+
+                #carry_over_storage
+            }
+        ),
+    }
+}
+
+/// Move the storage owned by a superstate over to the target state, if it is nested
+/// within the same superstate.
+fn codegen_carry_over_storage(ir: &Ir) -> Option<ImplItemMethod> {
+    let state_ident = &ir.state_machine.state_ident;
+    let mut carry_overs: Vec<Expr> = Vec::new();
+
+    for (name, superstate) in &ir.superstates {
+        let variants: Vec<&Ident> = ir
+            .states
+            .values()
+            .filter(|state| state.path.contains(name))
+            .map(|state| &state.variant.ident)
+            .collect();
+        if superstate.owned_storage.is_empty() || variants.is_empty() {
+            continue;
+        }
+
+        let fields = &superstate.owned_storage;
+        let sources: Vec<Ident> = fields
+            .iter()
+            .map(|field| format_ident!("source_{}", field))
+            .collect();
+        let targets: Vec<Ident> = fields
+            .iter()
+            .map(|field| format_ident!("target_{}", field))
+            .collect();
+        let source_pats: Vec<Pat> = variants
+            .iter()
+            .map(|variant| parse_quote!(#state_ident::#variant { #(#fields: #sources,)* .. }))
+            .collect();
+        let target_pats: Vec<Pat> = variants
+            .iter()
+            .map(|variant| parse_quote!(#state_ident::#variant { #(#fields: #targets,)* .. }))
+            .collect();
+
+        carry_overs.push(parse_quote!(
+            if let (#(#source_pats)|*, #(#target_pats)|*) = (&mut *self, &mut *target) {
+                #(core::mem::swap(#sources, #targets);)*
+            }
+        ));
+    }
+
+    if carry_overs.is_empty() {
+        return None;
+    }
+
+    Some(parse_quote!(
+        fn carry_over_storage(&mut self, target: &mut Self) {
+            #(#carry_overs)*
+        }
+    ))
+}
+
+fn codegen_try_call_entry_action(ir: &Ir, try_call_entry_action_arms: &[Arm]) -> ImplItemMethod {
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let context_ident = &ir.state_machine.context_ident;
+
+    match ir.state_machine.mode {
+        Mode::Blocking => parse_quote!(
+            fn try_call_entry_action(
+                &mut self,
+                shared_storage: &mut #shared_storage_type,
+                #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> bool {
+                match self {
+                    #(#try_call_entry_action_arms),*
+                }
+            }
+        ),
+        Mode::Awaitable => parse_quote!(
+            fn try_call_entry_action<'fut>(
+                &'fut mut self,
+                shared_storage: &'fut mut #shared_storage_type,
+                #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = bool> + 'fut + Send>> {
+                Box::pin(async move {
+                    match self {
+                        #(#try_call_entry_action_arms),*
+                    }
+                })
             }
         ),
     }
 }
 
+fn codegen_response_alias(ir: &Ir) -> Option<ItemType> {
+    let response_ident = ir.state_machine.response_ident.as_ref()?;
+    let state_ident = &ir.state_machine.state_ident;
+    let (_, state_generics, _) = &ir.state_machine.state_generics.split_for_impl();
+    let visibility = &ir.state_machine.visibility;
+
+    // Bounds on type aliases are not enforced, so they are left out.
+    let mut alias_generics: Generics = ir.state_machine.state_generics.clone();
+    alias_generics.where_clause = None;
+    for param in alias_generics.params.iter_mut() {
+        match param {
+            GenericParam::Type(param) => {
+                param.colon_token = None;
+                param.bounds.clear();
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Lifetime(param) => {
+                param.colon_token = None;
+                param.bounds.clear();
+            }
+            GenericParam::Const(param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+        }
+    }
+
+    Some(parse_quote!(
+        /// The response returned by the handlers of the state machine.
+        #visibility type #response_ident #alias_generics = statig::Response<#state_ident #state_generics>;
+    ))
+}
+
 fn codegen_superstate(ir: &Ir) -> ItemEnum {
     let superstate_ident = &ir.state_machine.superstate_ident;
     let (superstate_generics, _, _) = &ir.state_machine.superstate_generics.split_for_impl();
@@ -279,16 +1143,77 @@ fn codegen_superstate(ir: &Ir) -> ItemEnum {
     )
 }
 
-fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
-    let shared_storage_type = &ir.state_machine.shared_storage_type;
-    let mut shared_storage_generics = ir.state_machine.shared_storage_generics.clone();
-    let lifetime = Lifetime::new(SUPERSTATE_LIFETIME, Span::call_site());
-    let superstate_lifetime_def = LifetimeDef::new(lifetime.clone());
-    let superstate_lifetime_param = GenericParam::Lifetime(superstate_lifetime_def);
-    shared_storage_generics
-        .params
-        .push(superstate_lifetime_param);
-    match &mut shared_storage_generics.where_clause {
+fn codegen_superstate_impl(ir: &Ir) -> Option<ItemImpl> {
+    // The names of the superstates are only used for introspection.
+    if ir.state_machine.minimal {
+        return None;
+    }
+
+    let superstate_ident = &ir.state_machine.superstate_ident;
+    let (impl_generics, superstate_generics, where_clause) =
+        &ir.state_machine.superstate_generics.split_for_impl();
+    let visibility = &ir.state_machine.visibility;
+
+    let name_arms: Vec<Arm> = ir
+        .superstates
+        .values()
+        .map(|superstate| {
+            let variant_ident = &superstate.variant.ident;
+            let name = variant_ident.to_string();
+            parse_quote!(#superstate_ident::#variant_ident { .. } => #name)
+        })
+        .collect();
+
+    // The superstate enum can be empty, so the match is on the dereferenced value.
+    Some(parse_quote!(
+        impl #impl_generics #superstate_ident #superstate_generics #where_clause {
+            /// Get the name of the variant of the superstate (e.g. `"Blinking"`), which is
+            /// cheaper than formatting the superstate with `Debug`.
+            #visibility const fn name(&self) -> &'static str {
+                match *self {
+                    #(#name_arms,)*
+                }
+            }
+        }
+    ))
+}
+
+fn codegen_superstate_kind(ir: &Ir) -> Option<ItemEnum> {
+    // The superstate kinds are only used for introspection.
+    if ir.state_machine.minimal {
+        return None;
+    }
+
+    let superstate_kind_ident = &ir.state_machine.superstate_kind_ident;
+    let visibility = &ir.state_machine.visibility;
+
+    let mut variants: Vec<&Ident> = ir
+        .superstates
+        .values()
+        .map(|superstate| &superstate.variant.ident)
+        .collect();
+    variants.sort();
+
+    Some(parse_quote!(
+        /// The kind of a superstate, without the state-local storage it borrows. Kinds are
+        /// ordered by the names of their superstates.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #visibility enum #superstate_kind_ident {
+            #(#variants),*
+        }
+    ))
+}
+
+fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let mut shared_storage_generics = ir.state_machine.shared_storage_generics.clone();
+    let lifetime = ir.state_machine.superstate_lifetime.clone();
+    let superstate_lifetime_def = LifetimeDef::new(lifetime.clone());
+    let superstate_lifetime_param = GenericParam::Lifetime(superstate_lifetime_def);
+    shared_storage_generics
+        .params
+        .push(superstate_lifetime_param);
+    match &mut shared_storage_generics.where_clause {
         Some(clause) => clause.predicates.push(parse_quote!(Self: #lifetime)),
         None => shared_storage_generics.where_clause = parse_quote!(where Self: #lifetime),
     }
@@ -300,7 +1225,9 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
 
     let mut call_handler_arms: Vec<Arm> = Vec::new();
     let mut call_entry_action_arms: Vec<Arm> = Vec::new();
+    let mut try_call_entry_action_arms: Vec<Arm> = Vec::new();
     let mut call_exit_action_arms: Vec<Arm> = Vec::new();
+    let mut call_pre_dispatch_arms: Vec<Arm> = Vec::new();
     let mut superstate_arms: Vec<Arm> = Vec::new();
     let mut same_state_arms: Vec<Arm> = Vec::new();
 
@@ -309,20 +1236,38 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
         let handler_call = &state.handler_call;
         let entry_action_call = &state.entry_action_call;
         let exit_action_call = &state.exit_action_call;
+        let pre_dispatch_call = &state.pre_dispatch_call;
         let superstate_pat = &state.superstate_pat;
 
         call_handler_arms.push(parse_quote!(#pat => #handler_call));
         call_entry_action_arms.push(parse_quote!(#pat => #entry_action_call));
+        match &state.try_entry_action_call {
+            Some(try_entry_action_call) => {
+                try_call_entry_action_arms.push(parse_quote!(#pat => #try_entry_action_call))
+            }
+            None => {
+                try_call_entry_action_arms.push(parse_quote!(#pat => { #entry_action_call; true }))
+            }
+        }
         call_exit_action_arms.push(parse_quote!(#pat => #exit_action_call));
+        call_pre_dispatch_arms.push(parse_quote!(#pat => #pre_dispatch_call));
         superstate_arms.push(parse_quote!(#pat => #superstate_pat));
     }
 
     call_handler_arms.push(parse_quote!(_ => statig::Response::Super));
     call_entry_action_arms.push(parse_quote!(_ => {}));
+    try_call_entry_action_arms.push(parse_quote!(_ => true));
     call_exit_action_arms.push(parse_quote!(_ => {}));
+    call_pre_dispatch_arms.push(parse_quote!(_ => statig::Response::Super));
     superstate_arms.push(parse_quote!(_ => None));
     same_state_arms.push(parse_quote!(_ => false));
 
+    let try_call_entry_action = ir
+        .superstates
+        .values()
+        .any(|superstate| superstate.try_entry_action_call.is_some())
+        .then(|| codegen_try_call_entry_action(ir, &try_call_entry_action_arms));
+
     match ir.state_machine.mode {
         Mode::Blocking => {
             parse_quote!(
@@ -350,6 +1295,8 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
                         }
                     }
 
+                    #try_call_entry_action
+
                     fn call_exit_action(
                         &mut self,
                         shared_storage: &mut #shared_storage_type,
@@ -360,6 +1307,17 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
                         }
                     }
 
+                    fn call_pre_dispatch(
+                        &mut self,
+                        shared_storage: &mut #shared_storage_type,
+                        #event_ident: &<#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
+                        #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+                    ) -> statig::Response<<#shared_storage_type as statig::IntoStateMachine>::State> {
+                        match self {
+                            #(#call_pre_dispatch_arms),*
+                        }
+                    }
+
                     fn superstate(&mut self) -> Option<<#shared_storage_type as statig::IntoStateMachine>::Superstate<'_>> {
                         match self {
                             #(#superstate_arms),*
@@ -398,6 +1356,8 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
                         })
                     }
 
+                    #try_call_entry_action
+
                     fn call_exit_action<'fut>(
                         &'fut mut self,
                         shared_storage: &'fut mut #shared_storage_type,
@@ -410,6 +1370,19 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
                         })
                     }
 
+                    fn call_pre_dispatch<'fut>(
+                        &'fut mut self,
+                        shared_storage: &'fut mut #shared_storage_type,
+                        #event_ident: &'fut <#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
+                        #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+                    ) -> core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = statig::Response<<#shared_storage_type as statig::IntoStateMachine>::State>> + 'fut + Send>> {
+                        Box::pin(async move {
+                            match self {
+                                #(#call_pre_dispatch_arms),*
+                            }
+                        })
+                    }
+
                     fn superstate(&mut self) -> Option<<#shared_storage_type as statig::IntoStateMachine>::Superstate<'_>> {
                         match self {
                             #(#superstate_arms),*
@@ -420,3 +1393,753 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
         }
     }
 }
+
+fn codegen_validate(ir: &Ir) -> Option<ItemImpl> {
+    if !ir.state_machine.validate {
+        return None;
+    }
+
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+    let state_ident = &ir.state_machine.state_ident;
+    let visibility = &ir.state_machine.visibility;
+
+    let mode = match ir.state_machine.mode {
+        Mode::Blocking => quote!(blocking),
+        Mode::Awaitable => quote!(awaitable),
+    };
+
+    // A hierarchy can never be deeper than the amount of superstates plus the leaf state.
+    let max_depth = ir.superstates.len() + 1;
+
+    let mut superstate_initial_states: Vec<_> = ir
+        .superstates
+        .values()
+        .filter_map(|superstate| superstate.initial_state_constructor.as_ref())
+        .map(|constructor| constructor.sig.ident.clone())
+        .collect();
+    superstate_initial_states.sort();
+    let superstate_names = superstate_initial_states
+        .iter()
+        .map(|superstate| superstate.to_string());
+
+    Some(parse_quote!(
+        impl #impl_generics #shared_storage_type #where_clause {
+            /// Check the invariants of the state machine that can only be verified at runtime.
+            #[allow(unused)]
+            #visibility fn validate() -> core::result::Result<(), statig::__alloc::Vec<statig::__alloc::String>> {
+                use statig::#mode::StateExt as _;
+
+                let mut errors = statig::__alloc::Vec::new();
+
+                let mut initial_state: <Self as statig::IntoStateMachine>::State =
+                    <Self as statig::IntoStateMachine>::INITIAL;
+                if initial_state.bounded_depth(#max_depth).is_none() {
+                    errors.push(statig::__alloc::String::from(
+                        "the superstates of the initial state form a cycle",
+                    ));
+                }
+
+                #(
+                    let mut initial_state: <Self as statig::IntoStateMachine>::State =
+                        #state_ident::#superstate_initial_states();
+                    if initial_state.bounded_depth(#max_depth).is_none() {
+                        errors.push(statig::__alloc::format!(
+                            "the superstates of the initial state of `{}` form a cycle",
+                            #superstate_names
+                        ));
+                    }
+                )*
+
+                match errors.is_empty() {
+                    true => Ok(()),
+                    false => Err(errors),
+                }
+            }
+        }
+    ))
+}
+
+fn codegen_json(ir: &Ir) -> Option<ItemImpl> {
+    let json = ir.state_machine.json.as_ref()?;
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+    let visibility = &ir.state_machine.visibility;
+
+    Some(parse_quote!(
+        impl #impl_generics #shared_storage_type #where_clause {
+            /// JSON description of the states, superstates, transitions and actions of the
+            /// state machine.
+            #visibility const MACHINE_JSON: &str = #json;
+        }
+    ))
+}
+
+fn codegen_describe(ir: &Ir) -> Option<ItemImpl> {
+    let description = ir.state_machine.describe.as_ref()?;
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+    let visibility = &ir.state_machine.visibility;
+
+    Some(parse_quote!(
+        impl #impl_generics #shared_storage_type #where_clause {
+            /// Human-readable summary of the state machine, with its name, mode, number of
+            /// states and superstates and initial state.
+            #visibility fn describe() -> statig::__alloc::String {
+                statig::__alloc::String::from(#description)
+            }
+        }
+    ))
+}
+
+fn codegen_graphviz(ir: &Ir) -> Option<ItemImpl> {
+    let graphviz = ir.state_machine.graphviz.as_ref()?;
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+    let visibility = &ir.state_machine.visibility;
+
+    Some(parse_quote!(
+        impl #impl_generics #shared_storage_type #where_clause {
+            /// Graphviz description of the state hierarchy in the DOT format, with a
+            /// cluster for every superstate.
+            #visibility const fn graphviz() -> &'static str {
+                #graphviz
+            }
+        }
+    ))
+}
+
+fn codegen_transition_weights(ir: &Ir) -> Option<ItemImpl> {
+    let transition_weights = &ir.state_machine.transition_weights;
+    if transition_weights.is_empty() {
+        return None;
+    }
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+    let visibility = &ir.state_machine.visibility;
+
+    let entries = transition_weights.iter().map(|(from, to, weight)| {
+        let from = LitStr::new(&from.to_string(), from.span());
+        let to = LitStr::new(&to.to_string(), to.span());
+        quote!((#from, #to, #weight))
+    });
+
+    Some(parse_quote!(
+        impl #impl_generics #shared_storage_type #where_clause {
+            /// The weights of the transitions declared with `#[transition(to = "..", weight = ..)]`
+            /// as `(from, to, weight)`, for use by external simulators. The state machine does
+            /// not use them itself, and they are not checked to sum to one.
+            #visibility const fn transition_weights() -> &'static [(&'static str, &'static str, f64)] {
+                &[#(#entries),*]
+            }
+        }
+    ))
+}
+
+fn codegen_facade(ir: &Ir) -> Option<TokenStream> {
+    let facade_ident = ir.state_machine.facade_ident.as_ref()?;
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, type_generics, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+    let visibility = &ir.state_machine.visibility;
+
+    // Without a context the events can be handled without passing one in.
+    let unit: Type = parse_quote!(());
+    let context_type = quote!(<#shared_storage_type as statig::IntoStateMachine>::Context<'_>);
+    let (context_arg, fut_context_arg, handle) = match ir.state_machine.context_type == unit {
+        true => (quote!(), quote!(), quote!(handle(&event))),
+        false => (
+            quote!(, context: &mut #context_type),
+            quote!(, context: &'fut mut #context_type),
+            quote!(handle_with_context(&event, context)),
+        ),
+    };
+
+    let (mode, signature, body) = match ir.state_machine.mode {
+        Mode::Blocking => (
+            quote!(blocking),
+            quote!(fn on_event(
+                &mut self,
+                event: <#shared_storage_type as statig::IntoStateMachine>::Event<'_>
+                #context_arg
+            )),
+            quote!(self.#handle),
+        ),
+        Mode::Awaitable => (
+            quote!(awaitable),
+            quote!(fn on_event<'fut>(
+                &'fut mut self,
+                event: <#shared_storage_type as statig::IntoStateMachine>::Event<'fut>
+                #fut_context_arg
+            ) -> core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>>),
+            quote!(Box::pin(async move { self.#handle.await })),
+        ),
+    };
+
+    Some(quote!(
+        /// Facade for handling events, implemented by the state machines so consumers can
+        /// depend on it instead of on the concrete type.
+        #visibility trait #facade_ident #impl_generics #where_clause {
+            /// Handle the event.
+            #signature;
+        }
+
+        impl #impl_generics #facade_ident #type_generics for statig::#mode::StateMachine<#shared_storage_type> #where_clause {
+            #signature {
+                #body
+            }
+        }
+
+        impl #impl_generics #facade_ident #type_generics for statig::#mode::InitializedStateMachine<#shared_storage_type> #where_clause {
+            #signature {
+                #body
+            }
+        }
+    ))
+}
+
+fn codegen_diff(ir: &Ir) -> Option<ItemImpl> {
+    if !ir.state_machine.diff {
+        return None;
+    }
+
+    let state_ident = &ir.state_machine.state_ident;
+    let (impl_generics, state_generics, _) = &ir.state_machine.state_generics.split_for_impl();
+    let visibility = &ir.state_machine.visibility;
+
+    let mut arms: Vec<Arm> = ir
+        .states
+        .values()
+        .map(|state| {
+            let variant_ident = &state.variant.ident;
+            let fields: Vec<&Ident> = state
+                .variant
+                .fields
+                .iter()
+                .filter_map(|field| field.ident.as_ref())
+                .collect();
+            let names: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+            let sources: Vec<Ident> = fields
+                .iter()
+                .map(|field| format_ident!("source_{}", field))
+                .collect();
+            let targets: Vec<Ident> = fields
+                .iter()
+                .map(|field| format_ident!("target_{}", field))
+                .collect();
+            if fields.is_empty() {
+                return parse_quote!(
+                    (#state_ident::#variant_ident {}, #state_ident::#variant_ident {}) => {
+                        statig::__alloc::Vec::new()
+                    }
+                );
+            }
+            parse_quote!(
+                (
+                    #state_ident::#variant_ident { #(#fields: #sources,)* },
+                    #state_ident::#variant_ident { #(#fields: #targets,)* },
+                ) => {
+                    let mut diff = statig::__alloc::Vec::new();
+                    #(
+                        let target = statig::__alloc::format!("{:?}", #targets);
+                        if statig::__alloc::format!("{:?}", #sources) != target {
+                            diff.push((#names, target));
+                        }
+                    )*
+                    diff
+                }
+            )
+        })
+        .collect();
+
+    if ir.states.len() > 1 {
+        arms.push(parse_quote!(
+            _ => statig::__alloc::vec![("variant", statig::__alloc::format!("{:?}", other.kind()))]
+        ));
+    }
+
+    Some(parse_quote!(
+        impl #impl_generics #state_ident #state_generics {
+            /// List the fields that differ between this state and `other`, with the `Debug`
+            /// representation of their value in `other`. When `other` is a different kind of
+            /// state, a single `"variant"` entry with the kind of `other` is returned
+            /// instead.
+            #visibility fn diff(&self, other: &Self) -> statig::__alloc::Vec<(&'static str, statig::__alloc::String)> {
+                match (self, other) {
+                    #(#arms,)*
+                }
+            }
+        }
+    ))
+}
+
+fn codegen_send_sync(ir: &Ir) -> Option<TokenStream> {
+    if !ir.state_machine.send_sync {
+        return None;
+    }
+
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+
+    // Every state-local field is checked on its own, so a failing assertion points at the
+    // type of the field that is not `Send` or `Sync`.
+    let field_assertions: Vec<TokenStream> = ir
+        .states
+        .values()
+        .flat_map(|state| &state.variant.fields)
+        .map(|field| {
+            let ty = &field.ty;
+            quote_spanned!(ty.span()=> assert_send_sync::<#ty>();)
+        })
+        .collect();
+
+    Some(quote!(
+        const _: () = {
+            #[allow(dead_code)]
+            fn assert_send_sync<T: Send + Sync>() {}
+
+            #[allow(dead_code)]
+            fn assert_state_machine_send_sync #impl_generics () #where_clause {
+                #(#field_assertions)*
+                assert_send_sync::<#shared_storage_type>();
+            }
+        };
+    ))
+}
+
+fn codegen_replay(ir: &Ir) -> Option<ItemImpl> {
+    if !ir.state_machine.replay {
+        return None;
+    }
+
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+    let state_kind_ident = &ir.state_machine.state_kind_ident;
+    let visibility = &ir.state_machine.visibility;
+    let bounds = codegen_helper_bounds(ir);
+
+    let (mode, asyncness, awaiting) = match ir.state_machine.mode {
+        Mode::Blocking => (quote!(blocking), quote!(), quote!()),
+        Mode::Awaitable => (quote!(awaitable), quote!(async), quote!(.await)),
+    };
+
+    // Without a context the events can be replayed without passing one in.
+    let unit: Type = parse_quote!(());
+    let replay = (ir.state_machine.context_type == unit).then(|| {
+        quote!(
+            /// Create a state machine, handle all the given events and return the kind of
+            /// the state it ends up in.
+            #visibility #asyncness fn replay<'evt>(
+                self,
+                events: impl IntoIterator<Item = &'evt <Self as statig::IntoStateMachine>::Event<'evt>>,
+            ) -> #state_kind_ident #bounds {
+                self.replay_with_context(events, &mut ()) #awaiting
+            }
+        )
+    });
+
+    Some(parse_quote!(
+        impl #impl_generics #shared_storage_type #where_clause {
+            #replay
+
+            /// Create a state machine, handle all the given events with the given context
+            /// and return the kind of the state it ends up in.
+            #visibility #asyncness fn replay_with_context<'evt>(
+                self,
+                events: impl IntoIterator<Item = &'evt <Self as statig::IntoStateMachine>::Event<'evt>>,
+                context: &mut <Self as statig::IntoStateMachine>::Context<'_>,
+            ) -> #state_kind_ident #bounds {
+                let mut state_machine = statig::#mode::IntoStateMachineExt::state_machine(self);
+                state_machine.init_with_context(context) #awaiting;
+                for event in events {
+                    state_machine.handle_with_context(event, context) #awaiting;
+                }
+                state_machine.state().kind()
+            }
+        }
+    ))
+}
+
+fn codegen_apply_event_log(ir: &Ir) -> Option<ItemImpl> {
+    if !ir.state_machine.apply_event_log {
+        return None;
+    }
+
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+    let state_kind_ident = &ir.state_machine.state_kind_ident;
+    let visibility = &ir.state_machine.visibility;
+    let bounds = codegen_helper_bounds(ir);
+
+    let (mode, asyncness, awaiting) = match ir.state_machine.mode {
+        Mode::Blocking => (quote!(blocking), quote!(), quote!()),
+        Mode::Awaitable => (quote!(awaitable), quote!(async), quote!(.await)),
+    };
+
+    // Without a context the log can be applied without passing one in.
+    let unit: Type = parse_quote!(());
+    let apply_event_log = (ir.state_machine.context_type == unit).then(|| {
+        quote!(
+            /// Create a state machine and handle the events of the log one by one, checking
+            /// that it ends up in the expected kind of state after each of them. Returns the
+            /// index of the first entry where it did not, together with the kind of the
+            /// state it was in instead.
+            #visibility #asyncness fn apply_event_log<'evt>(
+                self,
+                log: &'evt [(<Self as statig::IntoStateMachine>::Event<'evt>, #state_kind_ident)],
+            ) -> core::result::Result<(), (usize, #state_kind_ident)> #bounds {
+                self.apply_event_log_with_context(log, &mut ()) #awaiting
+            }
+        )
+    });
+
+    Some(parse_quote!(
+        impl #impl_generics #shared_storage_type #where_clause {
+            #apply_event_log
+
+            /// Create a state machine and handle the events of the log one by one with the
+            /// given context, checking that it ends up in the expected kind of state after
+            /// each of them. Returns the index of the first entry where it did not, together
+            /// with the kind of the state it was in instead.
+            #visibility #asyncness fn apply_event_log_with_context<'evt>(
+                self,
+                log: &'evt [(<Self as statig::IntoStateMachine>::Event<'evt>, #state_kind_ident)],
+                context: &mut <Self as statig::IntoStateMachine>::Context<'_>,
+            ) -> core::result::Result<(), (usize, #state_kind_ident)> #bounds {
+                let mut state_machine = statig::#mode::IntoStateMachineExt::state_machine(self);
+                state_machine.init_with_context(context) #awaiting;
+                for (index, (event, expected)) in log.iter().enumerate() {
+                    state_machine.handle_with_context(event, context) #awaiting;
+                    let actual = state_machine.state().kind();
+                    if actual != *expected {
+                        return Err((index, actual));
+                    }
+                }
+                Ok(())
+            }
+        }
+    ))
+}
+
+/// The bounds that a generated helper which drives an awaitable state machine needs, as those
+/// are not implied by the impl block.
+fn codegen_helper_bounds(ir: &Ir) -> Option<WhereClause> {
+    match ir.state_machine.mode {
+        Mode::Blocking => None,
+        Mode::Awaitable => Some(parse_quote!(
+            where
+                Self: Send,
+                <Self as statig::IntoStateMachine>::State: Send,
+                for<'any> <Self as statig::IntoStateMachine>::Superstate<'any>: Send,
+                for<'any> <Self as statig::IntoStateMachine>::Event<'any>: Send + Sync,
+                for<'any> <Self as statig::IntoStateMachine>::Context<'any>: Send + Sync
+        )),
+    }
+}
+
+fn codegen_deref_storage(ir: &Ir) -> Option<ItemImpl> {
+    if !ir.state_machine.deref_storage {
+        return None;
+    }
+
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+
+    Some(parse_quote!(
+        impl #impl_generics statig::DerefStorageMut for #shared_storage_type #where_clause {}
+    ))
+}
+
+fn codegen_box_futures(ir: &Ir) -> Option<ItemImpl> {
+    if !ir.state_machine.box_futures {
+        return None;
+    }
+
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+
+    Some(parse_quote!(
+        impl #impl_generics statig::awaitable::BoxFutures for #shared_storage_type #where_clause {}
+    ))
+}
+
+fn codegen_serialize_path(ir: &Ir) -> Option<ItemImpl> {
+    if !ir.state_machine.serialize_path {
+        return None;
+    }
+
+    let state_ident = &ir.state_machine.state_ident;
+    let (impl_generics, state_generics, _) = &ir.state_machine.state_generics.split_for_impl();
+    let state_kind_ident = &ir.state_machine.state_kind_ident;
+    let visibility = &ir.state_machine.visibility;
+
+    // The bytes are the indices of the kinds, which follow the sorted names of the variants.
+    let mut state_variants: Vec<&Ident> = ir
+        .states
+        .values()
+        .map(|state| &state.variant.ident)
+        .collect();
+    state_variants.sort();
+    let mut superstate_variants: Vec<&Ident> = ir
+        .superstates
+        .values()
+        .map(|superstate| &superstate.variant.ident)
+        .collect();
+    superstate_variants.sort();
+    let index = |variants: &[&Ident], ident: &Ident| {
+        variants
+            .iter()
+            .position(|variant| *variant == ident)
+            .unwrap() as u8
+    };
+
+    let (serialize_arms, restore_arms): (Vec<Arm>, Vec<Arm>) = ir
+        .states
+        .values()
+        .map(|state| {
+            let variant_ident = &state.variant.ident;
+            let mut bytes = vec![index(&state_variants, variant_ident)];
+            bytes.extend(state.path.iter().rev().skip(1).map(|ancestor| {
+                index(
+                    &superstate_variants,
+                    &ir.superstates[ancestor].variant.ident,
+                )
+            }));
+            (
+                parse_quote!(#state_ident::#variant_ident { .. } => statig::__alloc::vec![#(#bytes),*]),
+                parse_quote!(
+                    [#(#bytes),*] => <Self as core::convert::TryFrom<#state_kind_ident>>::try_from(
+                        #state_kind_ident::#variant_ident
+                    ).map_err(Some)
+                ),
+            )
+        })
+        .unzip();
+
+    Some(parse_quote!(
+        impl #impl_generics #state_ident #state_generics {
+            /// Serialize the kind of the state followed by the kinds of its superstates, from
+            /// the direct superstate to the outermost one, as one byte per kind. The
+            /// state-local storage is left out.
+            #visibility fn serialize_path(&self) -> statig::__alloc::Vec<u8> {
+                match self {
+                    #(#serialize_arms,)*
+                }
+            }
+
+            /// Restore the state from the bytes created by
+            /// [`serialize_path`](Self::serialize_path). Fails with `None` when the bytes
+            /// are not the path of any state, and with the kind of the state when it has
+            /// state-local storage and can therefore not be created from its kind.
+            #visibility fn restore_path(
+                bytes: &[u8],
+            ) -> core::result::Result<Self, core::option::Option<#state_kind_ident>> {
+                match bytes {
+                    #(#restore_arms,)*
+                    _ => Err(None),
+                }
+            }
+        }
+    ))
+}
+
+fn codegen_fired_actions(ir: &Ir) -> Option<ItemImpl> {
+    let field = ir.state_machine.fired_actions.as_ref()?;
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+
+    Some(parse_quote!(
+        impl #impl_generics statig::RecordFiredActions for #shared_storage_type #where_clause {
+            fn fired_actions(&mut self) -> &mut statig::FiredActions {
+                &mut self.#field
+            }
+        }
+    ))
+}
+
+fn codegen_handler_error(ir: &Ir) -> Option<ItemImpl> {
+    let (field, error_type) = ir.state_machine.handler_error.as_ref()?;
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+
+    Some(parse_quote!(
+        impl #impl_generics statig::StoreHandlerError for #shared_storage_type #where_clause {
+            type Error = #error_type;
+
+            fn handler_error(&mut self) -> &mut Option<Self::Error> {
+                &mut self.#field
+            }
+        }
+    ))
+}
+
+/// Proc macros can not emit warnings on stable, so every hint uses a deprecated item
+/// instead, spanned at the second state so the warning points at it.
+fn codegen_merge_hints(ir: &Ir) -> Vec<TokenStream> {
+    ir.state_machine
+        .mergeable_states
+        .iter()
+        .map(|(first, second)| {
+            let note = format!(
+                "the states `{first}` and `{second}` have identical handlers, consider merging \
+                 them or sharing a handler"
+            );
+            quote_spanned!(second.span() =>
+                const _: () = {
+                    #[deprecated(note = #note)]
+                    struct MergeableStates;
+                    let _ = MergeableStates;
+                };
+            )
+        })
+        .collect()
+}
+
+/// Warn about transition hooks for a transition that is never performed by the handlers,
+/// spanned at the state that is transitioned from.
+fn codegen_transition_hook_hints(ir: &Ir) -> Vec<TokenStream> {
+    ir.state_machine
+        .transition_hooks
+        .iter()
+        .filter(|hook| !hook.occurs)
+        .map(|hook| {
+            let (from, to) = (&hook.from, &hook.to);
+            let note = format!(
+                "the transition from `{from}` to `{to}` never occurs, so its hook is never called"
+            );
+            quote_spanned!(from.span() =>
+                const _: () = {
+                    #[deprecated(note = #note)]
+                    struct UnusedTransitionHook;
+                    let _ = UnusedTransitionHook;
+                };
+            )
+        })
+        .collect()
+}
+
+fn codegen_transition_to(ir: &Ir) -> Option<ItemImpl> {
+    if !ir.state_machine.transition_to {
+        return None;
+    }
+
+    let shared_storage_type = &ir.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        &ir.state_machine.shared_storage_generics.split_for_impl();
+    let visibility = &ir.state_machine.visibility;
+
+    Some(parse_quote!(
+        impl #impl_generics #shared_storage_type #where_clause {
+            /// Create a response that transitions to the given state. The transition is
+            /// not performed immediately, it is executed by the state machine once the
+            /// handler returns the response.
+            #[allow(unused)]
+            #visibility fn transition_to(
+                &self,
+                target: <Self as statig::IntoStateMachine>::State,
+            ) -> statig::Response<<Self as statig::IntoStateMachine>::State> {
+                statig::Response::Transition(target)
+            }
+        }
+    ))
+}
+
+#[test]
+fn test_minimal_codegen() {
+    use crate::analyze::analyze;
+    use crate::lower::lower;
+
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on() -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let full = codegen(lower(&analyze(
+        vec![parse_quote!(initial = "State::on()")],
+        item_impl.clone(),
+    )))
+    .to_string();
+    let minimal = codegen(lower(&analyze(
+        vec![parse_quote!(initial = "State::on()"), parse_quote!(minimal)],
+        item_impl,
+    )))
+    .to_string();
+
+    assert!(full.contains("state_path"));
+    assert!(!minimal.contains("state_path"));
+    assert!(full.contains("fn name"));
+    assert!(!minimal.contains("fn name"));
+    assert!(!minimal.contains("STATE_NAME"));
+    assert!(full.contains("StateKind"));
+    assert!(!minimal.contains("StateKind"));
+    assert!(!minimal.contains("fn kind"));
+    assert!(!minimal.contains("TryFrom"));
+    assert!(full.contains("MAX_DEPTH"));
+    assert!(!minimal.contains("MAX_DEPTH"));
+    assert!(full.contains("DEBUG_STORAGE"));
+    assert!(!minimal.contains("DEBUG_STORAGE"));
+    assert!(!minimal.contains("transition_to"));
+    assert!(!minimal.contains("for u32"));
+    assert!(minimal.len() < full.len());
+}
+
+#[test]
+fn test_priority_codegen() {
+    use crate::analyze::analyze;
+    use crate::lower::lower;
+
+    let without_priority = codegen(lower(&analyze(
+        vec![parse_quote!(initial = "State::on()")],
+        parse_quote!(
+            impl Blinky {
+                #[state]
+                fn on() -> Response<State> {
+                    Response::Handled
+                }
+            }
+        ),
+    )))
+    .to_string();
+
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(priority = 2)]
+            fn on() -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+    let with_priority = codegen(lower(&analyze(
+        vec![parse_quote!(initial = "State::on()")],
+        item_impl.clone(),
+    )))
+    .to_string();
+    let minimal = codegen(lower(&analyze(
+        vec![parse_quote!(initial = "State::on()"), parse_quote!(minimal)],
+        item_impl,
+    )))
+    .to_string();
+
+    assert!(!without_priority.contains("fn priority"));
+    assert!(!without_priority.contains("STATE_PRIORITY"));
+    assert!(with_priority.contains("fn priority"));
+    assert!(with_priority.contains("STATE_PRIORITY"));
+    assert!(!minimal.contains("fn priority"));
+    assert!(minimal.contains("STATE_PRIORITY"));
+}
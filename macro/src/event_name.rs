@@ -0,0 +1,76 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use syn::{parse_quote, Arm, Data, DeriveInput};
+
+/// Implement `EventName` for an enum by returning the name of the variant, or for a struct
+/// by returning the name of the struct.
+pub fn derive_event_name(input: DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Enum(data) => {
+            let arms: Vec<Arm> = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let variant_ident = &variant.ident;
+                    let name = variant_ident.to_string();
+                    parse_quote!(Self::#variant_ident { .. } => #name)
+                })
+                .collect();
+            quote!(
+                match *self {
+                    #(#arms),*
+                }
+            )
+        }
+        Data::Struct(_) => {
+            let name = ident.to_string();
+            quote!(#name)
+        }
+        Data::Union(_) => abort!(
+            ident,
+            "`EventName` can not be derived for unions";
+            help = "use an enum or a struct for the event"
+        ),
+    };
+
+    quote!(
+        impl #impl_generics statig::EventName for #ident #type_generics #where_clause {
+            fn event_name(&self) -> &'static str {
+                #body
+            }
+        }
+    )
+}
+
+#[test]
+fn test_derive_event_name_for_enum() {
+    let input: DeriveInput = parse_quote!(
+        enum Event {
+            TimerElapsed,
+            ButtonPressed(u8),
+            Moved { x: i32 },
+        }
+    );
+
+    let rust = derive_event_name(input).to_string();
+
+    assert!(rust.contains("Self :: TimerElapsed { .. } => \"TimerElapsed\""));
+    assert!(rust.contains("Self :: ButtonPressed { .. } => \"ButtonPressed\""));
+    assert!(rust.contains("Self :: Moved { .. } => \"Moved\""));
+}
+
+#[test]
+#[should_panic]
+fn test_derive_event_name_for_union() {
+    let input: DeriveInput = parse_quote!(
+        union Event {
+            code: u8,
+        }
+    );
+
+    derive_event_name(input);
+}
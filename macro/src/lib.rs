@@ -7,12 +7,14 @@ use proc_macro_error::proc_macro_error;
 
 mod analyze;
 mod codegen;
+mod event_name;
 mod lower;
 mod parse;
 mod visitors;
 
 use analyze::analyze;
 use codegen::codegen;
+use event_name::derive_event_name;
 use lower::lower;
 use parse::{parse_args, parse_input};
 
@@ -45,3 +47,10 @@ pub fn superstate(_: TokenStream, input: TokenStream) -> TokenStream {
 pub fn action(_: TokenStream, input: TokenStream) -> TokenStream {
     input
 }
+
+#[proc_macro_error]
+#[proc_macro_derive(EventName)]
+pub fn event_name(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    derive_event_name(input).into()
+}
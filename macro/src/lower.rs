@@ -1,22 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::ops::Deref;
 
-use proc_macro2::Span;
 use proc_macro_error::abort;
 
 use syn::parse::Parser;
 use syn::parse_quote;
 use syn::{
-    Expr, ExprCall, Field, FnArg, GenericParam, Generics, Ident, ItemFn, ItemImpl, Lifetime, Pat,
-    PatType, Path, Type, Variant, Visibility, WhereClause, WherePredicate,
+    Expr, ExprCall, Field, FnArg, GenericParam, Generics, Ident, ItemFn, ItemImpl, Lifetime,
+    LitFloat, LitStr, Meta, Pat, PatType, Path, Type, Variant, Visibility, WhereClause,
+    WherePredicate,
 };
 
-use quote::format_ident;
+use quote::{format_ident, quote, ToTokens};
 
 use crate::analyze;
 use crate::analyze::Model;
 use crate::visitors::{GenericParamVisitor, LifetimeVisitor};
-use crate::SUPERSTATE_LIFETIME;
 
 /// Intermediate representation of the state machine.
 #[cfg_attr(test, derive(Debug, Eq, PartialEq))]
@@ -48,24 +47,105 @@ pub struct StateMachine {
     pub state_ident: Ident,
     /// Derives that will be applied on the state type.
     pub state_derives: Vec<Path>,
+    /// The representation of the state type (e.g. `C`), if any.
+    pub state_repr: Vec<Ident>,
     /// The generics associated with the state type.
     pub state_generics: Generics,
+    /// The name of the fieldless state kind enum (ex. `StateKind`).
+    pub state_kind_ident: Ident,
     /// The type of the superstate enum (ex. `Superstate<'sub>`)
     pub superstate_ident: Ident,
+    /// The name of the fieldless superstate kind enum (ex. `SuperstateKind`).
+    pub superstate_kind_ident: Ident,
+    /// The lifetime of the superstate enum (ex. `'sub`).
+    pub superstate_lifetime: Lifetime,
     /// Derives that will be applied to the superstate type.
     pub superstate_derives: Vec<Path>,
     /// The generics associated with the superstate type.
     pub superstate_generics: Generics,
     /// The path of the `on_transition` callback.
     pub on_transition: Option<Path>,
+    /// The hooks that are called on a transition between a specific pair of states.
+    pub transition_hooks: Vec<TransitionHook>,
+    /// The weights of the transitions as `(from, to, weight)`, sorted by the state they
+    /// start from.
+    pub transition_weights: Vec<(Ident, Ident, LitFloat)>,
     /// The path of the `on_dispatch` callback.
     pub on_dispatch: Option<Path>,
+    /// The event that is handled right after initialization, if defined.
+    pub startup_event: Option<Expr>,
+    /// The allocator that the state stack is allocated with, if defined.
+    pub stack_allocator: Option<Expr>,
+    /// The field of the shared storage that tracks the previous state kind, if defined.
+    pub previous_state_kind: Option<Ident>,
+    /// The field of the shared storage that holds the origin of the current event, if
+    /// defined.
+    pub origin: Option<Ident>,
+    /// The field of the shared storage that holds the sequence number of the current event,
+    /// if defined.
+    pub sequence: Option<Ident>,
+    /// The field of the shared storage that observes the lifecycle events, if defined.
+    pub observer: Option<Ident>,
+    /// The field of the shared storage that records the actions that fired, if defined.
+    pub fired_actions: Option<Ident>,
+    /// The field of the shared storage that holds the error of a fallible handler, together
+    /// with the type of the error, if defined.
+    pub handler_error: Option<(Ident, Type)>,
+    /// Whether a `validate` method should be generated.
+    pub validate: bool,
+    /// JSON description of the state machine, if it should be generated.
+    pub json: Option<String>,
+    /// Human-readable summary of the state machine, if it should be generated.
+    pub describe: Option<String>,
+    /// Graphviz description of the state hierarchy, if it should be generated.
+    pub graphviz: Option<String>,
+    /// Whether a `diff` method should be generated on the state enum.
+    pub diff: bool,
+    /// Whether to assert at compile time that the state machine is `Send` and `Sync`.
+    pub send_sync: bool,
+    /// Whether the states should convert into their `u32` index.
+    pub state_index: bool,
+    /// Whether `apply_event_log` methods should be generated on the shared storage type.
+    pub apply_event_log: bool,
+    /// Whether `replay` methods should be generated on the shared storage type.
+    pub replay: bool,
+    /// Whether a `transition_to` method should be generated for the handlers.
+    pub transition_to: bool,
+    /// The configuration predicate that all generated items are gated on, if defined.
+    pub cfg: Option<Meta>,
+    /// Whether the awaitable state machine can be used behind a `dyn` trait that returns
+    /// boxed futures.
+    pub box_futures: bool,
+    /// Whether the path of the state can be serialized as one byte per kind.
+    pub serialize_path: bool,
+    /// The names of the states that can be reached from the initial state through the
+    /// transitions in the handler bodies, in breadth-first order.
+    pub reachable_states: Vec<String>,
+    /// For every state, whether it can transition to each of the states in one step. The
+    /// rows and columns are in the order of the state kinds.
+    pub adjacency: Vec<Vec<bool>>,
+    /// The names of the states that can transition into each state, in the order of the
+    /// state kinds.
+    pub transitions_into: Vec<Vec<String>>,
+    /// Pairs of states with identical handlers that are hinted at as possible merges.
+    pub mergeable_states: Vec<(Ident, Ident)>,
+    /// Whether all optional introspection should be left out of the generated code.
+    pub minimal: bool,
+    /// Whether the state machine should give mutable access to the shared storage.
+    pub deref_storage: bool,
+    /// Whether dropping the state machine without shutting it down should panic in debug
+    /// builds.
+    pub drop_guard: bool,
     /// The visibility for the derived types,
     pub visibility: Visibility,
     /// The external input pattern.
     pub event_ident: Ident,
     /// The external input pattern.
     pub context_ident: Ident,
+    /// Optional name for a public alias of the response type (e.g. `Response`).
+    pub response_ident: Option<Ident>,
+    /// Optional name for the trait that the state machines implement to handle events.
+    pub facade_ident: Option<Ident>,
     /// Whether the state machine is sync (blocking) or async (awaitable).
     pub mode: Mode,
 }
@@ -85,6 +165,9 @@ pub struct State {
     /// The call to the entry action of the state, if defined
     /// (e.g. `Blinky::enter_on(shared_storage, led)`, `{}`, ..).
     pub entry_action_call: Expr,
+    /// The call to the entry action of the state that reports whether it succeeded,
+    /// if the action is fallible (e.g. `(Blinky::enter_on(shared_storage, led)).is_ok()`).
+    pub try_entry_action_call: Option<Expr>,
     /// The call to the exit action of the state, if defined
     /// (e.g. `Blinky::exit_on(shared_storage, led)`, `{}`, ..).
     pub exit_action_call: Expr,
@@ -94,6 +177,20 @@ pub struct State {
     /// The constructor to create the state
     /// (e.g. `const fn on(led: bool) -> Self { Self::On { led }}`).
     pub constructor: ItemFn,
+    /// The names of the superstates and the state itself, ordered from the outermost
+    /// superstate to the state (e.g. `[playing, on]`).
+    pub path: Vec<Ident>,
+    /// The names of the event variants that are matched on in the handler
+    /// (e.g. `["TimerElapsed", "_"]`).
+    pub handled_events: Vec<String>,
+    /// Whether the state machine can be dropped in this state without shutting it down.
+    pub terminal: bool,
+    /// The priority of the state when it is the current state of a parallel region, if it
+    /// is declared.
+    pub priority: Option<i32>,
+    /// The names the kind of the state can be parsed from, which are the name of the state
+    /// followed by its aliases (e.g. `["on", "lit"]`).
+    pub names: Vec<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -110,12 +207,42 @@ pub struct Superstate {
     /// The call to the entry action of the superstate, if defined
     /// (e.g. `Blinky::enter_playing(shared_storage, led)`)
     pub entry_action_call: Expr,
+    /// The call to the entry action of the superstate that reports whether it succeeded,
+    /// if the action is fallible (e.g. `(Blinky::enter_playing(shared_storage, led)).is_ok()`).
+    pub try_entry_action_call: Option<Expr>,
     /// The call to the exit action of the superstate, if defined
     /// (e.g. `Blinky::exit_playing(shared_storage, led)`).
     pub exit_action_call: Expr,
+    /// The call to the pre-dispatch filter of the superstate, if defined
+    /// (e.g. `Blinky::filter_playing(shared_storage, led, event)`, `statig::Response::Super`).
+    pub pre_dispatch_call: Expr,
     /// The pattern to create the superstate variant.
     /// (e.g. `Some(Superstate::Playing { led })`, `None`, ..).
     pub superstate_pat: Expr,
+    /// The constructor that creates the initial state of the superstate, if defined
+    /// (e.g. `const fn playing() -> Self { State::on(false) }`).
+    pub initial_state_constructor: Option<ItemFn>,
+    /// The fields of the storage owned by the superstate, which are moved over on
+    /// transitions between the states nested within it (e.g. `[count]`).
+    pub owned_storage: Vec<Ident>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransitionHook {
+    /// The name of the state that is transitioned from, which the hook was declared with.
+    pub from: Ident,
+    /// The name of the state that is transitioned to, which the hook was declared with.
+    pub to: Ident,
+    /// The variant of the state that is transitioned from (e.g. `Connecting`).
+    pub source_variant: Ident,
+    /// The variant of the state that is transitioned to (e.g. `Connected`).
+    pub target_variant: Ident,
+    /// The call to the hook
+    /// (e.g. `Client::on_connected(shared_storage)`).
+    pub handler_call: Expr,
+    /// Whether the transition is found in the handler bodies of the source state and its
+    /// superstates, or can not be ruled out because of transitions created at runtime.
+    pub occurs: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -135,14 +262,48 @@ pub fn lower(model: &Model) -> Ir {
     let item_impl = model.item_impl.clone();
     let initial_state = model.state_machine.initial_state.clone();
     let state_ident = model.state_machine.state_ident.clone();
+    let state_kind_ident = model.state_machine.state_kind_ident.clone();
     let superstate_ident = model.state_machine.superstate_ident.clone();
+    let superstate_kind_ident = model.state_machine.superstate_kind_ident.clone();
     let on_transition = model.state_machine.on_transition.clone();
     let on_dispatch = model.state_machine.on_dispatch.clone();
+    let startup_event = model.state_machine.startup_event.clone();
+    let stack_allocator = model.state_machine.stack_allocator.clone();
+    let previous_state_kind = model.state_machine.previous_state_kind.clone();
+    let origin = model.state_machine.origin.clone();
+    let sequence = model.state_machine.sequence.clone();
+    let observer = model.state_machine.observer.clone();
+    let fired_actions = model.state_machine.fired_actions.clone();
+    let validate = model.state_machine.validate;
+    let json = model.state_machine.json.then(|| lower_json(model));
+    let graphviz = model
+        .state_machine
+        .export_graph
+        .then(|| lower_graphviz(model));
+    let diff = model.state_machine.diff;
+    let send_sync = model.state_machine.send_sync;
+    let state_index = model.state_machine.state_index;
+    let apply_event_log = model.state_machine.apply_event_log;
+    let replay = model.state_machine.replay;
+    let transition_to = model.state_machine.transition_to;
+    let cfg = model.state_machine.cfg.clone();
+    let reachable_states = lower_reachable_states(model);
+    let adjacency = lower_adjacency(model);
+    let transitions_into = lower_transitions_into(model);
+    let transition_hooks = lower_transition_hooks(model, &adjacency);
+    let transition_weights = lower_transition_weights(model);
+    let mergeable_states = model.mergeable_states.clone();
+    let minimal = model.state_machine.minimal;
+    let deref_storage = model.state_machine.deref_storage;
+    let drop_guard = model.state_machine.drop_guard;
     let event_ident = model.state_machine.event_ident.clone();
     let context_ident = model.state_machine.context_ident.clone();
+    let response_ident = model.state_machine.response_ident.clone();
+    let facade_ident = model.state_machine.facade_ident.clone();
     let shared_storage_type = model.state_machine.shared_storage_type.clone();
     let shared_storage_generics = model.state_machine.shared_storage_generics.clone();
     let state_derives = model.state_machine.state_derives.clone();
+    let state_repr = model.state_machine.state_repr.clone();
     let superstate_derives = model.state_machine.superstate_derives.clone();
     let visibility = model.state_machine.visibility.clone();
 
@@ -159,8 +320,7 @@ pub fn lower(model: &Model) -> Ir {
         .iter()
         .inspect(|(_, value)| {
             if !value.state_inputs.is_empty() {
-                let lifetime = Lifetime::new(SUPERSTATE_LIFETIME, Span::call_site());
-                superstate_lifetime = Some(lifetime);
+                superstate_lifetime = Some(model.state_machine.superstate_lifetime.clone());
             }
         })
         .map(|(key, value)| (key.clone(), lower_superstate(value, &model.state_machine)))
@@ -172,6 +332,34 @@ pub fn lower(model: &Model) -> Ir {
         .map(|(key, value)| (key.clone(), lower_action(value, &model.state_machine)))
         .collect();
 
+    // Aliases must identify a single state, even when names are compared case-insensitively.
+    check_state_aliases(model);
+
+    // Adding the storage owned by superstates to the states nested within them.
+    for (key, state) in &mut states {
+        let owned_storage: Vec<&(Field, Expr)> =
+            ancestors(model, model.states[key].superstate.as_ref())
+                .iter()
+                .filter_map(|ancestor| model.superstates.get(ancestor))
+                .flat_map(|superstate| &superstate.owned_storage)
+                .collect();
+        if !owned_storage.is_empty() {
+            add_owned_storage(state, &owned_storage, &model.state_machine);
+        }
+    }
+
+    // Deriving `Copy` fails for fields that can never be `Copy`, so point out the field.
+    check_copy_derive(
+        &model.state_machine.state_derives,
+        states.values().map(|state| &state.variant),
+        &model.state_machine.state_ident,
+    );
+    check_copy_derive(
+        &model.state_machine.superstate_derives,
+        superstates.values().map(|superstate| &superstate.variant),
+        &model.state_machine.superstate_ident,
+    );
+
     // Linking states to their superstates and entry/exit actions.
     for (key, state) in &mut states {
         if let Some(superstate) = model
@@ -186,32 +374,47 @@ pub fn lower(model: &Model) -> Ir {
                 }
                 None => abort!(superstate, "superstate not found"),
             }
+
+            let mut path = ancestors(model, Some(superstate));
+            path.append(&mut state.path);
+            state.path = path;
         }
 
-        if let Some(entry_action) = model
-            .states
-            .get(key)
-            .and_then(|state| state.entry_action.as_ref())
-        {
-            match actions.get(entry_action) {
-                Some(action) => state.entry_action_call = action.handler_call.clone(),
-                None => abort!(entry_action, "entry action not found"),
-            }
+        let model_state = &model.states[key];
+
+        let mut entry_action_calls = Vec::new();
+        for entry_action in &model_state.entry_actions {
+            let Some(action) = actions.get(entry_action) else {
+                abort!(entry_action, "entry action not found")
+            };
+            let model_action = &model.actions[entry_action];
+            check_action_inputs(&state.variant, model_action, &model.state_machine, key);
+            entry_action_calls.push((&action.handler_call, model_action.is_fallible));
+        }
+        if !entry_action_calls.is_empty() {
+            let (entry_action_call, try_entry_action_call) =
+                chain_action_calls(&entry_action_calls);
+            state.entry_action_call = entry_action_call;
+            state.try_entry_action_call = try_entry_action_call;
         }
 
-        if let Some(exit_action) = model
-            .states
-            .get(key)
-            .and_then(|state| state.exit_action.as_ref())
-        {
-            match actions.get(exit_action) {
-                Some(action) => state.exit_action_call = action.handler_call.clone(),
-                None => abort!(exit_action, "exit action not found"),
-            }
+        // Exit actions run in the reverse order of their declaration, so the last one set up
+        // is the first one torn down.
+        let mut exit_action_calls = Vec::new();
+        for exit_action in model_state.exit_actions.iter().rev() {
+            let Some(action) = actions.get(exit_action) else {
+                abort!(exit_action, "exit action not found")
+            };
+            let model_action = &model.actions[exit_action];
+            check_action_inputs(&state.variant, model_action, &model.state_machine, key);
+            exit_action_calls.push((&action.handler_call, model_action.is_fallible));
+        }
+        if !exit_action_calls.is_empty() {
+            state.exit_action_call = chain_action_calls(&exit_action_calls).0;
         }
     }
 
-    // Linking superstates to superstates and entry/exit action.
+    // Linking superstates to superstates, entry/exit actions and pre-dispatch filters.
     let superstates_clone = superstates.clone();
     for (key, superstate) in &mut superstates {
         if let Some(superstate_superstate) = model
@@ -228,109 +431,384 @@ pub fn lower(model: &Model) -> Ir {
             }
         }
 
-        if let Some(entry_action) = model
+        if let Some(initial_state) = model
             .superstates
             .get(key)
-            .and_then(|state| state.entry_action.as_ref())
+            .and_then(|state| state.initial_state.as_ref())
         {
-            match actions.get(entry_action) {
-                Some(action) => superstate.entry_action_call = action.handler_call.clone(),
-                None => abort!(entry_action, "action not found"),
+            if !is_initial_state_of_superstate(model, key, initial_state) {
+                abort!(
+                    initial_state,
+                    "initial state must be a state or superstate nested within `{}`",
+                    key
+                )
             }
         }
 
-        if let Some(exit_action) = model
+        let model_superstate = &model.superstates[key];
+
+        let mut entry_action_calls = Vec::new();
+        for entry_action in &model_superstate.entry_actions {
+            let Some(action) = actions.get(entry_action) else {
+                abort!(entry_action, "action not found")
+            };
+            let model_action = &model.actions[entry_action];
+            check_superstate_action_borrows(superstate, model_action);
+            check_action_inputs(&superstate.variant, model_action, &model.state_machine, key);
+            entry_action_calls.push((&action.handler_call, model_action.is_fallible));
+        }
+        if !entry_action_calls.is_empty() {
+            let (entry_action_call, try_entry_action_call) =
+                chain_action_calls(&entry_action_calls);
+            superstate.entry_action_call = entry_action_call;
+            superstate.try_entry_action_call = try_entry_action_call;
+        }
+
+        let mut exit_action_calls = Vec::new();
+        for exit_action in model_superstate.exit_actions.iter().rev() {
+            let Some(action) = actions.get(exit_action) else {
+                abort!(exit_action, "action not found")
+            };
+            let model_action = &model.actions[exit_action];
+            check_superstate_action_borrows(superstate, model_action);
+            check_action_inputs(&superstate.variant, model_action, &model.state_machine, key);
+            exit_action_calls.push((&action.handler_call, model_action.is_fallible));
+        }
+        if !exit_action_calls.is_empty() {
+            superstate.exit_action_call = chain_action_calls(&exit_action_calls).0;
+        }
+
+        if let Some(pre_dispatch) = model
             .superstates
             .get(key)
-            .and_then(|state| state.exit_action.as_ref())
+            .and_then(|state| state.pre_dispatch.as_ref())
         {
-            match actions.get(exit_action) {
-                Some(action) => superstate.exit_action_call = action.handler_call.clone(),
-                None => abort!(exit_action, "action not found"),
+            match actions.get(pre_dispatch) {
+                Some(action) => {
+                    check_superstate_action_borrows(superstate, &model.actions[pre_dispatch]);
+                    superstate.pre_dispatch_call = action.handler_call.clone()
+                }
+                None => abort!(pre_dispatch, "action not found"),
             }
         }
     }
 
+    check_superstate_cycles(model);
+
     // Find event and/or context types and check whether there are any async functions.
-    let mut mode = Mode::Blocking;
+    let mut async_handler: Option<&Ident> = None;
     let mut event_type = None;
     let mut context_type = None;
 
-    for state in model.states.values() {
-        if let Some(pat_type) = &state.event_arg {
-            if let Pat::Ident(external_input_ident) = &*pat_type.pat {
-                if model
-                    .state_machine
-                    .event_ident
-                    .eq(&external_input_ident.ident)
-                {
-                    let ty = match &*pat_type.ty {
-                        Type::Reference(reference) => reference.elem.deref().clone(),
-                        _ => abort!(pat_type.ty, "event must be passed in as a reference"),
-                    };
-                    event_type = Some(ty);
-                }
-            }
+    let event_ident = &model.state_machine.event_ident;
+    let context_ident = &model.state_machine.context_ident;
+    let event_ref = model.state_machine.event_ref;
+
+    // The handlers are visited in order of their names, so the declaration that the others
+    // are checked against does not depend on the order of the hash maps.
+    let mut sorted_states: Vec<&analyze::State> = model.states.values().collect();
+    sorted_states.sort_by_key(|state| state.handler_name.to_string());
+    let mut sorted_superstates: Vec<&analyze::Superstate> = model.superstates.values().collect();
+    sorted_superstates.sort_by_key(|superstate| superstate.handler_name.to_string());
+
+    let external_inputs = sorted_states
+        .iter()
+        .map(|state| (&state.handler_name, &state.event_arg, &state.context_arg))
+        .chain(sorted_superstates.iter().map(|superstate| {
+            (
+                &superstate.handler_name,
+                &superstate.event_arg,
+                &superstate.context_arg,
+            )
+        }));
+    for (handler_name, event_arg, context_arg) in external_inputs {
+        if let Some(ty) = external_input_type(event_arg, event_ident, "event", event_ref) {
+            agree_on_type(&mut event_type, ty, event_arg, handler_name, "event");
         }
-        if let Some(pat_type) = &state.context_arg {
-            if let Pat::Ident(external_input_ident) = &*pat_type.pat {
-                if model
-                    .state_machine
-                    .context_ident
-                    .eq(&external_input_ident.ident)
-                {
-                    let ty = match &*pat_type.ty {
-                        Type::Reference(reference) => reference.elem.deref().clone(),
-                        _ => abort!(pat_type.ty, "context must be passed in as a reference"),
-                    };
-                    context_type = Some(ty);
-                }
+        if let Some(ty) = external_input_type(context_arg, context_ident, "context", true) {
+            agree_on_type(&mut context_type, ty, context_arg, handler_name, "context");
+        }
+    }
+    let event_type = event_type.map(|(ty, _)| ty);
+    let context_type = context_type.map(|(ty, _)| ty);
+
+    // The error type is inferred from the handlers that return a `Result`.
+    let mut error_type = None;
+    let returned_errors = sorted_states
+        .iter()
+        .map(|state| (&state.handler_name, &state.error_type))
+        .chain(
+            sorted_superstates
+                .iter()
+                .map(|superstate| (&superstate.handler_name, &superstate.error_type)),
+        );
+    for (handler_name, ty) in returned_errors {
+        if let Some(ty) = ty {
+            if model.state_machine.fallible.is_none() {
+                abort!(
+                    ty,
+                    "handlers can only return a `Result` when the state machine is fallible";
+                    help = "set `fallible` to the field of the shared storage that holds the error, e.g. `#[state_machine(fallible = \"error\")]`"
+                )
             }
+            agree_on_type(&mut error_type, ty.clone(), ty, handler_name, "error");
         }
+    }
+    let handler_error = match (&model.state_machine.fallible, error_type) {
+        (Some(field), Some((ty, _))) => Some((field.clone(), ty)),
+        (Some(field), None) => abort!(
+            field,
+            "the state machine is fallible, but none of the handlers return a `Result`"
+        ),
+        (None, _) => None,
+    };
+
+    for state in model.states.values() {
         if state.is_async {
-            mode = Mode::Awaitable;
+            async_handler = Some(&state.handler_name);
         }
     }
 
     for superstate in model.superstates.values() {
-        if let Some(pat_type) = &superstate.event_arg {
-            if let Pat::Ident(external_input_ident) = &*pat_type.pat {
-                if model
-                    .state_machine
-                    .event_ident
-                    .eq(&external_input_ident.ident)
-                {
-                    let ty = match &*pat_type.ty {
-                        Type::Reference(reference) => reference.elem.deref().clone(),
-                        _ => abort!(pat_type.ty, "event must be passed in as a reference"),
-                    };
-                    event_type = Some(ty);
+        if superstate.is_async {
+            async_handler = Some(&superstate.handler_name);
+        }
+    }
+
+    for action in model.actions.values() {
+        if action.is_async {
+            async_handler = Some(&action.handler_name);
+        }
+    }
+
+    // The mode is detected from the handlers, unless it is set explicitly.
+    let mode = match (&model.state_machine.mode, async_handler) {
+        (Some(forced), Some(handler)) if forced.value() == "blocking" => abort!(
+            forced,
+            "mode is set to `blocking`, but `{}` is async", handler;
+            help = "remove the `mode` argument or make `{}` a blocking function", handler
+        ),
+        (Some(forced), None) if forced.value() == "blocking" => Mode::Blocking,
+        (Some(_), _) => Mode::Awaitable,
+        (None, Some(_)) => Mode::Awaitable,
+        (None, None) => Mode::Blocking,
+    };
+
+    let describe = model
+        .state_machine
+        .describe
+        .then(|| lower_description(model, mode));
+
+    // Boxing the futures is only meaningful for state machines that return them.
+    let box_futures = match (&model.state_machine.box_futures, mode) {
+        (Some(path), Mode::Blocking) => abort!(
+            path,
+            "`box_futures` requires an awaitable state machine";
+            help = "remove `box_futures` or set `mode = \"awaitable\"`"
+        ),
+        (box_futures, _) => box_futures.is_some(),
+    };
+
+    // Every kind in the serialized path takes a single byte.
+    let serialize_path = match &model.state_machine.serialize_path {
+        Some(path) if model.states.len() > 256 || model.superstates.len() > 256 => abort!(
+            path,
+            "`serialize_path` supports at most 256 states and 256 superstates"
+        ),
+        serialize_path => serialize_path.is_some(),
+    };
+
+    // Dispatch the event to every region of a parallel state before calling its handler.
+    for (key, state) in &model.states {
+        if !state.parallel {
+            continue;
+        }
+        let lowered = states.get_mut(key).unwrap();
+        let region_calls: Vec<Expr> = lowered
+            .variant
+            .fields
+            .iter()
+            .map(|field| {
+                let region = field.ident.as_ref().unwrap();
+                match mode {
+                    Mode::Blocking => {
+                        parse_quote!(#region.handle_with_context(#event_ident, #context_ident))
+                    }
+                    Mode::Awaitable => {
+                        parse_quote!(#region.handle_with_context(#event_ident, #context_ident).await)
+                    }
                 }
+            })
+            .collect();
+        let handler_call = &lowered.handler_call;
+        lowered.handler_call = match region_calls.len() {
+            0 | 1 => parse_quote!({ #(#region_calls;)* #handler_call }),
+            // The regions are dispatched to in order of the priority of their current state,
+            // from high to low, and in the order they were declared when priorities are equal.
+            _ => {
+                let regions: Vec<&Ident> = lowered
+                    .variant
+                    .fields
+                    .iter()
+                    .map(|field| field.ident.as_ref().unwrap())
+                    .collect();
+                let indices = 0..regions.len();
+                let arms = 0..regions.len();
+                parse_quote!({
+                    let mut region_order = [#((#indices, #regions.state_priority())),*];
+                    region_order.sort_unstable_by_key(|&(region_index, priority)| {
+                        (core::cmp::Reverse(priority), region_index)
+                    });
+                    for (region_index, _) in region_order {
+                        match region_index {
+                            #(#arms => { #region_calls; })*
+                            _ => {}
+                        }
+                    }
+                    #handler_call
+                })
             }
-        }
-        if let Some(pat_type) = &superstate.context_arg {
-            if let Pat::Ident(external_input_ident) = &*pat_type.pat {
-                if model
+        };
+
+        // Activate the regions when the parallel state is entered, so the initial states of
+        // the ones that are lazily initialized are entered together with it. Which regions
+        // are lazy is decided by the `Region` implementation of their type. Regions with an
+        // initial state that is declared on the state machine are entered in that state.
+        let init_calls: Vec<Expr> = lowered
+            .variant
+            .fields
+            .iter()
+            .map(|field| {
+                let region = field.ident.as_ref().unwrap();
+                let initial = model
                     .state_machine
-                    .context_ident
-                    .eq(&external_input_ident.ident)
-                {
-                    let ty = match &*pat_type.ty {
-                        Type::Reference(reference) => reference.elem.deref().clone(),
-                        _ => abort!(pat_type.ty, "context must be passed in as a reference"),
-                    };
-                    context_type = Some(ty);
+                    .region_initial_states
+                    .iter()
+                    .find(|(name, _)| name == region)
+                    .map(|(_, initial)| initial);
+                match (mode, initial) {
+                    (Mode::Blocking, None) => parse_quote!(
+                        statig::blocking::Region::enter_region(#region, #context_ident)
+                    ),
+                    (Mode::Blocking, Some(initial)) => parse_quote!(
+                        statig::blocking::Region::enter_region_in(#region, #initial, #context_ident)
+                    ),
+                    (Mode::Awaitable, None) => parse_quote!(
+                        statig::awaitable::Region::enter_region(#region, #context_ident).await
+                    ),
+                    (Mode::Awaitable, Some(initial)) => parse_quote!(
+                        statig::awaitable::Region::enter_region_in(#region, #initial, #context_ident).await
+                    ),
                 }
+            })
+            .collect();
+        let entry_action_call = &lowered.entry_action_call;
+        lowered.entry_action_call = parse_quote!({ #(#init_calls;)* #entry_action_call });
+        if let Some(try_entry_action_call) = &lowered.try_entry_action_call {
+            lowered.try_entry_action_call =
+                Some(parse_quote!({ #(#init_calls;)* #try_entry_action_call }));
+        }
+
+        // Shut the regions down when the parallel state is exited, so the exit actions of
+        // their current states run before the exit action of the parallel state.
+        let shutdown_calls: Vec<Expr> = lowered
+            .variant
+            .fields
+            .iter()
+            .map(|field| {
+                let region = field.ident.as_ref().unwrap();
+                match mode {
+                    Mode::Blocking => parse_quote!(#region.shutdown_with_context(#context_ident)),
+                    Mode::Awaitable => {
+                        parse_quote!(#region.shutdown_with_context(#context_ident).await)
+                    }
+                }
+            })
+            .collect();
+        let exit_action_call = &lowered.exit_action_call;
+        lowered.exit_action_call = parse_quote!({ #(#shutdown_calls;)* #exit_action_call });
+    }
+
+    // Record the entries and exits of every state in the fields of the shared storage that
+    // count them or hold the timeout of the current state.
+    let recorders = [
+        &model.state_machine.entry_exit_counts,
+        &model.state_machine.timeout,
+    ];
+    for field in recorders.into_iter().flatten() {
+        for state in states.values_mut() {
+            let variant_ident = &state.variant.ident;
+            let kind: Expr = parse_quote!(#state_kind_ident::#variant_ident);
+            let entry_action_call = &state.entry_action_call;
+            state.entry_action_call = parse_quote!({
+                shared_storage.#field.record_entry(#kind);
+                #entry_action_call
+            });
+            if let Some(try_entry_action_call) = &state.try_entry_action_call {
+                state.try_entry_action_call = Some(parse_quote!({
+                    shared_storage.#field.record_entry(#kind);
+                    #try_entry_action_call
+                }));
             }
+            let exit_action_call = &state.exit_action_call;
+            state.exit_action_call = parse_quote!({
+                shared_storage.#field.record_exit(#kind);
+                #exit_action_call
+            });
         }
-        if superstate.is_async {
-            mode = Mode::Awaitable;
+    }
+
+    // Record the names of the entry and exit actions that fire. Entering a state always
+    // completes a transition, so it also marks the outcome as one.
+    if let Some(field) = &model.state_machine.fired_actions {
+        for (key, state) in &mut states {
+            let model_state = &model.states[key];
+            let entry_actions = &model_state.entry_actions;
+            state.entry_action_call =
+                fired_action_call(&state.entry_action_call, field, entry_actions.iter(), true);
+            if let Some(try_entry_action_call) = &state.try_entry_action_call {
+                state.try_entry_action_call = Some(fired_action_call(
+                    try_entry_action_call,
+                    field,
+                    entry_actions.iter(),
+                    true,
+                ));
+            }
+            let exit_actions = model_state.exit_actions.iter().rev();
+            state.exit_action_call =
+                fired_action_call(&state.exit_action_call, field, exit_actions, false);
+        }
+        for (key, superstate) in &mut superstates {
+            let model_superstate = &model.superstates[key];
+            let entry_actions = &model_superstate.entry_actions;
+            superstate.entry_action_call = fired_action_call(
+                &superstate.entry_action_call,
+                field,
+                entry_actions.iter(),
+                false,
+            );
+            if let Some(try_entry_action_call) = &superstate.try_entry_action_call {
+                superstate.try_entry_action_call = Some(fired_action_call(
+                    try_entry_action_call,
+                    field,
+                    entry_actions.iter(),
+                    false,
+                ));
+            }
+            let exit_actions = model_superstate.exit_actions.iter().rev();
+            superstate.exit_action_call =
+                fired_action_call(&superstate.exit_action_call, field, exit_actions, false);
         }
     }
 
-    for action in model.actions.values() {
-        if action.is_async {
-            mode = Mode::Awaitable;
+    // Wrap every handler call in a span that is named after the state or superstate.
+    if model.state_machine.tracing {
+        for (key, state) in &mut states {
+            state.handler_call = traced_handler_call(&state.handler_call, key, event_ident, mode);
+        }
+        for (key, superstate) in &mut superstates {
+            superstate.handler_call =
+                traced_handler_call(&superstate.handler_call, key, event_ident, mode);
         }
     }
 
@@ -361,7 +839,65 @@ pub fn lower(model: &Model) -> Ir {
     for state in model.states.values() {
         visitor.search(&state.state_inputs);
     }
-    let state_generic_params = visitor.finish();
+    let mut state_generic_params = visitor.finish();
+
+    // The machine-wide lifetime is always part of the state generics. The superstates only
+    // borrow from the states, so they only need it when their fields refer to it.
+    if let Some(lifetime) = &model.state_machine.lifetime {
+        let param = GenericParam::Lifetime(syn::LifetimeDef::new(lifetime.clone()));
+        state_generic_params.insert(param);
+    }
+
+    // Pass all generics of the shared storage on to the states. The ones that are not used
+    // by any state are held by a `PhantomData` field of the first state (sorted by name).
+    if model.state_machine.generics_passthrough {
+        let unused: Vec<&GenericParam> = shared_storage_generics_map
+            .iter()
+            .map(|(param, _)| param)
+            .filter(|param| !state_generic_params.contains(*param))
+            .collect();
+        if let (false, Some(key)) = (unused.is_empty(), states.keys().min().cloned()) {
+            let phantom_types: Vec<Type> = unused
+                .iter()
+                .map(|param| match param {
+                    GenericParam::Type(param) => {
+                        let ident = &param.ident;
+                        parse_quote!(#ident)
+                    }
+                    GenericParam::Lifetime(param) => {
+                        let lifetime = &param.lifetime;
+                        parse_quote!(&#lifetime ())
+                    }
+                    GenericParam::Const(param) => {
+                        let ident = &param.ident;
+                        parse_quote!([(); #ident])
+                    }
+                })
+                .collect();
+            let phantom_field =
+                quote!(__generics: core::marker::PhantomData<fn() -> (#(#phantom_types,)*)>);
+            let phantom: (Field, Expr) = (
+                Field::parse_named.parse2(phantom_field).unwrap(),
+                parse_quote!(core::marker::PhantomData),
+            );
+            // The storage owned by the superstates must stay out of the constructor.
+            let mut owned_storage: Vec<&(Field, Expr)> =
+                ancestors(model, model.states[&key].superstate.as_ref())
+                    .iter()
+                    .filter_map(|ancestor| model.superstates.get(ancestor))
+                    .flat_map(|superstate| &superstate.owned_storage)
+                    .collect();
+            owned_storage.push(&phantom);
+            add_owned_storage(
+                states.get_mut(&key).unwrap(),
+                &owned_storage,
+                &model.state_machine,
+            );
+        }
+        for (param, _) in &shared_storage_generics_map {
+            state_generic_params.insert(param.clone());
+        }
+    }
 
     let mut visitor = GenericParamVisitor::new(&model.state_machine.shared_storage_generics);
     for superstate in model.superstates.values() {
@@ -416,15 +952,51 @@ pub fn lower(model: &Model) -> Ir {
         context_type,
         state_ident,
         state_derives,
+        state_repr,
         state_generics,
+        state_kind_ident,
         superstate_ident,
+        superstate_kind_ident,
+        superstate_lifetime: model.state_machine.superstate_lifetime.clone(),
         superstate_derives,
         superstate_generics,
         on_transition,
+        transition_hooks,
+        transition_weights,
         on_dispatch,
+        startup_event,
+        stack_allocator,
+        previous_state_kind,
+        origin,
+        sequence,
+        observer,
+        fired_actions,
+        handler_error,
+        validate,
+        json,
+        describe,
+        graphviz,
+        diff,
+        send_sync,
+        transition_to,
+        replay,
+        apply_event_log,
+        state_index,
+        cfg,
+        box_futures,
+        serialize_path,
+        reachable_states,
+        adjacency,
+        transitions_into,
+        mergeable_states,
+        minimal,
+        deref_storage,
+        drop_guard,
         visibility,
         event_ident,
         context_ident,
+        response_ident,
+        facade_ident,
         mode,
     };
 
@@ -448,7 +1020,7 @@ pub fn lower_state(state: &analyze::State, state_machine: &analyze::StateMachine
     let mut variant_fields: Vec<_> = state
         .state_inputs
         .iter()
-        .map(fn_arg_to_state_field)
+        .map(|pat_type| fn_arg_to_state_field(pat_type, state.local_storage_by_value))
         .collect();
 
     for field in &state.local_storage {
@@ -464,11 +1036,31 @@ pub fn lower_state(state: &analyze::State, state_machine: &analyze::StateMachine
         .iter()
         .map(|field| field.ident.as_ref().unwrap().clone())
         .collect();
-    let handler_inputs: Vec<Ident> = state.inputs.iter().map(fn_arg_to_ident).collect();
+    let handler_inputs: Vec<Expr> = state
+        .inputs
+        .iter()
+        .map(|input| match input {
+            FnArg::Typed(pat_type) if state.shared_storage_fields.contains(pat_type) => {
+                fn_arg_to_shared_storage_field_borrow(pat_type, state_machine)
+            }
+            // The field is bound by reference when matching on the state, so inputs that are
+            // passed by value are copied out of it.
+            FnArg::Typed(pat_type)
+                if state.state_inputs.contains(pat_type)
+                    && !matches!(pat_type.ty.as_ref(), Type::Reference(_)) =>
+            {
+                let ident = fn_arg_to_ident(input);
+                parse_quote!(*#ident)
+            }
+            _ => fn_arg_to_input(input, state_machine),
+        })
+        .collect();
 
-    let variant = parse_quote!(#variant_name { #(#variant_fields),* });
+    let variant_attrs = &state.variant_attrs;
+    let variant = parse_quote!(#(#[#variant_attrs])* #variant_name { #(#variant_fields),* });
     let pat = parse_quote!(#state_name::#variant_name { #(#pat_fields),*});
-    let constructor = parse_quote!(const fn #state_handler_name ( #(#variant_fields),* ) -> Self { Self::#variant_name { #(#pat_fields),*} });
+    let visibility = &state_machine.visibility;
+    let constructor = parse_quote!(#visibility const fn #state_handler_name ( #(#variant_fields),* ) -> Self { Self::#variant_name { #(#pat_fields),*} });
 
     let handler_call = match &state.is_async {
         true => {
@@ -479,9 +1071,47 @@ pub fn lower_state(state: &analyze::State, state_machine: &analyze::StateMachine
         }
     };
 
+    // Handlers that return a `ControlFlow` are converted to a `Response`.
+    let handler_call = match state.control_flow {
+        true => parse_quote!(statig::Response::from(#handler_call)),
+        false => handler_call,
+    };
+
+    // Handlers that return a `Result` store the error in the shared storage and consider the
+    // event handled, so a failing handler never causes a transition.
+    let handler_call = match (&state.error_type, &state_machine.fallible) {
+        (Some(_), Some(field)) => fallible_handler_call(&handler_call, field),
+        _ => handler_call,
+    };
+
+    // Transitions out of a final state to targets that are only known at runtime can not be
+    // rejected while analyzing the handler, so they are checked in debug builds instead.
+    let handler_call = match state.is_final && state.dynamic_transitions {
+        true => {
+            let message = format!("final state `{state_handler_name}` can not transition");
+            parse_quote!({
+                let response = #handler_call;
+                debug_assert!(!matches!(response, statig::Response::Transition(_)), #message);
+                response
+            })
+        }
+        false => handler_call,
+    };
+
     let entry_action_call = parse_quote!({});
+    let try_entry_action_call = None;
     let exit_action_call = parse_quote!({});
     let superstate_pat = parse_quote!(None);
+    let path = vec![state_handler_name.clone()];
+    let handled_events = state.handled_events.clone();
+    let terminal = state.terminal;
+    let priority = state.priority;
+    let mut names = vec![state_handler_name.to_string()];
+    for alias in &state.aliases {
+        if !names.contains(&alias.value()) {
+            names.push(alias.value());
+        }
+    }
 
     State {
         variant,
@@ -489,8 +1119,14 @@ pub fn lower_state(state: &analyze::State, state_machine: &analyze::StateMachine
         constructor,
         handler_call,
         entry_action_call,
+        try_entry_action_call,
         exit_action_call,
         superstate_pat,
+        path,
+        handled_events,
+        terminal,
+        priority,
+        names,
     }
 }
 
@@ -509,7 +1145,7 @@ pub fn lower_superstate(
     let mut variant_fields: Vec<_> = superstate
         .state_inputs
         .iter()
-        .map(fn_arg_to_superstate_field)
+        .map(|pat_type| fn_arg_to_superstate_field(pat_type, &state_machine.superstate_lifetime))
         .collect();
 
     for field in &superstate.local_storage {
@@ -525,9 +1161,14 @@ pub fn lower_superstate(
         .iter()
         .map(|field| field.ident.as_ref().unwrap().clone())
         .collect();
-    let handler_inputs: Vec<Ident> = superstate.inputs.iter().map(fn_arg_to_ident).collect();
+    let handler_inputs: Vec<Expr> = superstate
+        .inputs
+        .iter()
+        .map(|input| fn_arg_to_input(input, state_machine))
+        .collect();
 
-    let variant = parse_quote!(#superstate_name { #(#variant_fields),* });
+    let variant_attrs = &superstate.variant_attrs;
+    let variant = parse_quote!(#(#[#variant_attrs])* #superstate_name { #(#variant_fields),* });
     let pat = parse_quote!(#superstate_type::#superstate_name { #(#pat_fields),*});
 
     let handler_call = match &superstate.is_async {
@@ -539,17 +1180,46 @@ pub fn lower_superstate(
         }
     };
 
+    // Handlers that return a `ControlFlow` are converted to a `Response`.
+    let handler_call = match superstate.control_flow {
+        true => parse_quote!(statig::Response::from(#handler_call)),
+        false => handler_call,
+    };
+
+    // Handlers that return a `Result` store the error in the shared storage and consider the
+    // event handled, so a failing handler never causes a transition.
+    let handler_call = match (&superstate.error_type, &state_machine.fallible) {
+        (Some(_), Some(field)) => fallible_handler_call(&handler_call, field),
+        _ => handler_call,
+    };
+
+    let visibility = &state_machine.visibility;
+    let initial_state_constructor = superstate.initial_state.as_ref().map(|initial_state| {
+        parse_quote!(#visibility const fn #superstate_handler_name() -> Self { #initial_state })
+    });
+
     let entry_action_call = parse_quote!({});
+    let try_entry_action_call = None;
     let exit_action_call = parse_quote!({});
+    let pre_dispatch_call = parse_quote!(statig::Response::Super);
     let superstate_pat = parse_quote!(None);
+    let owned_storage = superstate
+        .owned_storage
+        .iter()
+        .map(|(field, _)| field.ident.clone().unwrap())
+        .collect();
 
     Superstate {
         variant,
         pat,
         handler_call,
         entry_action_call,
+        try_entry_action_call,
         exit_action_call,
+        pre_dispatch_call,
         superstate_pat,
+        initial_state_constructor,
+        owned_storage,
     }
 }
 
@@ -579,7 +1249,11 @@ pub fn lower_action(action: &analyze::Action, state_machine: &analyze::StateMach
         }
     }
 
-    let handler_inputs: Vec<Ident> = action.inputs.iter().map(fn_arg_to_ident).collect();
+    let handler_inputs: Vec<Expr> = action
+        .inputs
+        .iter()
+        .map(|input| fn_arg_to_input(input, state_machine))
+        .collect();
 
     let handler_call = match &action.is_async {
         true => {
@@ -593,131 +1267,1019 @@ pub fn lower_action(action: &analyze::Action, state_machine: &analyze::StateMach
     Action { handler_call }
 }
 
-fn fn_arg_to_ident(fn_arg: &FnArg) -> Ident {
-    match fn_arg {
-        FnArg::Receiver(_) => parse_quote!(shared_storage),
-        FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
-            Pat::Ident(pat_ident) => pat_ident.ident.clone(),
-            _ => panic!("all patterns should be verified to be idents"),
-        },
+/// Check whether the initial state of a superstate points to a state or a superstate
+/// (with its own initial state) that is nested within that superstate.
+fn is_initial_state_of_superstate(
+    model: &Model,
+    superstate: &Ident,
+    initial_state: &ExprCall,
+) -> bool {
+    let Some(target) = constructor_ident(initial_state) else {
+        return false;
+    };
+
+    let parent = match (model.states.get(target), model.superstates.get(target)) {
+        (Some(state), _) => state.superstate.as_ref(),
+        (None, Some(target_superstate)) if target_superstate.initial_state.is_some() => {
+            target_superstate.superstate.as_ref()
+        }
+        _ => return false,
+    };
+
+    ancestors(model, parent).contains(superstate)
+}
+
+/// Get the superstate and all its ancestors, ordered from the outermost superstate to the
+/// given superstate.
+/// A superstate that is nested within itself, directly or through other superstates, would
+/// make the dispatch recurse forever, so the superstates must form a tree.
+fn check_superstate_cycles(model: &Model) {
+    let mut names: Vec<&Ident> = model.superstates.keys().collect();
+    names.sort();
+
+    for name in names {
+        let mut path: Vec<&Ident> = Vec::new();
+        let mut current = Some(name);
+        while let Some(ident) = current {
+            if let Some(start) = path.iter().position(|visited| *visited == ident) {
+                let members: Vec<String> = path[start..]
+                    .iter()
+                    .chain([&ident])
+                    .map(|member| format!("`{member}`"))
+                    .collect();
+                let last = path.last().unwrap();
+                abort!(
+                    model.superstates[*last].superstate.as_ref().unwrap(),
+                    "superstates form a cycle: {}",
+                    members.join(" -> ");
+                    help = "a superstate can not be nested within itself"
+                );
+            }
+            path.push(ident);
+            current = model
+                .superstates
+                .get(ident)
+                .and_then(|superstate| superstate.superstate.as_ref());
+        }
     }
 }
 
-fn fn_arg_to_state_field(pat_type: &PatType) -> Field {
-    let field_type = match pat_type.ty.as_ref() {
-        Type::Reference(reference) => reference.elem.clone(),
-        _ => abort!(pat_type, "input must be passed as a reference"),
-    };
+fn ancestors(model: &Model, mut superstate: Option<&Ident>) -> Vec<Ident> {
+    let mut ancestors = Vec::new();
 
-    match pat_type.pat.as_ref() {
-        Pat::Ident(pat_ident) => {
-            let field_ident = &pat_ident.ident;
-            Field::parse_named
-                .parse2(quote::quote!(#field_ident: #field_type))
-                .unwrap()
+    // Walk up the hierarchy, bounded by the number of superstates in case it contains a cycle.
+    while let Some(ident) = superstate {
+        if ancestors.len() == model.superstates.len() {
+            break;
         }
-        _ => panic!("all patterns should be verified to be idents"),
+        ancestors.insert(0, ident.clone());
+        superstate = model
+            .superstates
+            .get(ident)
+            .and_then(|superstate| superstate.superstate.as_ref());
     }
+
+    ancestors
 }
 
-fn fn_arg_to_superstate_field(pat_type: &PatType) -> Field {
-    let field_type = match pat_type.ty.as_ref() {
-        Type::Reference(reference) => {
-            let mut reference = reference.clone();
-            reference.lifetime = Some(Lifetime::new(SUPERSTATE_LIFETIME, Span::call_site()));
-            Type::Reference(reference)
+/// Superstates only borrow their fields from the underlying state, so an action can not
+/// borrow a field mutably when the superstate only holds a shared reference to it.
+fn check_superstate_action_borrows(superstate: &Superstate, action: &analyze::Action) {
+    for input in &action.inputs {
+        let FnArg::Typed(pat_type) = input else {
+            continue;
+        };
+        let (Pat::Ident(pat_ident), Type::Reference(reference)) =
+            (pat_type.pat.as_ref(), pat_type.ty.as_ref())
+        else {
+            continue;
+        };
+        if reference.mutability.is_none() {
+            continue;
         }
-        _ => abort!(pat_type, "input must be passed as a reference"),
-    };
-    match pat_type.pat.as_ref() {
-        Pat::Ident(pat_ident) => {
-            let field_ident = &pat_ident.ident;
-            Field::parse_named
-                .parse2(quote::quote!(#field_ident: #field_type))
-                .unwrap()
+
+        let field = superstate
+            .variant
+            .fields
+            .iter()
+            .find(|field| field.ident.as_ref() == Some(&pat_ident.ident));
+        if let Some(Type::Reference(field_reference)) = field.map(|field| &field.ty) {
+            if field_reference.mutability.is_none() {
+                abort!(
+                    pat_type,
+                    "`{}` is borrowed mutably, but the superstate only holds a shared reference to it",
+                    pat_ident.ident;
+                    help = "take `{}` as a `&mut` reference in the superstate handler", pat_ident.ident
+                )
+            }
         }
-        _ => panic!("all patterns should be verified to be idents"),
     }
 }
 
-pub fn get_generic_argument_ident(ty: &Type) -> Ident {
-    match ty {
-        Type::Path(path) => path.path.segments.last().map(|s| &s.ident).unwrap().clone(),
-        _ => panic!("can not get ident of shared storage"),
+/// Check that no alias of a state matches the name or an alias of another state, ignoring
+/// the case of the names.
+fn check_state_aliases(model: &Model) {
+    for (key, state) in &model.states {
+        for alias in &state.aliases {
+            let conflict = model
+                .states
+                .iter()
+                .filter(|(other_key, _)| *other_key != key)
+                .find(|(other_key, other)| {
+                    other_key.to_string().eq_ignore_ascii_case(&alias.value())
+                        || other.aliases.iter().any(|other_alias| {
+                            other_alias.value().eq_ignore_ascii_case(&alias.value())
+                        })
+                });
+            if let Some((other_key, _)) = conflict {
+                abort!(
+                    alias,
+                    "alias `{}` of `{}` is ambiguous, it also refers to `{}`",
+                    alias.value(),
+                    key,
+                    other_key;
+                    help = "state names and aliases are compared case-insensitively"
+                )
+            }
+        }
     }
 }
 
-fn predicate_bounded_to_param(predicate: &WherePredicate, param: &GenericParam) -> bool {
-    match (predicate, param) {
-        (WherePredicate::Type(l), GenericParam::Type(r))
-            if get_generic_argument_ident(&l.bounded_ty) == r.ident =>
-        {
-            true
+/// Check that `Copy` is not derived for an enum with a variant that has a field which can
+/// never be `Copy` (e.g. a `String` or a mutable reference).
+fn check_copy_derive<'a>(
+    derives: &[Path],
+    variants: impl Iterator<Item = &'a Variant>,
+    enum_name: &Ident,
+) {
+    // Whether the type is known to never implement `Copy`, regardless of its generics.
+    fn never_copy(ty: &Type) -> bool {
+        const NEVER_COPY: [&str; 12] = [
+            "String", "Vec", "VecDeque", "Box", "Rc", "Arc", "HashMap", "HashSet", "BTreeMap",
+            "BTreeSet", "RefCell", "Mutex",
+        ];
+        match ty {
+            Type::Reference(reference) => reference.mutability.is_some(),
+            Type::Path(path) => path.path.segments.last().map_or(false, |segment| {
+                NEVER_COPY.iter().any(|name| segment.ident == name)
+            }),
+            Type::Array(array) => never_copy(&array.elem),
+            Type::Tuple(tuple) => tuple.elems.iter().any(never_copy),
+            Type::Paren(paren) => never_copy(&paren.elem),
+            _ => false,
         }
-        (WherePredicate::Lifetime(l), GenericParam::Lifetime(r)) if l.lifetime == r.lifetime => {
-            true
+    }
+
+    let Some(copy) = derives.iter().find(|derive| {
+        derive
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "Copy")
+    }) else {
+        return;
+    };
+
+    for variant in variants {
+        if let Some(field) = variant.fields.iter().find(|field| never_copy(&field.ty)) {
+            abort!(
+                copy,
+                "`Copy` can not be derived for `{}`, because the field `{}` of `{}` is not `Copy`",
+                enum_name,
+                field.ident.as_ref().unwrap(),
+                variant.ident;
+                help = "remove `Copy` from the derives of `{}`, `Clone` can still be derived", enum_name
+            )
         }
-        _ => false,
     }
 }
 
-/// Create hash map that associates certain generics with their predicates.
-fn map_generics(generics: &Generics) -> Vec<(GenericParam, Vec<WherePredicate>)> {
-    let mut map: Vec<_> = generics
-        .params
+/// Add the fields owned by the superstates of a state to its variant. They are not part of
+/// the constructor, but are set to their initial value instead.
+fn add_owned_storage(
+    state: &mut State,
+    owned_storage: &[&(Field, Expr)],
+    state_machine: &analyze::StateMachine,
+) {
+    let state_name = &state_machine.state_ident;
+    let variant_name = state.variant.ident.clone();
+    let state_handler_name = state.constructor.sig.ident.clone();
+
+    let mut variant_fields: Vec<Field> = state.variant.fields.iter().cloned().collect();
+    for (field, _) in owned_storage {
+        if !variant_fields.iter().any(|f| f.ident == field.ident) {
+            variant_fields.push(field.clone());
+        }
+    }
+
+    let is_owned = |field: &Field| owned_storage.iter().any(|(f, _)| f.ident == field.ident);
+    let constructor_fields: Vec<&Field> = variant_fields
         .iter()
-        .map(|param| (param.clone(), Vec::new()))
+        .filter(|field| !is_owned(field))
+        .collect();
+    let constructor_idents: Vec<&Ident> = constructor_fields
+        .iter()
+        .map(|field| field.ident.as_ref().unwrap())
+        .collect();
+    let owned_idents: Vec<&Ident> = owned_storage
+        .iter()
+        .map(|(field, _)| field.ident.as_ref().unwrap())
+        .collect();
+    let initial_values: Vec<&Expr> = owned_storage.iter().map(|(_, value)| value).collect();
+    let pat_fields: Vec<&Ident> = variant_fields
+        .iter()
+        .map(|field| field.ident.as_ref().unwrap())
         .collect();
 
-    // Iterate over the generic parameters and add them to the map.
-    for (param, predicates) in &mut map {
-        for predicate in generics
-            .where_clause
+    let variant_attrs = &state.variant.attrs;
+    state.variant = parse_quote!(#(#variant_attrs)* #variant_name { #(#variant_fields),* });
+    state.pat = parse_quote!(#state_name::#variant_name { #(#pat_fields),*});
+    let visibility = &state_machine.visibility;
+    state.constructor = parse_quote!(
+        #visibility const fn #state_handler_name ( #(#constructor_fields),* ) -> Self {
+            Self::#variant_name { #(#constructor_idents,)* #(#owned_idents: #initial_values),* }
+        }
+    );
+}
+
+/// Check that every input of an entry or exit action, other than the receiver and the
+/// context, is a state-local field of the state or superstate that uses it.
+fn check_action_inputs(
+    variant: &Variant,
+    action: &analyze::Action,
+    state_machine: &analyze::StateMachine,
+    state_name: &Ident,
+) {
+    for input in &action.inputs {
+        let FnArg::Typed(pat_type) = input else {
+            continue;
+        };
+        let Pat::Ident(pat_ident) = pat_type.pat.as_ref() else {
+            continue;
+        };
+        if state_machine.context_ident.eq(&pat_ident.ident) {
+            continue;
+        }
+
+        let is_field = variant
+            .fields
             .iter()
-            .flat_map(|clause| &clause.predicates)
-        {
-            if predicate_bounded_to_param(predicate, param) {
-                predicates.push(predicate.clone())
-            }
+            .any(|field| field.ident.as_ref() == Some(&pat_ident.ident));
+        if !is_field {
+            abort!(
+                pat_type,
+                "`{}` is not a state-local field of `{}`, which uses the action `{}`",
+                pat_ident.ident,
+                state_name,
+                action.handler_name;
+                help = "add `{}` as an input to the `{}` handler", pat_ident.ident, state_name
+            )
         }
     }
+}
 
-    map
+/// Get the name of the constructor that is called (e.g. `on` for `State::on(false)`).
+fn constructor_ident(call: &ExprCall) -> Option<&Ident> {
+    match call.func.as_ref() {
+        Expr::Path(path) => path.path.segments.last().map(|segment| &segment.ident),
+        _ => None,
+    }
 }
 
-fn snake_case_to_pascal_case(snake: &Ident) -> Ident {
-    let mut pascal = String::new();
-    for part in snake.to_string().split('_') {
-        let mut characters = part.chars();
-        pascal.push_str(&characters.next().map_or_else(String::new, |c| {
-            c.to_uppercase().chain(characters).collect()
-        }));
+/// Resolve a transition target to a state, following the initial states of superstates.
+fn resolve<'a>(model: &'a Model, mut target: &'a Ident) -> Option<&'a Ident> {
+    for _ in 0..=model.superstates.len() {
+        if model.states.contains_key(target) {
+            return Some(target);
+        }
+        let initial_state = model.superstates.get(target)?.initial_state.as_ref()?;
+        target = constructor_ident(initial_state)?;
     }
-    format_ident!("{}", pascal)
+    None
 }
 
-#[cfg(test)]
-fn create_analyze_state_machine() -> analyze::StateMachine {
-    analyze::StateMachine {
-        initial_state: parse_quote!(State::on()),
-        shared_storage_type: parse_quote!(Blinky),
-        shared_storage_path: parse_quote!(Blinky),
-        shared_storage_generics: parse_quote!(),
-        state_ident: parse_quote!(State),
-        state_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
-        superstate_ident: parse_quote!(Superstate),
-        superstate_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
-        on_transition: None,
-        on_dispatch: None,
-        visibility: parse_quote!(pub),
-        event_ident: parse_quote!(input),
-        context_ident: parse_quote!(context),
+/// Find the states that can be reached from the initial state by following the transitions
+/// in the handler bodies of the states and their superstates, in breadth-first order.
+/// Transitions to states that are created at runtime can not be followed.
+fn lower_reachable_states(model: &Model) -> Vec<String> {
+    let mut reachable: Vec<&Ident> = Vec::new();
+    let mut queue: VecDeque<&Ident> = VecDeque::new();
+
+    if let Some(initial) = constructor_ident(&model.state_machine.initial_state)
+        .and_then(|initial| resolve(model, initial))
+    {
+        reachable.push(initial);
+        queue.push_back(initial);
+    }
+
+    while let Some(state) = queue.pop_front() {
+        let state = &model.states[state];
+
+        // The transitions of the state itself come first, then those of its superstates
+        // from the innermost to the outermost one.
+        let ancestors = ancestors(model, state.superstate.as_ref());
+        let superstate_transitions = ancestors
+            .iter()
+            .rev()
+            .filter_map(|superstate| model.superstates.get(superstate))
+            .flat_map(|superstate| &superstate.transitions);
+
+        for target in state.transitions.iter().chain(superstate_transitions) {
+            if let Some(target) = resolve(model, target) {
+                if !reachable.contains(&target) {
+                    reachable.push(target);
+                    queue.push_back(target);
+                }
+            }
+        }
     }
+
+    reachable.iter().map(|state| state.to_string()).collect()
 }
 
-#[cfg(test)]
-fn create_lower_state_machine() -> StateMachine {
-    let mut superstate_generics = Generics::default();
+/// Create the adjacency matrix of the states, with the rows and columns in the order of the
+/// state kinds (sorted by variant name). A state is adjacent to the states targeted by the
+/// transitions in its handler and the handlers of its superstates. When one of these
+/// handlers transitions to a state that is created at runtime, or to a target that can not
+/// be resolved to a state, the whole row is marked as adjacent.
+fn lower_adjacency(model: &Model) -> Vec<Vec<bool>> {
+    let mut states: Vec<(Ident, &Ident)> = model
+        .states
+        .keys()
+        .map(|state| (snake_case_to_pascal_case(state), state))
+        .collect();
+    states.sort();
+
+    states
+        .iter()
+        .map(|(_, source)| {
+            let (targets, dynamic) = transition_targets(model, source);
+            states
+                .iter()
+                .map(|(_, target)| dynamic || targets.contains(&Some(*target)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Find the targets of the transitions in the handler of a state and the handlers of its
+/// superstates, resolved to states, and whether the state can transition to any state
+/// because one of these handlers transitions to a state that is created at runtime or to a
+/// target that can not be resolved.
+fn transition_targets<'a>(model: &'a Model, source: &Ident) -> (Vec<Option<&'a Ident>>, bool) {
+    let state = &model.states[source];
+    let ancestors = ancestors(model, state.superstate.as_ref());
+    let superstates: Vec<&analyze::Superstate> = ancestors
+        .iter()
+        .filter_map(|superstate| model.superstates.get(superstate))
+        .collect();
+
+    let targets: Vec<Option<&Ident>> = state
+        .transitions
+        .iter()
+        .chain(
+            superstates
+                .iter()
+                .flat_map(|superstate| &superstate.transitions),
+        )
+        .map(|target| resolve(model, target))
+        .collect();
+
+    let dynamic = state.dynamic_transitions
+        || superstates
+            .iter()
+            .any(|superstate| superstate.dynamic_transitions)
+        || targets.contains(&None);
+
+    (targets, dynamic)
+}
+
+/// Create the reverse lookup of the transitions, listing for every state (in the order of
+/// the state kinds) the names of the states that can transition into it, sorted by name.
+/// The initial state is entered from `"<start>"`, which comes first. The states that can
+/// transition to any state are not listed by name unless they target the state explicitly,
+/// but collapse into a single `"<dynamic>"` entry at the end.
+fn lower_transitions_into(model: &Model) -> Vec<Vec<String>> {
+    let mut states: Vec<(Ident, &Ident)> = model
+        .states
+        .keys()
+        .map(|state| (snake_case_to_pascal_case(state), state))
+        .collect();
+    states.sort();
+
+    let initial = constructor_ident(&model.state_machine.initial_state)
+        .and_then(|initial| resolve(model, initial));
+
+    let mut sources: Vec<&Ident> = model.states.keys().collect();
+    sources.sort();
+    let sources: Vec<(&Ident, Vec<Option<&Ident>>, bool)> = sources
+        .into_iter()
+        .map(|source| {
+            let (targets, dynamic) = transition_targets(model, source);
+            (source, targets, dynamic)
+        })
+        .collect();
+    let any_dynamic = sources.iter().any(|(_, _, dynamic)| *dynamic);
+
+    states
+        .iter()
+        .map(|(_, target)| {
+            let mut predecessors = Vec::new();
+            if initial == Some(*target) {
+                predecessors.push(String::from("<start>"));
+            }
+            predecessors.extend(
+                sources
+                    .iter()
+                    .filter(|(_, targets, _)| targets.contains(&Some(*target)))
+                    .map(|(source, _, _)| source.to_string()),
+            );
+            if any_dynamic {
+                predecessors.push(String::from("<dynamic>"));
+            }
+            predecessors
+        })
+        .collect()
+}
+
+/// Lower the hooks of specific transitions, using the adjacency matrix to find out whether
+/// the transition can occur at all.
+fn lower_transition_hooks(model: &Model, adjacency: &[Vec<bool>]) -> Vec<TransitionHook> {
+    let shared_storage_path = &model.state_machine.shared_storage_path;
+    let (_, shared_storage_type_generics, _) =
+        &model.state_machine.shared_storage_generics.split_for_impl();
+    let shared_storage_turbofish = shared_storage_type_generics.as_turbofish();
+
+    let mut variants: Vec<Ident> = model.states.keys().map(snake_case_to_pascal_case).collect();
+    variants.sort();
+    let index = |name: &Ident| {
+        if model.states.contains_key(name) {
+            let variant = snake_case_to_pascal_case(name);
+            return variants.binary_search(&variant).unwrap();
+        }
+        match model.superstates.contains_key(name) {
+            true => abort!(
+                name,
+                "`{}` is a superstate", name;
+                help = "transition hooks can only be declared between states"
+            ),
+            false => abort!(name, "state `{}` not found", name),
+        }
+    };
+
+    model
+        .state_machine
+        .transition_hooks
+        .iter()
+        .map(|hook| {
+            let (source, target) = (index(&hook.from), index(&hook.to));
+            let call = &hook.call;
+            let receiver = fn_arg_to_input(&parse_quote!(&mut self), &model.state_machine);
+            TransitionHook {
+                from: hook.from.clone(),
+                to: hook.to.clone(),
+                source_variant: variants[source].clone(),
+                target_variant: variants[target].clone(),
+                handler_call: parse_quote!(
+                    #shared_storage_path #shared_storage_turbofish ::#call(#receiver)
+                ),
+                occurs: adjacency[source][target],
+            }
+        })
+        .collect()
+}
+
+fn lower_transition_weights(model: &Model) -> Vec<(Ident, Ident, LitFloat)> {
+    let mut states: Vec<_> = model.states.values().collect();
+    states.sort_by_key(|state| state.handler_name.to_string());
+
+    let mut transition_weights = Vec::new();
+    for state in states {
+        for (to, weight) in &state.transition_weights {
+            if !model.states.contains_key(to) && !model.superstates.contains_key(to) {
+                abort!(to, "state `{}` not found", to);
+            }
+            transition_weights.push((state.handler_name.clone(), to.clone(), weight.clone()));
+        }
+    }
+    transition_weights
+}
+
+/// Create a JSON description of the states, superstates, transitions and actions of
+/// the state machine. Everything is sorted by name so the output is deterministic.
+fn lower_json(model: &Model) -> String {
+    fn json_string(ident: Option<&Ident>) -> String {
+        match ident {
+            Some(ident) => format!("\"{ident}\""),
+            None => String::from("null"),
+        }
+    }
+
+    fn json_array<'a>(idents: impl IntoIterator<Item = &'a Ident>) -> String {
+        let items: Vec<String> = idents
+            .into_iter()
+            .map(|ident| json_string(Some(ident)))
+            .collect();
+        format!("[{}]", items.join(","))
+    }
+
+    // A single action is kept as a string, several are listed in the order they run in.
+    fn json_actions<'a>(idents: impl ExactSizeIterator<Item = &'a Ident>) -> String {
+        match idents.len() {
+            0 | 1 => json_string(idents.last()),
+            _ => json_array(idents),
+        }
+    }
+
+    let mut states: Vec<_> = model.states.values().collect();
+    states.sort_by_key(|state| state.handler_name.to_string());
+    let states: Vec<String> = states
+        .into_iter()
+        .map(|state| {
+            let mut transitions = state.transitions.clone();
+            transitions.sort_by_key(|ident| ident.to_string());
+            format!(
+                "{{\"name\":{},\"superstate\":{},\"entry_action\":{},\"exit_action\":{},\"transitions\":{}}}",
+                json_string(Some(&state.handler_name)),
+                json_string(state.superstate.as_ref()),
+                json_actions(state.entry_actions.iter()),
+                json_actions(state.exit_actions.iter().rev()),
+                json_array(&transitions),
+            )
+        })
+        .collect();
+
+    let mut superstates: Vec<_> = model.superstates.values().collect();
+    superstates.sort_by_key(|superstate| superstate.handler_name.to_string());
+    let superstates: Vec<String> = superstates
+        .into_iter()
+        .map(|superstate| {
+            let mut transitions = superstate.transitions.clone();
+            transitions.sort_by_key(|ident| ident.to_string());
+            format!(
+                "{{\"name\":{},\"superstate\":{},\"initial\":{},\"entry_action\":{},\"exit_action\":{},\"transitions\":{}}}",
+                json_string(Some(&superstate.handler_name)),
+                json_string(superstate.superstate.as_ref()),
+                json_string(superstate.initial_state.as_ref().and_then(constructor_ident)),
+                json_actions(superstate.entry_actions.iter()),
+                json_actions(superstate.exit_actions.iter().rev()),
+                json_array(&transitions),
+            )
+        })
+        .collect();
+
+    let mut actions: Vec<_> = model.actions.keys().collect();
+    actions.sort_by_key(|action| action.to_string());
+
+    format!(
+        "{{\"initial\":{},\"states\":[{}],\"superstates\":[{}],\"actions\":{}}}",
+        json_string(constructor_ident(&model.state_machine.initial_state)),
+        states.join(","),
+        superstates.join(","),
+        json_array(actions),
+    )
+}
+
+/// Describe the state hierarchy in the DOT format of Graphviz. Every superstate is a cluster
+/// that contains a node for the superstate itself and the nodes of its children, which point
+/// to it with a dashed edge. The names are quoted, as states can be named after DOT keywords
+/// (e.g. `node`).
+fn lower_graphviz(model: &Model) -> String {
+    fn write_children(dot: &mut String, model: &Model, parent: Option<&Ident>, depth: usize) {
+        let indent = "    ".repeat(depth);
+
+        let mut superstates: Vec<_> = model
+            .superstates
+            .values()
+            .filter(|superstate| superstate.superstate.as_ref() == parent)
+            .collect();
+        superstates.sort_by_key(|superstate| superstate.handler_name.to_string());
+        for superstate in superstates {
+            let name = &superstate.handler_name;
+            dot.push_str(&format!("{indent}subgraph \"cluster_{name}\" {{\n"));
+            dot.push_str(&format!("{indent}    label=\"{name}\";\n"));
+            dot.push_str(&format!("{indent}    \"{name}\" [shape=box];\n"));
+            write_children(dot, model, Some(name), depth + 1);
+            dot.push_str(&format!("{indent}}}\n"));
+        }
+
+        let mut states: Vec<_> = model
+            .states
+            .values()
+            .filter(|state| state.superstate.as_ref() == parent)
+            .collect();
+        states.sort_by_key(|state| state.handler_name.to_string());
+        for state in states {
+            dot.push_str(&format!("{indent}\"{}\";\n", state.handler_name));
+        }
+    }
+
+    let name = model
+        .state_machine
+        .shared_storage_path
+        .segments
+        .last()
+        .map_or(String::new(), |segment| segment.ident.to_string());
+
+    let mut dot = format!("digraph \"{name}\" {{\n");
+    write_children(&mut dot, model, None, 1);
+
+    let mut edges: Vec<(&Ident, &Ident)> = model
+        .states
+        .values()
+        .filter_map(|state| Some((&state.handler_name, state.superstate.as_ref()?)))
+        .chain(model.superstates.values().filter_map(|superstate| {
+            Some((&superstate.handler_name, superstate.superstate.as_ref()?))
+        }))
+        .collect();
+    edges.sort_by_key(|(child, _)| child.to_string());
+    for (child, parent) in edges {
+        dot.push_str(&format!(
+            "    \"{child}\" -> \"{parent}\" [style=dashed];\n"
+        ));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Create a one line summary of the state machine, with its name, mode, number of states
+/// and superstates and initial state.
+fn lower_description(model: &Model, mode: Mode) -> String {
+    fn count(number: usize, noun: &str) -> String {
+        match number {
+            1 => format!("1 {noun}"),
+            _ => format!("{number} {noun}s"),
+        }
+    }
+
+    let name = model
+        .state_machine
+        .shared_storage_path
+        .segments
+        .last()
+        .map_or(String::new(), |segment| segment.ident.to_string());
+    let mode = match mode {
+        Mode::Blocking => "blocking",
+        Mode::Awaitable => "awaitable",
+    };
+    let initial = constructor_ident(&model.state_machine.initial_state)
+        .map_or(String::new(), |ident| ident.to_string());
+
+    format!(
+        "{name}: {mode} state machine with {} and {}, starting in `{initial}`",
+        count(model.states.len(), "state"),
+        count(model.superstates.len(), "superstate"),
+    )
+}
+
+/// Get the type of the event or context that is passed in to a handler. When these inputs
+/// are passed by reference, the type behind the reference is returned.
+fn external_input_type(
+    input: &Option<PatType>,
+    ident: &Ident,
+    name: &str,
+    by_reference: bool,
+) -> Option<Type> {
+    let pat_type = input.as_ref()?;
+    match &*pat_type.pat {
+        Pat::Ident(pat) if ident.eq(&pat.ident) => {}
+        _ => return None,
+    }
+    match (&*pat_type.ty, by_reference) {
+        (Type::Reference(reference), true) => Some(reference.elem.deref().clone()),
+        (ty, true) => abort!(
+            ty,
+            "{} must be passed in as a reference", name;
+            help = "consider changing the type of `{}` to `&{}`", ident, ty.to_token_stream()
+        ),
+        (Type::Reference(reference), false) => abort!(
+            reference,
+            "{} must be passed in by value", name;
+            help = "consider changing the type of `{}` to `{}`", ident, reference.elem.to_token_stream()
+        ),
+        (ty, false) => Some(ty.clone()),
+    }
+}
+
+/// Keep the type of the event, context or error that is declared first, and reject the
+/// declarations that use a different type. Lifetimes are left out of the comparison, as they
+/// are renamed when the type is lowered anyway.
+fn agree_on_type(
+    found: &mut Option<(Type, String)>,
+    ty: Type,
+    input: &impl ToTokens,
+    handler_name: &Ident,
+    name: &str,
+) {
+    let normalized = |ty: &Type| {
+        let mut ty = ty.clone();
+        LifetimeVisitor::new("'normalized").rename_type(&mut ty);
+        ty
+    };
+    match found {
+        Some((first, first_handler)) if normalized(first) != normalized(&ty) => abort!(
+            input,
+            "all states must agree on the {} type", name;
+            help = "`{}` uses `{}`, while `{}` uses `{}`",
+                first_handler, first.to_token_stream(), handler_name, ty.to_token_stream()
+        ),
+        Some(_) => {}
+        None => *found = Some((ty, handler_name.to_string())),
+    }
+}
+
+/// Convert the `Result` of a fallible handler into a `Response`, storing the error in the
+/// given field of the shared storage.
+fn fallible_handler_call(handler_call: &Expr, field: &Ident) -> Expr {
+    parse_quote!(match #handler_call {
+        Ok(response) => statig::Response::from(response),
+        Err(error) => {
+            shared_storage.#field = Some(error);
+            statig::Response::Handled
+        }
+    })
+}
+
+/// Wrap a handler call in a `tracing` span that is named after the state or superstate
+/// and records the event. For async handlers the span is kept across the await points by
+/// instrumenting the call.
+fn traced_handler_call(handler_call: &Expr, name: &Ident, event_ident: &Ident, mode: Mode) -> Expr {
+    let name = LitStr::new(&name.to_string(), name.span());
+    let span: Expr = parse_quote!(
+        statig::tracing::span!(statig::tracing::Level::TRACE, #name, event = ?#event_ident)
+    );
+    match mode {
+        Mode::Blocking => parse_quote!({
+            let span = #span;
+            let _enter = span.enter();
+            #handler_call
+        }),
+        Mode::Awaitable => parse_quote!(
+            statig::tracing::Instrument::instrument(async { #handler_call }, #span).await
+        ),
+    }
+}
+
+/// Wrap an entry or exit action call so the name of the action is recorded in the given
+/// field of the shared storage before it is called, and optionally mark the outcome as a
+/// transition.
+fn fired_action_call<'a>(
+    call: &Expr,
+    field: &Ident,
+    actions: impl Iterator<Item = &'a Ident>,
+    transition: bool,
+) -> Expr {
+    let record_transition = transition.then(|| quote!(shared_storage.#field.record_transition();));
+    let record_actions = actions.map(|action| {
+        let name = LitStr::new(&action.to_string(), action.span());
+        quote!(shared_storage.#field.record_action(#name);)
+    });
+    parse_quote!({
+        #record_transition
+        #(#record_actions)*
+        #call
+    })
+}
+
+/// Chain the calls to the entry or exit actions of a state, in the given order, into a single
+/// call. When any of the actions is fallible, this also returns a call that reports whether
+/// all of them succeeded, which stops at the first one that fails.
+fn chain_action_calls(calls: &[(&Expr, bool)]) -> (Expr, Option<Expr>) {
+    let call = match calls {
+        [(call, false)] => (*call).clone(),
+        _ => {
+            let stmts = calls.iter().map(|(call, is_fallible)| match is_fallible {
+                true => quote!(let _ = #call;),
+                false => quote!(#call;),
+            });
+            parse_quote!({ #(#stmts)* })
+        }
+    };
+    let try_call = calls.iter().any(|(_, is_fallible)| *is_fallible).then(|| {
+        let mut conditions = calls.iter().map(|(call, is_fallible)| -> Expr {
+            match is_fallible {
+                true => parse_quote!((#call).is_ok()),
+                false => parse_quote!({ #call; true }),
+            }
+        });
+        let first = conditions.next().unwrap();
+        parse_quote!(#first #(&& #conditions)*)
+    });
+    (call, try_call)
+}
+
+/// Get the expression that passes the input to a handler. Events are copied out of the
+/// reference they are dispatched with when `event_ref = false`.
+fn fn_arg_to_input(fn_arg: &FnArg, state_machine: &analyze::StateMachine) -> Expr {
+    if let (FnArg::Receiver(_), Some(field)) = (fn_arg, &state_machine.shared_storage_field) {
+        return parse_quote!(&mut shared_storage.#field);
+    }
+    let ident = fn_arg_to_ident(fn_arg);
+    match !state_machine.event_ref && state_machine.event_ident.eq(&ident) {
+        true => parse_quote!(*#ident),
+        false => parse_quote!(#ident),
+    }
+}
+
+fn fn_arg_to_ident(fn_arg: &FnArg) -> Ident {
+    match fn_arg {
+        FnArg::Receiver(_) => parse_quote!(shared_storage),
+        FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+            Pat::Ident(pat_ident) => pat_ident.ident.clone(),
+            _ => panic!("all patterns should be verified to be idents"),
+        },
+    }
+}
+
+fn fn_arg_to_shared_storage_field_borrow(
+    pat_type: &PatType,
+    state_machine: &analyze::StateMachine,
+) -> Expr {
+    let mutability = match pat_type.ty.as_ref() {
+        Type::Reference(reference) => reference.mutability,
+        _ => abort!(
+            pat_type,
+            "field of the shared storage must be passed as a reference"
+        ),
+    };
+
+    match pat_type.pat.as_ref() {
+        Pat::Ident(pat_ident) => {
+            let field_ident = &pat_ident.ident;
+            match &state_machine.shared_storage_field {
+                Some(field) => parse_quote!(&#mutability shared_storage.#field.#field_ident),
+                None => parse_quote!(&#mutability shared_storage.#field_ident),
+            }
+        }
+        _ => panic!("all patterns should be verified to be idents"),
+    }
+}
+
+/// States own their local storage, so the reference is stripped from the field type.
+/// When the handler is called, the field is borrowed with the mutability declared by
+/// the handler. Inputs that are not references are only allowed when the state is marked
+/// `local_storage_by_value`, in which case their type is kept as is.
+fn fn_arg_to_state_field(pat_type: &PatType, by_value: bool) -> Field {
+    let field_type = match (pat_type.ty.as_ref(), by_value) {
+        (Type::Reference(reference), _) => reference.elem.as_ref().clone(),
+        (ty, true) => ty.clone(),
+        (_, false) => abort!(
+            pat_type,
+            "input must be passed as a reference";
+            help = "mark the state with `#[state(local_storage_by_value)]` to pass `Copy` types by value"
+        ),
+    };
+
+    match pat_type.pat.as_ref() {
+        Pat::Ident(pat_ident) => {
+            let field_ident = &pat_ident.ident;
+            Field::parse_named
+                .parse2(quote::quote!(#field_ident: #field_type))
+                .unwrap()
+        }
+        _ => panic!("all patterns should be verified to be idents"),
+    }
+}
+
+fn fn_arg_to_superstate_field(pat_type: &PatType, lifetime: &Lifetime) -> Field {
+    let field_type = match pat_type.ty.as_ref() {
+        Type::Reference(reference) => {
+            let mut reference = reference.clone();
+            reference.lifetime = Some(lifetime.clone());
+            Type::Reference(reference)
+        }
+        _ => abort!(pat_type, "input must be passed as a reference"),
+    };
+    match pat_type.pat.as_ref() {
+        Pat::Ident(pat_ident) => {
+            let field_ident = &pat_ident.ident;
+            Field::parse_named
+                .parse2(quote::quote!(#field_ident: #field_type))
+                .unwrap()
+        }
+        _ => panic!("all patterns should be verified to be idents"),
+    }
+}
+
+pub fn get_generic_argument_ident(ty: &Type) -> Ident {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| &s.ident).unwrap().clone(),
+        _ => panic!("can not get ident of shared storage"),
+    }
+}
+
+fn predicate_bounded_to_param(predicate: &WherePredicate, param: &GenericParam) -> bool {
+    match (predicate, param) {
+        (WherePredicate::Type(l), GenericParam::Type(r))
+            if get_generic_argument_ident(&l.bounded_ty) == r.ident =>
+        {
+            true
+        }
+        (WherePredicate::Lifetime(l), GenericParam::Lifetime(r)) if l.lifetime == r.lifetime => {
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Create hash map that associates certain generics with their predicates.
+fn map_generics(generics: &Generics) -> Vec<(GenericParam, Vec<WherePredicate>)> {
+    let mut map: Vec<_> = generics
+        .params
+        .iter()
+        .map(|param| (param.clone(), Vec::new()))
+        .collect();
+
+    // Iterate over the generic parameters and add them to the map.
+    for (param, predicates) in &mut map {
+        for predicate in generics
+            .where_clause
+            .iter()
+            .flat_map(|clause| &clause.predicates)
+        {
+            if predicate_bounded_to_param(predicate, param) {
+                predicates.push(predicate.clone())
+            }
+        }
+    }
+
+    map
+}
+
+fn snake_case_to_pascal_case(snake: &Ident) -> Ident {
+    let mut pascal = String::new();
+    for part in snake.to_string().split('_') {
+        let mut characters = part.chars();
+        pascal.push_str(&characters.next().map_or_else(String::new, |c| {
+            c.to_uppercase().chain(characters).collect()
+        }));
+    }
+    format_ident!("{}", pascal)
+}
+
+#[cfg(test)]
+fn create_analyze_state_machine() -> analyze::StateMachine {
+    analyze::StateMachine {
+        initial_state: parse_quote!(State::on()),
+        region_initial_states: Vec::new(),
+        shared_storage_type: parse_quote!(Blinky),
+        shared_storage_path: parse_quote!(Blinky),
+        shared_storage_field: None,
+        shared_storage_generics: parse_quote!(),
+        lifetime: None,
+        superstate_lifetime: parse_quote!('sub),
+        state_ident: parse_quote!(State),
+        state_kind_ident: parse_quote!(StateKind),
+        state_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
+        state_repr: vec![],
+        superstate_ident: parse_quote!(Superstate),
+        superstate_kind_ident: parse_quote!(SuperstateKind),
+        superstate_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
+        on_transition: None,
+        transition_hooks: vec![],
+        on_dispatch: None,
+        startup_event: None,
+        stack_allocator: None,
+        previous_state_kind: None,
+        origin: None,
+        sequence: None,
+        observer: None,
+        validate: false,
+        json: false,
+        describe: false,
+        export_graph: false,
+        diff: false,
+        send_sync: false,
+        transition_to: false,
+        replay: false,
+        apply_event_log: false,
+        state_index: false,
+        mode: None,
+        minimal: false,
+        deref_storage: false,
+        drop_guard: false,
+        tracing: false,
+        generics_passthrough: false,
+        merge_hints: false,
+        cfg: None,
+        box_futures: None,
+        serialize_path: None,
+        entry_exit_counts: None,
+        timeout: None,
+        fired_actions: None,
+        fallible: None,
+        visibility: parse_quote!(pub),
+        event_ident: parse_quote!(input),
+        event_ref: true,
+        context_ident: parse_quote!(context),
+        response_ident: None,
+        facade_ident: None,
+    }
+}
+
+#[cfg(test)]
+fn create_lower_state_machine() -> StateMachine {
+    let mut superstate_generics = Generics::default();
     superstate_generics.params.push(parse_quote!('sub));
     StateMachine {
         initial_state: parse_quote!(State::on()),
@@ -728,15 +2290,51 @@ fn create_lower_state_machine() -> StateMachine {
         #[rustfmt::skip]
         state_ident: parse_quote!(State),
         state_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
+        state_repr: vec![],
         state_generics: Generics::default(),
+        state_kind_ident: parse_quote!(StateKind),
         superstate_ident: parse_quote!(Superstate),
+        superstate_kind_ident: parse_quote!(SuperstateKind),
+        superstate_lifetime: parse_quote!('sub),
         superstate_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
         superstate_generics,
         on_transition: None,
+        transition_hooks: vec![],
+        transition_weights: vec![],
         on_dispatch: None,
+        startup_event: None,
+        stack_allocator: None,
+        previous_state_kind: None,
+        origin: None,
+        sequence: None,
+        observer: None,
+        fired_actions: None,
+        handler_error: None,
+        validate: false,
+        json: None,
+        describe: None,
+        graphviz: None,
+        diff: false,
+        send_sync: false,
+        transition_to: false,
+        replay: false,
+        apply_event_log: false,
+        state_index: false,
+        cfg: None,
+        box_futures: false,
+        serialize_path: false,
+        reachable_states: vec![String::from("on")],
+        adjacency: vec![vec![false]],
+        transitions_into: vec![vec![String::from("<start>")]],
+        mergeable_states: vec![],
+        minimal: false,
+        deref_storage: false,
+        drop_guard: false,
         visibility: parse_quote!(pub),
         event_ident: parse_quote!(input),
         context_ident: parse_quote!(context),
+        response_ident: None,
+        facade_ident: None,
         mode: Mode::Blocking,
     }
 }
@@ -746,8 +2344,8 @@ fn create_analyze_state() -> analyze::State {
     analyze::State {
         handler_name: parse_quote!(on),
         superstate: parse_quote!(playing),
-        entry_action: parse_quote!(enter_on),
-        exit_action: None,
+        entry_actions: vec![parse_quote!(enter_on)],
+        exit_actions: Vec::new(),
         local_storage: vec![],
         inputs: vec![
             parse_quote!(&mut self),
@@ -776,6 +2374,21 @@ fn create_analyze_state() -> analyze::State {
                 panic!();
             },
         ],
+        shared_storage_fields: vec![],
+        transitions: vec![],
+        dynamic_transitions: false,
+        transition_weights: vec![],
+        control_flow: false,
+        error_type: None,
+        handled_events: vec![],
+        parallel: false,
+        allow_missing_event: false,
+        local_storage_by_value: false,
+        terminal: false,
+        is_final: false,
+        priority: None,
+        aliases: vec![],
+        variant_attrs: vec![],
         is_async: false,
     }
 }
@@ -790,13 +2403,19 @@ fn create_lower_state() -> State {
         pat: parse_quote!(State::On { led, counter }),
         handler_call: parse_quote!(Blinky::on(shared_storage, input, led, counter)),
         entry_action_call: parse_quote!({}),
+        try_entry_action_call: None,
         exit_action_call: parse_quote!({}),
         superstate_pat: parse_quote!(None),
         constructor: parse_quote!(
-            const fn on(led: bool, counter: usize) -> Self {
+            pub const fn on(led: bool, counter: usize) -> Self {
                 Self::On { led, counter }
             }
         ),
+        path: vec![parse_quote!(on)],
+        handled_events: vec![],
+        terminal: false,
+        priority: None,
+        names: vec!["on".to_string()],
     }
 }
 
@@ -805,6 +2424,7 @@ fn create_linked_lower_state() -> State {
     let mut state = create_lower_state();
     state.superstate_pat = parse_quote!(Some(Superstate::Playing { led, counter }));
     state.entry_action_call = parse_quote!(Blinky::enter_on(shared_storage, led));
+    state.path = vec![parse_quote!(playing), parse_quote!(on)];
     state
 }
 
@@ -813,9 +2433,13 @@ fn create_analyze_superstate() -> analyze::Superstate {
     analyze::Superstate {
         handler_name: parse_quote!(playing),
         superstate: None,
-        entry_action: None,
-        exit_action: None,
+        initial_state: None,
+        entry_actions: Vec::new(),
+        exit_actions: Vec::new(),
+        pre_dispatch: None,
         local_storage: vec![],
+        variant_attrs: vec![],
+        owned_storage: vec![],
         inputs: vec![
             parse_quote!(&mut self),
             parse_quote!(input: &Event),
@@ -843,6 +2467,10 @@ fn create_analyze_superstate() -> analyze::Superstate {
                 panic!();
             },
         ],
+        transitions: vec![],
+        dynamic_transitions: false,
+        control_flow: false,
+        error_type: None,
         is_async: false,
     }
 }
@@ -857,8 +2485,12 @@ fn create_lower_superstate() -> Superstate {
         pat: parse_quote!(Superstate::Playing { led, counter }),
         handler_call: parse_quote!(Blinky::playing(shared_storage, input, led, counter)),
         entry_action_call: parse_quote!({}),
+        try_entry_action_call: None,
         exit_action_call: parse_quote!({}),
+        pre_dispatch_call: parse_quote!(statig::Response::Super),
         superstate_pat: parse_quote!(None),
+        initial_state_constructor: None,
+        owned_storage: vec![],
     }
 }
 
@@ -867,6 +2499,7 @@ fn create_analyze_action() -> analyze::Action {
     analyze::Action {
         handler_name: parse_quote!(enter_on),
         inputs: vec![parse_quote!(&mut self), parse_quote!(led: &mut bool)],
+        is_fallible: false,
         is_async: false,
     }
 }
@@ -895,6 +2528,7 @@ fn create_analyze_model() -> analyze::Model {
             .into_iter()
             .map(|state| (state.handler_name.clone(), state))
             .collect(),
+        mergeable_states: vec![],
     }
 }
 
@@ -956,3 +2590,587 @@ fn test_lower() {
 
     assert_eq!(actual, expected);
 }
+
+#[test]
+#[should_panic]
+fn test_superstate_action_borrows_shared_field_mutably() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(superstate = "playing")]
+            fn on(led: &mut bool) -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate(entry_action = "enter_playing")]
+            fn playing(led: &bool) -> Response<State> {
+                Response::Handled
+            }
+
+            #[action]
+            fn enter_playing(led: &mut bool) {}
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_action_input_missing_from_state() {
+    let attribute_args = vec![parse_quote!(initial = "State::on(false)")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(entry_action = "enter")]
+            fn on(led: &mut bool) -> Response<State> {
+                Response::Handled
+            }
+
+            #[state(entry_action = "enter")]
+            fn off() -> Response<State> {
+                Response::Handled
+            }
+
+            #[action]
+            fn enter(led: &mut bool) {}
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_copy_derive_with_string_field() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::on(String::new())"),
+        parse_quote!(state(derive(Copy, Clone))),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(name: &mut String) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_forced_blocking_mode_with_async_handler() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::on()"),
+        parse_quote!(mode = "blocking"),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            async fn on() -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_event_passed_by_value() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: Event) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_superstate_event_passed_by_value() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(superstate = "playing")]
+            fn on(event: &Event) -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate]
+            fn playing(event: Event) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_context_passed_by_value() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event, context: Context) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_superstate_context_passed_by_value() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(superstate = "playing")]
+            fn on(event: &Event) -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate]
+            fn playing(event: &Event, context: Context) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_local_storage_passed_by_value() {
+    let attribute_args = vec![parse_quote!(initial = "State::on(0)")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(counter: u8) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+fn test_superstate_generics_come_from_superstates() {
+    let attribute_args = vec![parse_quote!(initial = "State::off()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl<T, U> Blinky<T, U> {
+            #[state(superstate = "playing")]
+            fn on(value: &mut T) -> Response<State> {
+                Response::Handled
+            }
+
+            #[state]
+            fn off(other: &mut U) -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate]
+            fn playing(value: &T) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    let ir = lower(&model);
+
+    let params = |generics: &Generics| -> Vec<String> {
+        generics
+            .type_params()
+            .map(|param| param.ident.to_string())
+            .collect()
+    };
+
+    // `U` is only used by a state, so it must not end up on the superstate enum.
+    assert_eq!(params(&ir.state_machine.state_generics), ["T", "U"]);
+    assert_eq!(params(&ir.state_machine.superstate_generics), ["T"]);
+}
+
+#[test]
+#[should_panic]
+fn test_conflicting_event_types() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &EventA) -> Response<State> {
+                Response::Handled
+            }
+
+            #[state]
+            fn off(event: &EventB) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_conflicting_context_types() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(superstate = "playing")]
+            fn on(context: &mut ContextA) -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate]
+            fn playing(context: &mut ContextB) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+fn test_event_types_differing_in_lifetimes_agree() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event<'_>) -> Response<State> {
+                Response::Handled
+            }
+
+            #[state]
+            fn off<'a>(event: &'a Event<'a>) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    let ir = lower(&model);
+
+    assert_eq!(ir.state_machine.event_type, parse_quote!(Event<'event>));
+}
+
+#[test]
+fn test_error_type_is_inferred() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::on()"),
+        parse_quote!(fallible = "error"),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event) -> Result<Response<State>, Error> {
+                Ok(Response::Handled)
+            }
+
+            #[state]
+            fn off(event: &Event) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    let ir = lower(&model);
+
+    assert_eq!(
+        ir.state_machine.handler_error,
+        Some((parse_quote!(error), parse_quote!(Error)))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_result_without_fallible() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event) -> Result<Response<State>, Error> {
+                Ok(Response::Handled)
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_conflicting_error_types() {
+    let attribute_args = vec![
+        parse_quote!(initial = "State::on()"),
+        parse_quote!(fallible = "error"),
+    ];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event) -> Result<Response<State>, ErrorA> {
+                Ok(Response::Handled)
+            }
+
+            #[state]
+            fn off(event: &Event) -> Result<Response<State>, ErrorB> {
+                Ok(Response::Handled)
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+fn test_transition_hooks_occur() {
+    let attribute_args = vec![parse_quote!(initial = "State::off()")];
+    let item_impl: ItemImpl = parse_quote!(
+        #[transition(from = "off", to = "on", call = "switched_on")]
+        #[transition(from = "on", to = "off", call = "switched_off")]
+        impl Blinky {
+            #[state]
+            fn on() -> Response<State> {
+                Response::Handled
+            }
+
+            #[state]
+            fn off() -> Response<State> {
+                Response::Transition(State::on())
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    assert!(model.item_impl.attrs.is_empty());
+
+    let ir = lower(&model);
+    let occurs: Vec<(String, String, bool)> = ir
+        .state_machine
+        .transition_hooks
+        .iter()
+        .map(|hook| (hook.from.to_string(), hook.to.to_string(), hook.occurs))
+        .collect();
+    assert_eq!(
+        occurs,
+        vec![
+            (String::from("off"), String::from("on"), true),
+            (String::from("on"), String::from("off"), false)
+        ]
+    );
+}
+
+#[test]
+fn test_variant_attrs_are_kept_with_owned_storage() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(superstate = "playing", variant_attrs(serde(rename = "lit")))]
+            fn on(count: &u8) -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate(owned_storage("count: u8 = 0"), variant_attrs(doc = "Playing"))]
+            fn playing() -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    let ir = lower(&model);
+
+    let on: Ident = parse_quote!(on);
+    let on_attrs: Vec<syn::Attribute> = vec![parse_quote!(#[serde(rename = "lit")])];
+    assert_eq!(ir.states[&on].variant.attrs, on_attrs);
+    assert_eq!(ir.states[&on].variant.fields.len(), 1);
+
+    let playing: Ident = parse_quote!(playing);
+    let playing_attrs: Vec<syn::Attribute> = vec![parse_quote!(#[doc = "Playing"])];
+    assert_eq!(ir.superstates[&playing].variant.attrs, playing_attrs);
+}
+
+#[test]
+fn test_multiple_actions_are_chained_in_order() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(
+                entry_action = "init_a",
+                entry_action = "init_b",
+                exit_actions(exit_a, exit_b)
+            )]
+            fn on() -> Response<State> {
+                Response::Handled
+            }
+
+            #[action]
+            fn init_a() {}
+
+            #[action]
+            fn init_b() {}
+
+            #[action]
+            fn exit_a() {}
+
+            #[action]
+            fn exit_b() {}
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    let ir = lower(&model);
+
+    let on: Ident = parse_quote!(on);
+    let entry_action_call: Expr = parse_quote!({
+        Blinky::init_a();
+        Blinky::init_b();
+    });
+    let exit_action_call: Expr = parse_quote!({
+        Blinky::exit_b();
+        Blinky::exit_a();
+    });
+    assert_eq!(ir.states[&on].entry_action_call, entry_action_call);
+    assert_eq!(ir.states[&on].try_entry_action_call, None);
+    assert_eq!(ir.states[&on].exit_action_call, exit_action_call);
+}
+
+#[test]
+#[should_panic]
+fn test_missing_action_in_list() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(entry_actions(init_a, init_b))]
+            fn on() -> Response<State> {
+                Response::Handled
+            }
+
+            #[action]
+            fn init_a() {}
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_superstate_cycle() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(superstate = "a")]
+            fn on() -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate(superstate = "b")]
+            fn a() -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate(superstate = "c")]
+            fn b() -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate(superstate = "a")]
+            fn c() -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_superstate_self_cycle() {
+    let attribute_args = vec![parse_quote!(initial = "State::on()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(superstate = "playing")]
+            fn on() -> Response<State> {
+                Response::Handled
+            }
+
+            #[superstate(superstate = "playing")]
+            fn playing() -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    lower(&model);
+}
+
+#[test]
+fn test_state_without_event_under_superstate_with_event() {
+    let attribute_args = vec![parse_quote!(initial = "State::idle()")];
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(superstate = "playing")]
+            fn idle() -> Response<State> {
+                Response::Super
+            }
+
+            #[state(superstate = "playing")]
+            fn on(event: &Event) -> Response<State> {
+                Response::Super
+            }
+
+            #[superstate]
+            fn playing(event: &Event) -> Response<State> {
+                Response::Handled
+            }
+        }
+    );
+
+    let model = analyze::analyze(attribute_args, item_impl);
+    let ir = lower(&model);
+
+    let idle: Ident = parse_quote!(idle);
+    let idle_call: Expr = parse_quote!(Blinky::idle());
+    assert_eq!(ir.states[&idle].handler_call, idle_call);
+
+    let on: Ident = parse_quote!(on);
+    let on_call: Expr = parse_quote!(Blinky::on(event));
+    assert_eq!(ir.states[&on].handler_call, on_call);
+
+    let event_type: Type = parse_quote!(Event);
+    assert_eq!(ir.state_machine.event_type, event_type);
+}
@@ -0,0 +1,92 @@
+use syn::visit::{self, Visit};
+use syn::{Block, Expr, ExprMatch, Ident, Pat};
+
+/// Visitor to find the names of the event variants that are matched on in the body of a
+/// handler (e.g. `Event::TimerElapsed` in `match event { Event::TimerElapsed => .. }`).
+/// Wildcards and bindings are reported as `"_"`.
+#[derive(Debug)]
+pub struct EventVisitor<'a> {
+    event_ident: &'a Ident,
+    found: Vec<String>,
+}
+
+impl<'a> EventVisitor<'a> {
+    pub fn new(event_ident: &'a Ident) -> Self {
+        Self {
+            event_ident,
+            found: Vec::new(),
+        }
+    }
+
+    pub fn search(&mut self, block: &Block) {
+        self.visit_block(block);
+    }
+
+    pub fn finish(self) -> Vec<String> {
+        self.found
+    }
+
+    fn is_event(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Path(path) => path.path.is_ident(self.event_ident),
+            Expr::Unary(unary) => self.is_event(&unary.expr),
+            Expr::Paren(paren) => self.is_event(&paren.expr),
+            _ => false,
+        }
+    }
+
+    fn add_pat(&mut self, pat: &Pat) {
+        let name = match pat {
+            Pat::Path(pat) => pat.path.segments.last().map(|s| s.ident.to_string()),
+            Pat::TupleStruct(pat) => pat.path.segments.last().map(|s| s.ident.to_string()),
+            Pat::Struct(pat) => pat.path.segments.last().map(|s| s.ident.to_string()),
+            Pat::Reference(pat) => return self.add_pat(&pat.pat),
+            Pat::Or(pat) => {
+                for case in &pat.cases {
+                    self.add_pat(case);
+                }
+                return;
+            }
+            _ => None,
+        };
+
+        let name = name.unwrap_or_else(|| "_".to_string());
+        if !self.found.contains(&name) {
+            self.found.push(name);
+        }
+    }
+}
+
+impl<'ast, 'a> Visit<'ast> for EventVisitor<'a> {
+    fn visit_expr_match(&mut self, expr_match: &'ast ExprMatch) {
+        if self.is_event(&expr_match.expr) {
+            for arm in &expr_match.arms {
+                self.add_pat(&arm.pat);
+            }
+        }
+
+        visit::visit_expr_match(self, expr_match);
+    }
+}
+
+#[test]
+fn visit_events() {
+    use syn::parse_quote;
+
+    let block: Block = parse_quote!({
+        match event {
+            Event::A => Handled,
+            Event::B(_) | Event::C { .. } => match *event {
+                Event::A => Handled,
+                _ => Super,
+            },
+            other => Super,
+        }
+    });
+
+    let event_ident = parse_quote!(event);
+    let mut visitor = EventVisitor::new(&event_ident);
+    visitor.search(&block);
+
+    assert_eq!(visitor.finish(), ["A", "B", "C", "_"]);
+}
@@ -1,5 +1,9 @@
+mod event_visitor;
 mod generic_param_visitor;
 mod lifetime_visitor;
+mod transition_visitor;
 
+pub use event_visitor::*;
 pub use generic_param_visitor::*;
 pub use lifetime_visitor::*;
+pub use transition_visitor::*;
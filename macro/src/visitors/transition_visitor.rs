@@ -0,0 +1,120 @@
+use syn::visit::{self, Visit};
+use syn::{Block, Expr, ExprCall, Ident};
+
+/// Visitor to find all the states that are targeted by a transition in the body of a handler
+/// (e.g. `Transition(State::on())` or `Response::Transition(State::on())`). Transitions to
+/// states that are created at runtime (e.g. `Transition(next)`) are recorded as dynamic.
+#[derive(Debug)]
+pub struct TransitionVisitor<'a> {
+    state_ident: &'a Ident,
+    found: Vec<Ident>,
+    dynamic: bool,
+}
+
+impl<'a> TransitionVisitor<'a> {
+    pub fn new(state_ident: &'a Ident) -> Self {
+        Self {
+            state_ident,
+            found: Vec::new(),
+            dynamic: false,
+        }
+    }
+
+    pub fn search(&mut self, block: &Block) {
+        self.visit_block(block);
+    }
+
+    /// Whether a transition was found whose target state is only known at runtime.
+    pub fn has_dynamic(&self) -> bool {
+        self.dynamic
+    }
+
+    pub fn finish(self) -> Vec<Ident> {
+        self.found
+    }
+}
+
+impl<'ast, 'a> Visit<'ast> for TransitionVisitor<'a> {
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        // Pushing a state onto the state stack also transitions to it, and so does breaking
+        // out of a handler that returns a `ControlFlow`.
+        let is_transition = match call.func.as_ref() {
+            Expr::Path(path) => path.path.segments.last().map_or(false, |segment| {
+                segment.ident == "Transition" || segment.ident == "Push" || segment.ident == "Break"
+            }),
+            _ => false,
+        };
+
+        if is_transition {
+            let constructor = match call.args.first() {
+                Some(Expr::Call(target)) => match target.func.as_ref() {
+                    Expr::Path(path) => {
+                        let mut segments = path.path.segments.iter().rev();
+                        match (segments.next(), segments.next()) {
+                            (Some(constructor), Some(state))
+                                if &state.ident == self.state_ident =>
+                            {
+                                Some(&constructor.ident)
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            match constructor {
+                Some(constructor) if !self.found.contains(constructor) => {
+                    self.found.push(constructor.clone())
+                }
+                Some(_) => {}
+                None => self.dynamic = true,
+            }
+        }
+
+        visit::visit_expr_call(self, call);
+    }
+}
+
+#[test]
+fn visit_transitions() {
+    use syn::parse_quote;
+
+    let block: Block = parse_quote!({
+        match event {
+            Event::A => Transition(State::on()),
+            Event::B => Response::Transition(State::off(false)),
+            Event::C => statig::Response::Transition(State::on()),
+            Event::D => Transition(Other::on()),
+            _ => Super,
+        }
+    });
+
+    let state_ident = parse_quote!(State);
+    let mut visitor = TransitionVisitor::new(&state_ident);
+    visitor.search(&block);
+
+    let expected: Vec<Ident> = vec![parse_quote!(on), parse_quote!(off)];
+
+    assert!(visitor.has_dynamic());
+    assert_eq!(visitor.finish(), expected);
+}
+
+#[test]
+fn visit_static_transitions() {
+    use syn::parse_quote;
+
+    let block: Block = parse_quote!({
+        match event {
+            Event::A => Transition(State::on()),
+            _ => Handled,
+        }
+    });
+
+    let state_ident = parse_quote!(State);
+    let mut visitor = TransitionVisitor::new(&state_ident);
+    visitor.search(&block);
+
+    assert!(!visitor.has_dynamic());
+}
@@ -0,0 +1,30 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+/// The future returned by [`StateMachine::dispatch`](crate::awaitable::StateMachine::dispatch)
+/// and [`InitializedStateMachine::dispatch`](crate::awaitable::InitializedStateMachine::dispatch).
+///
+/// Unlike the future of an `async fn` this type can be named, so it can be stored in a struct
+/// and polled by a hand-written executor. It borrows the state machine mutably for as long as
+/// it exists. Dropping it before it completes has the same effect as cancelling `handle`.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Dispatch<'a> {
+    future: Pin<Box<dyn Future<Output = ()> + 'a>>,
+}
+
+impl<'a> Dispatch<'a> {
+    pub(crate) fn new(future: impl Future<Output = ()> + 'a) -> Self {
+        Self {
+            future: Box::pin(future),
+        }
+    }
+}
+
+impl<'a> Future for Dispatch<'a> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.future.as_mut().poll(cx)
+    }
+}
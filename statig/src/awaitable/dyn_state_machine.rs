@@ -0,0 +1,49 @@
+use core::future::Future;
+use core::pin::Pin;
+
+use super::awaitable;
+use super::{InitializedStateMachine, StateMachine};
+use crate::IntoStateMachine;
+
+/// Marker trait for types whose awaitable state machines can be used behind a
+/// [`DynStateMachine`]. Implemented by the `state_machine` macro when `box_futures` is
+/// set, so the allocation of the boxed futures is opt-in.
+pub trait BoxFutures: IntoStateMachine {}
+
+/// Object safe interface to awaitable state machines that handle events of type `E`, so
+/// different state machines can be stored together (e.g. in a
+/// `Vec<Box<dyn DynStateMachine<Event>>>`). Every call to `handle` allocates the returned
+/// future on the heap.
+pub trait DynStateMachine<E> {
+    /// Handle the given event.
+    fn handle<'a>(&'a mut self, event: &'a E) -> Pin<Box<dyn Future<Output = ()> + 'a>>;
+}
+
+impl<M, E> DynStateMachine<E> for StateMachine<M>
+where
+    M: IntoStateMachine + BoxFutures + Send,
+    for<'evt> M: IntoStateMachine<Event<'evt> = E>,
+    for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    for<'evt> M::Event<'evt>: Send + Sync,
+    M::State: awaitable::State<M> + 'static + Send,
+    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+{
+    fn handle<'a>(&'a mut self, event: &'a E) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
+        Box::pin(StateMachine::handle(self, event))
+    }
+}
+
+impl<M, E> DynStateMachine<E> for InitializedStateMachine<M>
+where
+    M: IntoStateMachine + BoxFutures + Send,
+    for<'evt> M: IntoStateMachine<Event<'evt> = E>,
+    for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    for<'evt> M::Event<'evt>: Send + Sync,
+    for<'ctx> M::Context<'ctx>: Send + Sync,
+    M::State: awaitable::State<M> + 'static + Send,
+    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+{
+    fn handle<'a>(&'a mut self, event: &'a E) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
+        Box::pin(InitializedStateMachine::handle(self, event))
+    }
+}
@@ -1,5 +1,8 @@
 //! Module for awaitable (async) mode.
 
+mod dispatch;
+mod dyn_state_machine;
+mod region;
 mod state;
 mod state_machine;
 mod superstate;
@@ -7,6 +10,9 @@ mod superstate;
 pub use crate::Response::{self, *};
 pub use crate::*;
 
+pub use dispatch::*;
+pub use dyn_state_machine::*;
+pub use region::*;
 pub use state::*;
 pub use state_machine::*;
 pub use superstate::*;
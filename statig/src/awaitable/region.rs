@@ -0,0 +1,83 @@
+use core::future::Future;
+use core::pin::Pin;
+
+use crate::awaitable::{self, InitializedStateMachine, StateMachine};
+use crate::IntoStateMachine;
+
+/// A state machine that can be used as a region of a `#[state(parallel)]` state. The regions
+/// are activated through this trait when the parallel state is entered, so they are recognized
+/// by their type (including through type aliases) instead of by their name.
+pub trait Region {
+    /// The type the state machine of the region is created from.
+    type Machine: IntoStateMachine;
+
+    /// Activate the region when its parallel state is entered. A lazily initialized state
+    /// machine is initialized, which executes the entry actions of its initial state. A state
+    /// machine that is already initialized is left as it is.
+    fn enter_region<'fut>(
+        &'fut mut self,
+        context: &'fut mut <Self::Machine as IntoStateMachine>::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>>;
+
+    /// Activate the region in the given state, which is declared for the region with
+    /// `#[state_machine(initial(region = "..."))]`, instead of the initial state of its state
+    /// machine. A lazily initialized state machine is initialized in the given state. A state
+    /// machine that is already initialized transitions to it.
+    fn enter_region_in<'fut>(
+        &'fut mut self,
+        initial: <Self::Machine as IntoStateMachine>::State,
+        context: &'fut mut <Self::Machine as IntoStateMachine>::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>>;
+}
+
+impl<M> Region for StateMachine<M>
+where
+    M: IntoStateMachine + Send,
+    M::State: awaitable::State<M> + 'static + Send,
+    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+    for<'evt> M::Event<'evt>: Send + Sync,
+    for<'ctx> M::Context<'ctx>: Send + Sync,
+{
+    type Machine = M;
+
+    fn enter_region<'fut>(
+        &'fut mut self,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+        Box::pin(self.init_with_context(context))
+    }
+
+    fn enter_region_in<'fut>(
+        &'fut mut self,
+        initial: M::State,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+        Box::pin(self.enter_in_with_context(initial, context))
+    }
+}
+
+impl<M> Region for InitializedStateMachine<M>
+where
+    M: IntoStateMachine + Send,
+    M::State: awaitable::State<M> + 'static + Send,
+    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+    for<'evt> M::Event<'evt>: Send + Sync,
+    for<'ctx> M::Context<'ctx>: Send + Sync,
+{
+    type Machine = M;
+
+    fn enter_region<'fut>(
+        &'fut mut self,
+        _: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+        Box::pin(core::future::ready(()))
+    }
+
+    fn enter_region_in<'fut>(
+        &'fut mut self,
+        initial: M::State,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+        Box::pin(self.enter_in_with_context(initial, context))
+    }
+}
@@ -31,6 +31,21 @@ where
         Box::pin(core::future::ready(()))
     }
 
+    /// Call the entry action for the current state and return whether it succeeded. Only
+    /// fallible entry actions can fail, by default this calls
+    /// [`call_entry_action`](Self::call_entry_action) and succeeds.
+    fn try_call_entry_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>> {
+        let entry_action = self.call_entry_action(shared_storage, context);
+        Box::pin(async move {
+            entry_action.await;
+            true
+        })
+    }
+
     #[allow(unused)]
     /// Call the exit action for the current state.
     fn call_exit_action<'fut>(
@@ -45,6 +60,11 @@ where
     fn superstate(&mut self) -> Option<M::Superstate<'_>> {
         None
     }
+
+    #[allow(unused)]
+    /// Move the storage owned by the superstates that the current state shares with the
+    /// target state over to the target state.
+    fn carry_over_storage(&mut self, target: &mut Self) {}
 }
 
 /// Extensions for `State` trait.
@@ -69,6 +89,18 @@ where
         }
     }
 
+    /// Get the depth of the current state, or `None` if it exceeds the given limit
+    /// (e.g. because the superstates form a cycle).
+    fn bounded_depth(&mut self, limit: usize) -> Option<usize> {
+        if limit == 0 {
+            return None;
+        }
+        match self.superstate() {
+            Some(mut superstate) => superstate.bounded_depth(limit - 1).map(|depth| depth + 1),
+            None => Some(1),
+        }
+    }
+
     /// Get the depth of the common ancestor of two states.
     fn common_ancestor_depth(source: &mut Self, target: &mut Self) -> usize {
         if Self::same_state(source, target) {
@@ -111,6 +143,17 @@ where
         context: &'fut mut M::Context<'_>,
     ) -> Pin<Box<dyn Future<Output = Response<Self>> + 'fut + Send>> {
         let future = async move {
+            // The superstates can keep the event from reaching the state.
+            if let Some(mut superstate) = self.superstate() {
+                match superstate
+                    .pre_dispatch(shared_storage, event, context)
+                    .await
+                {
+                    Response::Super => {}
+                    response => return response,
+                }
+            }
+
             M::ON_DISPATCH(shared_storage, StateOrSuperstate::State(self), event);
 
             let response = self.call_handler(shared_storage, event, context).await;
@@ -130,28 +173,86 @@ where
                     None => Response::Super,
                 },
                 Response::Transition(state) => Response::Transition(state),
+                Response::Push(state) => Response::Push(state),
+                Response::Pop => Response::Pop,
             }
         };
         Box::pin(future)
     }
 
+    /// Call the entry action of the superstate that is the given amount of levels above the
+    /// current state, or of the current state itself when `level` is zero, and return whether
+    /// it succeeded.
+    fn try_call_entry_action_at<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        level: usize,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>> {
+        Box::pin(async move {
+            match level {
+                0 => self.try_call_entry_action(shared_storage, context).await,
+                _ => match self.superstate() {
+                    Some(mut superstate) => {
+                        superstate
+                            .try_call_entry_action_at(shared_storage, context, level - 1)
+                            .await
+                    }
+                    None => true,
+                },
+            }
+        })
+    }
+
+    /// Call the exit action of the superstate that is the given amount of levels above the
+    /// current state, or of the current state itself when `level` is zero.
+    fn call_exit_action_at<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        level: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+        Box::pin(async move {
+            match level {
+                0 => self.call_exit_action(shared_storage, context).await,
+                _ => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate
+                            .call_exit_action_at(shared_storage, context, level - 1)
+                            .await;
+                    }
+                }
+            }
+        })
+    }
+
     /// Starting from the current state, climb a given amount of levels and execute all the
-    /// entry actions while going back down to the current state.
+    /// entry actions while going back down to the current state. If one of the entry
+    /// actions fails, the exit actions of the superstates that were already entered are
+    /// executed to compensate and `false` is returned.
     fn enter<'fut>(
         &'fut mut self,
         shared_storage: &'fut mut M,
         context: &'fut mut M::Context<'_>,
         levels: usize,
-    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>> {
         let future = async move {
             match levels {
-                0 => (),
-                1 => self.call_entry_action(shared_storage, context).await,
+                0 => true,
+                1 => self.try_call_entry_action(shared_storage, context).await,
                 _ => {
                     if let Some(mut superstate) = self.superstate() {
-                        superstate.enter(shared_storage, context, levels - 1).await;
+                        if !superstate.enter(shared_storage, context, levels - 1).await {
+                            return false;
+                        }
+                    }
+                    if self.try_call_entry_action(shared_storage, context).await {
+                        return true;
+                    }
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate.exit(shared_storage, context, levels - 1).await;
                     }
-                    self.call_entry_action(shared_storage, context).await;
+                    false
                 }
             }
         };
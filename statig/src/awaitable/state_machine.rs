@@ -1,7 +1,10 @@
+use core::borrow::Borrow;
 use core::fmt::Debug;
 
-use super::awaitable;
-use crate::{Inner, IntoStateMachine};
+use super::awaitable::{self, Dispatch};
+use crate::{Command, DerefStorageMut, Inner, IntoStateMachine, StoreHandlerError};
+#[cfg(feature = "instrumentation")]
+use crate::{Outcome, RecordFiredActions};
 
 /// A state machine where the shared storage is of type `Self`.
 pub trait IntoStateMachineExt: IntoStateMachine
@@ -15,23 +18,18 @@ where
     where
         Self: Sized,
     {
-        let inner = Inner {
-            shared_storage: self,
-            state: Self::INITIAL,
-        };
+        let inner = Inner::new(self, Self::INITIAL);
         StateMachine {
             inner,
             initialized: false,
+            shutdown_called: false,
         }
     }
 
     /// Create an uninitialized state machine that must be explicitly initialized with
     /// [`init`](UninitializedStateMachine::init).
     fn uninitialized_state_machine(self) -> UninitializedStateMachine<Self> {
-        let inner = Inner {
-            shared_storage: self,
-            state: Self::INITIAL,
-        };
+        let inner = Inner::new(self, Self::INITIAL);
         UninitializedStateMachine { inner }
     }
 }
@@ -51,6 +49,26 @@ where
 {
     inner: Inner<M>,
     initialized: bool,
+    shutdown_called: bool,
+}
+
+impl<M> StateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    /// Create a state machine that will be lazily initialized. Unlike
+    /// [`state_machine`](IntoStateMachineExt::state_machine) this can be called in a
+    /// `const` context, so state machines can be stored in a `static` (e.g.
+    /// `static MACHINES: Mutex<[StateMachine<Blinky>; 4]> = Mutex::new([MACHINE; 4]);`).
+    /// This requires the shared storage to be const-constructible as well.
+    pub const fn new(shared_storage: M) -> Self {
+        let inner = Inner::new(shared_storage, M::INITIAL);
+        Self {
+            inner,
+            initialized: false,
+            shutdown_called: false,
+        }
+    }
 }
 
 impl<M> StateMachine<M>
@@ -107,6 +125,185 @@ where
         self.inner.async_handle_with_context(event, context).await;
     }
 
+    /// Handle an event tagged with the place it originated from. The origin is passed to
+    /// the shared storage before the event is dispatched, so it can be used in the
+    /// `on_dispatch` and `on_transition` callbacks. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub async fn handle_with_origin(&mut self, event: &M::Event<'_>, origin: &'static str)
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_origin_and_context(event, origin, &mut ())
+            .await;
+    }
+
+    /// Handle an event tagged with the place it originated from. The origin is passed to
+    /// the shared storage before the event is dispatched, so it can be used in the
+    /// `on_dispatch` and `on_transition` callbacks. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub async fn handle_with_origin_and_context(
+        &mut self,
+        event: &M::Event<'_>,
+        origin: &'static str,
+        context: &mut M::Context<'_>,
+    ) where
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        for<'evt> M::Event<'evt>: Send + Sync,
+    {
+        if !self.initialized {
+            self.inner.async_init_with_context(context).await;
+            self.initialized = true;
+        }
+        self.inner
+            .async_handle_with_origin(event, origin, context)
+            .await;
+    }
+
+    /// Handle an event and return the outcome together with the names of the entry and
+    /// exit actions that fired, in the order they fired. Actions that fire while the state
+    /// machine is initialized are not included. Requires the `instrumentation` feature.
+    #[cfg(feature = "instrumentation")]
+    pub async fn handle_recording(&mut self, event: &M::Event<'_>) -> (Outcome, Vec<&'static str>)
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M: RecordFiredActions,
+    {
+        self.handle_recording_with_context(event, &mut ()).await
+    }
+
+    /// Handle an event and return the outcome together with the names of the entry and
+    /// exit actions that fired, in the order they fired. Actions that fire while the state
+    /// machine is initialized are not included. Requires the `instrumentation` feature.
+    #[cfg(feature = "instrumentation")]
+    pub async fn handle_recording_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> (Outcome, Vec<&'static str>)
+    where
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        M: RecordFiredActions,
+    {
+        self.init_with_context(context).await;
+        self.inner.shared_storage.fired_actions().take();
+        self.inner.async_handle_with_context(event, context).await;
+        self.inner.shared_storage.fired_actions().take()
+    }
+
+    /// Handle an event and return the error of the handler that failed, if any. A failing
+    /// handler is considered to have handled the event, so the state machine does not
+    /// transition. If the state machine is still uninitialized, it will be initialized
+    /// before handling the event.
+    pub async fn try_handle(
+        &mut self,
+        event: &M::Event<'_>,
+    ) -> Result<(), <M as StoreHandlerError>::Error>
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M: StoreHandlerError,
+    {
+        self.try_handle_with_context(event, &mut ()).await
+    }
+
+    /// Handle an event and return the error of the handler that failed, if any. A failing
+    /// handler is considered to have handled the event, so the state machine does not
+    /// transition. If the state machine is still uninitialized, it will be initialized
+    /// before handling the event.
+    pub async fn try_handle_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M as StoreHandlerError>::Error>
+    where
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        M: StoreHandlerError,
+    {
+        self.init_with_context(context).await;
+        self.inner.shared_storage.handler_error().take();
+        self.inner.async_handle_with_context(event, context).await;
+        match self.inner.shared_storage.handler_error().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Handle an event that is either owned or borrowed. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub async fn handle_ref_event<'evt, E>(&mut self, event: E)
+    where
+        E: Borrow<M::Event<'evt>>,
+        for<'evt2> M::Event<'evt2>: Send + Sync,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(event.borrow(), &mut ()).await;
+    }
+
+    /// Handle an event only if the predicate returns `true` for the kind of the current
+    /// state (e.g. `StateKind`), and return whether the event was handled. If the state
+    /// machine is still uninitialized, it will be initialized before handling the event.
+    pub async fn handle_if<K, P>(&mut self, predicate: P, event: &M::Event<'_>) -> bool
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        K: for<'a> From<&'a M::State>,
+        P: FnOnce(K) -> bool,
+    {
+        self.handle_if_with_context(predicate, event, &mut ()).await
+    }
+
+    /// Handle an event only if the predicate returns `true` for the kind of the current
+    /// state (e.g. `StateKind`), and return whether the event was handled. If the state
+    /// machine is still uninitialized, it will be initialized before handling the event.
+    pub async fn handle_if_with_context<K, P>(
+        &mut self,
+        predicate: P,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> bool
+    where
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        K: for<'a> From<&'a M::State>,
+        P: FnOnce(K) -> bool,
+    {
+        if !predicate(self.state_kind()) {
+            return false;
+        }
+        self.handle_with_context(event, context).await;
+        true
+    }
+
+    /// Handle an event through a [`Dispatch`] future that can be named and polled manually.
+    /// If the state machine is still uninitialized, it will be initialized before handling
+    /// the event.
+    pub fn dispatch<'a>(&'a mut self, event: &'a M::Event<'_>) -> Dispatch<'a>
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        Dispatch::new(async move { self.handle_with_context(event, &mut ()).await })
+    }
+
+    /// Handle an event through a [`Dispatch`] future that can be named and polled manually.
+    /// If the state machine is still uninitialized, it will be initialized before handling
+    /// the event.
+    pub fn dispatch_with_context<'a>(
+        &'a mut self,
+        event: &'a M::Event<'_>,
+        context: &'a mut M::Context<'_>,
+    ) -> Dispatch<'a>
+    where
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        for<'evt> M::Event<'evt>: Send + Sync,
+    {
+        Dispatch::new(self.handle_with_context(event, context))
+    }
+
     pub async fn step(&mut self)
     where
         for<'evt, 'ctx> M: IntoStateMachine<Event<'evt> = (), Context<'ctx> = ()>,
@@ -125,10 +322,166 @@ where
         self.handle_with_context(&(), context).await;
     }
 
+    /// Transition to the state of the given kind, executing the exit and entry actions
+    /// between the current state and the target state. Only states without state-local
+    /// storage can be created from their kind, for other kinds the error of the conversion
+    /// is returned. If the state machine is still uninitialized, it will be initialized
+    /// first.
+    pub async fn reset_to<K>(&mut self, kind: K) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M::State: TryFrom<K>,
+    {
+        self.reset_to_with_context(kind, &mut ()).await
+    }
+
+    /// Transition to the state of the given kind, executing the exit and entry actions
+    /// between the current state and the target state. Only states without state-local
+    /// storage can be created from their kind, for other kinds the error of the conversion
+    /// is returned. If the state machine is still uninitialized, it will be initialized
+    /// first.
+    pub async fn reset_to_with_context<K>(
+        &mut self,
+        kind: K,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M::State: TryFrom<K>,
+    {
+        let state = <M::State as TryFrom<K>>::try_from(kind)?;
+        if !self.initialized {
+            self.inner.async_init_with_context(context).await;
+            self.initialized = true;
+        }
+        self.inner.async_transition(state, context).await;
+        Ok(())
+    }
+
+    /// Initialize the state machine in the given state instead of its initial state, or
+    /// transition to it if the state machine is already initialized. Used to activate a
+    /// region in the initial state that is declared for it by its parent.
+    pub(crate) async fn enter_in_with_context(
+        &mut self,
+        state: M::State,
+        context: &mut M::Context<'_>,
+    ) where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        if self.initialized {
+            self.inner.async_transition(state, context).await;
+        } else {
+            self.inner.state = state;
+            self.init_with_context(context).await;
+        }
+    }
+
+    /// Apply a command from outside the state machine (e.g. from a debugging UI): handle an
+    /// event, reset the state machine to its initial state or force it into the state of the
+    /// given kind. Forcing a state with state-local storage returns the error of
+    /// [`reset_to`](Self::reset_to). If the state machine is still
+    /// uninitialized, it will be initialized first.
+    pub async fn apply_command<K>(
+        &mut self,
+        command: Command<M::Event<'_>, K>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M::State: TryFrom<K>,
+    {
+        self.apply_command_with_context(command, &mut ()).await
+    }
+
+    /// Apply a command from outside the state machine (e.g. from a debugging UI): handle an
+    /// event, reset the state machine to its initial state or force it into the state of the
+    /// given kind. Forcing a state with state-local storage returns the error of
+    /// [`reset_to`](Self::reset_to). If the state machine is still
+    /// uninitialized, it will be initialized first.
+    pub async fn apply_command_with_context<K>(
+        &mut self,
+        command: Command<M::Event<'_>, K>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M::State: TryFrom<K>,
+    {
+        match command {
+            Command::Event(event) => self.handle_with_context(&event, context).await,
+            Command::Reset => {
+                if !self.initialized {
+                    self.inner.async_init_with_context(context).await;
+                    self.initialized = true;
+                }
+                self.inner.async_transition(M::INITIAL, context).await;
+            }
+            Command::ForceState(kind) => return self.reset_to_with_context(kind, context).await,
+        }
+        Ok(())
+    }
+
+    /// Shut the state machine down by executing the exit actions of the current state and
+    /// all of its superstates. If the state machine is still uninitialized, no actions are
+    /// executed. The state machine should not handle any more events afterwards.
+    pub async fn shutdown(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.shutdown_with_context(&mut ()).await;
+    }
+
+    /// Shut the state machine down by executing the exit actions of the current state and
+    /// all of its superstates. If the state machine is still uninitialized, no actions are
+    /// executed. The state machine should not handle any more events afterwards.
+    pub async fn shutdown_with_context(&mut self, context: &mut M::Context<'_>)
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        if self.initialized && !self.shutdown_called {
+            self.inner.async_shutdown_with_context(context).await;
+        }
+        self.shutdown_called = true;
+    }
+
     /// Get the current state.
     pub fn state(&self) -> &M::State {
         &self.inner.state
     }
+
+    /// Get the kind of the current state (e.g. `StateKind`), without borrowing the state
+    /// itself. This works even when the state is not `Copy`.
+    pub fn state_kind<'a, K>(&'a self) -> K
+    where
+        K: From<&'a M::State>,
+    {
+        K::from(&self.inner.state)
+    }
+
+    /// Get the priority of the current state, which is declared with
+    /// `#[state(priority = ..)]` and is `0` by default.
+    pub fn state_priority(&self) -> i32 {
+        M::STATE_PRIORITY(&self.inner.state)
+    }
+
+    /// Consume the state machine and return the shared storage, dropping the state. No exit
+    /// actions are executed, and the drop guard does not consider this a missing `shutdown`.
+    pub fn into_storage(self) -> M {
+        let state_machine = core::mem::ManuallyDrop::new(self);
+        // SAFETY: The inner state machine is read exactly once and `state_machine` is never
+        // used or dropped afterwards.
+        let inner = unsafe { core::ptr::read(&state_machine.inner) };
+        inner.shared_storage
+    }
 }
 
 impl<M> Clone for StateMachine<M>
@@ -139,7 +492,12 @@ where
     fn clone(&self) -> Self {
         let inner = self.inner.clone();
         let initialized = self.initialized;
-        Self { inner, initialized }
+        let shutdown_called = self.shutdown_called;
+        Self {
+            inner,
+            initialized,
+            shutdown_called,
+        }
     }
 }
 
@@ -160,18 +518,58 @@ where
 {
 }
 
+/// Shows the name of the current state, followed by the shared storage when it implements
+/// `Debug`. The state itself does not have to implement `Debug`.
+impl<M> Debug for StateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("StateMachine");
+        debug.field(
+            "state",
+            &format_args!("{}", M::STATE_NAME(&self.inner.state)),
+        );
+        if let Some(shared_storage) = M::DEBUG_STORAGE(&self.inner.shared_storage) {
+            debug.field("shared_storage", shared_storage);
+        }
+        debug.finish()
+    }
+}
+
 impl<M> Default for StateMachine<M>
 where
     M: IntoStateMachine + Default,
 {
     fn default() -> Self {
-        let inner = Inner {
-            shared_storage: M::default(),
-            state: M::INITIAL,
-        };
+        let inner = Inner::new(M::default(), M::INITIAL);
         Self {
             inner,
             initialized: false,
+            shutdown_called: false,
+        }
+    }
+}
+
+/// In debug builds, panics when the state machine is dropped without
+/// [`shutdown`](Self::shutdown) being called, if [`IntoStateMachine::DROP_GUARD`] is set
+/// and the state machine has been initialized but is not in a terminal state.
+///
+/// The check is left out of release builds, but the `Drop` implementation itself is present
+/// in every profile, so whether the state machine can be taken apart does not depend on it.
+impl<M> Drop for StateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    fn drop(&mut self) {
+        if cfg!(debug_assertions)
+            && M::DROP_GUARD
+            && self.initialized
+            && !self.shutdown_called
+            && !(M::IS_TERMINAL)(&self.inner.state)
+            && !std::thread::panicking()
+        {
+            panic!("state machine was dropped without calling `shutdown`");
         }
     }
 }
@@ -187,6 +585,15 @@ where
     }
 }
 
+impl<M> core::ops::DerefMut for StateMachine<M>
+where
+    M: DerefStorageMut,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner.shared_storage
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<M> serde::Serialize for StateMachine<M>
 where
@@ -217,6 +624,7 @@ where
         Ok(StateMachine {
             inner,
             initialized: false,
+            shutdown_called: false,
         })
     }
 }
@@ -236,6 +644,7 @@ where
     M: IntoStateMachine,
 {
     inner: Inner<M>,
+    shutdown_called: bool,
 }
 
 impl<M> InitializedStateMachine<M>
@@ -264,6 +673,177 @@ where
         self.inner.async_handle_with_context(event, context).await;
     }
 
+    /// Handle the given event tagged with the place it originated from. The origin is
+    /// passed to the shared storage before the event is dispatched, so it can be used in
+    /// the `on_dispatch` and `on_transition` callbacks.
+    pub async fn handle_with_origin(&mut self, event: &M::Event<'_>, origin: &'static str)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.handle_with_origin_and_context(event, origin, &mut ())
+            .await;
+    }
+
+    /// Handle the given event tagged with the place it originated from. The origin is
+    /// passed to the shared storage before the event is dispatched, so it can be used in
+    /// the `on_dispatch` and `on_transition` callbacks.
+    pub async fn handle_with_origin_and_context(
+        &mut self,
+        event: &M::Event<'_>,
+        origin: &'static str,
+        context: &mut M::Context<'_>,
+    ) where
+        M: IntoStateMachine,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.inner
+            .async_handle_with_origin(event, origin, context)
+            .await;
+    }
+
+    /// Handle the given event and return the outcome together with the names of the entry
+    /// and exit actions that fired, in the order they fired. Requires the `instrumentation`
+    /// feature.
+    #[cfg(feature = "instrumentation")]
+    pub async fn handle_recording(&mut self, event: &M::Event<'_>) -> (Outcome, Vec<&'static str>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M: RecordFiredActions,
+    {
+        self.handle_recording_with_context(event, &mut ()).await
+    }
+
+    /// Handle the given event and return the outcome together with the names of the entry
+    /// and exit actions that fired, in the order they fired. Requires the `instrumentation`
+    /// feature.
+    #[cfg(feature = "instrumentation")]
+    pub async fn handle_recording_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> (Outcome, Vec<&'static str>)
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M: RecordFiredActions,
+    {
+        self.inner.shared_storage.fired_actions().take();
+        self.inner.async_handle_with_context(event, context).await;
+        self.inner.shared_storage.fired_actions().take()
+    }
+
+    /// Handle the given event and return the error of the handler that failed, if any. A
+    /// failing handler is considered to have handled the event, so the state machine does
+    /// not transition.
+    pub async fn try_handle(
+        &mut self,
+        event: &M::Event<'_>,
+    ) -> Result<(), <M as StoreHandlerError>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M: StoreHandlerError,
+    {
+        self.try_handle_with_context(event, &mut ()).await
+    }
+
+    /// Handle the given event and return the error of the handler that failed, if any. A
+    /// failing handler is considered to have handled the event, so the state machine does
+    /// not transition.
+    pub async fn try_handle_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M as StoreHandlerError>::Error>
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M: StoreHandlerError,
+    {
+        self.inner.shared_storage.handler_error().take();
+        self.inner.async_handle_with_context(event, context).await;
+        match self.inner.shared_storage.handler_error().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Handle an event that is either owned or borrowed.
+    pub async fn handle_ref_event<'evt, E>(&mut self, event: E)
+    where
+        E: Borrow<M::Event<'evt>>,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt2> M::Event<'evt2>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.handle_with_context(event.borrow(), &mut ()).await;
+    }
+
+    /// Handle an event only if the predicate returns `true` for the kind of the current
+    /// state (e.g. `StateKind`), and return whether the event was handled.
+    pub async fn handle_if<K, P>(&mut self, predicate: P, event: &M::Event<'_>) -> bool
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        K: for<'a> From<&'a M::State>,
+        P: FnOnce(K) -> bool,
+    {
+        self.handle_if_with_context(predicate, event, &mut ()).await
+    }
+
+    /// Handle an event only if the predicate returns `true` for the kind of the current
+    /// state (e.g. `StateKind`), and return whether the event was handled.
+    pub async fn handle_if_with_context<K, P>(
+        &mut self,
+        predicate: P,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> bool
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        K: for<'a> From<&'a M::State>,
+        P: FnOnce(K) -> bool,
+    {
+        if !predicate(self.state_kind()) {
+            return false;
+        }
+        self.handle_with_context(event, context).await;
+        true
+    }
+
+    /// Handle the given event through a [`Dispatch`] future that can be named and polled
+    /// manually.
+    pub fn dispatch<'a>(&'a mut self, event: &'a M::Event<'_>) -> Dispatch<'a>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        Dispatch::new(async move { self.handle_with_context(event, &mut ()).await })
+    }
+
+    /// Handle the given event through a [`Dispatch`] future that can be named and polled
+    /// manually.
+    pub fn dispatch_with_context<'a>(
+        &'a mut self,
+        event: &'a M::Event<'_>,
+        context: &'a mut M::Context<'_>,
+    ) -> Dispatch<'a>
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        Dispatch::new(self.handle_with_context(event, context))
+    }
+
     /// This is the same as `handle(())` in the case `Event` is of type `()`.
     pub async fn step(&mut self)
     where
@@ -282,10 +862,146 @@ where
         self.handle_with_context(&(), context).await;
     }
 
+    /// Transition to the state of the given kind, executing the exit and entry actions
+    /// between the current state and the target state. Only states without state-local
+    /// storage can be created from their kind, for other kinds the error of the conversion
+    /// is returned.
+    pub async fn reset_to<K>(&mut self, kind: K) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M::State: TryFrom<K>,
+    {
+        self.reset_to_with_context(kind, &mut ()).await
+    }
+
+    /// Transition to the state of the given kind, executing the exit and entry actions
+    /// between the current state and the target state. Only states without state-local
+    /// storage can be created from their kind, for other kinds the error of the conversion
+    /// is returned.
+    pub async fn reset_to_with_context<K>(
+        &mut self,
+        kind: K,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M::State: TryFrom<K>,
+    {
+        let state = <M::State as TryFrom<K>>::try_from(kind)?;
+        self.inner.async_transition(state, context).await;
+        Ok(())
+    }
+
+    /// Transition to the given state. Used to activate a region in the initial state that is
+    /// declared for it by its parent.
+    pub(crate) async fn enter_in_with_context(
+        &mut self,
+        state: M::State,
+        context: &mut M::Context<'_>,
+    ) where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.inner.async_transition(state, context).await;
+    }
+
+    /// Apply a command from outside the state machine (e.g. from a debugging UI): handle an
+    /// event, reset the state machine to its initial state or force it into the state of the
+    /// given kind. Forcing a state with state-local storage returns the error of
+    /// [`reset_to`](Self::reset_to).
+    pub async fn apply_command<K>(
+        &mut self,
+        command: Command<M::Event<'_>, K>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M::State: TryFrom<K>,
+    {
+        self.apply_command_with_context(command, &mut ()).await
+    }
+
+    /// Apply a command from outside the state machine (e.g. from a debugging UI): handle an
+    /// event, reset the state machine to its initial state or force it into the state of the
+    /// given kind. Forcing a state with state-local storage returns the error of
+    /// [`reset_to`](Self::reset_to).
+    pub async fn apply_command_with_context<K>(
+        &mut self,
+        command: Command<M::Event<'_>, K>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        M::State: TryFrom<K>,
+    {
+        match command {
+            Command::Event(event) => self.handle_with_context(&event, context).await,
+            Command::Reset => self.inner.async_transition(M::INITIAL, context).await,
+            Command::ForceState(kind) => return self.reset_to_with_context(kind, context).await,
+        }
+        Ok(())
+    }
+
+    /// Shut the state machine down by executing the exit actions of the current state and
+    /// all of its superstates. The state machine should not handle any more events
+    /// afterwards.
+    pub async fn shutdown(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.shutdown_with_context(&mut ()).await;
+    }
+
+    /// Shut the state machine down by executing the exit actions of the current state and
+    /// all of its superstates. The state machine should not handle any more events
+    /// afterwards.
+    pub async fn shutdown_with_context(&mut self, context: &mut M::Context<'_>)
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        if !self.shutdown_called {
+            self.inner.async_shutdown_with_context(context).await;
+        }
+        self.shutdown_called = true;
+    }
+
     /// Get an immutable reference to the current state of the state machine.
     pub fn state(&self) -> &M::State {
         &self.inner.state
     }
+
+    /// Get the kind of the current state (e.g. `StateKind`), without borrowing the state
+    /// itself. This works even when the state is not `Copy`.
+    pub fn state_kind<'a, K>(&'a self) -> K
+    where
+        K: From<&'a M::State>,
+    {
+        K::from(&self.inner.state)
+    }
+
+    /// Get the priority of the current state, which is declared with
+    /// `#[state(priority = ..)]` and is `0` by default.
+    pub fn state_priority(&self) -> i32 {
+        M::STATE_PRIORITY(&self.inner.state)
+    }
+
+    /// Consume the state machine and return the shared storage, dropping the state. No exit
+    /// actions are executed, and the drop guard does not consider this a missing `shutdown`.
+    pub fn into_storage(self) -> M {
+        let state_machine = core::mem::ManuallyDrop::new(self);
+        // SAFETY: The inner state machine is read exactly once and `state_machine` is never
+        // used or dropped afterwards.
+        let inner = unsafe { core::ptr::read(&state_machine.inner) };
+        inner.shared_storage
+    }
 }
 
 impl<M> Clone for InitializedStateMachine<M>
@@ -296,20 +1012,49 @@ where
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            shutdown_called: self.shutdown_called,
+        }
+    }
+}
+
+/// In debug builds, panics when the state machine is dropped without
+/// [`shutdown`](Self::shutdown) being called, if [`IntoStateMachine::DROP_GUARD`] is set
+/// and the state machine is not in a terminal state.
+///
+/// The check is left out of release builds, but the `Drop` implementation itself is present
+/// in every profile, so whether the state machine can be taken apart does not depend on it.
+impl<M> Drop for InitializedStateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    fn drop(&mut self) {
+        if cfg!(debug_assertions)
+            && M::DROP_GUARD
+            && !self.shutdown_called
+            && !(M::IS_TERMINAL)(&self.inner.state)
+            && !std::thread::panicking()
+        {
+            panic!("state machine was dropped without calling `shutdown`");
         }
     }
 }
 
+/// Shows the name of the current state, followed by the shared storage when it implements
+/// `Debug`. The state itself does not have to implement `Debug`.
 impl<M> Debug for InitializedStateMachine<M>
 where
-    M: IntoStateMachine + Debug,
-    M::State: Debug,
+    M: IntoStateMachine,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("InitializedStateMachine")
-            .field("shared_storage", &self.inner.shared_storage as &dyn Debug)
-            .field("state", &self.inner.state as &dyn Debug)
-            .finish()
+        let mut debug = f.debug_struct("InitializedStateMachine");
+        debug.field(
+            "state",
+            &format_args!("{}", M::STATE_NAME(&self.inner.state)),
+        );
+        if let Some(shared_storage) = M::DEBUG_STORAGE(&self.inner.shared_storage) {
+            debug.field("shared_storage", shared_storage);
+        }
+        debug.finish()
     }
 }
 
@@ -341,6 +1086,15 @@ where
     }
 }
 
+impl<M> core::ops::DerefMut for InitializedStateMachine<M>
+where
+    M: DerefStorageMut,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner.shared_storage
+    }
+}
+
 #[cfg(feature = "serde")]
 /// Once an [`InitializedStateMachine`] is serialized, it can only be deserialized into
 /// an [`UnInitializedStateMachine`] which can then be re-initialized with the
@@ -354,12 +1108,7 @@ where
     where
         S: serde::Serializer,
     {
-        use serde::ser::SerializeStruct;
-
-        let mut serializer = serializer.serialize_struct("StateMachine", 2)?;
-        serializer.serialize_field("shared_storage", &self.inner.shared_storage)?;
-        serializer.serialize_field("state", &self.inner.state)?;
-        serializer.end()
+        self.inner.serialize(serializer)
     }
 }
 
@@ -384,6 +1133,51 @@ where
     inner: Inner<M>,
 }
 
+impl<M> UninitializedStateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    /// Create an uninitialized state machine that wraps the given shared storage (e.g. a
+    /// pre-populated buffer) and starts in the initial state once it is initialized. Unlike
+    /// [`uninitialized_state_machine`](IntoStateMachineExt::uninitialized_state_machine)
+    /// this can be called in a `const` context, so state machines can be stored in a
+    /// `static`. This requires the shared storage to be const-constructible as well.
+    pub const fn new(shared_storage: M) -> Self {
+        let inner = Inner::new(shared_storage, M::INITIAL);
+        Self { inner }
+    }
+
+    /// Replace the initial state the state machine starts in once it is initialized, e.g.
+    /// when it depends on runtime configuration. The entry actions towards the given state
+    /// are executed by [`init`](Self::init) instead of those towards the initial state
+    /// declared on the state machine.
+    ///
+    /// ```
+    /// # use statig::prelude::*;
+    /// # #[derive(Default)]
+    /// # pub struct Blinky;
+    /// #
+    /// # pub struct Event;
+    /// #
+    /// # #[state_machine(initial = "State::on()")]
+    /// # impl Blinky {
+    /// #     #[state]
+    /// #     fn on(event: &Event) -> Response<State> { Handled }
+    /// #
+    /// #     #[state]
+    /// #     fn off(event: &Event) -> Response<State> { Handled }
+    /// # }
+    /// #
+    /// let uninitialized_state_machine = Blinky::default()
+    ///     .uninitialized_state_machine()
+    ///     .with_initial_state(State::off());
+    /// ```
+    pub fn with_initial_state(mut self, state: M::State) -> Self {
+        self.inner.state = state;
+        self
+    }
+}
+
 impl<M> UninitializedStateMachine<M>
 where
     M: IntoStateMachine + Send,
@@ -420,7 +1214,10 @@ where
         for<'evt> M::Event<'evt>: Send + Sync,
         for<'ctx> M::Context<'ctx>: Send + Sync,
     {
-        let mut state_machine = InitializedStateMachine { inner: self.inner };
+        let mut state_machine = InitializedStateMachine {
+            inner: self.inner,
+            shutdown_called: false,
+        };
         state_machine.inner.async_init_with_context(&mut ()).await;
         state_machine
     }
@@ -454,7 +1251,10 @@ where
         for<'evt> M::Event<'evt>: Send + Sync,
         for<'ctx> M::Context<'ctx>: Send + Sync,
     {
-        let mut state_machine = InitializedStateMachine { inner: self.inner };
+        let mut state_machine = InitializedStateMachine {
+            inner: self.inner,
+            shutdown_called: false,
+        };
         state_machine.inner.async_init_with_context(context).await;
         state_machine
     }
@@ -472,16 +1272,22 @@ where
     }
 }
 
+/// Shows the name of the current state, followed by the shared storage when it implements
+/// `Debug`. The state itself does not have to implement `Debug`.
 impl<M> Debug for UninitializedStateMachine<M>
 where
-    M: IntoStateMachine + Debug,
-    M::State: Debug,
+    M: IntoStateMachine,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("UnInitializedStateMachine")
-            .field("shared_storage", &self.inner.shared_storage as &dyn Debug)
-            .field("state", &self.inner.state as &dyn Debug)
-            .finish()
+        let mut debug = f.debug_struct("UnInitializedStateMachine");
+        debug.field(
+            "state",
+            &format_args!("{}", M::STATE_NAME(&self.inner.state)),
+        );
+        if let Some(shared_storage) = M::DEBUG_STORAGE(&self.inner.shared_storage) {
+            debug.field("shared_storage", shared_storage);
+        }
+        debug.finish()
     }
 }
 
@@ -513,6 +1319,15 @@ where
     }
 }
 
+impl<M> core::ops::DerefMut for UninitializedStateMachine<M>
+where
+    M: DerefStorageMut,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner.shared_storage
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<M> serde::Serialize for UninitializedStateMachine<M>
 where
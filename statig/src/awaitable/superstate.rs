@@ -29,6 +29,21 @@ where
         Box::pin(core::future::ready(()))
     }
 
+    /// Call the entry action for the current superstate and return whether it succeeded. Only
+    /// fallible entry actions can fail, by default this calls
+    /// [`call_entry_action`](Self::call_entry_action) and succeeds.
+    fn try_call_entry_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>> {
+        let entry_action = self.call_entry_action(shared_storage, context);
+        Box::pin(async move {
+            entry_action.await;
+            true
+        })
+    }
+
     #[allow(unused)]
     /// Call the exit action for the current superstate.
     fn call_exit_action<'fut>(
@@ -39,6 +54,18 @@ where
         Box::pin(core::future::ready(()))
     }
 
+    #[allow(unused)]
+    /// Call the pre-dispatch filter for the current superstate. Returning
+    /// [`Super`](Response::Super) lets the event continue to the children.
+    fn call_pre_dispatch<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        event: &'fut M::Event<'_>,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = Response<M::State>> + 'fut + Send>> {
+        Box::pin(core::future::ready(Response::Super))
+    }
+
     /// Return the superstate of the current superstate, if there is one.
     fn superstate(&mut self) -> Option<M::Superstate<'_>>
     where
@@ -79,6 +106,18 @@ where
         }
     }
 
+    /// Get the depth of the current superstate, or `None` if it exceeds the given limit
+    /// (e.g. because the superstates form a cycle).
+    fn bounded_depth(&mut self, limit: usize) -> Option<usize> {
+        if limit == 0 {
+            return None;
+        }
+        match self.superstate() {
+            Some(mut superstate) => superstate.bounded_depth(limit - 1).map(|depth| depth + 1),
+            None => Some(1),
+        }
+    }
+
     /// Get the depth of the common ancestor of two states.
     fn common_ancestor_depth(
         mut source: M::Superstate<'_>,
@@ -103,6 +142,30 @@ where
         }
     }
 
+    /// Run the pre-dispatch filters of the current superstate and its superstates,
+    /// starting from the outermost one, until one of them returns a response other
+    /// than `Super`.
+    fn pre_dispatch<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        event: &'fut M::Event<'_>,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = Response<M::State>> + 'fut + Send>> {
+        Box::pin(async move {
+            if let Some(mut superstate) = self.superstate() {
+                match superstate
+                    .pre_dispatch(shared_storage, event, context)
+                    .await
+                {
+                    Response::Super => {}
+                    response => return response,
+                }
+            }
+
+            self.call_pre_dispatch(shared_storage, event, context).await
+        })
+    }
+
     /// Handle the given event in the current superstate.
     fn handle<'fut>(
         &'fut mut self,
@@ -128,28 +191,86 @@ where
                     None => Response::Super,
                 },
                 Response::Transition(state) => Response::Transition(state),
+                Response::Push(state) => Response::Push(state),
+                Response::Pop => Response::Pop,
+            }
+        })
+    }
+
+    /// Call the entry action of the superstate that is the given amount of levels above the
+    /// current superstate, or of the current superstate itself when `level` is zero, and return whether
+    /// it succeeded.
+    fn try_call_entry_action_at<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        level: usize,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>> {
+        Box::pin(async move {
+            match level {
+                0 => self.try_call_entry_action(shared_storage, context).await,
+                _ => match self.superstate() {
+                    Some(mut superstate) => {
+                        superstate
+                            .try_call_entry_action_at(shared_storage, context, level - 1)
+                            .await
+                    }
+                    None => true,
+                },
+            }
+        })
+    }
+
+    /// Call the exit action of the superstate that is the given amount of levels above the
+    /// current superstate, or of the current superstate itself when `level` is zero.
+    fn call_exit_action_at<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        level: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+        Box::pin(async move {
+            match level {
+                0 => self.call_exit_action(shared_storage, context).await,
+                _ => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate
+                            .call_exit_action_at(shared_storage, context, level - 1)
+                            .await;
+                    }
+                }
             }
         })
     }
 
     /// Starting from the current superstate, climb a given amount of levels and execute all the
-    /// entry actions while going back down to the current superstate.
+    /// entry actions while going back down to the current superstate. If one of the entry
+    /// actions fails, the exit actions of the superstates that were already entered are
+    /// executed to compensate and `false` is returned.
     fn enter<'fut>(
         &'fut mut self,
         shared_storage: &'fut mut M,
         context: &'fut mut M::Context<'_>,
         mut levels: usize,
-    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>> {
         Box::pin(async move {
             match levels {
-                0 => (),
-                1 => self.call_entry_action(shared_storage, context).await,
+                0 => true,
+                1 => self.try_call_entry_action(shared_storage, context).await,
                 _ => {
                     if let Some(mut superstate) = self.superstate() {
                         levels -= 1;
-                        superstate.enter(shared_storage, context, levels).await;
+                        if !superstate.enter(shared_storage, context, levels).await {
+                            return false;
+                        }
+                    }
+                    if self.try_call_entry_action(shared_storage, context).await {
+                        return true;
+                    }
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate.exit(shared_storage, context, levels).await;
                     }
-                    self.call_entry_action(shared_storage, context).await;
+                    false
                 }
             }
         })
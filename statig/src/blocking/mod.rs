@@ -1,5 +1,6 @@
 //! Module for blocking (sync) mode.
 
+mod region;
 mod state;
 mod state_machine;
 mod superstate;
@@ -7,6 +8,7 @@ mod superstate;
 pub use crate::Response::{self, *};
 pub use crate::*;
 
+pub use region::*;
 pub use state::*;
 pub use state_machine::*;
 pub use superstate::*;
@@ -0,0 +1,57 @@
+use crate::blocking::{self, InitializedStateMachine, StateMachine};
+use crate::IntoStateMachine;
+
+/// A state machine that can be used as a region of a `#[state(parallel)]` state. The regions
+/// are activated through this trait when the parallel state is entered, so they are recognized
+/// by their type (including through type aliases) instead of by their name.
+pub trait Region {
+    /// The type the state machine of the region is created from.
+    type Machine: IntoStateMachine;
+
+    /// Activate the region when its parallel state is entered. A lazily initialized state
+    /// machine is initialized, which executes the entry actions of its initial state. A state
+    /// machine that is already initialized is left as it is.
+    fn enter_region(&mut self, context: &mut <Self::Machine as IntoStateMachine>::Context<'_>);
+
+    /// Activate the region in the given state, which is declared for the region with
+    /// `#[state_machine(initial(region = "..."))]`, instead of the initial state of its state
+    /// machine. A lazily initialized state machine is initialized in the given state. A state
+    /// machine that is already initialized transitions to it.
+    fn enter_region_in(
+        &mut self,
+        initial: <Self::Machine as IntoStateMachine>::State,
+        context: &mut <Self::Machine as IntoStateMachine>::Context<'_>,
+    );
+}
+
+impl<M> Region for StateMachine<M>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    type Machine = M;
+
+    fn enter_region(&mut self, context: &mut M::Context<'_>) {
+        self.init_with_context(context);
+    }
+
+    fn enter_region_in(&mut self, initial: M::State, context: &mut M::Context<'_>) {
+        self.enter_in_with_context(initial, context);
+    }
+}
+
+impl<M> Region for InitializedStateMachine<M>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    type Machine = M;
+
+    fn enter_region(&mut self, _: &mut M::Context<'_>) {}
+
+    fn enter_region_in(&mut self, initial: M::State, context: &mut M::Context<'_>) {
+        self.enter_in_with_context(initial, context);
+    }
+}
@@ -22,6 +22,18 @@ where
     /// Call the entry action for the current state.
     fn call_entry_action(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>) {}
 
+    /// Call the entry action for the current state and return whether it succeeded. Only
+    /// fallible entry actions can fail, by default this calls
+    /// [`call_entry_action`](Self::call_entry_action) and succeeds.
+    fn try_call_entry_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+    ) -> bool {
+        self.call_entry_action(shared_storage, context);
+        true
+    }
+
     #[allow(unused)]
     /// Call the exit action for the current state.
     fn call_exit_action(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>) {}
@@ -30,6 +42,11 @@ where
     fn superstate(&mut self) -> Option<M::Superstate<'_>> {
         None
     }
+
+    #[allow(unused)]
+    /// Move the storage owned by the superstates that the current state shares with the
+    /// target state over to the target state.
+    fn carry_over_storage(&mut self, target: &mut Self) {}
 }
 
 /// Extensions for `State` trait.
@@ -52,6 +69,32 @@ where
         }
     }
 
+    /// Get the depth of the current state, or `None` if it exceeds the given limit
+    /// (e.g. because the superstates form a cycle).
+    fn bounded_depth(&mut self, limit: usize) -> Option<usize> {
+        if limit == 0 {
+            return None;
+        }
+        match self.superstate() {
+            Some(mut superstate) => superstate.bounded_depth(limit - 1).map(|depth| depth + 1),
+            None => Some(1),
+        }
+    }
+
+    /// Call the given closure with the current state, followed by each of its superstates
+    /// from the direct superstate of the state up to the outermost superstate.
+    fn for_each_active<F>(&mut self, mut visit: F)
+    where
+        F: FnMut(StateOrSuperstate<'_, '_, M>),
+    {
+        visit(StateOrSuperstate::State(self));
+
+        if let Some(mut superstate) = self.superstate() {
+            visit(StateOrSuperstate::Superstate(&superstate));
+            superstate.for_each_ancestor(&mut visit);
+        }
+    }
+
     /// Get the depth of the common ancestor of two states.
     fn common_ancestor_depth(source: &mut Self, target: &mut Self) -> usize {
         if Self::same_state(source, target) {
@@ -96,6 +139,14 @@ where
     where
         Self: Sized,
     {
+        // The superstates can keep the event from reaching the state.
+        if let Some(mut superstate) = self.superstate() {
+            match superstate.pre_dispatch(shared_storage, event, context) {
+                Response::Super => {}
+                response => return response,
+            }
+        }
+
         M::ON_DISPATCH(shared_storage, StateOrSuperstate::State(self), event);
 
         let response = self.call_handler(shared_storage, event, context);
@@ -115,20 +166,37 @@ where
                 None => Response::Super,
             },
             Response::Transition(state) => Response::Transition(state),
+            Response::Push(state) => Response::Push(state),
+            Response::Pop => Response::Pop,
         }
     }
 
     /// Starting from the current state, climb a given amount of levels and execute all the
-    /// entry actions while going back down to the current state.
-    fn enter(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>, levels: usize) {
+    /// entry actions while going back down to the current state. If one of the entry
+    /// actions fails, the exit actions of the superstates that were already entered are
+    /// executed to compensate and `false` is returned.
+    fn enter(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        levels: usize,
+    ) -> bool {
         match levels {
-            0 => (),
-            1 => self.call_entry_action(shared_storage, context),
+            0 => true,
+            1 => self.try_call_entry_action(shared_storage, context),
             _ => {
                 if let Some(mut superstate) = self.superstate() {
-                    superstate.enter(shared_storage, context, levels - 1);
+                    if !superstate.enter(shared_storage, context, levels - 1) {
+                        return false;
+                    }
+                }
+                if self.try_call_entry_action(shared_storage, context) {
+                    return true;
+                }
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.exit(shared_storage, context, levels - 1);
                 }
-                self.call_entry_action(shared_storage, context);
+                false
             }
         }
     }
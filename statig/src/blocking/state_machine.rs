@@ -1,7 +1,12 @@
+use core::borrow::Borrow;
 use core::fmt::Debug;
 
-use super::blocking;
-use crate::{Inner, IntoStateMachine};
+use super::blocking::{self, StateExt as _};
+use crate::{
+    Command, DerefStorageMut, DryRun, Inner, IntoStateMachine, StateOrSuperstate, StoreHandlerError,
+};
+#[cfg(feature = "instrumentation")]
+use crate::{Outcome, RecordFiredActions};
 
 /// A state machine where the shared storage is of type `Self`.
 pub trait IntoStateMachineExt: IntoStateMachine
@@ -13,10 +18,7 @@ where
     where
         Self: Sized,
     {
-        let inner = Inner {
-            shared_storage: self,
-            state: Self::INITIAL,
-        };
+        let inner = Inner::new(self, Self::INITIAL);
         StateMachine {
             inner,
             initialized: false,
@@ -26,10 +28,7 @@ where
     /// Create an uninitialized state machine that must be explicitly initialized with
     /// [`init`](UninitializedStateMachine::init).
     fn uninitialized_state_machine(self) -> UninitializedStateMachine<Self> {
-        let inner = Inner {
-            shared_storage: self,
-            state: Self::INITIAL,
-        };
+        let inner = Inner::new(self, Self::INITIAL);
         UninitializedStateMachine { inner }
     }
 }
@@ -50,6 +49,24 @@ where
     initialized: bool,
 }
 
+impl<M> StateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    /// Create a state machine that will be lazily initialized. Unlike
+    /// [`state_machine`](IntoStateMachineExt::state_machine) this can be called in a
+    /// `const` context, so state machines can be stored in a `static` (e.g.
+    /// `static MACHINES: Mutex<[StateMachine<Blinky>; 4]> = Mutex::new([MACHINE; 4]);`).
+    /// This requires the shared storage to be const-constructible as well.
+    pub const fn new(shared_storage: M) -> Self {
+        let inner = Inner::new(shared_storage, M::INITIAL);
+        Self {
+            inner,
+            initialized: false,
+        }
+    }
+}
+
 impl<M> StateMachine<M>
 where
     M: IntoStateMachine,
@@ -93,6 +110,212 @@ where
         self.inner.handle_with_context(event, context);
     }
 
+    /// Handle an event tagged with the place it originated from. The origin is passed to
+    /// the shared storage before the event is dispatched, so it can be used in the
+    /// `on_dispatch` and `on_transition` callbacks. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub fn handle_with_origin(&mut self, event: &M::Event<'_>, origin: &'static str)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_origin_and_context(event, origin, &mut ());
+    }
+
+    /// Handle an event tagged with the place it originated from. The origin is passed to
+    /// the shared storage before the event is dispatched, so it can be used in the
+    /// `on_dispatch` and `on_transition` callbacks. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub fn handle_with_origin_and_context(
+        &mut self,
+        event: &M::Event<'_>,
+        origin: &'static str,
+        context: &mut M::Context<'_>,
+    ) {
+        if !self.initialized {
+            self.inner.init_with_context(context);
+            self.initialized = true;
+        }
+        self.inner.handle_with_origin(event, origin, context);
+    }
+
+    /// Handle an event and return the outcome together with the names of the entry and
+    /// exit actions that fired, in the order they fired. Actions that fire while the state
+    /// machine is initialized are not included. Requires the `instrumentation` feature.
+    #[cfg(feature = "instrumentation")]
+    pub fn handle_recording(&mut self, event: &M::Event<'_>) -> (Outcome, Vec<&'static str>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M: RecordFiredActions,
+    {
+        self.handle_recording_with_context(event, &mut ())
+    }
+
+    /// Handle an event and return the outcome together with the names of the entry and
+    /// exit actions that fired, in the order they fired. Actions that fire while the state
+    /// machine is initialized are not included. Requires the `instrumentation` feature.
+    #[cfg(feature = "instrumentation")]
+    pub fn handle_recording_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> (Outcome, Vec<&'static str>)
+    where
+        M: RecordFiredActions,
+    {
+        self.init_with_context(context);
+        self.inner.shared_storage.fired_actions().take();
+        self.inner.handle_with_context(event, context);
+        self.inner.shared_storage.fired_actions().take()
+    }
+
+    /// Handle an event and return the error of the handler that failed, if any. A failing
+    /// handler is considered to have handled the event, so the state machine does not
+    /// transition. If the state machine is still uninitialized, it will be initialized
+    /// before handling the event.
+    pub fn try_handle(
+        &mut self,
+        event: &M::Event<'_>,
+    ) -> Result<(), <M as StoreHandlerError>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M: StoreHandlerError,
+    {
+        self.try_handle_with_context(event, &mut ())
+    }
+
+    /// Handle an event and return the error of the handler that failed, if any. A failing
+    /// handler is considered to have handled the event, so the state machine does not
+    /// transition. If the state machine is still uninitialized, it will be initialized
+    /// before handling the event.
+    pub fn try_handle_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M as StoreHandlerError>::Error>
+    where
+        M: StoreHandlerError,
+    {
+        self.init_with_context(context);
+        self.inner.shared_storage.handler_error().take();
+        self.inner.handle_with_context(event, context);
+        match self.inner.shared_storage.handler_error().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Handle an event that is either owned or borrowed. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub fn handle_ref_event<'evt, E>(&mut self, event: E)
+    where
+        E: Borrow<M::Event<'evt>>,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(event.borrow(), &mut ());
+    }
+
+    /// Handle an event only if the predicate returns `true` for the kind of the current
+    /// state (e.g. `StateKind`), and return whether the event was handled. If the state
+    /// machine is still uninitialized, it will be initialized before handling the event.
+    pub fn handle_if<K, P>(&mut self, predicate: P, event: &M::Event<'_>) -> bool
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        K: for<'a> From<&'a M::State>,
+        P: FnOnce(K) -> bool,
+    {
+        self.handle_if_with_context(predicate, event, &mut ())
+    }
+
+    /// Handle an event only if the predicate returns `true` for the kind of the current
+    /// state (e.g. `StateKind`), and return whether the event was handled. If the state
+    /// machine is still uninitialized, it will be initialized before handling the event.
+    pub fn handle_if_with_context<K, P>(
+        &mut self,
+        predicate: P,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> bool
+    where
+        K: for<'a> From<&'a M::State>,
+        P: FnOnce(K) -> bool,
+    {
+        if !predicate(self.state_kind()) {
+            return false;
+        }
+        self.handle_with_context(event, context);
+        true
+    }
+
+    /// Report the transition the event would cause without changing the state machine or
+    /// executing any entry or exit actions. The handlers are called on a copy of the shared
+    /// storage and the state, so side effects on the context or outside of the state
+    /// machine still take place. An uninitialized state machine is treated as if it was
+    /// already in its initial state.
+    pub fn dry_run(&self, event: &M::Event<'_>) -> DryRun<M::State>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M: Clone,
+        M::State: Clone,
+    {
+        self.dry_run_with_context(event, &mut ())
+    }
+
+    /// Report the transition the event would cause without changing the state machine or
+    /// executing any entry or exit actions. See [`dry_run`](Self::dry_run).
+    pub fn dry_run_with_context(
+        &self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> DryRun<M::State>
+    where
+        M: Clone,
+        M::State: Clone,
+    {
+        self.inner.dry_run_with_context(event, context)
+    }
+
+    /// Handle an event, catching a panic in one of the handlers or actions. If a panic is
+    /// caught, the state machine is restored to how it was before the event was handled
+    /// and the panic payload is returned.
+    #[cfg(feature = "std")]
+    pub fn handle_catch_unwind(
+        &mut self,
+        event: &M::Event<'_>,
+    ) -> Result<(), std::boxed::Box<dyn core::any::Any + Send>>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M: Clone,
+        M::State: Clone,
+    {
+        self.handle_catch_unwind_with_context(event, &mut ())
+    }
+
+    /// Handle an event, catching a panic in one of the handlers or actions. If a panic is
+    /// caught, the state machine is restored to how it was before the event was handled
+    /// and the panic payload is returned. Changes that were made to the context are not
+    /// undone.
+    #[cfg(feature = "std")]
+    pub fn handle_catch_unwind_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), std::boxed::Box<dyn core::any::Any + Send>>
+    where
+        M: Clone,
+        M::State: Clone,
+    {
+        // The state machine is restored from the snapshot, so it can not be observed in a
+        // broken state after a panic.
+        let snapshot = self.clone();
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.handle_with_context(event, context)
+        }))
+        .map_err(|payload| {
+            *self = snapshot;
+            payload
+        })
+    }
+
     pub fn step(&mut self)
     where
         for<'evt, 'ctx> M: IntoStateMachine<Event<'evt> = (), Context<'ctx> = ()>,
@@ -107,10 +330,150 @@ where
         self.handle_with_context(&(), context);
     }
 
+    /// Transition to the state of the given kind, executing the exit and entry actions
+    /// between the current state and the target state. Only states without state-local
+    /// storage can be created from their kind, for other kinds the error of the conversion
+    /// is returned. If the state machine is still uninitialized, it will be initialized
+    /// first.
+    pub fn reset_to<K>(&mut self, kind: K) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M::State: TryFrom<K>,
+    {
+        self.reset_to_with_context(kind, &mut ())
+    }
+
+    /// Transition to the state of the given kind, executing the exit and entry actions
+    /// between the current state and the target state. Only states without state-local
+    /// storage can be created from their kind, for other kinds the error of the conversion
+    /// is returned. If the state machine is still uninitialized, it will be initialized
+    /// first.
+    pub fn reset_to_with_context<K>(
+        &mut self,
+        kind: K,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        M::State: TryFrom<K>,
+    {
+        let state = <M::State as TryFrom<K>>::try_from(kind)?;
+        if !self.initialized {
+            self.inner.init_with_context(context);
+            self.initialized = true;
+        }
+        self.inner.transition(state, context);
+        Ok(())
+    }
+
+    /// Initialize the state machine in the given state instead of its initial state, or
+    /// transition to it if the state machine is already initialized. Used to activate a
+    /// region in the initial state that is declared for it by its parent.
+    pub(crate) fn enter_in_with_context(&mut self, state: M::State, context: &mut M::Context<'_>) {
+        if self.initialized {
+            self.inner.transition(state, context);
+        } else {
+            self.inner.state = state;
+            self.init_with_context(context);
+        }
+    }
+
+    /// Apply a command from outside the state machine (e.g. from a debugging UI): handle an
+    /// event, reset the state machine to its initial state or force it into the state of the
+    /// given kind. Forcing a state with state-local storage returns the error of
+    /// [`reset_to`](Self::reset_to). If the state machine is still
+    /// uninitialized, it will be initialized first.
+    pub fn apply_command<K>(
+        &mut self,
+        command: Command<M::Event<'_>, K>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M::State: TryFrom<K>,
+    {
+        self.apply_command_with_context(command, &mut ())
+    }
+
+    /// Apply a command from outside the state machine (e.g. from a debugging UI): handle an
+    /// event, reset the state machine to its initial state or force it into the state of the
+    /// given kind. Forcing a state with state-local storage returns the error of
+    /// [`reset_to`](Self::reset_to). If the state machine is still
+    /// uninitialized, it will be initialized first.
+    pub fn apply_command_with_context<K>(
+        &mut self,
+        command: Command<M::Event<'_>, K>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        M::State: TryFrom<K>,
+    {
+        match command {
+            Command::Event(event) => self.handle_with_context(&event, context),
+            Command::Reset => {
+                if !self.initialized {
+                    self.inner.init_with_context(context);
+                    self.initialized = true;
+                }
+                self.inner.transition(M::INITIAL, context);
+            }
+            Command::ForceState(kind) => return self.reset_to_with_context(kind, context),
+        }
+        Ok(())
+    }
+
+    /// Shut the state machine down by executing the exit actions of the current state and
+    /// all of its superstates. If the state machine is still uninitialized, no actions are
+    /// executed. The state machine should not handle any more events afterwards.
+    pub fn shutdown(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.shutdown_with_context(&mut ());
+    }
+
+    /// Shut the state machine down by executing the exit actions of the current state and
+    /// all of its superstates. If the state machine is still uninitialized, no actions are
+    /// executed. The state machine should not handle any more events afterwards.
+    pub fn shutdown_with_context(&mut self, context: &mut M::Context<'_>) {
+        if self.initialized {
+            self.inner.shutdown_with_context(context);
+        }
+    }
+
     /// Get the current state.
     pub fn state(&self) -> &M::State {
         &self.inner.state
     }
+
+    /// Call the given closure with the current state, followed by each of the superstates
+    /// that are active because they contain it, from the direct superstate of the state
+    /// up to the outermost superstate. The superstates borrow the state-local storage
+    /// mutably, which is why this takes `&mut self`.
+    pub fn for_each_active<F>(&mut self, visit: F)
+    where
+        F: FnMut(StateOrSuperstate<'_, '_, M>),
+    {
+        self.inner.state.for_each_active(visit);
+    }
+
+    /// Get the kind of the current state (e.g. `StateKind`), without borrowing the state
+    /// itself. This works even when the state is not `Copy`.
+    pub fn state_kind<'a, K>(&'a self) -> K
+    where
+        K: From<&'a M::State>,
+    {
+        K::from(&self.inner.state)
+    }
+
+    /// Get the priority of the current state, which is declared with
+    /// `#[state(priority = ..)]` and is `0` by default.
+    pub fn state_priority(&self) -> i32 {
+        M::STATE_PRIORITY(&self.inner.state)
+    }
+
+    /// Consume the state machine and return the shared storage, dropping the state.
+    pub fn into_storage(self) -> M {
+        self.inner.shared_storage
+    }
 }
 
 impl<M> Clone for StateMachine<M>
@@ -142,15 +505,31 @@ where
 {
 }
 
+/// Shows the name of the current state, followed by the shared storage when it implements
+/// `Debug`. The state itself does not have to implement `Debug`.
+impl<M> Debug for StateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("StateMachine");
+        debug.field(
+            "state",
+            &format_args!("{}", M::STATE_NAME(&self.inner.state)),
+        );
+        if let Some(shared_storage) = M::DEBUG_STORAGE(&self.inner.shared_storage) {
+            debug.field("shared_storage", shared_storage);
+        }
+        debug.finish()
+    }
+}
+
 impl<M> Default for StateMachine<M>
 where
     M: IntoStateMachine + Default,
 {
     fn default() -> Self {
-        let inner = Inner {
-            shared_storage: M::default(),
-            state: M::INITIAL,
-        };
+        let inner = Inner::new(M::default(), M::INITIAL);
         Self {
             inner,
             initialized: false,
@@ -169,6 +548,15 @@ where
     }
 }
 
+impl<M> core::ops::DerefMut for StateMachine<M>
+where
+    M: DerefStorageMut,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner.shared_storage
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<M> serde::Serialize for StateMachine<M>
 where
@@ -243,6 +631,213 @@ where
         self.inner.handle_with_context(event, context);
     }
 
+    /// Handle the given event tagged with the place it originated from. The origin is
+    /// passed to the shared storage before the event is dispatched, so it can be used in
+    /// the `on_dispatch` and `on_transition` callbacks.
+    pub fn handle_with_origin(&mut self, event: &M::Event<'_>, origin: &'static str)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.handle_with_origin_and_context(event, origin, &mut ());
+    }
+
+    /// Handle the given event tagged with the place it originated from. The origin is
+    /// passed to the shared storage before the event is dispatched, so it can be used in
+    /// the `on_dispatch` and `on_transition` callbacks.
+    pub fn handle_with_origin_and_context(
+        &mut self,
+        event: &M::Event<'_>,
+        origin: &'static str,
+        context: &mut M::Context<'_>,
+    ) where
+        M: IntoStateMachine,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.inner.handle_with_origin(event, origin, context);
+    }
+
+    /// Handle the given event and return the outcome together with the names of the entry
+    /// and exit actions that fired, in the order they fired. Requires the `instrumentation`
+    /// feature.
+    #[cfg(feature = "instrumentation")]
+    pub fn handle_recording(&mut self, event: &M::Event<'_>) -> (Outcome, Vec<&'static str>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M: RecordFiredActions,
+    {
+        self.handle_recording_with_context(event, &mut ())
+    }
+
+    /// Handle the given event and return the outcome together with the names of the entry
+    /// and exit actions that fired, in the order they fired. Requires the `instrumentation`
+    /// feature.
+    #[cfg(feature = "instrumentation")]
+    pub fn handle_recording_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> (Outcome, Vec<&'static str>)
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M: RecordFiredActions,
+    {
+        self.inner.shared_storage.fired_actions().take();
+        self.inner.handle_with_context(event, context);
+        self.inner.shared_storage.fired_actions().take()
+    }
+
+    /// Handle the given event and return the error of the handler that failed, if any. A
+    /// failing handler is considered to have handled the event, so the state machine does
+    /// not transition.
+    pub fn try_handle(
+        &mut self,
+        event: &M::Event<'_>,
+    ) -> Result<(), <M as StoreHandlerError>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M: StoreHandlerError,
+    {
+        self.try_handle_with_context(event, &mut ())
+    }
+
+    /// Handle the given event and return the error of the handler that failed, if any. A
+    /// failing handler is considered to have handled the event, so the state machine does
+    /// not transition.
+    pub fn try_handle_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M as StoreHandlerError>::Error>
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M: StoreHandlerError,
+    {
+        self.inner.shared_storage.handler_error().take();
+        self.inner.handle_with_context(event, context);
+        match self.inner.shared_storage.handler_error().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Handle an event that is either owned or borrowed.
+    pub fn handle_ref_event<'evt, E>(&mut self, event: E)
+    where
+        E: Borrow<M::Event<'evt>>,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.handle_with_context(event.borrow(), &mut ());
+    }
+
+    /// Handle an event only if the predicate returns `true` for the kind of the current
+    /// state (e.g. `StateKind`), and return whether the event was handled.
+    pub fn handle_if<K, P>(&mut self, predicate: P, event: &M::Event<'_>) -> bool
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        K: for<'a> From<&'a M::State>,
+        P: FnOnce(K) -> bool,
+    {
+        self.handle_if_with_context(predicate, event, &mut ())
+    }
+
+    /// Handle an event only if the predicate returns `true` for the kind of the current
+    /// state (e.g. `StateKind`), and return whether the event was handled.
+    pub fn handle_if_with_context<K, P>(
+        &mut self,
+        predicate: P,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> bool
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        K: for<'a> From<&'a M::State>,
+        P: FnOnce(K) -> bool,
+    {
+        if !predicate(self.state_kind()) {
+            return false;
+        }
+        self.handle_with_context(event, context);
+        true
+    }
+
+    /// Report the transition the event would cause without changing the state machine or
+    /// executing any entry or exit actions. The handlers are called on a copy of the shared
+    /// storage and the state, so side effects on the context or outside of the state
+    /// machine still take place.
+    pub fn dry_run(&self, event: &M::Event<'_>) -> DryRun<M::State>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M: Clone,
+        M::State: Clone,
+    {
+        self.dry_run_with_context(event, &mut ())
+    }
+
+    /// Report the transition the event would cause without changing the state machine or
+    /// executing any entry or exit actions. See [`dry_run`](Self::dry_run).
+    pub fn dry_run_with_context(
+        &self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> DryRun<M::State>
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M: Clone,
+        M::State: Clone,
+    {
+        self.inner.dry_run_with_context(event, context)
+    }
+
+    /// Handle an event, catching a panic in one of the handlers or actions. If a panic is
+    /// caught, the state machine is restored to how it was before the event was handled
+    /// and the panic payload is returned.
+    #[cfg(feature = "std")]
+    pub fn handle_catch_unwind(
+        &mut self,
+        event: &M::Event<'_>,
+    ) -> Result<(), std::boxed::Box<dyn core::any::Any + Send>>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M: Clone,
+        M::State: Clone,
+    {
+        self.handle_catch_unwind_with_context(event, &mut ())
+    }
+
+    /// Handle an event, catching a panic in one of the handlers or actions. If a panic is
+    /// caught, the state machine is restored to how it was before the event was handled
+    /// and the panic payload is returned. Changes that were made to the context are not
+    /// undone.
+    #[cfg(feature = "std")]
+    pub fn handle_catch_unwind_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), std::boxed::Box<dyn core::any::Any + Send>>
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M: Clone,
+        M::State: Clone,
+    {
+        // The state machine is restored from the snapshot, so it can not be observed in a
+        // broken state after a panic.
+        let snapshot = self.clone();
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.handle_with_context(event, context)
+        }))
+        .map_err(|payload| {
+            *self = snapshot;
+            payload
+        })
+    }
+
     /// This is the same as `handle(())` in the case `Event` is of type `()`.
     pub fn step(&mut self)
     where
@@ -261,10 +856,140 @@ where
         self.handle_with_context(&(), context);
     }
 
+    /// Transition to the state of the given kind, executing the exit and entry actions
+    /// between the current state and the target state. Only states without state-local
+    /// storage can be created from their kind, for other kinds the error of the conversion
+    /// is returned.
+    pub fn reset_to<K>(&mut self, kind: K) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M::State: TryFrom<K>,
+    {
+        self.reset_to_with_context(kind, &mut ())
+    }
+
+    /// Transition to the state of the given kind, executing the exit and entry actions
+    /// between the current state and the target state. Only states without state-local
+    /// storage can be created from their kind, for other kinds the error of the conversion
+    /// is returned.
+    pub fn reset_to_with_context<K>(
+        &mut self,
+        kind: K,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M::State: TryFrom<K>,
+    {
+        let state = <M::State as TryFrom<K>>::try_from(kind)?;
+        self.inner.transition(state, context);
+        Ok(())
+    }
+
+    /// Transition to the given state. Used to activate a region in the initial state that is
+    /// declared for it by its parent.
+    pub(crate) fn enter_in_with_context(&mut self, state: M::State, context: &mut M::Context<'_>)
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.inner.transition(state, context);
+    }
+
+    /// Apply a command from outside the state machine (e.g. from a debugging UI): handle an
+    /// event, reset the state machine to its initial state or force it into the state of the
+    /// given kind. Forcing a state with state-local storage returns the error of
+    /// [`reset_to`](Self::reset_to).
+    pub fn apply_command<K>(
+        &mut self,
+        command: Command<M::Event<'_>, K>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M::State: TryFrom<K>,
+    {
+        self.apply_command_with_context(command, &mut ())
+    }
+
+    /// Apply a command from outside the state machine (e.g. from a debugging UI): handle an
+    /// event, reset the state machine to its initial state or force it into the state of the
+    /// given kind. Forcing a state with state-local storage returns the error of
+    /// [`reset_to`](Self::reset_to).
+    pub fn apply_command_with_context<K>(
+        &mut self,
+        command: Command<M::Event<'_>, K>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), <M::State as TryFrom<K>>::Error>
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M::State: TryFrom<K>,
+    {
+        match command {
+            Command::Event(event) => self.handle_with_context(&event, context),
+            Command::Reset => self.inner.transition(M::INITIAL, context),
+            Command::ForceState(kind) => return self.reset_to_with_context(kind, context),
+        }
+        Ok(())
+    }
+
+    /// Shut the state machine down by executing the exit actions of the current state and
+    /// all of its superstates. The state machine should not handle any more events
+    /// afterwards.
+    pub fn shutdown(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.shutdown_with_context(&mut ());
+    }
+
+    /// Shut the state machine down by executing the exit actions of the current state and
+    /// all of its superstates. The state machine should not handle any more events
+    /// afterwards.
+    pub fn shutdown_with_context(&mut self, context: &mut M::Context<'_>)
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.inner.shutdown_with_context(context);
+    }
+
     /// Get an immutable reference to the current state of the state machine.
     pub fn state(&self) -> &M::State {
         &self.inner.state
     }
+
+    /// Call the given closure with the current state, followed by each of the superstates
+    /// that are active because they contain it, from the direct superstate of the state
+    /// up to the outermost superstate. The superstates borrow the state-local storage
+    /// mutably, which is why this takes `&mut self`.
+    pub fn for_each_active<F>(&mut self, visit: F)
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        F: FnMut(StateOrSuperstate<'_, '_, M>),
+    {
+        self.inner.state.for_each_active(visit);
+    }
+
+    /// Get the kind of the current state (e.g. `StateKind`), without borrowing the state
+    /// itself. This works even when the state is not `Copy`.
+    pub fn state_kind<'a, K>(&'a self) -> K
+    where
+        K: From<&'a M::State>,
+    {
+        K::from(&self.inner.state)
+    }
+
+    /// Get the priority of the current state, which is declared with
+    /// `#[state(priority = ..)]` and is `0` by default.
+    pub fn state_priority(&self) -> i32 {
+        M::STATE_PRIORITY(&self.inner.state)
+    }
+
+    /// Consume the state machine and return the shared storage, dropping the state.
+    pub fn into_storage(self) -> M {
+        self.inner.shared_storage
+    }
 }
 
 impl<M> Clone for InitializedStateMachine<M>
@@ -279,16 +1004,22 @@ where
     }
 }
 
+/// Shows the name of the current state, followed by the shared storage when it implements
+/// `Debug`. The state itself does not have to implement `Debug`.
 impl<M> Debug for InitializedStateMachine<M>
 where
-    M: IntoStateMachine + Debug,
-    M::State: Debug,
+    M: IntoStateMachine,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("InitializedStateMachine")
-            .field("shared_storage", &self.inner.shared_storage as &dyn Debug)
-            .field("state", &self.inner.state as &dyn Debug)
-            .finish()
+        let mut debug = f.debug_struct("InitializedStateMachine");
+        debug.field(
+            "state",
+            &format_args!("{}", M::STATE_NAME(&self.inner.state)),
+        );
+        if let Some(shared_storage) = M::DEBUG_STORAGE(&self.inner.shared_storage) {
+            debug.field("shared_storage", shared_storage);
+        }
+        debug.finish()
     }
 }
 
@@ -320,6 +1051,15 @@ where
     }
 }
 
+impl<M> core::ops::DerefMut for InitializedStateMachine<M>
+where
+    M: DerefStorageMut,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner.shared_storage
+    }
+}
+
 #[cfg(feature = "serde")]
 /// Once an [`InitializedStateMachine`] is serialized, it can only be deserialized into
 /// an [`UnInitializedStateMachine`] which can then be re-initialized with the
@@ -333,12 +1073,7 @@ where
     where
         S: serde::Serializer,
     {
-        use serde::ser::SerializeStruct;
-
-        let mut serializer = serializer.serialize_struct("StateMachine", 2)?;
-        serializer.serialize_field("shared_storage", &self.inner.shared_storage)?;
-        serializer.serialize_field("state", &self.inner.state)?;
-        serializer.end()
+        self.inner.serialize(serializer)
     }
 }
 
@@ -363,6 +1098,51 @@ where
     inner: Inner<M>,
 }
 
+impl<M> UninitializedStateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    /// Create an uninitialized state machine that wraps the given shared storage (e.g. a
+    /// pre-populated buffer) and starts in the initial state once it is initialized. Unlike
+    /// [`uninitialized_state_machine`](IntoStateMachineExt::uninitialized_state_machine)
+    /// this can be called in a `const` context, so state machines can be stored in a
+    /// `static`. This requires the shared storage to be const-constructible as well.
+    pub const fn new(shared_storage: M) -> Self {
+        let inner = Inner::new(shared_storage, M::INITIAL);
+        Self { inner }
+    }
+
+    /// Replace the initial state the state machine starts in once it is initialized, e.g.
+    /// when it depends on runtime configuration. The entry actions towards the given state
+    /// are executed by [`init`](Self::init) instead of those towards the initial state
+    /// declared on the state machine.
+    ///
+    /// ```
+    /// # use statig::prelude::*;
+    /// # #[derive(Default)]
+    /// # pub struct Blinky;
+    /// #
+    /// # pub struct Event;
+    /// #
+    /// # #[state_machine(initial = "State::on()")]
+    /// # impl Blinky {
+    /// #     #[state]
+    /// #     fn on(event: &Event) -> Response<State> { Handled }
+    /// #
+    /// #     #[state]
+    /// #     fn off(event: &Event) -> Response<State> { Handled }
+    /// # }
+    /// #
+    /// let uninitialized_state_machine = Blinky::default()
+    ///     .uninitialized_state_machine()
+    ///     .with_initial_state(State::off());
+    /// ```
+    pub fn with_initial_state(mut self, state: M::State) -> Self {
+        self.inner.state = state;
+        self
+    }
+}
+
 impl<M> UninitializedStateMachine<M>
 where
     M: IntoStateMachine,
@@ -448,16 +1228,22 @@ where
     }
 }
 
+/// Shows the name of the current state, followed by the shared storage when it implements
+/// `Debug`. The state itself does not have to implement `Debug`.
 impl<M> Debug for UninitializedStateMachine<M>
 where
-    M: IntoStateMachine + Debug,
-    M::State: Debug,
+    M: IntoStateMachine,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("UnInitializedStateMachine")
-            .field("shared_storage", &self.inner.shared_storage as &dyn Debug)
-            .field("state", &self.inner.state as &dyn Debug)
-            .finish()
+        let mut debug = f.debug_struct("UnInitializedStateMachine");
+        debug.field(
+            "state",
+            &format_args!("{}", M::STATE_NAME(&self.inner.state)),
+        );
+        if let Some(shared_storage) = M::DEBUG_STORAGE(&self.inner.shared_storage) {
+            debug.field("shared_storage", shared_storage);
+        }
+        debug.finish()
     }
 }
 
@@ -489,6 +1275,15 @@ where
     }
 }
 
+impl<M> core::ops::DerefMut for UninitializedStateMachine<M>
+where
+    M: DerefStorageMut,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner.shared_storage
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<M> serde::Serialize for UninitializedStateMachine<M>
 where
@@ -21,10 +21,34 @@ where
     /// Call the entry action for the current superstate.
     fn call_entry_action(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>) {}
 
+    /// Call the entry action for the current superstate and return whether it succeeded.
+    /// Only fallible entry actions can fail, by default this calls
+    /// [`call_entry_action`](Self::call_entry_action) and succeeds.
+    fn try_call_entry_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+    ) -> bool {
+        self.call_entry_action(shared_storage, context);
+        true
+    }
+
     #[allow(unused)]
     /// Call the exit action for the current superstate.
     fn call_exit_action(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>) {}
 
+    #[allow(unused)]
+    /// Call the pre-dispatch filter for the current superstate. Returning
+    /// [`Super`](Response::Super) lets the event continue to the children.
+    fn call_pre_dispatch(
+        &mut self,
+        shared_storage: &mut M,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Response<M::State> {
+        Response::Super
+    }
+
     /// Return the superstate of the current superstate, if there is one.
     fn superstate(&mut self) -> Option<M::Superstate<'_>>
     where
@@ -63,6 +87,18 @@ where
         }
     }
 
+    /// Get the depth of the current superstate, or `None` if it exceeds the given limit
+    /// (e.g. because the superstates form a cycle).
+    fn bounded_depth(&mut self, limit: usize) -> Option<usize> {
+        if limit == 0 {
+            return None;
+        }
+        match self.superstate() {
+            Some(mut superstate) => superstate.bounded_depth(limit - 1).map(|depth| depth + 1),
+            None => Some(1),
+        }
+    }
+
     /// Get the depth of the common ancestor of two states.
     fn common_ancestor_depth(
         mut source: M::Superstate<'_>,
@@ -87,6 +123,40 @@ where
         }
     }
 
+    /// Call the given closure with each superstate of the current superstate, from its
+    /// direct superstate up to the outermost superstate.
+    fn for_each_ancestor<F>(&mut self, visit: &mut F)
+    where
+        F: FnMut(StateOrSuperstate<'_, '_, M>),
+    {
+        if let Some(mut superstate) = self.superstate() {
+            visit(StateOrSuperstate::Superstate(&superstate));
+            superstate.for_each_ancestor(visit);
+        }
+    }
+
+    /// Run the pre-dispatch filters of the current superstate and its superstates,
+    /// starting from the outermost one, until one of them returns a response other
+    /// than `Super`.
+    fn pre_dispatch(
+        &mut self,
+        shared_storage: &mut M,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Response<M::State>
+    where
+        Self: Sized,
+    {
+        if let Some(mut superstate) = self.superstate() {
+            match superstate.pre_dispatch(shared_storage, event, context) {
+                Response::Super => {}
+                response => return response,
+            }
+        }
+
+        self.call_pre_dispatch(shared_storage, event, context)
+    }
+
     /// Handle the given event in the current superstate.
     fn handle(
         &mut self,
@@ -114,21 +184,38 @@ where
                 None => Response::Super,
             },
             Response::Transition(state) => Response::Transition(state),
+            Response::Push(state) => Response::Push(state),
+            Response::Pop => Response::Pop,
         }
     }
 
     /// Starting from the current superstate, climb a given amount of levels and execute all the
-    /// entry actions while going back down to the current superstate.
-    fn enter(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>, mut levels: usize) {
+    /// entry actions while going back down to the current superstate. If one of the entry
+    /// actions fails, the exit actions of the superstates that were already entered are
+    /// executed to compensate and `false` is returned.
+    fn enter(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        mut levels: usize,
+    ) -> bool {
         match levels {
-            0 => (),
-            1 => self.call_entry_action(shared_storage, context),
+            0 => true,
+            1 => self.try_call_entry_action(shared_storage, context),
             _ => {
                 if let Some(mut superstate) = self.superstate() {
                     levels -= 1;
-                    superstate.enter(shared_storage, context, levels);
+                    if !superstate.enter(shared_storage, context, levels) {
+                        return false;
+                    }
+                }
+                if self.try_call_entry_action(shared_storage, context) {
+                    return true;
+                }
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.exit(shared_storage, context, levels);
                 }
-                self.call_entry_action(shared_storage, context);
+                false
             }
         }
     }
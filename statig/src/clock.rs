@@ -0,0 +1,25 @@
+/// Source of the current time for [`Timeout`](crate::Timeout)s.
+///
+/// Keep the clock in the shared storage instead of reading the system time in the
+/// handlers, so tests can supply a clock that advances deterministically.
+pub trait Clock {
+    /// Point in time that is returned by the clock.
+    type Instant: PartialOrd;
+
+    /// Get the current time.
+    fn now(&self) -> Self::Instant;
+}
+
+/// Clock that reads the monotonic system time.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    type Instant = std::time::Instant;
+
+    fn now(&self) -> Self::Instant {
+        std::time::Instant::now()
+    }
+}
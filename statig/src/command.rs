@@ -0,0 +1,16 @@
+/// A command for controlling a state machine from the outside, e.g. from the control panel
+/// of a debugging UI. Next to dispatching events it has built-in commands to reset the state
+/// machine or to force it into a state, which are applied with `apply_command`.
+///
+/// `E` is the event type of the state machine and `K` the kind of its states (e.g.
+/// `StateKind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command<E, K> {
+    /// Handle the event.
+    Event(E),
+    /// Transition to the initial state of the state machine.
+    Reset,
+    /// Transition to the state of the given kind. Only states without state-local storage
+    /// can be created from their kind, so other states can not be forced.
+    ForceState(K),
+}
@@ -0,0 +1,32 @@
+//! Support for formatting the shared storage in the `Debug` implementation of the state
+//! machines only when it implements `Debug`. The `state_machine` macro calls
+//! `(&Wrap(shared_storage)).debug_storage()` with both traits in scope: method resolution
+//! picks [`ViaDebug`] when the shared storage implements `Debug`, and otherwise adds a
+//! reference and falls back to [`ViaFallback`].
+
+use core::fmt::Debug;
+
+/// Wrapper around the shared storage that the traits are implemented on.
+pub struct Wrap<'a, T>(pub &'a T);
+
+/// Implemented when the shared storage implements `Debug`.
+pub trait ViaDebug<'a> {
+    fn debug_storage(&self) -> Option<&'a dyn Debug>;
+}
+
+impl<'a, T: Debug> ViaDebug<'a> for Wrap<'a, T> {
+    fn debug_storage(&self) -> Option<&'a dyn Debug> {
+        Some(self.0)
+    }
+}
+
+/// Implemented for every shared storage, but only used when [`ViaDebug`] is not.
+pub trait ViaFallback<'a> {
+    fn debug_storage(&self) -> Option<&'a dyn Debug>;
+}
+
+impl<'a, T> ViaFallback<'a> for &Wrap<'a, T> {
+    fn debug_storage(&self) -> Option<&'a dyn Debug> {
+        None
+    }
+}
@@ -0,0 +1,19 @@
+/// The outcome of handling an event on a copy of a state machine, as reported by
+/// `dry_run`. It describes the transition that would be taken, without any of the entry
+/// or exit actions having been executed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DryRun<S> {
+    /// The event would be handled without a transition.
+    Handled,
+    /// The event would cause a transition to the target state.
+    Transition {
+        /// The state the state machine would transition to.
+        target: S,
+        /// The number of states and superstates whose exit actions would be executed,
+        /// starting from the current state.
+        exit_levels: usize,
+        /// The number of states and superstates whose entry actions would be executed,
+        /// ending with the target state.
+        enter_levels: usize,
+    },
+}
@@ -0,0 +1,63 @@
+/// Keeps track of how many times each kind of state was entered and exited.
+///
+/// Add it as a field of the shared storage and pass the name of the field to
+/// `#[state_machine(entry_exit_counts = "field_name")]`, so tests can verify the
+/// transitions that were taken. Requires the `instrumentation` feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryExitCounts<K> {
+    counts: Vec<(K, usize, usize)>,
+}
+
+impl<K> EntryExitCounts<K>
+where
+    K: Copy + PartialEq,
+{
+    /// Create a new tracker for which no entries or exits have been recorded yet.
+    pub const fn new() -> Self {
+        Self { counts: Vec::new() }
+    }
+
+    /// Get the number of times the state of the given kind was entered.
+    pub fn entry_count(&self, kind: K) -> usize {
+        self.find(kind).map_or(0, |(_, entries, _)| *entries)
+    }
+
+    /// Get the number of times the state of the given kind was exited.
+    pub fn exit_count(&self, kind: K) -> usize {
+        self.find(kind).map_or(0, |(_, _, exits)| *exits)
+    }
+
+    /// Record that the state of the given kind was entered.
+    pub fn record_entry(&mut self, kind: K) {
+        self.find_or_insert(kind).1 += 1;
+    }
+
+    /// Record that the state of the given kind was exited.
+    pub fn record_exit(&mut self, kind: K) {
+        self.find_or_insert(kind).2 += 1;
+    }
+
+    fn find(&self, kind: K) -> Option<&(K, usize, usize)> {
+        self.counts.iter().find(|(k, _, _)| *k == kind)
+    }
+
+    fn find_or_insert(&mut self, kind: K) -> &mut (K, usize, usize) {
+        let index = match self.counts.iter().position(|(k, _, _)| *k == kind) {
+            Some(index) => index,
+            None => {
+                self.counts.push((kind, 0, 0));
+                self.counts.len() - 1
+            }
+        };
+        &mut self.counts[index]
+    }
+}
+
+impl<K> Default for EntryExitCounts<K>
+where
+    K: Copy + PartialEq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
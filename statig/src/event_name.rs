@@ -0,0 +1,8 @@
+/// Trait for getting the name of an event, e.g. to log it alongside the name of the state.
+///
+/// Use `#[derive(EventName)]` to implement it for an enum, in which case the name of the
+/// variant is returned. For a struct the name of the struct is returned.
+pub trait EventName {
+    /// Get the name of the event (e.g. `"TimerElapsed"`).
+    fn event_name(&self) -> &'static str;
+}
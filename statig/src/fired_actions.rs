@@ -0,0 +1,65 @@
+use crate::IntoStateMachine;
+
+/// The outcome of handling an event, as reported by `handle_recording`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The event was handled without a transition.
+    Handled,
+    /// The event caused a transition.
+    Transition,
+}
+
+/// Records the names of the entry and exit actions that fired while handling an event.
+///
+/// Add it as a field of the shared storage and pass the name of the field to
+/// `#[state_machine(fired_actions = "field_name")]`. The state machine can then handle
+/// events with `handle_recording`, which returns the outcome together with the actions
+/// that fired, so tests can verify the side effects of a transition. Requires the
+/// `instrumentation` feature.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FiredActions {
+    actions: Vec<&'static str>,
+    transitioned: bool,
+}
+
+impl FiredActions {
+    /// Create a new recorder for which no actions have fired yet.
+    pub const fn new() -> Self {
+        Self {
+            actions: Vec::new(),
+            transitioned: false,
+        }
+    }
+
+    /// Get the names of the actions that fired since the last call to `take`.
+    pub fn actions(&self) -> &[&'static str] {
+        &self.actions
+    }
+
+    /// Record that the action with the given name fired.
+    pub fn record_action(&mut self, name: &'static str) {
+        self.actions.push(name);
+    }
+
+    /// Record that a state was entered as the result of a transition.
+    pub fn record_transition(&mut self) {
+        self.transitioned = true;
+    }
+
+    /// Take the outcome and the actions that fired since the last call to `take`.
+    pub fn take(&mut self) -> (Outcome, Vec<&'static str>) {
+        let outcome = match core::mem::take(&mut self.transitioned) {
+            true => Outcome::Transition,
+            false => Outcome::Handled,
+        };
+        (outcome, core::mem::take(&mut self.actions))
+    }
+}
+
+/// Trait for types whose shared storage records the actions that fire, so their state
+/// machines can handle events with `handle_recording`. Implemented by the `state_machine`
+/// macro when `fired_actions` is set.
+pub trait RecordFiredActions: IntoStateMachine {
+    /// Get the recorder of the fired actions.
+    fn fired_actions(&mut self) -> &mut FiredActions;
+}
@@ -0,0 +1,17 @@
+use crate::IntoStateMachine;
+
+/// Trait for types whose handlers can fail, so their state machines can handle events with
+/// `try_handle`. Implemented by the `state_machine` macro when `fallible` is set.
+///
+/// A handler that returns `Err` stores the error in the field of the shared storage that is
+/// passed to `#[state_machine(fallible = "field_name")]`, which must be an `Option` of the
+/// error type. The event is then considered handled, so the state machine does not
+/// transition and the superstates are not consulted. The field is cleared before every
+/// event is handled, so after `handle` it only holds the error of that event.
+pub trait StoreHandlerError: IntoStateMachine {
+    /// The error that is returned by the fallible handlers.
+    type Error;
+
+    /// Get the error of the handler that failed while handling the current event, if any.
+    fn handler_error(&mut self) -> &mut Option<Self::Error>;
+}
@@ -1,7 +1,7 @@
 #[cfg(feature = "async")]
 use crate::awaitable::{self, StateExt as _};
 use crate::blocking::{self, StateExt as _};
-use crate::{IntoStateMachine, Response};
+use crate::{DryRun, IntoStateMachine, Response};
 
 /// Private internal representation of a state machine that is used for the public types.
 pub(crate) struct Inner<M>
@@ -10,6 +10,158 @@ where
 {
     pub shared_storage: M,
     pub state: M::State,
+    /// States that were left with a `Push` response and are returned to with `Pop`.
+    #[cfg(feature = "state_stack")]
+    pub stack: Stack<M::State>,
+    /// Transition of which the future was dropped before it completed.
+    #[cfg(feature = "async")]
+    pending: Option<PendingTransition<M::State>>,
+}
+
+/// The collection that holds the state stack.
+#[cfg(all(feature = "state_stack", not(feature = "allocator-api2")))]
+type Stack<S> = alloc::vec::Vec<S>;
+
+/// The collection that holds the state stack, allocated with the allocator of the state
+/// machine.
+#[cfg(all(feature = "state_stack", feature = "allocator-api2"))]
+type Stack<S> = allocator_api2::vec::Vec<S, crate::StackAllocator>;
+
+/// A transition that is performed one exit or entry action at a time, so that it can be
+/// picked up where it was left when the future performing it is dropped at one of the await
+/// points.
+#[cfg(feature = "async")]
+#[derive(Clone, PartialEq, Eq)]
+struct PendingTransition<S> {
+    target: S,
+    exit_levels: usize,
+    enter_levels: usize,
+    step: Step,
+    kind: TransitionKind,
+}
+
+/// The progress of a pending transition.
+#[cfg(feature = "async")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// Exiting the source state, after the given amount of exit actions have completed.
+    Exit(usize),
+    /// Entering the target state, after the given amount of entry actions have completed.
+    Enter(usize),
+    /// One of the entry actions failed, so the levels that were already `entered` are exited
+    /// again. When `restoring`, these are the levels of the source state instead of the target.
+    Compensate {
+        entered: usize,
+        exited: usize,
+        restoring: bool,
+    },
+    /// Entering the source state again, after the given amount of entry actions have completed.
+    Restore(usize),
+}
+
+/// What to do with the state stack once a pending transition completes.
+#[cfg(feature = "async")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TransitionKind {
+    Transition,
+    #[cfg(feature = "state_stack")]
+    Push,
+    #[cfg(feature = "state_stack")]
+    Pop,
+}
+
+/// Pending transition in the form it is serialized in: the target state, the amount of exit
+/// and entry levels, the step as a tag with its two counters and the kind as a tag.
+#[cfg(all(feature = "async", feature = "serde"))]
+type SerializedTransition<S> = (S, usize, usize, (u8, usize, usize), u8);
+
+#[cfg(all(feature = "async", feature = "serde"))]
+impl<S> PendingTransition<S> {
+    fn serialized(&self) -> SerializedTransition<&S> {
+        let step = match self.step {
+            Step::Exit(exited) => (0, exited, 0),
+            Step::Enter(entered) => (1, entered, 0),
+            Step::Compensate {
+                entered,
+                exited,
+                restoring: false,
+            } => (2, entered, exited),
+            Step::Compensate {
+                entered,
+                exited,
+                restoring: true,
+            } => (3, entered, exited),
+            Step::Restore(entered) => (4, entered, 0),
+        };
+        let kind = match self.kind {
+            TransitionKind::Transition => 0,
+            #[cfg(feature = "state_stack")]
+            TransitionKind::Push => 1,
+            #[cfg(feature = "state_stack")]
+            TransitionKind::Pop => 2,
+        };
+        (
+            &self.target,
+            self.exit_levels,
+            self.enter_levels,
+            step,
+            kind,
+        )
+    }
+
+    fn deserialized<E>(serialized: SerializedTransition<S>) -> Result<Self, E>
+    where
+        E: serde::de::Error,
+    {
+        let (target, exit_levels, enter_levels, (step, first, second), kind) = serialized;
+        let step = match step {
+            0 => Step::Exit(first),
+            1 => Step::Enter(first),
+            2 | 3 => Step::Compensate {
+                entered: first,
+                exited: second,
+                restoring: step == 3,
+            },
+            4 => Step::Restore(first),
+            _ => return Err(E::custom("invalid step of pending transition")),
+        };
+        let kind = match kind {
+            0 => TransitionKind::Transition,
+            #[cfg(feature = "state_stack")]
+            1 => TransitionKind::Push,
+            #[cfg(feature = "state_stack")]
+            2 => TransitionKind::Pop,
+            // Without a state stack there is nothing to remember or return to.
+            #[cfg(not(feature = "state_stack"))]
+            1 | 2 => TransitionKind::Transition,
+            _ => return Err(E::custom("invalid kind of pending transition")),
+        };
+        Ok(Self {
+            target,
+            exit_levels,
+            enter_levels,
+            step,
+            kind,
+        })
+    }
+}
+
+impl<M> Inner<M>
+where
+    M: IntoStateMachine,
+{
+    pub const fn new(shared_storage: M, state: M::State) -> Self {
+        Self {
+            shared_storage,
+            state,
+            #[cfg(all(feature = "state_stack", not(feature = "allocator-api2")))]
+            stack: alloc::vec::Vec::new(),
+            #[cfg(all(feature = "state_stack", feature = "allocator-api2"))]
+            stack: allocator_api2::vec::Vec::new_in(M::STACK_ALLOCATOR),
+            #[cfg(feature = "async")]
+            pending: None,
+        }
+    }
 }
 
 impl<M> Inner<M>
@@ -18,25 +170,113 @@ where
     M::State: blocking::State<M>,
     for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
 {
-    /// Initialize the state machine by executing all entry actions towards the initial state.
+    /// Initialize the state machine by executing all entry actions towards the initial state
+    /// and handling the startup event, if there is one.
     pub fn init_with_context(&mut self, context: &mut M::Context<'_>) {
         let enter_levels = self.state.depth();
         self.state
             .enter(&mut self.shared_storage, context, enter_levels);
+
+        if let Some(event) = &M::STARTUP_EVENT {
+            self.handle_with_context(event, context);
+        }
     }
 
     /// Handle the given event.
     pub fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>) {
+        self.handle_with_origin(event, "", context);
+    }
+
+    /// Execute the exit actions of the current state and all of its superstates.
+    pub fn shutdown_with_context(&mut self, context: &mut M::Context<'_>) {
+        let exit_levels = self.state.depth();
+        self.state
+            .exit(&mut self.shared_storage, context, exit_levels);
+    }
+
+    /// Handle the given event, passing its origin to the shared storage before it is
+    /// dispatched.
+    pub fn handle_with_origin(
+        &mut self,
+        event: &M::Event<'_>,
+        origin: &'static str,
+        context: &mut M::Context<'_>,
+    ) {
+        M::ON_HANDLE(&mut self.shared_storage);
+        M::ON_ORIGIN(&mut self.shared_storage, origin);
         let response = self.state.handle(&mut self.shared_storage, event, context);
         match response {
             Response::Super => {}
             Response::Handled => {}
             Response::Transition(state) => self.transition(state, context),
+            #[cfg(feature = "state_stack")]
+            Response::Push(state) => {
+                if let Ok(previous) = self.swap_transition(state, context) {
+                    self.stack.push(previous);
+                }
+            }
+            #[cfg(feature = "state_stack")]
+            Response::Pop => {
+                if let Some(previous) = self.stack.pop() {
+                    if let Err(previous) = self.swap_transition(previous, context) {
+                        self.stack.push(previous);
+                    }
+                }
+            }
+            // Without a state stack there is nothing to remember or return to.
+            #[cfg(not(feature = "state_stack"))]
+            Response::Push(state) => self.transition(state, context),
+            #[cfg(not(feature = "state_stack"))]
+            Response::Pop => {}
+        }
+    }
+
+    /// Handle the given event on a copy of the shared storage and the state, and report the
+    /// transition that would be taken without executing any entry or exit actions.
+    pub fn dry_run_with_context(
+        &self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> DryRun<M::State>
+    where
+        M: Clone,
+        M::State: Clone,
+    {
+        let mut shared_storage = self.shared_storage.clone();
+        let mut state = self.state.clone();
+        let mut target = match state.handle(&mut shared_storage, event, context) {
+            Response::Super | Response::Handled => return DryRun::Handled,
+            Response::Transition(target) => target,
+            Response::Push(target) => target,
+            #[cfg(feature = "state_stack")]
+            Response::Pop => match self.stack.last() {
+                Some(previous) => previous.clone(),
+                None => return DryRun::Handled,
+            },
+            #[cfg(not(feature = "state_stack"))]
+            Response::Pop => return DryRun::Handled,
+        };
+        let (exit_levels, enter_levels) = state.transition_path(&mut target);
+        DryRun::Transition {
+            target,
+            exit_levels,
+            enter_levels,
         }
     }
 
     /// Transition from the current state to the given target state.
-    pub fn transition(&mut self, mut target: M::State, context: &mut M::Context<'_>) {
+    pub fn transition(&mut self, target: M::State, context: &mut M::Context<'_>) {
+        let _ = self.swap_transition(target, context);
+    }
+
+    /// Transition from the current state to the given target state and return the previous
+    /// state. If one of the entry actions fails the transition is rolled back, and the target
+    /// state is returned as the error instead.
+    fn swap_transition(
+        &mut self,
+        mut target: M::State,
+        context: &mut M::Context<'_>,
+    ) -> Result<M::State, M::State> {
         // Get the transition path we need to perform from one state to the next.
         let (exit_levels, enter_levels) = self.state.transition_path(&mut target);
 
@@ -44,14 +284,27 @@ where
         self.state
             .exit(&mut self.shared_storage, context, exit_levels);
 
+        // Keep the storage of the superstates that are not exited.
+        <M::State as blocking::State<M>>::carry_over_storage(&mut self.state, &mut target);
+
         // Update the state.
         core::mem::swap(&mut self.state, &mut target);
 
-        // Perform the entry actions from the common ancestor state into the new state.
-        self.state
-            .enter(&mut self.shared_storage, context, enter_levels);
+        // Perform the entry actions from the common ancestor state into the new state. If one
+        // of them fails, roll back the transition by entering the previous state again.
+        if !self
+            .state
+            .enter(&mut self.shared_storage, context, enter_levels)
+        {
+            core::mem::swap(&mut self.state, &mut target);
+            <M::State as blocking::State<M>>::carry_over_storage(&mut target, &mut self.state);
+            self.state
+                .enter(&mut self.shared_storage, context, exit_levels);
+            return Err(target);
+        }
 
         M::ON_TRANSITION(&mut self.shared_storage, &target, &self.state);
+        Ok(target)
     }
 }
 
@@ -69,6 +322,10 @@ where
         self.state
             .enter(&mut self.shared_storage, context, enter_levels)
             .await;
+
+        if let Some(event) = &M::STARTUP_EVENT {
+            self.async_handle_with_context(event, context).await;
+        }
     }
 
     pub async fn async_handle_with_context(
@@ -76,6 +333,19 @@ where
         event: &M::Event<'_>,
         context: &mut M::Context<'_>,
     ) {
+        self.async_handle_with_origin(event, "", context).await;
+    }
+
+    pub async fn async_handle_with_origin(
+        &mut self,
+        event: &M::Event<'_>,
+        origin: &'static str,
+        context: &mut M::Context<'_>,
+    ) {
+        self.async_finish_transition(context).await;
+
+        M::ON_HANDLE(&mut self.shared_storage);
+        M::ON_ORIGIN(&mut self.shared_storage, origin);
         let response = self
             .state
             .handle(&mut self.shared_storage, event, context)
@@ -83,29 +353,198 @@ where
         match response {
             Response::Super => {}
             Response::Handled => {}
-            Response::Transition(state) => self.async_transition(state, context).await,
+            Response::Transition(state) => {
+                self.async_start_transition(state, TransitionKind::Transition, context)
+                    .await
+            }
+            #[cfg(feature = "state_stack")]
+            Response::Push(state) => {
+                self.async_start_transition(state, TransitionKind::Push, context)
+                    .await
+            }
+            #[cfg(feature = "state_stack")]
+            Response::Pop => {
+                if let Some(previous) = self.stack.pop() {
+                    self.async_start_transition(previous, TransitionKind::Pop, context)
+                        .await;
+                }
+            }
+            // Without a state stack there is nothing to remember or return to.
+            #[cfg(not(feature = "state_stack"))]
+            Response::Push(state) => {
+                self.async_start_transition(state, TransitionKind::Transition, context)
+                    .await
+            }
+            #[cfg(not(feature = "state_stack"))]
+            Response::Pop => {}
         }
     }
 
-    /// Transition from the current state to the given target state.
-    pub async fn async_transition(&mut self, mut target: M::State, context: &mut M::Context<'_>) {
-        // Get the transition path we need to perform from one state to the next.
-        let (exit_levels, enter_levels) = self.state.transition_path(&mut target);
+    /// Execute the exit actions of the current state and all of its superstates.
+    pub async fn async_shutdown_with_context(&mut self, context: &mut M::Context<'_>) {
+        self.async_finish_transition(context).await;
 
-        // Perform the exit from the previous state towards the common ancestor state.
+        let exit_levels = self.state.depth();
         self.state
             .exit(&mut self.shared_storage, context, exit_levels)
             .await;
+    }
 
-        // Update the state.
-        core::mem::swap(&mut self.state, &mut target);
-
-        // Perform the entry actions from the common ancestor state into the new state.
-        self.state
-            .enter(&mut self.shared_storage, context, enter_levels)
+    /// Transition from the current state to the given target state.
+    ///
+    /// The state is only updated after all the exit and entry actions have completed. If the
+    /// future is dropped at one of the await points, the state machine is left in the previous
+    /// state and the rest of the transition is performed before it handles anything else.
+    /// None of the actions that already completed are executed a second time.
+    pub async fn async_transition(&mut self, target: M::State, context: &mut M::Context<'_>) {
+        self.async_finish_transition(context).await;
+        self.async_start_transition(target, TransitionKind::Transition, context)
             .await;
+    }
 
-        M::ON_TRANSITION(&mut self.shared_storage, &target, &self.state);
+    /// Plan the transition from the current state to the given target state and perform it.
+    /// There must be no other transition pending.
+    async fn async_start_transition(
+        &mut self,
+        mut target: M::State,
+        kind: TransitionKind,
+        context: &mut M::Context<'_>,
+    ) {
+        // Get the transition path we need to perform from one state to the next.
+        let (exit_levels, enter_levels) = self.state.transition_path(&mut target);
+
+        self.pending = Some(PendingTransition {
+            target,
+            exit_levels,
+            enter_levels,
+            step: Step::Exit(0),
+            kind,
+        });
+        self.async_finish_transition(context).await;
+    }
+
+    /// Perform the remaining steps of the pending transition, if there is one. If one of the
+    /// entry actions fails the transition is rolled back by entering the previous state again.
+    async fn async_finish_transition(&mut self, context: &mut M::Context<'_>) {
+        let (kind, result) = loop {
+            let pending = match &mut self.pending {
+                Some(pending) => pending,
+                None => return,
+            };
+            match pending.step {
+                // Perform the exit from the previous state towards the common ancestor state.
+                Step::Exit(exited) if exited < pending.exit_levels => {
+                    self.state
+                        .call_exit_action_at(&mut self.shared_storage, context, exited)
+                        .await;
+                    pending.step = Step::Exit(exited + 1);
+                }
+                Step::Exit(_) => {
+                    // Keep the storage of the superstates that are not exited.
+                    <M::State as awaitable::State<M>>::carry_over_storage(
+                        &mut self.state,
+                        &mut pending.target,
+                    );
+                    pending.step = Step::Enter(0);
+                }
+                // Perform the entry actions from the common ancestor state into the new state.
+                Step::Enter(entered) if entered < pending.enter_levels => {
+                    let level = pending.enter_levels - 1 - entered;
+                    pending.step = match pending
+                        .target
+                        .try_call_entry_action_at(&mut self.shared_storage, context, level)
+                        .await
+                    {
+                        true => Step::Enter(entered + 1),
+                        false => Step::Compensate {
+                            entered,
+                            exited: 0,
+                            restoring: false,
+                        },
+                    };
+                }
+                Step::Enter(_) => {
+                    let PendingTransition {
+                        mut target, kind, ..
+                    } = self.pending.take().expect("transition is pending");
+
+                    // Update the state.
+                    core::mem::swap(&mut self.state, &mut target);
+
+                    M::ON_TRANSITION(&mut self.shared_storage, &target, &self.state);
+                    break (kind, Ok(target));
+                }
+                // Exit the levels that were entered before the entry action that failed.
+                Step::Compensate {
+                    entered,
+                    exited,
+                    restoring,
+                } if exited < entered => {
+                    match restoring {
+                        true => {
+                            let level = pending.exit_levels - entered + exited;
+                            self.state
+                                .call_exit_action_at(&mut self.shared_storage, context, level)
+                                .await
+                        }
+                        false => {
+                            let level = pending.enter_levels - entered + exited;
+                            pending
+                                .target
+                                .call_exit_action_at(&mut self.shared_storage, context, level)
+                                .await
+                        }
+                    }
+                    pending.step = Step::Compensate {
+                        entered,
+                        exited: exited + 1,
+                        restoring,
+                    };
+                }
+                Step::Compensate {
+                    restoring: false, ..
+                } => {
+                    <M::State as awaitable::State<M>>::carry_over_storage(
+                        &mut pending.target,
+                        &mut self.state,
+                    );
+                    pending.step = Step::Restore(0);
+                }
+                // Roll back the transition by entering the previous state again.
+                Step::Restore(entered) if entered < pending.exit_levels => {
+                    let level = pending.exit_levels - 1 - entered;
+                    pending.step = match self
+                        .state
+                        .try_call_entry_action_at(&mut self.shared_storage, context, level)
+                        .await
+                    {
+                        true => Step::Restore(entered + 1),
+                        false => Step::Compensate {
+                            entered,
+                            exited: 0,
+                            restoring: true,
+                        },
+                    };
+                }
+                Step::Compensate {
+                    restoring: true, ..
+                }
+                | Step::Restore(_) => {
+                    let PendingTransition { target, kind, .. } =
+                        self.pending.take().expect("transition is pending");
+                    break (kind, Err(target));
+                }
+            }
+        };
+
+        #[cfg(feature = "state_stack")]
+        match (kind, result) {
+            (TransitionKind::Push, Ok(previous)) => self.stack.push(previous),
+            (TransitionKind::Pop, Err(previous)) => self.stack.push(previous),
+            _ => {}
+        };
+        #[cfg(not(feature = "state_stack"))]
+        let _ = (kind, result);
     }
 }
 
@@ -118,6 +557,10 @@ where
         Self {
             shared_storage: self.shared_storage.clone(),
             state: self.state.clone(),
+            #[cfg(feature = "state_stack")]
+            stack: self.stack.clone(),
+            #[cfg(feature = "async")]
+            pending: self.pending.clone(),
         }
     }
 }
@@ -128,6 +571,14 @@ where
     M::State: PartialEq,
 {
     fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "state_stack")]
+        if self.stack.as_slice() != other.stack.as_slice() {
+            return false;
+        }
+        #[cfg(feature = "async")]
+        if self.pending != other.pending {
+            return false;
+        }
         self.shared_storage == other.shared_storage && self.state == other.state
     }
 }
@@ -139,6 +590,17 @@ where
 {
 }
 
+/// Fields of a serialized state machine.
+#[cfg(feature = "serde")]
+const FIELDS: &[&str] = &[
+    "shared_storage",
+    "state",
+    #[cfg(feature = "state_stack")]
+    "stack",
+    #[cfg(feature = "async")]
+    "pending",
+];
+
 #[cfg(feature = "serde")]
 impl<M> serde::Serialize for Inner<M>
 where
@@ -151,9 +613,18 @@ where
     {
         use serde::ser::SerializeStruct;
 
-        let mut serializer = serializer.serialize_struct("StateMachine", 2)?;
+        let mut serializer = serializer.serialize_struct("StateMachine", FIELDS.len())?;
         serializer.serialize_field("shared_storage", &self.shared_storage)?;
         serializer.serialize_field("state", &self.state)?;
+        #[cfg(feature = "state_stack")]
+        serializer.serialize_field("stack", self.stack.as_slice())?;
+        // A transition of which the future was dropped is completed after deserializing,
+        // just like it would have been by the original state machine.
+        #[cfg(feature = "async")]
+        serializer.serialize_field(
+            "pending",
+            &self.pending.as_ref().map(PendingTransition::serialized),
+        )?;
         serializer.end()
     }
 }
@@ -175,6 +646,10 @@ where
         enum Field {
             SharedStorage,
             State,
+            #[cfg(feature = "state_stack")]
+            Stack,
+            #[cfg(feature = "async")]
+            Pending,
         }
 
         impl<'de> serde::Deserialize<'de> for Field {
@@ -188,7 +663,7 @@ where
                     type Value = Field;
 
                     fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-                        formatter.write_str("`shared_storage` or `state`")
+                        formatter.write_str("a state machine field")
                     }
 
                     fn visit_str<E>(self, value: &str) -> Result<Field, E>
@@ -198,6 +673,10 @@ where
                         match value {
                             "shared_storage" => Ok(Field::SharedStorage),
                             "state" => Ok(Field::State),
+                            #[cfg(feature = "state_stack")]
+                            "stack" => Ok(Field::Stack),
+                            #[cfg(feature = "async")]
+                            "pending" => Ok(Field::Pending),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -230,10 +709,18 @@ where
                 let state = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
-                let inner = Inner {
-                    shared_storage,
-                    state,
-                };
+                #[allow(unused_mut)]
+                let mut inner = Inner::new(shared_storage, state);
+                #[cfg(feature = "state_stack")]
+                if let Some(stack) = seq.next_element::<alloc::vec::Vec<M::State>>()? {
+                    inner.stack.extend(stack);
+                }
+                #[cfg(feature = "async")]
+                if let Some(Some(pending)) =
+                    seq.next_element::<Option<SerializedTransition<M::State>>>()?
+                {
+                    inner.pending = Some(PendingTransition::deserialized::<A::Error>(pending)?);
+                }
                 Ok(inner)
             }
 
@@ -243,6 +730,10 @@ where
             {
                 let mut shared_storage = None;
                 let mut state = None;
+                #[cfg(feature = "state_stack")]
+                let mut stack: Option<alloc::vec::Vec<M::State>> = None;
+                #[cfg(feature = "async")]
+                let mut pending: Option<Option<SerializedTransition<M::State>>> = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::SharedStorage => {
@@ -257,20 +748,40 @@ where
                             }
                             state = Some(map.next_value()?);
                         }
+                        #[cfg(feature = "state_stack")]
+                        Field::Stack => {
+                            if stack.is_some() {
+                                return Err(serde::de::Error::duplicate_field("stack"));
+                            }
+                            stack = Some(map.next_value()?);
+                        }
+                        #[cfg(feature = "async")]
+                        Field::Pending => {
+                            if pending.is_some() {
+                                return Err(serde::de::Error::duplicate_field("pending"));
+                            }
+                            pending = Some(map.next_value()?);
+                        }
                     }
                 }
                 let shared_storage = shared_storage
                     .ok_or_else(|| serde::de::Error::missing_field("shared_storage"))?;
                 let state = state.ok_or_else(|| serde::de::Error::missing_field("state"))?;
-                let inner = Inner {
-                    shared_storage,
-                    state,
-                };
+                #[allow(unused_mut)]
+                let mut inner = Inner::new(shared_storage, state);
+                // A missing stack or pending transition is accepted, so state machines that
+                // were serialized without the `state_stack` or `async` feature can still be
+                // deserialized.
+                #[cfg(feature = "state_stack")]
+                inner.stack.extend(stack.unwrap_or_default());
+                #[cfg(feature = "async")]
+                if let Some(Some(pending)) = pending {
+                    inner.pending = Some(PendingTransition::deserialized::<V::Error>(pending)?);
+                }
                 Ok(inner)
             }
         }
 
-        const FIELDS: &[&str] = &["shared_storage", "state"];
         deserializer.deserialize_struct(
             "StateMachine",
             FIELDS,
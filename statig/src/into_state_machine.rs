@@ -1,3 +1,5 @@
+use core::fmt::Debug;
+
 use crate::StateOrSuperstate;
 
 /// Trait for transorming a type into a state machine.
@@ -22,6 +24,9 @@ where
     /// Initial state of the state machine.
     const INITIAL: Self::State;
 
+    /// Optional event that is handled right after the state machine is initialized.
+    const STARTUP_EVENT: Option<Self::Event<'static>> = None;
+
     /// Method that is called *before* an event is dispatched to a state or
     /// superstate handler.
     const ON_DISPATCH: fn(&mut Self, StateOrSuperstate<'_, '_, Self>, &Self::Event<'_>) =
@@ -29,4 +34,46 @@ where
 
     /// Method that is called *after* every transition.
     const ON_TRANSITION: fn(&mut Self, &Self::State, &Self::State) = |_, _, _| {};
+
+    /// Method that is called once for every event that is handled, before it is dispatched
+    /// to the state and superstate handlers.
+    const ON_HANDLE: fn(&mut Self) = |_| {};
+
+    /// Method that is called with the origin of an event before it is dispatched. Events
+    /// that are handled without an origin have a blank origin.
+    const ON_ORIGIN: fn(&mut Self, &'static str) = |_, _| {};
+
+    /// Whether an awaitable state machine should panic in debug builds when it is dropped
+    /// without `shutdown` having been called while it is not in a terminal state.
+    const DROP_GUARD: bool = false;
+
+    /// Method that is called to check whether a state is terminal. A state machine in a
+    /// terminal state can be dropped without calling `shutdown`.
+    const IS_TERMINAL: fn(&Self::State) -> bool = |_| false;
+
+    /// Method that is called to get the name of a state. Used by the `Debug` implementation
+    /// of the state machine.
+    const STATE_NAME: fn(&Self::State) -> &'static str = |_| "";
+
+    /// Method that is called to get the priority of a state. Used to decide the order in
+    /// which the regions of a parallel state handle events.
+    const STATE_PRIORITY: fn(&Self::State) -> i32 = |_| 0;
+
+    /// Method that is called to get the shared storage as `Debug`, if it implements it. Used
+    /// by the `Debug` implementation of the state machine.
+    const DEBUG_STORAGE: fn(&Self) -> Option<&dyn Debug> = |_| None;
+
+    /// Allocator that the state stack is allocated with. By default this is the global
+    /// allocator. Without the `state_stack` feature there is no stack and it is unused.
+    #[cfg(feature = "allocator-api2")]
+    const STACK_ALLOCATOR: StackAllocator = &allocator_api2::alloc::Global;
 }
+
+/// Reference to the allocator that the state stack of a state machine is allocated with.
+#[cfg(feature = "allocator-api2")]
+pub type StackAllocator = &'static (dyn allocator_api2::alloc::Allocator + Sync);
+
+/// Marker trait for types whose state machines give mutable access to the shared storage
+/// by implementing [`DerefMut`](core::ops::DerefMut). Implemented by the `state_machine`
+/// macro when `deref_storage` is set.
+pub trait DerefStorageMut: IntoStateMachine {}
@@ -124,6 +124,17 @@
 //! - `Transition`: Transition to another state.
 //! - `Super`: Defer the event to the next superstate.
 //!
+//! With `#[state_machine(transition_to)]`, handlers that take `&self` or
+//! `&mut self` can also create the transition with
+//! `self.transition_to(State::led_off())`. This only builds the `Transition`
+//! response, the handler still has to return it and the transition is performed
+//! by the state machine afterwards.
+//!
+//! With `#[state_machine(fallible = "field_name")]`, handlers can also return a
+//! `Result` of a `Response`, so a failing handler can use `?`. The state machine
+//! stays in its current state when a handler returns an `Err`, and
+//! `try_handle` returns the error to the caller.
+//!
 //! ### Superstates
 //!
 //! Superstates allow you to create a hierarchy of states. States can defer an event
@@ -220,6 +231,36 @@
 //! # }
 //! ```
 //!
+//! An entry action that returns a `Result` is fallible. When it returns an
+//! `Err`, the superstates that were already entered during the transition
+//! are exited again in reverse order and the state machine re-enters the
+//! state it was transitioning from, as if the transition never happened.
+//!
+//! ### State stack
+//!
+//! With the `state_stack` feature, a handler can return `Push(state)` to
+//! transition to a state while remembering the current one, for example to
+//! open a modal dialog. Returning `Pop` later transitions back to the state
+//! that was pushed most recently, running the exit and entry actions just
+//! like a regular transition. When no state was pushed, `Pop` is treated as
+//! `Handled`. The pushed states are part of the serialized state machine and
+//! are taken into account when comparing state machines.
+//!
+//! `Push` and `Pop` are always available, so a crate that returns them keeps
+//! compiling when the feature is turned off elsewhere in the dependency graph.
+//! Without `state_stack` no states are remembered: `Push(state)` is a regular
+//! transition and `Pop` is treated as `Handled`.
+//!
+//! The pushed states are kept in a `Vec`, which is the only collection a
+//! state machine allocates. It uses the global allocator, unless the
+//! `allocator-api2` feature is enabled. The allocator of the stack can then be
+//! set with `#[state_machine(stack_allocator = "ALLOCATOR")]`, where
+//! `ALLOCATOR` is a `static` that implements the `Allocator` trait of the
+//! [`allocator-api2`](https://docs.rs/allocator-api2) crate, which works on
+//! stable Rust. The `state_stack` feature only requires `alloc`, so it can be
+//! used in `no_std` state machines that provide a global allocator, or the
+//! allocator of the stack. State machines without it never allocate.
+//!
 //! ### Shared storage
 //!
 //! If the type on which your state machine is implemented has any fields, you
@@ -287,6 +328,44 @@
 //! # }
 //! ```
 //!
+//! The shared storage can also be generic, for example over a trait that
+//! abstracts the hardware, so tests can drive the state machine with a mock
+//! implementation. Associated constants of the trait can be used in the
+//! handlers and in the `initial` state.
+//!
+//! ```
+//! # use statig::prelude::*;
+//! pub trait Led {
+//!     const BLINKS: usize;
+//!
+//!     fn set(&mut self, on: bool);
+//! }
+//!
+//! pub struct Blinky<L: Led> {
+//!     led: L,
+//! }
+//!
+//! # pub struct Event;
+//! #
+//! #[state_machine(initial = "State::blinking(L::BLINKS)")]
+//! impl<L: Led> Blinky<L> {
+//!     #[state]
+//!     fn blinking(&mut self, remaining: &mut usize, event: &Event) -> Response<State> {
+//!         self.led.set(*remaining % 2 == 0);
+//!         *remaining -= 1;
+//!         match remaining {
+//!             0 => Transition(State::done()),
+//!             _ => Handled,
+//!         }
+//!     }
+//!
+//!     #[state]
+//!     fn done(event: &Event) -> Response<State> {
+//!         Handled
+//!     }
+//! }
+//! ```
+//!
 //! ### State-local storage
 //!
 //! Sometimes you have data that only exists in a certain state. Instead of
@@ -375,6 +454,13 @@
 //! state_machine.handle_with_context(&Event::TimerElapsed, &mut context);
 //! ```
 //!
+//! The context can also borrow data that only lives for a single call (e.g. `context: &mut Context<'call>`).
+//! Handlers can declare such lifetimes themselves, as long as they are not used in the state-local storage.
+//! Since the context is always passed in as `&mut`, it is also the place for mutable resources that are
+//! owned outside of the state machine and can differ per call, such as a connection pool. Multiple resources
+//! are passed in together by borrowing them in the fields of a context struct (e.g.
+//! `struct Resources<'call> { pool: &'call mut Pool, log: &'call mut Log }`).
+//!
 //! ### Introspection
 //!
 //! For logging purposes you can define two callbacks that will be called at specific
@@ -415,6 +501,48 @@
 //! }
 //! ```
 //!
+//! To react to one specific transition instead, declare a `#[transition]` attribute
+//! below `#[state_machine]`. The method passed to `call` is only called when the state
+//! machine transitions from the `from` state to the `to` state, after `on_transition`.
+//! A hook for a transition that none of the handlers of the `from` state and its
+//! superstates perform results in a warning.
+//!
+//! ```
+//! # use statig::prelude::*;
+//! #
+//! # struct Client;
+//! #
+//! # enum Event { Connect }
+//! #
+//! #[state_machine(initial = "State::connecting()")]
+//! #[transition(from = "connecting", to = "connected", call = "on_connected")]
+//! impl Client {
+//!     #[state]
+//!     fn connecting(event: &Event) -> Response<State> {
+//!         Transition(State::connected())
+//!     }
+//!
+//!     #[state]
+//!     fn connected(event: &Event) -> Response<State> {
+//!         Handled
+//!     }
+//! }
+//!
+//! impl Client {
+//!     fn on_connected(&mut self) {
+//!         println!("connected");
+//!     }
+//! }
+//! ```
+//!
+//! When deriving `Debug` is too heavy (e.g. on embedded targets), the generated
+//! `name()` method on the state and superstate enums returns the name of the
+//! variant, such as `"On"`, without formatting any of its fields. It is also what
+//! the `Debug` implementations of the state machine types print for the current state,
+//! so `format!("{:?}", state_machine)` works even when the state does not implement
+//! `Debug`. The shared storage is only included when it implements `Debug`.
+//! Minimal state machines leave the names out, so their state is printed blank.
+//!
 //! ### Async
 //!
 //! All handlers and actions can be made async. The `#[state_machine]` macro will
@@ -647,6 +775,16 @@
 //! looks like take a look at the test [with](./statig/tests/transition_macro.rs)
 //! and [without](./statig/tests/transition.rs) macros.
 //!
+//! ### Can a handler dispatch an event to its own state machine?
+//!
+//! No, and this is checked for you. `handle()` takes the state machine by
+//! `&mut`, so it can not be called again while a handler is running. When the
+//! state machine is shared behind a `RefCell` (e.g. so a callback can reach
+//! it), the reentrant `borrow_mut()` panics and `try_borrow_mut()` returns an
+//! error, which means there is no need for a separate guard. Handlers are free
+//! to dispatch events to other state machines, including other instances of
+//! the same type.
+//!
 //! ### What advantage does this have over using the typestate pattern?
 //!
 //! I would say they serve a different purpose. The [typestate pattern](http://cliffle.com/blog/rust-typestate/)
@@ -674,10 +812,25 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![allow(incomplete_features)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod clock;
+mod command;
+mod dry_run;
+#[cfg(feature = "instrumentation")]
+mod entry_exit_counts;
+mod event_name;
+#[cfg(feature = "instrumentation")]
+mod fired_actions;
+mod handler_error;
 mod inner;
 mod into_state_machine;
+mod observer;
+mod previous_state_kind;
 mod response;
 mod state_or_superstate;
+mod timeout;
 
 /// Macro for deriving the state and superstate enum.
 ///
@@ -706,14 +859,45 @@ mod state_or_superstate;
 ///   
 ///   <br/>
 ///
+/// - `#[state_machine(state(kind = "CustomStateKindName"))]`
+///
+///   Set the name of the fieldless enum with the kinds of the states, for
+///   example when the default name is already taken in the module.
+///
+///   _Default_: the name of the state enum followed by `Kind`
+///
+///   <br/>
+///
+/// - `#[state_machine(superstate(kind = "CustomSuperstateKindName"))]`
+///
+///   Set the name of the fieldless enum with the kinds of the superstates.
+///
+///   _Default_: the name of the superstate enum followed by `Kind`
+///
+///   <br/>
+///
 /// - `#[state_machine(state(derive(SomeTrait, AnotherTrait)))]`
 ///
-///   Apply the derive macro with the passed traits to the state enum.
+///   Apply the derive macro with the passed traits to the state enum. No
+///   traits are derived by default, so states with fields that are not `Copy`
+///   (such as a `String`) only need to leave out `Copy`. Deriving `Copy` when
+///   a state-local field is known to never be `Copy` is rejected with an
+///   error that names the field.
 ///
 ///   _Default_: `()`
 ///
 ///   <br/>
 ///
+/// - `#[state_machine(state(repr = "C"))]`
+///
+///   Apply the given representation to the state enum (e.g. `"C"` or
+///   `"C, u8"`), so its layout can be relied on across an FFI boundary. The
+///   variants are sorted by name, so their discriminants match the indices of
+///   the state kinds. The types of the state-local fields must be FFI-safe
+///   themselves for the layout to be meaningful.
+///
+///   <br/>
+///
 /// - `#[state_machine(superstate(derive(SomeTrait, AnotherTrait)))]`
 ///
 ///   Apply the derive macro with the passed traits to the superstate enum.
@@ -721,6 +905,416 @@ mod state_or_superstate;
 ///   _Default_: `()`
 ///
 ///   <br/>
+///
+/// - `#[state_machine(visibility = "pub(crate)")]`
+///
+///   Set the visibility of the generated types and of their methods,
+///   including the constructors of the states (e.g. `State::on()`), so
+///   states can be created outside of the module of the state machine.
+///
+///   _Default_: `pub`
+///
+///   <br/>
+///
+/// - `#[state_machine(response_identifier = "Response")]`
+///
+///   Generate a type alias with the given name for `Response<State>`, at the
+///   same visibility as the state enum, so other modules and macros can name
+///   and construct the responses of the state machine.
+///
+///   <br/>
+///
+/// - `#[state_machine(facade = "Driver")]`
+///
+///   Generate a trait with the given name and an `on_event` method that takes
+///   the event by value, implemented by the state machine types by
+///   delegating to `handle`, so consumers can depend on the trait instead of
+///   on the concrete type. When the state machine has a context, it is passed
+///   as the second argument. In awaitable mode `on_event` returns a boxed
+///   future instead.
+///
+///   <br/>
+///
+/// - `#[state_machine(event_ref = false)]`
+///
+///   Pass the event to the handlers by value (e.g. `event: Event`) instead of
+///   by reference. The state machine is still given a reference to the event
+///   when handling it, which is copied for every handler it is dispatched to,
+///   including the superstates it bubbles up to. The event type must therefore
+///   implement `Copy`.
+///
+///   _Default_: `true`
+///
+///   <br/>
+///
+/// - `#[state_machine(actions(reset_led = "self.led = false"))]`
+///
+///   Declare actions inline. Each action is added as a method with a
+///   `&mut self` receiver to the impl block, so it can be used as the entry
+///   or exit action of multiple states. The name of an inline action can not
+///   be the same as one of the methods in the impl block.
+///
+///   <br/>
+///
+/// - `#[state_machine(mode = "awaitable")]`
+///
+///   Set the mode of the state machine to `blocking` or `awaitable`, instead
+///   of detecting it from the handlers. Setting the mode to `blocking` while
+///   one of the handlers or actions is async results in an error.
+///
+///   _Default_: `awaitable` if any handler or action is async, otherwise `blocking`
+///
+///   <br/>
+///
+/// - `#[state_machine(generics_passthrough)]`
+///
+///   Make all generics of the shared storage part of the generics of the
+///   state enum, not only the ones that are used by the state-local storage.
+///   This keeps the state type (e.g. `State<T>`) predictable for generic
+///   shared storages. Generics that are not used by any state are held by a
+///   hidden `__generics` field of the first state, sorted by name.
+///
+///   <br/>
+///
+/// - `#[state_machine(merge_hints)]`
+///
+///   Emit a warning for every pair of states whose handlers take the same
+///   inputs and have structurally identical bodies, as a hint that they could
+///   be merged or share a handler. The comparison is done on the syntax of the
+///   handlers, so it can also report states that only look the same, which is
+///   why it is a warning and never an error.
+///
+///   <br/>
+///
+/// - `#[state_machine(cfg = "feature = \"fsm\"")]`
+///
+///   Gate every item the macro generates, including the impl block itself,
+///   on the given configuration predicate, for state machines that are
+///   optional. When the predicate is false, the state machine is left out
+///   entirely and only the type the impl block is on remains.
+///
+///   <br/>
+///
+/// - `#[state_machine(minimal)]`
+///
+///   Leave all optional introspection (such as the `name()` of states and
+///   superstates, `state_path()`, `ancestors()`, `handled_events()`, the
+///   `StateKind` and `SuperstateKind` enums with their conversions, `kind()`
+///   and `MAX_DEPTH`) out of the generated code, keeping only what is needed to
+///   dispatch events. The shared storage is also left out of the `Debug`
+///   output of the state machine. Calling one of these methods on a minimal
+///   state machine results in a compile error. This can not be combined with
+///   `json`, `validate`, `diff`, `serialize_path`, `state_index`,
+///   `transition_to`, `replay`, `apply_event_log`, `previous_state_kind`,
+///   `entry_exit_counts` or `timeout`, which all rely on the left out code.
+///
+///   <br/>
+///
+/// - `#[state_machine(startup_event = "Event::Start")]`
+///
+///   Handle the given event right after the state machine is initialized and
+///   has entered its initial state. If the initial state does not handle the
+///   event it is passed on to its superstates like any other event.
+///
+///   <br/>
+///
+/// - `#[state_machine(stack_allocator = "ALLOCATOR")]`
+///
+///   Allocate the state stack with the given `static` allocator instead of
+///   the global allocator. The allocator must implement the `Allocator` trait
+///   of the re-exported `statig::allocator_api2` crate. This requires the
+///   `allocator-api2` feature and only has an effect together with the
+///   `state_stack` feature.
+///
+///   <br/>
+///
+/// - `#[state_machine(previous_state_kind = "field_name")]`
+///
+///   Record the kind of state every event is dispatched to in the given field
+///   of the shared storage, which must be a
+///   [`PreviousStateKind<StateKind>`](crate::PreviousStateKind). Handlers and
+///   external code can then get the kind of state the state machine was in
+///   on the previous dispatch. This is `None` during the first dispatch.
+///
+///   <br/>
+///
+/// - `#[state_machine(origin = "field_name")]`
+///
+///   Store the origin of every event in the given field of the shared
+///   storage, which must be a `&'static str`, before the event is
+///   dispatched. Events handled with `handle_with_origin` set it to the
+///   given origin, all other events set it to an empty string. The field
+///   can then be read in the `on_dispatch` and `on_transition` callbacks.
+///
+///   <br/>
+///
+/// - `#[state_machine(sequence = "field_name")]`
+///
+///   Increment the given `u64` field of the shared storage once for every
+///   event that is handled (including the startup event), before the event
+///   is dispatched. Handlers can read the field to get the sequence number of
+///   the event they are handling, for example to deduplicate work. The
+///   counter wraps around after `u64::MAX`.
+///
+///   <br/>
+///
+/// - `#[state_machine(observer = "field_name")]`
+///
+///   Call the methods of the [`Observer`](crate::Observer) stored in the
+///   given field of the shared storage at every lifecycle event. The
+///   observer is called before the `on_dispatch` and `on_transition`
+///   callbacks, if these are also set.
+///
+///   <br/>
+///
+/// - `#[state_machine(deref_storage)]`
+///
+///   Next to `Deref`, also implement `DerefMut` for the state machine so the
+///   shared storage can be mutated directly through it. Methods of the state
+///   machine itself (such as `handle` and `state`) take precedence over
+///   methods of the shared storage with the same name.
+///
+///   <br/>
+///
+/// - `#[state_machine(shared_storage = "Outer.inner")]`
+///
+///   Use the `inner` field of `Outer` as the storage the handlers operate on,
+///   when the type the impl block is on is embedded in a larger struct. The
+///   state machine is then implemented on `Outer`, and handlers that take
+///   `self` or borrow fields of the shared storage are given the `inner`
+///   field instead. Options that name fields of the shared storage, such as
+///   `observer`, refer to fields of `Outer`.
+///
+///   <br/>
+///
+/// - `#[state_machine(lifetime = "'sm")]`
+///
+///   Always add the lifetime `'sm` of the shared storage to the generics of
+///   the state enum, so states can hold on to data borrowed for the whole
+///   lifetime of the state machine (e.g. `local_storage("rest: &'sm [u8]")`)
+///   without naming the lifetime in their handler inputs. The lifetime must
+///   be declared on the impl block. Superstates only hold references into the
+///   states, so they only take `'sm` when their fields refer to it. The
+///   superstate enum has a lifetime of its own named `'sub`, which is renamed
+///   (e.g. to `'sub0`) when the impl block already declares a `'sub`.
+///
+///   <br/>
+///
+/// - `#[state_machine(entry_exit_counts = "field_name")]`
+///
+///   Record every entry and exit of a state in a field of the shared storage
+///   of type `statig::EntryExitCounts<StateKind>`, which exposes them through
+///   `entry_count(kind)` and `exit_count(kind)`. A transition from a state to
+///   itself counts as both an exit and an entry. This requires the
+///   `instrumentation` feature.
+///
+///   <br/>
+///
+/// - `#[state_machine(fired_actions = "field_name")]`
+///
+///   Record the names of the entry and exit actions that fire in a field of
+///   the shared storage of type `statig::FiredActions`. The state machine can
+///   then handle events with `handle_recording`, which returns the
+///   [`Outcome`](crate::Outcome) of the event together with the actions that
+///   fired, in the order they fired. This requires the `instrumentation`
+///   feature.
+///
+///   <br/>
+///
+/// - `#[state_machine(fallible = "field_name")]`
+///
+///   Allow handlers to return `Result<Response<State>, E>`. The error type is
+///   inferred from the handlers, which must all agree on it, and the field of
+///   the shared storage must be of type `Option<E>`. A handler that returns an
+///   `Err` stores the error in the field and is considered to have handled the
+///   event, so the state machine does not transition. Events are then handled
+///   with `try_handle`, which returns the error. The field is cleared before
+///   every event, so when events are handled with `handle` instead, it only
+///   holds the error of the last event and is `None` when that event was
+///   handled without an error.
+///
+///   <br/>
+///
+/// - `#[state_machine(timeout = "field_name")]`
+///
+///   Track the entries and exits of the states in a field of the shared
+///   storage of type `statig::Timeout<StateKind, Instant>`, where `Instant`
+///   is any ordered time type. Handlers and entry actions arm the timeout for
+///   the current state with `arm(deadline)`, and it is disarmed when that
+///   state is exited, so re-entering a state starts without a timeout until
+///   it is armed again. External code checks for expiry with
+///   `expired(&now)` or `poll(&now, on_timeout)`. To keep the timeouts
+///   testable the current time can instead be read from a `statig::Clock`
+///   in the shared storage with `arm_after(&clock, duration)`,
+///   `expired_with(&clock)` and `poll_with(&clock, on_timeout)`. Under `std`
+///   the `statig::SystemClock` reads `std::time::Instant::now()`, while tests
+///   can supply a clock that advances deterministically.
+///
+///   <br/>
+///
+/// - `#[state_machine(box_futures)]`
+///
+///   Make the awaitable state machine usable behind a
+///   [`DynStateMachine`](crate::awaitable::DynStateMachine), whose `handle`
+///   returns a boxed future so different state machines that handle the same
+///   event type can be stored together (e.g. in a
+///   `Vec<Box<dyn DynStateMachine<Event>>>`). Every event handled through it
+///   allocates, which is why this is opt-in. This requires the `async`
+///   feature.
+///
+///   <br/>
+///
+/// - `#[state_machine(drop_guard)]`
+///
+///   In debug builds, panic when an awaitable state machine is dropped
+///   without `shutdown` having been called, unless it is in a state marked
+///   with `#[state(terminal)]`. This helps to catch state machines that are
+///   dropped while they still hold on to resources. It panics rather than
+///   printing a warning, because a warning from a destructor is easily missed
+///   in the output of a test run, while a panic fails the test that forgot to
+///   shut the state machine down. The check is left out of release builds.
+///
+///   <br/>
+///
+/// - `#[state_machine(tracing)]`
+///
+///   Wrap every call to a state or superstate handler in a `TRACE` level
+///   `tracing` span that is named after the state and records the event,
+///   which must therefore implement `Debug`. For async handlers the span is
+///   kept across await points. This requires the `tracing` feature.
+///
+///   <br/>
+///
+/// - `#[state_machine(validate)]`
+///
+///   Generate a `validate() -> Result<(), Vec<String>>` method on the shared
+///   storage type that checks the invariants of the state machine that can
+///   only be verified at runtime, such as the superstates of the initial
+///   state not forming a cycle. Requires the `alloc` feature.
+///
+///   <br/>
+///
+/// - `#[state_machine(json)]`
+///
+///   Generate a `MACHINE_JSON` constant on the shared storage type containing
+///   a JSON description of the states, superstates, transitions and actions
+///   of the state machine, for use by external tooling. Transitions are
+///   detected by looking for `Transition(State::target(..))` in the handlers.
+///   All entries are sorted by name. States with several entry or exit
+///   actions list them in the order they run in.
+///
+///   <br/>
+///
+/// - `#[state_machine(describe)]`
+///
+///   Generate a `describe()` function on the shared storage type returning a
+///   one line summary of the state machine, such as ``"Blinky: blocking state
+///   machine with 2 states and 1 superstate, starting in `led_on`"``. This
+///   requires the `alloc` feature.
+///
+///   <br/>
+///
+/// - `#[state_machine(export_graph)]`
+///
+///   Generate a `const fn graphviz()` on the shared storage type returning the
+///   state hierarchy in the DOT format of Graphviz. Every state is a node and
+///   every superstate a cluster around its children, which point to the node
+///   of the superstate with a dashed edge. States that never show up inside
+///   the expected cluster are easy to spot by rendering the graph with
+///   `dot -Tsvg`. All entries are sorted by name.
+///
+///   <br/>
+///
+/// - `#[state_machine(diff)]`
+///
+///   Generate a `diff(&self, other: &State)` method on the state enum that
+///   lists the names of the state-local fields that differ between two states
+///   of the same kind, together with the `Debug` representation of their
+///   value in `other`. Two states of different kinds result in a single
+///   `"variant"` entry. This requires the `alloc` feature and all state-local
+///   fields to implement `Debug`.
+///
+///   <br/>
+///
+/// - `#[state_machine(serialize_path)]`
+///
+///   Generate a `serialize_path()` method on the state enum that encodes the
+///   kind of the state followed by the kinds of its superstates as one byte
+///   each, and a `restore_path(bytes)` function that creates the state again.
+///   This is a compact way to persist the state of a state machine whose
+///   current state has no state-local storage. Restoring a state with
+///   state-local storage fails with its kind, and bytes that are not the path
+///   of any state fail with `None`. This requires the `alloc` feature.
+///
+///   <br/>
+///
+/// - `#[state_machine(send_sync)]`
+///
+///   Assert at compile time that the shared storage and every state-local
+///   field are `Send` and `Sync`, so the state machine can be moved to and
+///   shared with other threads (e.g. by an actor framework). A field that is
+///   not, such as a `Cell`, fails to compile with an error that points at it.
+///
+///   ```compile_fail
+///   # use core::cell::Cell;
+///   # use statig::prelude::*;
+///   struct Counter;
+///
+///   #[state_machine(initial = "State::counting(Cell::new(0))", send_sync)]
+///   impl Counter {
+///       #[state]
+///       fn counting(count: &Cell<u32>) -> Response<State> {
+///           count.set(count.get() + 1);
+///           Handled
+///       }
+///   }
+///   ```
+///
+///   <br/>
+///
+/// - `#[state_machine(state_index)]`
+///
+///   Implement `From<&State> for u32`, which gives every state a stable
+///   numeric index (e.g. for logging four bytes over a serial link instead of
+///   a name). Indices follow the sorted names of the states, so they only
+///   change when states are added, removed or renamed.
+///
+///   <br/>
+///
+/// - `#[state_machine(replay)]`
+///
+///   Generate a `replay(events)` method on the shared storage type that
+///   creates a state machine, handles all the given events and returns the
+///   kind of the state it ends up in, which keeps regression tests short. A
+///   `replay_with_context(events, context)` variant is generated as well. For
+///   awaitable state machines both are `async` and require the shared storage,
+///   the state, the events and the context to be `Send` (and the events and
+///   context `Sync`). This can not be combined with `minimal`.
+///
+///   <br/>
+///
+/// - `#[state_machine(apply_event_log)]`
+///
+///   Generate an `apply_event_log(log)` method on the shared storage type that
+///   creates a state machine and handles the events of a log of
+///   `(event, expected kind)` pairs one by one. It returns the index of the
+///   first entry after which the state machine was not in the expected kind of
+///   state, together with the kind it was in instead. An empty log is always
+///   `Ok`. An `apply_event_log_with_context(log, context)` variant is generated
+///   as well, with the same `Send` and `Sync` requirements as `replay` for
+///   awaitable state machines. This can not be combined with `minimal`.
+///
+///   <br/>
+///
+/// - `#[state_machine(transition_to)]`
+///
+///   Generate a `transition_to(&self, target)` method on the shared storage
+///   type that creates a `Transition` response, so handlers that take `&self`
+///   or `&mut self` can write `self.transition_to(State::led_off())`. The
+///   handler still has to return the response. This can not be combined with
+///   `minimal`.
+///
+///   <br/>
 #[cfg(feature = "macro")]
 pub use statig_macro::state_machine;
 
@@ -755,11 +1349,148 @@ pub use statig_macro::state_machine;
 ///
 ///   <br/>
 ///
+/// - `#[state(entry_actions(entry_action_a, entry_action_b))]`
+///
+///   Set several entry actions on the state, which run in the order they
+///   are declared in. Repeating `entry_action = ".."` has the same effect.
+///
+///   <br/>
+///
+/// - `#[state(exit_actions(exit_action_a, exit_action_b))]`
+///
+///   Set several exit actions on the state, which run in the reverse order
+///   they are declared in, so the last one that was set up is the first one
+///   that is torn down. Repeating `exit_action = ".."` has the same effect.
+///
+///   <br/>
+///
 /// - `#[state(local_storage("field_name_a: FieldTypeA", "field_name_b: FieldTypeB"))]`
 ///
 ///   Add local storage to this state. These will be added as fields to the enum variant.
 ///
 ///   <br/>
+///
+/// - `#[state(local_storage_by_value)]`
+///
+///   Allow inputs of the state-local storage that are not references (e.g.
+///   `counter: u8`). Their type is stored as is in the enum variant and the
+///   value is copied into the handler on every call, so it must be `Copy` and
+///   changes made by the handler are not kept. Inputs that are references are
+///   still borrowed from the variant.
+///
+///   <br/>
+///
+/// - `#[state(shared_storage_fields("field_name_a", "field_name_b"))]`
+///
+///   Borrow the inputs with the given names from the fields of the shared
+///   storage instead of the state-local storage. The inputs must be
+///   references (e.g. `field_name_a: &mut FieldTypeA`) and each field can
+///   only be borrowed once. This allows a handler to borrow multiple disjoint
+///   fields of the shared storage, but it can then no longer take `&mut self`.
+///
+///   <br/>
+///
+/// - `#[state(parallel)]`
+///
+///   Treat every field of the state-local storage as an orthogonal region.
+///   The regions are state machines themselves (e.g. an
+///   `InitializedStateMachine<Display>`) that share the event and context
+///   type of the parent. Every event is first handled by each region in the
+///   order of the priority of their current state (see below) and then in the
+///   order of the fields, after which the state handler is called, so it
+///   can inspect the regions and decide whether to transition. A transition
+///   within one region does not affect the other regions. Every region
+///   starts in the initial state of its own state machine, unless the parent
+///   declares another initial state for it by the name of its field (e.g.
+///   `#[state_machine(initial = "State::idle()", initial(left = "left::State::b()"))]`).
+///   A lazily initialized region is then initialized in that state, and a
+///   region that is already initialized transitions to it. Declaring the
+///   initial state of a region on a state machine without parallel states, or
+///   for a field that is not a region of a parallel state, is an error.
+///   Regions that are lazily initialized (e.g. a
+///   `StateMachine<Display>`) are initialized when the parallel state is
+///   entered, so the entry actions of all their initial states run together
+///   with the entry of the parallel state, including when it is the initial
+///   state of the parent. Regions are activated through the
+///   [`Region`](blocking::Region) trait, so the field types can also be
+///   aliases of the state machine types. When the parallel state is exited
+///   every region is shut down first, which runs the exit actions of its
+///   current state and superstates before the exit action of the parallel
+///   state.
+///
+///   <br/>
+///
+/// - `#[state(priority = 10)]`
+///
+///   Set the priority of a state in the state machine of a region, which is
+///   `0` by default. The priority only decides the dispatch order: when the
+///   regions of a parallel state handle an event, the region whose current
+///   state has the highest priority goes first, and regions with equal
+///   priorities go in the order they were declared. Conflicting transitions
+///   are not resolved by statig, but the order is deterministic, so a region
+///   can, for example, leave a request in the context that later regions and
+///   the parallel state respect. The priority of the current state is
+///   available through `state_priority()` on the state machine, and, when a
+///   state declares one, through the `priority()` method of the state, which
+///   is left out of minimal state machines.
+///
+///   <br/>
+///
+/// - `#[state(allow_missing_event)]`
+///
+///   Make explicit that the state does not take the event, even though other
+///   states do. Adding an event input to the handler of such a state is an
+///   error.
+///
+///   <br/>
+///
+/// - `#[state(terminal)]`
+///
+///   Mark the state as terminal, so the state machine can be dropped in this
+///   state without calling `shutdown` when `drop_guard` is set.
+///
+///   <br/>
+///
+/// - `#[state(final)]`
+///
+///   Mark the state as final, so its handler is not allowed to transition.
+///   A transition to a state that is written out in the handler (e.g.
+///   `Transition(State::on())`) is rejected at compile time. A transition to
+///   a state that is only known at runtime can not be detected, so it is
+///   caught by a `debug_assert!` instead.
+///
+///   <br/>
+///
+/// - `#[state(alias = "alias_name")]`
+///
+///   Add an alternative name that the kind of the state can be parsed from
+///   with `StateKind::from_name` and `StateKind::from_name_ignore_case`, in
+///   addition to the name of the state itself. The attribute can be repeated.
+///   An alias that matches the name or an alias of another state, ignoring
+///   case, is rejected at compile time.
+///
+///   <br/>
+///
+/// - `#[state(variant_attrs(serde(rename = "idle")))]`
+///
+///   Add the given attributes to the variant of the state enum. This is meant
+///   for the helper attributes of the derives that are applied with
+///   `#[state_machine(state(derive(...)))]`, such as `serde::Serialize` or
+///   `strum::EnumIter`.
+///
+///   <br/>
+///
+/// - `#[transition(to = "target_state", weight = 0.01)]`
+///
+///   Annotate a transition out of the state with a weight, e.g. its
+///   probability for a Monte Carlo simulation. This is a separate attribute
+///   next to `#[state]`. The weights are collected in a
+///   `transition_weights()` function on the shared storage type returning
+///   `(from, to, weight)` tuples, sorted by the state they start from. They
+///   are only metadata for external simulators, the state machine does not
+///   use them and does not check that they sum to one.
+///
+///   <br/>
 #[cfg(feature = "macro")]
 pub use statig_macro::state;
 
@@ -778,7 +1509,19 @@ pub use statig_macro::state;
 ///
 /// - `#[superstate(superstate = "superstate_name")]`
 ///
-///   Set the superstate of the superstate.
+///   Set the superstate of the superstate. Superstates can be nested to any
+///   depth, but a superstate can not be nested within itself, directly or
+///   through other superstates.
+///
+///   <br/>
+///
+/// - `#[superstate(initial = "State::initial_state()")]`
+///
+///   Set the initial state of the superstate. This generates a constructor
+///   on the state enum with the name of the superstate, so a transition
+///   can target the superstate with `Transition(State::superstate_name())`
+///   and land in its initial state. The initial state must be nested within
+///   the superstate.
 ///
 ///   <br/>
 ///
@@ -794,6 +1537,32 @@ pub use statig_macro::state;
 ///
 ///   <br/>
 ///
+/// - `#[superstate(entry_actions(entry_action_a, entry_action_b))]`
+///
+///   Set several entry actions on the superstate, which run in the order they
+///   are declared in. Repeating `entry_action = ".."` has the same effect.
+///
+///   <br/>
+///
+/// - `#[superstate(exit_actions(exit_action_a, exit_action_b))]`
+///
+///   Set several exit actions on the superstate, which run in the reverse order
+///   they are declared in, so the last one that was set up is the first one
+///   that is torn down. Repeating `exit_action = ".."` has the same effect.
+///
+///   <br/>
+///
+/// - `#[superstate(pre_dispatch = "pre_dispatch_action_name")]`
+///
+///   Set an action that filters events before they reach the states nested
+///   within the superstate. The action can take the same inputs as the
+///   superstate handler and returns a `Response`. Returning `Super` lets the
+///   event continue to the child, any other response is used instead of
+///   calling the child. Filters of nested superstates run from the outermost
+///   superstate inwards.
+///
+///   <br/>
+///
 /// - `#[superstate(local_storage("field_name_a: &'a mut FieldTypeA"))]`
 ///
 ///   Add local storage to this superstate. These will be added as fields to
@@ -803,6 +1572,27 @@ pub use statig_macro::state;
 ///   associated lifetime `'a`.
 ///
 ///   <br/>
+///
+/// - `#[superstate(owned_storage("field_name: FieldType = initial_value"))]`
+///
+///   Add storage that is owned by this superstate. The field is added to the
+///   variants of all the states nested within the superstate and set to its
+///   initial value when one of them is constructed. On transitions between
+///   these states the value is moved over to the target state, so it persists
+///   as long as the superstate is not exited. Leaving the superstate drops the
+///   value and entering it again starts from the initial value. The initial
+///   value must be a constant expression. Handlers access the field by taking
+///   it as a reference, like any other state-local storage.
+///
+///   <br/>
+///
+/// - `#[superstate(variant_attrs(doc = "Superstate of the active states"))]`
+///
+///   Add the given attributes to the variant of the superstate enum, e.g. the
+///   helper attributes of the derives that are applied with
+///   `#[state_machine(superstate(derive(...)))]`.
+///
+///   <br/>
 #[cfg(feature = "macro")]
 pub use statig_macro::superstate;
 
@@ -813,11 +1603,32 @@ pub use statig_macro::superstate;
 #[cfg(feature = "macro")]
 pub use statig_macro::action;
 
+/// Derive macro for implementing [`EventName`](crate::EventName) on the event type.
+///
+/// For an enum the name of the variant is returned, whatever the fields of the
+/// variant are. For a struct the name of the struct is returned. Unions are not
+/// supported.
+///
+/// ```
+/// use statig::EventName;
+///
+/// #[derive(EventName)]
+/// enum Event {
+///     TimerElapsed,
+///     ButtonPressed { id: u8 },
+/// }
+///
+/// assert_eq!(Event::ButtonPressed { id: 1 }.event_name(), "ButtonPressed");
+/// ```
+#[cfg(feature = "macro")]
+pub use statig_macro::EventName;
+
 /// Prelude containing the necessary imports for use with macro.
 pub mod prelude {
     #[cfg(feature = "async")]
     pub use crate::awaitable::{IntoStateMachineExt as _, StateExt as _, *};
     pub use crate::blocking::{IntoStateMachineExt as _, StateExt as _, *};
+    pub use crate::EventName;
     pub use crate::Response::{self, *};
     pub use crate::StateOrSuperstate;
     #[cfg(feature = "macro")]
@@ -831,6 +1642,44 @@ pub mod awaitable;
 
 pub(crate) use inner::*;
 
+pub use clock::*;
+pub use command::*;
+pub use dry_run::*;
+#[cfg(feature = "instrumentation")]
+pub use entry_exit_counts::*;
+pub use event_name::*;
+#[cfg(feature = "instrumentation")]
+pub use fired_actions::*;
+
+pub use handler_error::*;
 pub use into_state_machine::*;
+pub use observer::*;
+pub use previous_state_kind::*;
 pub use response::*;
 pub use state_or_superstate::*;
+pub use timeout::*;
+
+/// Support for the `Debug` implementation of the state machines generated by the
+/// `state_machine` macro.
+#[doc(hidden)]
+pub mod debug_storage;
+
+/// Re-export of the collections and formatting of `alloc` used by the code that is
+/// generated by the `state_machine` macro, so it also builds without `std`.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub mod __alloc {
+    pub use alloc::format;
+    pub use alloc::string::String;
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+}
+
+/// Re-export of `allocator-api2` for implementing the allocator of the state stack.
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2;
+
+/// Re-export of `tracing` for the spans that are emitted by the `state_machine` macro.
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing;
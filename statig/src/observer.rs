@@ -0,0 +1,23 @@
+use crate::{IntoStateMachine, StateOrSuperstate};
+
+/// Trait for observing all the lifecycle events of a state machine in one place.
+///
+/// Add a type implementing this trait as a field of the shared storage and pass the name
+/// of the field to `#[state_machine(observer = "field_name")]`. Every method has a no-op
+/// default, so only the events of interest need to be implemented.
+pub trait Observer<M>
+where
+    M: IntoStateMachine,
+{
+    /// Method that is called *before* an event is dispatched to a state or
+    /// superstate handler.
+    fn on_dispatch(
+        &mut self,
+        _state_or_superstate: StateOrSuperstate<'_, '_, M>,
+        _event: &M::Event<'_>,
+    ) {
+    }
+
+    /// Method that is called *after* every transition.
+    fn on_transition(&mut self, _source: &M::State, _target: &M::State) {}
+}
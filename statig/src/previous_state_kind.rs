@@ -0,0 +1,43 @@
+/// Keeps track of the kind of state the state machine was in on the previous dispatch.
+///
+/// Add it as a field of the shared storage and pass the name of the field to
+/// `#[state_machine(previous_state_kind = "field_name")]`, so handlers and external code
+/// can compare against the previous state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreviousStateKind<K> {
+    previous: Option<K>,
+    current: Option<K>,
+}
+
+impl<K> PreviousStateKind<K>
+where
+    K: Copy,
+{
+    /// Create a new tracker for which no dispatch has been recorded yet.
+    pub const fn new() -> Self {
+        Self {
+            previous: None,
+            current: None,
+        }
+    }
+
+    /// Get the kind of state the state machine was in on the previous dispatch. Returns
+    /// `None` during the first dispatch.
+    pub fn get(&self) -> Option<K> {
+        self.previous
+    }
+
+    /// Record the kind of state an event is being dispatched to.
+    pub fn record(&mut self, kind: K) {
+        self.previous = self.current.replace(kind);
+    }
+}
+
+impl<K> Default for PreviousStateKind<K>
+where
+    K: Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,5 @@
 use core::fmt::Debug;
+use core::ops::ControlFlow;
 
 /// Response returned by event handlers in a state machine.
 pub enum Response<S> {
@@ -8,6 +9,13 @@ pub enum Response<S> {
     Super,
     /// Transition to the given state.
     Transition(S),
+    /// Transition to the given state and remember the current state, so it can be returned
+    /// to with `Pop`. Without the `state_stack` feature nothing is remembered and this is the
+    /// same as `Transition`.
+    Push(S),
+    /// Transition back to the state that was most recently left with `Push`. When no state
+    /// was pushed, or without the `state_stack` feature, the event is considered handled.
+    Pop,
 }
 
 impl<S> Debug for Response<S>
@@ -22,6 +30,21 @@ where
                 .debug_tuple("Transition")
                 .field(state as &dyn Debug)
                 .finish(),
+            Self::Push(state) => f.debug_tuple("Push").field(state as &dyn Debug).finish(),
+            Self::Pop => f.debug_tuple("Pop").finish(),
+        }
+    }
+}
+
+/// Handlers can return a [`ControlFlow`] instead of a [`Response`]. `Continue` considers the
+/// event handled and `Break` transitions to the given state. There is no `ControlFlow`
+/// equivalent of [`Super`](Response::Super), so handlers that need to defer events to their
+/// superstate must return a `Response`.
+impl<S> From<ControlFlow<S>> for Response<S> {
+    fn from(control_flow: ControlFlow<S>) -> Self {
+        match control_flow {
+            ControlFlow::Continue(()) => Self::Handled,
+            ControlFlow::Break(state) => Self::Transition(state),
         }
     }
 }
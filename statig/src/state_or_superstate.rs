@@ -13,6 +13,17 @@ where
     Superstate(&'a M::Superstate<'b>),
 }
 
+impl<'a, 'b, M: IntoStateMachine> Clone for StateOrSuperstate<'a, 'b, M>
+where
+    M::State: 'b,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, 'b, M: IntoStateMachine> Copy for StateOrSuperstate<'a, 'b, M> where M::State: 'b {}
+
 impl<'a, 'b, M: IntoStateMachine> core::fmt::Debug for StateOrSuperstate<'a, 'b, M>
 where
     M::State: Debug,
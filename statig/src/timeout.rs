@@ -0,0 +1,121 @@
+use core::ops::Add;
+
+use crate::Clock;
+
+/// Timeout that is armed by a state and disarmed when that state is exited.
+///
+/// Add it as a field of the shared storage and pass the name of the field to
+/// `#[state_machine(timeout = "field_name")]`. Handlers and entry actions can then arm it
+/// with a deadline, while the timer itself lives outside of the state machine: external
+/// code polls the timeout with the current time and is notified with the kind of the state
+/// that armed it once the deadline has passed. The `*_with` methods read the current time
+/// from a [`Clock`] instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timeout<K, I> {
+    current: Option<K>,
+    armed: Option<(K, I)>,
+}
+
+impl<K, I> Timeout<K, I>
+where
+    K: Copy + PartialEq,
+    I: PartialOrd,
+{
+    /// Create a new timeout that is not armed.
+    pub const fn new() -> Self {
+        Self {
+            current: None,
+            armed: None,
+        }
+    }
+
+    /// Arm the timeout for the current state with the given deadline, replacing the
+    /// deadline if it was already armed. Before the state machine is initialized there is
+    /// no current state and this is a no-op.
+    pub fn arm(&mut self, deadline: I) {
+        if let Some(kind) = self.current {
+            self.armed = Some((kind, deadline));
+        }
+    }
+
+    /// Disarm the timeout.
+    pub fn disarm(&mut self) {
+        self.armed = None;
+    }
+
+    /// Get the kind of the state that armed the timeout and its deadline.
+    pub fn armed(&self) -> Option<(K, &I)> {
+        self.armed
+            .as_ref()
+            .map(|(kind, deadline)| (*kind, deadline))
+    }
+
+    /// Get the kind of the state that armed the timeout if its deadline has passed at `now`.
+    pub fn expired(&self, now: &I) -> Option<K> {
+        match &self.armed {
+            Some((kind, deadline)) if deadline <= now => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Call `on_timeout` with the kind of the state that armed the timeout if its deadline
+    /// has passed at `now`, and disarm it so it only fires once.
+    pub fn poll(&mut self, now: &I, on_timeout: impl FnOnce(K)) {
+        if let Some(kind) = self.expired(now) {
+            self.armed = None;
+            on_timeout(kind);
+        }
+    }
+
+    /// Arm the timeout for the current state to expire `duration` after the current time of
+    /// the clock. See [`arm`](Self::arm).
+    pub fn arm_after<C, D>(&mut self, clock: &C, duration: D)
+    where
+        C: Clock<Instant = I>,
+        I: Add<D, Output = I>,
+    {
+        self.arm(clock.now() + duration);
+    }
+
+    /// Get the kind of the state that armed the timeout if its deadline has passed at the
+    /// current time of the clock.
+    pub fn expired_with<C>(&self, clock: &C) -> Option<K>
+    where
+        C: Clock<Instant = I>,
+    {
+        self.expired(&clock.now())
+    }
+
+    /// Call `on_timeout` with the kind of the state that armed the timeout if its deadline
+    /// has passed at the current time of the clock. See [`poll`](Self::poll).
+    pub fn poll_with<C>(&mut self, clock: &C, on_timeout: impl FnOnce(K))
+    where
+        C: Clock<Instant = I>,
+    {
+        self.poll(&clock.now(), on_timeout);
+    }
+
+    /// Record that the state of the given kind was entered.
+    pub fn record_entry(&mut self, kind: K) {
+        self.current = Some(kind);
+    }
+
+    /// Record that the state of the given kind was exited, disarming the timeout if it was
+    /// armed by that state.
+    pub fn record_exit(&mut self, kind: K) {
+        if matches!(self.armed, Some((armed, _)) if armed == kind) {
+            self.armed = None;
+        }
+        self.current = None;
+    }
+}
+
+impl<K, I> Default for Timeout<K, I>
+where
+    K: Copy + PartialEq,
+    I: PartialOrd,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
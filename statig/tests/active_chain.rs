@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Player;
+
+    #[state_machine(initial = "State::idle()")]
+    impl Player {
+        #[state]
+        fn idle(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::buffering(0)),
+            }
+        }
+
+        #[state(superstate = "streaming")]
+        fn buffering(level: &mut u8, event: &Event) -> Response {
+            match event {
+                Event::Next => {
+                    *level += 1;
+                    Transition(State::idle())
+                }
+            }
+        }
+
+        #[superstate(superstate = "playing")]
+        fn streaming(event: &Event) -> Response {
+            match event {
+                Event::Next => Super,
+            }
+        }
+
+        #[superstate]
+        fn playing(event: &Event) -> Response {
+            match event {
+                Event::Next => Super,
+            }
+        }
+    }
+
+    fn active_names(state_machine: &mut StateMachine<Player>) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        state_machine.for_each_active(|state_or_superstate| match state_or_superstate {
+            StateOrSuperstate::State(state) => names.push(state.name()),
+            StateOrSuperstate::Superstate(superstate) => names.push(superstate.name()),
+        });
+        names
+    }
+
+    #[test]
+    fn test_active_chain_of_nested_state() {
+        let mut state_machine = Player::default().state_machine();
+        state_machine.handle(&Event::Next);
+
+        assert_eq!(
+            active_names(&mut state_machine),
+            ["Buffering", "Streaming", "Playing"]
+        );
+    }
+
+    #[test]
+    fn test_active_chain_without_superstate() {
+        let mut state_machine = Player::default().state_machine();
+        state_machine.init();
+
+        assert_eq!(active_names(&mut state_machine), ["Idle"]);
+    }
+
+    #[test]
+    fn test_inside_superstate() {
+        let mut state_machine = Player::default().uninitialized_state_machine().init();
+        state_machine.handle(&Event::Next);
+
+        let mut inside_playing = false;
+        state_machine.for_each_active(|state_or_superstate| {
+            if let StateOrSuperstate::Superstate(Superstate::Playing {}) = state_or_superstate {
+                inside_playing = true;
+            }
+        });
+        assert!(inside_playing);
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+        Reset,
+        Jump(bool),
+    }
+
+    struct Machine;
+
+    #[state_machine(initial = "State::idle()")]
+    impl Machine {
+        #[state]
+        fn idle(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::running()),
+                // Transitioning to the current state is a self-loop.
+                Event::Reset => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+
+        #[state(superstate = "active")]
+        fn running(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::stopped()),
+                _ => Super,
+            }
+        }
+
+        #[state]
+        fn stopped(event: &Event) -> Response {
+            match event {
+                Event::Jump(forward) => {
+                    let target = if *forward {
+                        State::running()
+                    } else {
+                        State::idle()
+                    };
+                    Transition(target)
+                }
+                _ => Handled,
+            }
+        }
+
+        #[superstate]
+        fn active(event: &Event) -> Response {
+            match event {
+                Event::Reset => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_adjacency() {
+        assert_eq!(StateKind::STATE_COUNT, 3);
+
+        assert!(StateKind::Idle.can_transition_to(StateKind::Running));
+        assert!(!StateKind::Idle.can_transition_to(StateKind::Stopped));
+
+        // Transitions of the superstate are included.
+        assert!(StateKind::Running.can_transition_to(StateKind::Idle));
+        assert!(StateKind::Running.can_transition_to(StateKind::Stopped));
+        assert!(!StateKind::Running.can_transition_to(StateKind::Running));
+    }
+
+    #[test]
+    fn test_is_valid_transition() {
+        assert!(StateKind::is_valid_transition(
+            StateKind::Idle,
+            StateKind::Running
+        ));
+        assert!(!StateKind::is_valid_transition(
+            StateKind::Idle,
+            StateKind::Stopped
+        ));
+
+        // Dynamic transitions can not be checked.
+        assert!(StateKind::is_valid_transition(
+            StateKind::Stopped,
+            StateKind::Stopped
+        ));
+
+        StateKind::assert_valid_transition(StateKind::Running, StateKind::Stopped);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "invalid transition from Idle to Stopped")]
+    fn test_assert_valid_transition() {
+        StateKind::assert_valid_transition(StateKind::Idle, StateKind::Stopped);
+    }
+
+    #[test]
+    fn test_adjacency_self_loop() {
+        assert!(StateKind::ADJACENCY[StateKind::Idle as usize][StateKind::Idle as usize]);
+    }
+
+    #[test]
+    fn test_adjacency_dynamic_transition() {
+        assert_eq!(
+            StateKind::ADJACENCY[StateKind::Stopped as usize],
+            [true; StateKind::STATE_COUNT]
+        );
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Arm,
+        Trigger,
+    }
+
+    #[derive(Default)]
+    struct Alarm {
+        triggered: usize,
+    }
+
+    #[state_machine(initial = "State::disarmed()", state(derive(Eq, PartialEq, Debug)))]
+    impl Alarm {
+        #[state]
+        fn disarmed(event: &Event) -> Response {
+            match event {
+                Event::Arm => Transition(State::armed()),
+                Event::Trigger => Handled,
+            }
+        }
+
+        #[state]
+        fn armed(&mut self, event: &Event) -> Response {
+            match event {
+                Event::Trigger => {
+                    self.triggered += 1;
+                    Transition(State::ringing())
+                }
+                Event::Arm => Handled,
+            }
+        }
+
+        /// Rings until the state machine is reset, whatever the event.
+        #[state(allow_missing_event)]
+        fn ringing() -> Response {
+            Handled
+        }
+    }
+
+    #[test]
+    fn test_state_without_event() {
+        let mut state_machine = Alarm::default().state_machine();
+
+        state_machine.handle(&Event::Arm);
+        state_machine.handle(&Event::Trigger);
+        assert_eq!(state_machine.state(), &State::ringing());
+
+        state_machine.handle(&Event::Arm);
+        state_machine.handle(&Event::Trigger);
+        assert_eq!(state_machine.state(), &State::ringing());
+        assert_eq!(state_machine.triggered, 1);
+    }
+}
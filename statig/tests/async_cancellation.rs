@@ -0,0 +1,148 @@
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod tests {
+
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Start,
+    }
+
+    /// Future that is pending the first time it is polled.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            match self.0 {
+                true => Poll::Ready(()),
+                false => {
+                    self.0 = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    fn poll_once<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        Pin::new(future).poll(&mut cx)
+    }
+
+    #[derive(Default, serde::Serialize, serde::Deserialize)]
+    struct Machine {
+        idle_exits: u32,
+        running_entries: u32,
+    }
+
+    #[state_machine(
+        initial = "State::idle()",
+        state(derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize))
+    )]
+    impl Machine {
+        #[state(exit_action = "exit_idle")]
+        async fn idle(event: &Event) -> Response {
+            match event {
+                Event::Start => Transition(State::running()),
+            }
+        }
+
+        #[action]
+        async fn exit_idle(&mut self) {
+            YieldOnce(false).await;
+            self.idle_exits += 1;
+        }
+
+        #[state(entry_action = "enter_running")]
+        async fn running(event: &Event) -> Response {
+            match event {
+                Event::Start => Handled,
+            }
+        }
+
+        #[action]
+        async fn enter_running(&mut self) {
+            YieldOnce(false).await;
+            self.running_entries += 1;
+        }
+    }
+
+    #[test]
+    fn test_drop_dispatch_during_exit_action() {
+        let mut state_machine =
+            futures::executor::block_on(Machine::default().uninitialized_state_machine().init());
+
+        {
+            let mut future = Box::pin(state_machine.handle(&Event::Start));
+            assert!(poll_once(&mut future).is_pending());
+        }
+
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.idle_exits, 0);
+
+        // The exit action that was interrupted is performed again before the next event.
+        futures::executor::block_on(state_machine.handle(&Event::Start));
+
+        assert_eq!(state_machine.state(), &State::running());
+        assert_eq!(state_machine.idle_exits, 1);
+        assert_eq!(state_machine.running_entries, 1);
+    }
+
+    #[test]
+    fn test_drop_dispatch_during_entry_action() {
+        let mut state_machine =
+            futures::executor::block_on(Machine::default().uninitialized_state_machine().init());
+
+        {
+            let mut future = Box::pin(state_machine.handle(&Event::Start));
+            assert!(poll_once(&mut future).is_pending());
+            assert!(poll_once(&mut future).is_pending());
+        }
+
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.idle_exits, 1);
+        assert_eq!(state_machine.running_entries, 0);
+
+        // The next dispatch completes the transition without exiting `idle` a second time,
+        // and then handles the event in `running`.
+        futures::executor::block_on(state_machine.handle(&Event::Start));
+
+        assert_eq!(state_machine.state(), &State::running());
+        assert_eq!(state_machine.idle_exits, 1);
+        assert_eq!(state_machine.running_entries, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialize_pending_transition() {
+        let mut state_machine =
+            futures::executor::block_on(Machine::default().uninitialized_state_machine().init());
+
+        {
+            let mut future = Box::pin(state_machine.handle(&Event::Start));
+            assert!(poll_once(&mut future).is_pending());
+            assert!(poll_once(&mut future).is_pending());
+        }
+
+        let ser = serde_json::to_string(&state_machine).unwrap();
+        let de: statig::awaitable::UninitializedStateMachine<Machine> =
+            serde_json::from_str(&ser).unwrap();
+        assert_eq!(serde_json::to_string(&de).unwrap(), ser);
+
+        // The deserialized state machine completes the transition that was pending.
+        let mut de = futures::executor::block_on(de.init());
+        futures::executor::block_on(de.handle(&Event::Start));
+
+        assert_eq!(de.state(), &State::running());
+        assert_eq!(de.idle_exits, 1);
+        assert_eq!(de.running_entries, 1);
+    }
+}
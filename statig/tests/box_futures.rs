@@ -0,0 +1,100 @@
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod tests {
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use statig::awaitable::DynStateMachine;
+
+    pub enum Event {
+        Tick,
+    }
+
+    static TICKS: AtomicUsize = AtomicUsize::new(0);
+    static TOGGLES: AtomicUsize = AtomicUsize::new(0);
+
+    mod counter {
+        use super::{Event, TICKS};
+        use std::sync::atomic::Ordering;
+
+        use statig::prelude::*;
+
+        #[derive(Default)]
+        pub struct Counter;
+
+        #[state_machine(
+            initial = "State::counting()",
+            box_futures,
+            state(derive(Eq, PartialEq, Debug))
+        )]
+        impl Counter {
+            #[state]
+            async fn counting(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => {
+                        TICKS.fetch_add(1, Ordering::SeqCst);
+                        Handled
+                    }
+                }
+            }
+        }
+    }
+
+    mod toggle {
+        use super::{Event, TOGGLES};
+        use std::sync::atomic::Ordering;
+
+        use statig::prelude::*;
+
+        #[derive(Default)]
+        pub struct Toggle;
+
+        #[state_machine(
+            initial = "State::off()",
+            box_futures,
+            state(derive(Eq, PartialEq, Debug))
+        )]
+        impl Toggle {
+            #[state]
+            async fn off(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::on()),
+                }
+            }
+
+            #[state(entry_action = "enter_on")]
+            async fn on(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::off()),
+                }
+            }
+
+            #[action]
+            async fn enter_on() {
+                TOGGLES.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dyn_state_machines() {
+        use statig::awaitable::IntoStateMachineExt;
+
+        let mut machines: Vec<Box<dyn DynStateMachine<Event>>> = vec![
+            Box::new(counter::Counter.state_machine()),
+            Box::new(toggle::Toggle.state_machine()),
+        ];
+
+        let future = async move {
+            for _ in 0..3 {
+                for machine in &mut machines {
+                    machine.handle(&Event::Tick).await;
+                }
+            }
+        };
+        futures::executor::block_on(future);
+
+        assert_eq!(TICKS.load(Ordering::SeqCst), 3);
+        assert_eq!(TOGGLES.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+        Panic,
+    }
+
+    #[derive(Clone, Default)]
+    struct Plugin {
+        handled: usize,
+    }
+
+    #[state_machine(initial = "State::first()", state(derive(Clone, Eq, PartialEq, Debug)))]
+    impl Plugin {
+        #[state]
+        fn first(&mut self, event: &Event) -> Response<State> {
+            self.handled += 1;
+            match event {
+                Event::Next => Transition(State::second()),
+                Event::Panic => panic!("handler panicked"),
+            }
+        }
+
+        #[state(entry_action = "enter_second")]
+        fn second(&mut self, event: &Event) -> Response<State> {
+            self.handled += 1;
+            match event {
+                Event::Next => Transition(State::first()),
+                Event::Panic => Handled,
+            }
+        }
+
+        #[action]
+        fn enter_second(&mut self) {
+            if self.handled > 2 {
+                panic!("entry action panicked");
+            }
+        }
+    }
+
+    #[test]
+    fn test_recover_from_panicking_handler() {
+        let mut state_machine = Plugin::default().state_machine();
+
+        let result = state_machine.handle_catch_unwind(&Event::Panic);
+        assert!(result.is_err());
+        assert_eq!(state_machine.state(), &State::first());
+        assert_eq!(state_machine.handled, 0);
+
+        // The state machine can still be used after the panic.
+        assert!(state_machine.handle_catch_unwind(&Event::Next).is_ok());
+        assert_eq!(state_machine.state(), &State::second());
+        assert_eq!(state_machine.handled, 1);
+    }
+
+    #[test]
+    fn test_recover_from_panicking_entry_action() {
+        let mut state_machine = Plugin::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Next);
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::first());
+
+        // The entry action panics half way through the transition.
+        let result = state_machine.handle_catch_unwind(&Event::Next);
+        let payload = result.unwrap_err();
+        assert_eq!(payload.downcast_ref(), Some(&"entry action panicked"));
+        assert_eq!(state_machine.state(), &State::first());
+        assert_eq!(state_machine.handled, 2);
+    }
+}
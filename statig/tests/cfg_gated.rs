@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+
+    mod enabled {
+        use statig::prelude::*;
+
+        pub enum Event {
+            Toggle,
+        }
+
+        pub struct Lamp;
+
+        #[state_machine(
+            initial = "State::off()",
+            cfg = "all()",
+            state(derive(Eq, PartialEq, Debug))
+        )]
+        impl Lamp {
+            #[state]
+            fn off(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::on()),
+                }
+            }
+
+            #[state]
+            fn on(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                }
+            }
+        }
+    }
+
+    mod disabled {
+        use statig::state_machine;
+
+        #[allow(dead_code)]
+        pub enum Event {
+            Toggle,
+        }
+
+        pub struct Lamp;
+
+        // None of the generated items exist, so this does not conflict with them.
+        pub struct State;
+
+        #[state_machine(initial = "State::off()", cfg = "any()")]
+        impl Lamp {
+            #[state]
+            fn off(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::on()),
+                }
+            }
+
+            #[state]
+            fn on(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                }
+            }
+        }
+    }
+
+    use statig::prelude::*;
+
+    #[test]
+    fn test_cfg_enabled() {
+        let mut state_machine = enabled::Lamp.state_machine();
+        state_machine.handle(&enabled::Event::Toggle);
+        assert_eq!(state_machine.state(), &enabled::State::on());
+    }
+
+    #[test]
+    fn test_cfg_disabled() {
+        let _lamp = disabled::Lamp;
+        assert_eq!(core::mem::size_of::<disabled::State>(), 0);
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use statig::prelude::*;
+    use statig::{Clock, Timeout};
+
+    type Response = statig::Response<State>;
+
+    /// Clock that only advances when the test tells it to.
+    #[derive(Clone, Default)]
+    struct MockClock {
+        now: Rc<Cell<u64>>,
+    }
+
+    impl MockClock {
+        fn advance(&self, ticks: u64) {
+            self.now.set(self.now.get() + ticks);
+        }
+    }
+
+    impl Clock for MockClock {
+        type Instant = u64;
+
+        fn now(&self) -> u64 {
+            self.now.get()
+        }
+    }
+
+    enum Event {
+        Connect,
+        TimedOut,
+    }
+
+    struct Link<C: Clock<Instant = u64>> {
+        clock: C,
+        timeout: Timeout<StateKind, u64>,
+    }
+
+    #[state_machine(
+        initial = "State::idle()",
+        timeout = "timeout",
+        deref_storage,
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl<C: Clock<Instant = u64>> Link<C> {
+        #[state]
+        fn idle(event: &Event) -> Response {
+            match event {
+                Event::Connect => Transition(State::connecting()),
+                _ => Handled,
+            }
+        }
+
+        #[state(entry_action = "enter_connecting")]
+        fn connecting(event: &Event) -> Response {
+            match event {
+                Event::TimedOut => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+
+        #[action]
+        fn enter_connecting(&mut self) {
+            self.timeout.arm_after(&self.clock, 5);
+        }
+    }
+
+    #[test]
+    fn test_mock_clock_drives_timeout() {
+        let clock = MockClock::default();
+        let mut state_machine = Link {
+            clock: clock.clone(),
+            timeout: Timeout::new(),
+        }
+        .state_machine();
+
+        clock.advance(10);
+        state_machine.handle(&Event::Connect);
+        assert_eq!(
+            state_machine.timeout.armed(),
+            Some((StateKind::Connecting, &15))
+        );
+
+        clock.advance(4);
+        assert_eq!(state_machine.timeout.expired_with(&clock), None);
+
+        clock.advance(1);
+        let mut expired = None;
+        state_machine
+            .timeout
+            .poll_with(&clock, |kind| expired = Some(kind));
+        assert_eq!(expired, Some(StateKind::Connecting));
+
+        state_machine.handle(&Event::TimedOut);
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_system_clock() {
+        use statig::SystemClock;
+
+        let earlier = SystemClock.now();
+        assert!(SystemClock.now() >= earlier);
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+    use statig::Command;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Start,
+        Tick,
+    }
+
+    #[derive(Default)]
+    struct Timer;
+
+    #[state_machine(initial = "State::idle()")]
+    impl Timer {
+        #[state]
+        fn idle(event: &Event) -> Response {
+            match event {
+                Event::Start => Transition(State::running(0)),
+                Event::Tick => Handled,
+            }
+        }
+
+        #[state]
+        fn running(ticks: &mut u32, event: &Event) -> Response {
+            match event {
+                Event::Tick => {
+                    *ticks += 1;
+                    Handled
+                }
+                Event::Start => Handled,
+            }
+        }
+
+        #[state]
+        fn paused(event: &Event) -> Response {
+            match event {
+                Event::Start => Transition(State::running(0)),
+                Event::Tick => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_event_and_reset() {
+        let mut state_machine = Timer.state_machine();
+
+        let result = state_machine.apply_command(Command::<_, StateKind>::Event(Event::Start));
+        assert_eq!(result, Ok(()));
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Running);
+
+        let result = state_machine.apply_command(Command::<_, StateKind>::Reset);
+        assert_eq!(result, Ok(()));
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Idle);
+    }
+
+    #[test]
+    fn test_apply_force_state() {
+        let mut state_machine = Timer.uninitialized_state_machine().init();
+
+        let result = state_machine.apply_command(Command::ForceState(StateKind::Paused));
+        assert_eq!(result, Ok(()));
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Paused);
+
+        // States with state-local storage can not be created from their kind.
+        let result = state_machine.apply_command(Command::ForceState(StateKind::Running));
+        assert_eq!(result, Err(StateKind::Running));
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Paused);
+    }
+}
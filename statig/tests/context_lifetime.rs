@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Default)]
+    struct Logger;
+
+    struct CallContext<'call> {
+        log: &'call mut Vec<&'static str>,
+    }
+
+    enum Event {
+        Toggle,
+    }
+
+    #[state_machine(initial = "State::on()")]
+    impl Logger {
+        #[state]
+        #[allow(clippy::needless_lifetimes)]
+        fn on<'call>(context: &mut CallContext<'call>, event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => {
+                    context.log.push("on");
+                    Transition(State::off())
+                }
+            }
+        }
+
+        #[state]
+        fn off(context: &mut CallContext<'_>, event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => {
+                    context.log.push("off");
+                    Transition(State::on())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_context_with_call_lifetime() {
+        let mut log = Vec::new();
+
+        let mut state_machine = Logger::default()
+            .uninitialized_state_machine()
+            .init_with_context(&mut CallContext { log: &mut log });
+
+        for _ in 0..3 {
+            // Every call borrows the log with a new lifetime.
+            let mut context = CallContext { log: &mut log };
+            state_machine.handle_with_context(&Event::Toggle, &mut context);
+        }
+
+        assert_eq!(log, ["on", "off", "on"]);
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Default)]
+    struct Pool {
+        free: Vec<u32>,
+    }
+
+    struct Resources<'call> {
+        pool: &'call mut Pool,
+        log: &'call mut Vec<u32>,
+    }
+
+    enum Event {
+        Acquire,
+        Release,
+    }
+
+    #[derive(Default)]
+    struct Client;
+
+    #[state_machine(initial = "State::idle()", state(derive(Eq, PartialEq, Debug)))]
+    impl Client {
+        #[state]
+        fn idle(context: &mut Resources<'_>, event: &Event) -> Response<State> {
+            match event {
+                Event::Acquire => match context.pool.free.pop() {
+                    Some(connection) => {
+                        context.log.push(connection);
+                        Transition(State::busy(connection))
+                    }
+                    None => Handled,
+                },
+                Event::Release => Handled,
+            }
+        }
+
+        #[state]
+        fn busy(
+            connection: &mut u32,
+            context: &mut Resources<'_>,
+            event: &Event,
+        ) -> Response<State> {
+            match event {
+                Event::Acquire => Handled,
+                Event::Release => {
+                    context.pool.free.push(*connection);
+                    Transition(State::idle())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_handlers_mutate_external_resources() {
+        let mut first = Pool { free: vec![1, 2] };
+        let mut second = Pool::default();
+        let mut log = Vec::new();
+        let mut state_machine = Client.state_machine();
+
+        let mut resources = Resources {
+            pool: &mut first,
+            log: &mut log,
+        };
+        state_machine.handle_with_context(&Event::Acquire, &mut resources);
+        assert_eq!(state_machine.state(), &State::busy(2));
+
+        // The connection is returned to a different pool than it came from.
+        let mut resources = Resources {
+            pool: &mut second,
+            log: &mut log,
+        };
+        state_machine.handle_with_context(&Event::Release, &mut resources);
+        state_machine.handle_with_context(&Event::Acquire, &mut resources);
+        assert_eq!(state_machine.state(), &State::busy(2));
+
+        assert_eq!(first.free, [1]);
+        assert!(second.free.is_empty());
+        assert_eq!(log, [2, 2]);
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+
+    use core::ops::ControlFlow;
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+        Ignore,
+        Reset,
+    }
+
+    struct Switch;
+
+    #[state_machine(initial = "State::off()", state(derive(Eq, PartialEq, Debug)))]
+    impl Switch {
+        #[state]
+        fn off(event: &Event) -> ControlFlow<State> {
+            match event {
+                Event::Toggle => ControlFlow::Break(State::on()),
+                _ => ControlFlow::Continue(()),
+            }
+        }
+
+        #[state(superstate = "powered")]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::off()),
+                _ => Super,
+            }
+        }
+
+        #[superstate]
+        fn powered(event: &Event) -> ControlFlow<State> {
+            match event {
+                Event::Reset => ControlFlow::Break(State::off()),
+                _ => ControlFlow::Continue(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_control_flow_handlers() {
+        let mut state_machine = Switch.state_machine();
+
+        state_machine.handle(&Event::Ignore);
+        assert_eq!(state_machine.state(), &State::off());
+
+        state_machine.handle(&Event::Toggle);
+        assert_eq!(state_machine.state(), &State::on());
+
+        state_machine.handle(&Event::Ignore);
+        assert_eq!(state_machine.state(), &State::on());
+
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.state(), &State::off());
+    }
+
+    #[test]
+    fn test_control_flow_transitions_are_reachable() {
+        assert_eq!(State::reachable_states(), ["off", "on"]);
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+    }
+
+    mod with_debug_storage {
+        use super::Event;
+        use statig::prelude::*;
+
+        #[derive(Default, Debug)]
+        pub struct Switch {
+            pub toggles: usize,
+        }
+
+        #[state_machine(initial = "State::off()")]
+        impl Switch {
+            #[state]
+            fn off(&mut self, event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => {
+                        self.toggles += 1;
+                        Transition(State::on())
+                    }
+                }
+            }
+
+            #[state]
+            fn on(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                }
+            }
+        }
+    }
+
+    mod without_debug_storage {
+        use super::Event;
+        use statig::prelude::*;
+
+        #[derive(Default)]
+        pub struct Switch;
+
+        #[state_machine(initial = "State::off()")]
+        impl Switch {
+            #[state]
+            fn off(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::on()),
+                }
+            }
+
+            #[state]
+            fn on(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_debug_with_debug_storage() {
+        let mut state_machine = with_debug_storage::Switch::default().state_machine();
+        state_machine.handle(&Event::Toggle);
+
+        let debug = format!("{:?}", state_machine);
+        assert!(debug.contains("On"));
+        assert!(debug.contains("Switch { toggles: 1 }"));
+    }
+
+    #[test]
+    fn test_debug_without_debug_storage() {
+        let mut state_machine = without_debug_storage::Switch::default().state_machine();
+        state_machine.handle(&Event::Toggle);
+
+        let debug = format!("{:?}", state_machine);
+        assert!(debug.contains("On"));
+        assert!(!debug.contains("shared_storage"));
+    }
+
+    #[test]
+    fn test_debug_initialized_and_uninitialized() {
+        let state_machine = without_debug_storage::Switch::default().uninitialized_state_machine();
+
+        let debug = format!("{:?}", state_machine);
+        assert!(debug.contains("UnInitializedStateMachine"));
+        assert!(debug.contains("Off"));
+        assert!(!debug.contains("shared_storage"));
+
+        let mut state_machine = state_machine.init();
+        state_machine.handle(&Event::Toggle);
+
+        let debug = format!("{:?}", state_machine);
+        assert!(debug.contains("InitializedStateMachine"));
+        assert!(debug.contains("On"));
+        assert!(!debug.contains("shared_storage"));
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Tick,
+    }
+
+    #[derive(Default)]
+    struct Clock {
+        ticks: usize,
+    }
+
+    impl Clock {
+        fn ticks(&self) -> usize {
+            self.ticks
+        }
+
+        fn reset(&mut self) {
+            self.ticks = 0;
+        }
+
+        // Shadowed by the method of the state machine.
+        fn state(&self) -> &'static str {
+            "storage"
+        }
+    }
+
+    #[state_machine(
+        initial = "State::running()",
+        deref_storage,
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Clock {
+        #[state]
+        fn running(&mut self, event: &Event) -> Response {
+            match event {
+                Event::Tick => {
+                    self.ticks += 1;
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_deref_storage() {
+        let mut state_machine = Clock::default().state_machine();
+
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Tick);
+        assert_eq!(state_machine.ticks(), 2);
+
+        state_machine.reset();
+        assert_eq!(state_machine.ticks(), 0);
+
+        state_machine.ticks = 5;
+        state_machine.handle(&Event::Tick);
+        assert_eq!(state_machine.ticks(), 6);
+    }
+
+    #[test]
+    fn test_inherent_methods_take_precedence() {
+        let state_machine = Clock::default().state_machine();
+
+        assert_eq!(state_machine.state(), &State::running());
+        assert_eq!((*state_machine).state(), "storage");
+    }
+}
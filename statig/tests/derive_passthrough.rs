@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Counter;
+
+    #[state_machine(
+        initial = "State::idle()",
+        state(derive(serde::Serialize, Debug)),
+        superstate(derive(Debug))
+    )]
+    impl Counter {
+        #[state(variant_attrs(serde(rename = "waiting")))]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::counting(0, 1)),
+            }
+        }
+
+        #[state(superstate = "active", variant_attrs(serde(rename_all = "UPPERCASE")))]
+        fn counting(count: &mut u32, step: &u32, event: &Event) -> Response<State> {
+            match event {
+                Event::Next => {
+                    *count += step;
+                    Handled
+                }
+            }
+        }
+
+        #[superstate(variant_attrs(allow(dead_code)))]
+        fn active(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Super,
+            }
+        }
+    }
+
+    #[test]
+    fn test_derived_serialize_uses_variant_attrs() {
+        assert_eq!(
+            serde_json::to_string(&State::idle()).unwrap(),
+            "{\"waiting\":{}}"
+        );
+        assert_eq!(
+            serde_json::to_string(&State::counting(2, 1)).unwrap(),
+            "{\"Counting\":{\"COUNT\":2,\"STEP\":1}}"
+        );
+    }
+}
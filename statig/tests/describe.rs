@@ -0,0 +1,45 @@
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        TimerElapsed,
+    }
+
+    struct Blinky;
+
+    #[state_machine(initial = "State::led_on()", describe)]
+    impl Blinky {
+        #[state(superstate = "blinking")]
+        fn led_on(event: &Event) -> Response<State> {
+            match event {
+                Event::TimerElapsed => Transition(State::led_off()),
+            }
+        }
+
+        #[state(superstate = "blinking")]
+        fn led_off(event: &Event) -> Response<State> {
+            match event {
+                Event::TimerElapsed => Transition(State::led_on()),
+            }
+        }
+
+        #[superstate]
+        fn blinking(event: &Event) -> Response<State> {
+            match event {
+                Event::TimerElapsed => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        let description = Blinky::describe();
+
+        assert!(description.starts_with("Blinky: blocking state machine"));
+        assert!(description.contains("2 states and 1 superstate"));
+        assert!(description.ends_with("starting in `led_on`"));
+    }
+}
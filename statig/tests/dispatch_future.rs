@@ -0,0 +1,88 @@
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod tests {
+
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+
+    use statig::awaitable::{Dispatch, StateMachine};
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Start,
+    }
+
+    /// Future that is pending the first time it is polled.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            match self.0 {
+                true => Poll::Ready(()),
+                false => {
+                    self.0 = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Machine {
+        entered_running: bool,
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Machine {
+        #[state]
+        async fn idle(event: &Event) -> Response {
+            match event {
+                Event::Start => Transition(State::running()),
+            }
+        }
+
+        #[state(entry_action = "enter_running")]
+        async fn running(event: &Event) -> Response {
+            match event {
+                Event::Start => Handled,
+            }
+        }
+
+        #[action]
+        async fn enter_running(&mut self) {
+            YieldOnce(false).await;
+            self.entered_running = true;
+        }
+    }
+
+    /// The dispatch future can be stored by name, without boxing it.
+    struct Task<'a> {
+        dispatch: Dispatch<'a>,
+    }
+
+    #[test]
+    fn test_poll_dispatch_to_completion() {
+        let mut state_machine: StateMachine<Machine> = Machine::default().state_machine();
+
+        let mut polls = 0;
+        {
+            let mut task = Task {
+                dispatch: state_machine.dispatch(&Event::Start),
+            };
+            let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+            while Pin::new(&mut task.dispatch).poll(&mut cx).is_pending() {
+                polls += 1;
+            }
+        }
+
+        assert_eq!(polls, 1);
+        assert_eq!(state_machine.state(), &State::running());
+        assert!(state_machine.entered_running);
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Switch {
+        origin: &'static str,
+        transitions: Vec<&'static str>,
+    }
+
+    #[state_machine(
+        initial = "State::off()",
+        origin = "origin",
+        on_transition = "Self::on_transition"
+    )]
+    impl Switch {
+        #[state]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::on()),
+            }
+        }
+
+        #[state]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+    }
+
+    impl Switch {
+        fn on_transition(&mut self, _source: &State, _target: &State) {
+            self.transitions.push(self.origin);
+        }
+    }
+
+    #[test]
+    fn test_origin_in_on_transition() {
+        let mut state_machine = Switch::default().state_machine();
+
+        state_machine.handle_with_origin(&Event::Toggle, "button");
+        state_machine.handle_with_origin(&Event::Toggle, "timer");
+        assert_eq!(state_machine.transitions, ["button", "timer"]);
+    }
+
+    #[test]
+    fn test_handle_has_blank_origin() {
+        let mut state_machine = Switch::default().state_machine();
+
+        state_machine.handle_with_origin(&Event::Toggle, "button");
+        state_machine.handle(&Event::Toggle);
+        assert_eq!(state_machine.transitions, ["button", ""]);
+        assert_eq!(state_machine.origin, "");
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(test)]
+#[cfg(feature = "async")]
+#[cfg(debug_assertions)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Open,
+        Close,
+    }
+
+    #[derive(Default)]
+    struct Connection {
+        closed: bool,
+    }
+
+    #[state_machine(
+        initial = "State::idle()",
+        drop_guard,
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Connection {
+        #[state]
+        async fn idle(event: &Event) -> Response {
+            match event {
+                Event::Open => Transition(State::open()),
+                Event::Close => Transition(State::closed()),
+            }
+        }
+
+        #[state(exit_action = "exit_open")]
+        async fn open(event: &Event) -> Response {
+            match event {
+                Event::Close => Transition(State::closed()),
+                Event::Open => Handled,
+            }
+        }
+
+        #[action]
+        async fn exit_open(&mut self) {
+            self.closed = true;
+        }
+
+        #[state(terminal)]
+        async fn closed() -> Response {
+            Handled
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "state machine was dropped without calling `shutdown`")]
+    fn test_drop_without_shutdown() {
+        let mut state_machine = Connection::default().state_machine();
+        futures::executor::block_on(state_machine.handle(&Event::Open));
+        drop(state_machine);
+    }
+
+    #[test]
+    fn test_drop_after_shutdown() {
+        let mut state_machine = Connection::default().state_machine();
+        futures::executor::block_on(async {
+            state_machine.handle(&Event::Open).await;
+            state_machine.shutdown().await;
+        });
+        assert!(state_machine.closed);
+    }
+
+    #[test]
+    fn test_drop_in_terminal_state() {
+        let mut state_machine =
+            futures::executor::block_on(Connection::default().uninitialized_state_machine().init());
+        futures::executor::block_on(state_machine.handle(&Event::Close));
+        assert_eq!(state_machine.state(), &State::closed());
+    }
+
+    #[test]
+    fn test_drop_uninitialized() {
+        let state_machine = Connection::default().state_machine();
+        drop(state_machine);
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+    use statig::DryRun;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+        Stay,
+    }
+
+    #[derive(Clone, Default)]
+    struct Machine {
+        entered: usize,
+        exited: usize,
+    }
+
+    #[state_machine(initial = "State::a()", state(derive(Clone, Eq, PartialEq, Debug)))]
+    impl Machine {
+        #[state(superstate = "left", entry_action = "enter", exit_action = "exit")]
+        fn a(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::b()),
+                Event::Stay => Handled,
+            }
+        }
+
+        #[superstate(entry_action = "enter", exit_action = "exit")]
+        fn left() -> Response {
+            Handled
+        }
+
+        #[state(entry_action = "enter", exit_action = "exit")]
+        fn b(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::a()),
+                Event::Stay => Handled,
+            }
+        }
+
+        #[action]
+        fn enter(&mut self) {
+            self.entered += 1;
+        }
+
+        #[action]
+        fn exit(&mut self) {
+            self.exited += 1;
+        }
+    }
+
+    #[test]
+    fn test_dry_run_matches_transition() {
+        let mut state_machine = Machine::default().uninitialized_state_machine().init();
+        let (entered, exited) = (state_machine.entered, state_machine.exited);
+
+        let plan = state_machine.dry_run(&Event::Next);
+        assert_eq!(
+            plan,
+            DryRun::Transition {
+                target: State::b(),
+                exit_levels: 2,
+                enter_levels: 1,
+            }
+        );
+
+        // Nothing was executed by the dry run.
+        assert_eq!(state_machine.state(), &State::a());
+        assert_eq!(state_machine.entered, entered);
+        assert_eq!(state_machine.exited, exited);
+
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::b());
+        assert_eq!(state_machine.exited - exited, 2);
+        assert_eq!(state_machine.entered - entered, 1);
+    }
+
+    #[test]
+    fn test_dry_run_without_transition() {
+        let state_machine = Machine::default().state_machine();
+
+        assert_eq!(state_machine.dry_run(&Event::Stay), DryRun::Handled);
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Press,
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        presses: usize,
+        led: bool,
+    }
+
+    impl Counter {
+        fn increment(&mut self) {
+            self.presses += 1;
+        }
+    }
+
+    #[derive(Default)]
+    struct Device {
+        name: &'static str,
+        counter: Counter,
+    }
+
+    #[state_machine(
+        initial = "State::off()",
+        shared_storage = "Device.counter",
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl Counter {
+        #[state(entry_action = "enter_off")]
+        fn off(&mut self, event: &Event) -> Response {
+            match event {
+                Event::Press => {
+                    self.increment();
+                    Transition(State::on())
+                }
+            }
+        }
+
+        #[state(shared_storage_fields("led"))]
+        fn on(led: &mut bool, event: &Event) -> Response {
+            match event {
+                Event::Press => {
+                    *led = true;
+                    Transition(State::off())
+                }
+            }
+        }
+
+        #[action]
+        fn enter_off(&mut self) {
+            self.led = false;
+        }
+    }
+
+    #[test]
+    fn test_dispatch_through_outer_struct() {
+        let mut state_machine = Device {
+            name: "button",
+            ..Default::default()
+        }
+        .state_machine();
+
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.state(), &State::on());
+        assert_eq!(state_machine.counter.presses, 1);
+
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.state(), &State::off());
+        assert!(!state_machine.counter.led);
+        assert_eq!(state_machine.name, "button");
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+#[cfg(feature = "instrumentation")]
+mod tests {
+
+    use statig::prelude::*;
+    use statig::EntryExitCounts;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Toggle,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Switch {
+        counts: EntryExitCounts<StateKind>,
+    }
+
+    #[state_machine(initial = "State::off()", entry_exit_counts = "counts")]
+    impl Switch {
+        #[state]
+        fn off(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::on()),
+                Event::Reset => Transition(State::off()),
+            }
+        }
+
+        #[state]
+        fn on(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::off()),
+                Event::Reset => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_entry_exit_counts() {
+        let mut state_machine = Switch::default().state_machine();
+
+        state_machine.init();
+        assert_eq!(state_machine.counts.entry_count(StateKind::Off), 1);
+        assert_eq!(state_machine.counts.exit_count(StateKind::Off), 0);
+
+        state_machine.handle(&Event::Toggle);
+        state_machine.handle(&Event::Toggle);
+        assert_eq!(state_machine.counts.entry_count(StateKind::On), 1);
+        assert_eq!(state_machine.counts.exit_count(StateKind::On), 1);
+        assert_eq!(state_machine.counts.entry_count(StateKind::Off), 2);
+        assert_eq!(state_machine.counts.exit_count(StateKind::Off), 1);
+    }
+
+    #[test]
+    fn test_self_transition_counts_both() {
+        let mut state_machine = Switch::default().state_machine();
+
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.counts.entry_count(StateKind::Off), 2);
+        assert_eq!(state_machine.counts.exit_count(StateKind::Off), 1);
+        assert_eq!(state_machine.counts.entry_count(StateKind::On), 0);
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    #[derive(Clone, Copy)]
+    enum Event {
+        Press,
+        Tick(u8),
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        ticks: Vec<u8>,
+    }
+
+    #[state_machine(
+        initial = "State::idle()",
+        event_ref = false,
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl Counter {
+        #[state(superstate = "running")]
+        fn idle(event: Event) -> Response {
+            match event {
+                Event::Press => Transition(State::counting()),
+                Event::Tick(_) => Super,
+            }
+        }
+
+        #[state(superstate = "running")]
+        fn counting(event: Event) -> Response {
+            match event {
+                Event::Press => Transition(State::idle()),
+                Event::Tick(_) => Super,
+            }
+        }
+
+        /// The event is copied again when it bubbles up to the superstate.
+        #[superstate]
+        fn running(&mut self, event: Event) -> Response {
+            match event {
+                Event::Tick(value) => {
+                    self.ticks.push(value);
+                    Handled
+                }
+                Event::Press => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_passed_by_value() {
+        let mut state_machine = Counter::default().state_machine();
+
+        state_machine.handle(&Event::Tick(1));
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.state(), &State::counting());
+
+        state_machine.handle(&Event::Tick(2));
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.state(), &State::idle());
+
+        assert_eq!(state_machine.ticks, [1, 2]);
+    }
+}
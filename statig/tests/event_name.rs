@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    #[derive(EventName)]
+    enum Event {
+        TimerElapsed,
+        ButtonPressed(u8),
+        Moved { x: i32, y: i32 },
+    }
+
+    #[derive(EventName)]
+    struct Tick;
+
+    #[derive(EventName)]
+    enum Generic<T> {
+        Value(T),
+    }
+
+    #[test]
+    fn test_event_name_of_variants() {
+        assert_eq!(Event::TimerElapsed.event_name(), "TimerElapsed");
+        assert_eq!(Event::ButtonPressed(1).event_name(), "ButtonPressed");
+        assert_eq!(Event::Moved { x: 1, y: 2 }.event_name(), "Moved");
+    }
+
+    #[test]
+    fn test_event_name_of_struct() {
+        assert_eq!(Tick.event_name(), "Tick");
+    }
+
+    #[test]
+    fn test_event_name_of_generic_enum() {
+        assert_eq!(Generic::Value(String::new()).event_name(), "Value");
+    }
+}
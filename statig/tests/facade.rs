@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    pub enum Event {
+        Press,
+    }
+
+    #[derive(Default)]
+    pub struct Switch {
+        presses: usize,
+    }
+
+    #[state_machine(initial = "State::off()", facade = "Driver")]
+    impl Switch {
+        #[state]
+        fn off(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Press => {
+                    self.presses += 1;
+                    Transition(State::on())
+                }
+            }
+        }
+
+        #[state]
+        fn on(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Press => {
+                    self.presses += 1;
+                    Transition(State::off())
+                }
+            }
+        }
+    }
+
+    fn press_twice(driver: &mut dyn Driver) {
+        driver.on_event(Event::Press);
+        driver.on_event(Event::Press);
+    }
+
+    #[test]
+    fn test_lazy_state_machine_through_facade() {
+        let mut state_machine = Switch::default().state_machine();
+        press_twice(&mut state_machine);
+
+        assert_eq!(state_machine.presses, 2);
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Off);
+    }
+
+    #[test]
+    fn test_initialized_state_machine_through_facade() {
+        let mut state_machine = Switch::default().uninitialized_state_machine().init();
+        let driver: &mut dyn Driver = &mut state_machine;
+        driver.on_event(Event::Press);
+
+        assert_eq!(state_machine.presses, 1);
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::On);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod async_tests {
+
+    use statig::prelude::*;
+
+    pub enum Event {
+        Press,
+    }
+
+    #[derive(Default)]
+    pub struct Switch {
+        presses: usize,
+    }
+
+    #[state_machine(initial = "State::off()", facade = "AsyncDriver")]
+    impl Switch {
+        #[state]
+        async fn off(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Press => {
+                    self.presses += 1;
+                    Transition(State::on())
+                }
+            }
+        }
+
+        #[state]
+        async fn on(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Handled,
+            }
+        }
+    }
+
+    async fn press(driver: &mut impl AsyncDriver) {
+        driver.on_event(Event::Press).await;
+    }
+
+    #[test]
+    fn test_awaitable_state_machine_through_facade() {
+        futures::executor::block_on(async {
+            let mut state_machine = Switch::default().state_machine();
+            press(&mut state_machine).await;
+            press(&mut state_machine).await;
+
+            assert_eq!(state_machine.presses, 1);
+            assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::On);
+        });
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Error {
+        Empty,
+        Jammed,
+    }
+
+    enum Event {
+        Dispense,
+        Refill(usize),
+        Jam,
+    }
+
+    #[derive(Default)]
+    struct VendingMachine {
+        error: Option<Error>,
+    }
+
+    #[state_machine(
+        initial = "State::empty()",
+        fallible = "error",
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl VendingMachine {
+        #[state(superstate = "operational")]
+        fn empty(event: &Event) -> Result<Response<State>, Error> {
+            match event {
+                Event::Dispense => Err(Error::Empty),
+                Event::Refill(items) => Ok(Transition(State::stocked(*items))),
+                _ => Ok(Super),
+            }
+        }
+
+        #[state(superstate = "operational")]
+        fn stocked(items: &mut usize, event: &Event) -> Result<Response<State>, Error> {
+            match event {
+                Event::Dispense => {
+                    *items = items.checked_sub(1).ok_or(Error::Empty)?;
+                    if *items == 0 {
+                        Ok(Transition(State::empty()))
+                    } else {
+                        Ok(Handled)
+                    }
+                }
+                _ => Ok(Super),
+            }
+        }
+
+        #[superstate]
+        fn operational(event: &Event) -> Result<Response<State>, Error> {
+            match event {
+                Event::Jam => Err(Error::Jammed),
+                _ => Ok(Handled),
+            }
+        }
+    }
+
+    #[test]
+    fn test_failing_handler_returns_error() {
+        let mut state_machine = VendingMachine::default().state_machine();
+
+        assert_eq!(
+            state_machine.try_handle(&Event::Dispense),
+            Err(Error::Empty)
+        );
+        assert_eq!(state_machine.state(), &State::empty());
+
+        assert_eq!(state_machine.try_handle(&Event::Refill(1)), Ok(()));
+        assert_eq!(state_machine.state(), &State::stocked(1));
+
+        assert_eq!(state_machine.try_handle(&Event::Dispense), Ok(()));
+        assert_eq!(state_machine.state(), &State::empty());
+    }
+
+    #[test]
+    fn test_failing_handler_does_not_transition() {
+        let mut state_machine = VendingMachine::default().state_machine();
+        state_machine.handle(&Event::Refill(0));
+
+        // The error is bubbled up with `?` before the handler can transition.
+        assert_eq!(
+            state_machine.try_handle(&Event::Dispense),
+            Err(Error::Empty)
+        );
+        assert_eq!(state_machine.state(), &State::stocked(0));
+    }
+
+    #[test]
+    fn test_failing_superstate_returns_error() {
+        let mut state_machine = VendingMachine::default().state_machine();
+        state_machine.handle(&Event::Refill(2));
+
+        assert_eq!(state_machine.try_handle(&Event::Jam), Err(Error::Jammed));
+        assert_eq!(state_machine.state(), &State::stocked(2));
+
+        // The error is taken, so it is not returned again for the next event.
+        assert_eq!(state_machine.try_handle(&Event::Dispense), Ok(()));
+        assert_eq!(state_machine.state(), &State::stocked(1));
+    }
+
+    #[test]
+    fn test_handle_clears_previous_error() {
+        let mut state_machine = VendingMachine::default().state_machine();
+
+        state_machine.handle(&Event::Dispense);
+        assert_eq!(state_machine.error, Some(Error::Empty));
+
+        // The error of the previous event is cleared before the next one is handled.
+        state_machine.handle(&Event::Refill(1));
+        assert_eq!(state_machine.error, None);
+    }
+}
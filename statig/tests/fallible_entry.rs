@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Connect,
+    }
+
+    #[derive(Default)]
+    struct Modem {
+        refuse: bool,
+        path: Vec<&'static str>,
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Eq, PartialEq, Debug)))]
+    impl Modem {
+        #[state(entry_action = "enter_idle", exit_action = "exit_idle")]
+        fn idle(event: &Event) -> Response {
+            match event {
+                Event::Connect => Transition(State::connected()),
+            }
+        }
+
+        #[superstate(entry_action = "enter_online", exit_action = "exit_online")]
+        fn online(event: &Event) -> Response {
+            match event {
+                Event::Connect => Handled,
+            }
+        }
+
+        #[state(superstate = "online", entry_action = "enter_connected")]
+        fn connected(event: &Event) -> Response {
+            match event {
+                Event::Connect => Super,
+            }
+        }
+
+        #[action]
+        fn enter_idle(&mut self) {
+            self.path.push("enter idle");
+        }
+
+        #[action]
+        fn exit_idle(&mut self) {
+            self.path.push("exit idle");
+        }
+
+        #[action]
+        fn enter_online(&mut self) {
+            self.path.push("enter online");
+        }
+
+        #[action]
+        fn exit_online(&mut self) {
+            self.path.push("exit online");
+        }
+
+        #[action]
+        fn enter_connected(&mut self) -> Result<(), ()> {
+            if self.refuse {
+                self.path.push("refuse connected");
+                Err(())
+            } else {
+                self.path.push("enter connected");
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_fallible_entry_succeeds() {
+        let mut state_machine = Modem::default().state_machine();
+        state_machine.init();
+        let start = state_machine.path.len();
+
+        state_machine.handle(&Event::Connect);
+        assert_eq!(state_machine.state(), &State::connected());
+        assert_eq!(
+            state_machine.path[start..],
+            ["exit idle", "enter online", "enter connected"]
+        );
+    }
+
+    #[test]
+    fn test_fallible_entry_fails() {
+        let mut state_machine = Modem {
+            refuse: true,
+            ..Default::default()
+        }
+        .state_machine();
+        state_machine.init();
+        let start = state_machine.path.len();
+
+        state_machine.handle(&Event::Connect);
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(
+            state_machine.path[start..],
+            [
+                "exit idle",
+                "enter online",
+                "refuse connected",
+                "exit online",
+                "enter idle"
+            ]
+        );
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Finish,
+        Restart(State),
+    }
+
+    #[derive(Default)]
+    struct Job;
+
+    #[state_machine(
+        initial = "State::running()",
+        state(derive(Clone, Eq, PartialEq, Debug))
+    )]
+    impl Job {
+        #[state]
+        fn running(event: &Event) -> Response {
+            match event {
+                Event::Finish => Transition(State::done()),
+                Event::Restart(_) => Handled,
+            }
+        }
+
+        #[state(final)]
+        fn done(event: &Event) -> Response {
+            match event {
+                // The target is only known at runtime, so this is checked in debug builds.
+                Event::Restart(target) => Transition(target.clone()),
+                Event::Finish => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_final_state_handles_events() {
+        let mut state_machine = Job.state_machine();
+
+        state_machine.handle(&Event::Finish);
+        state_machine.handle(&Event::Finish);
+        assert_eq!(state_machine.state(), &State::done());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "final state `done` can not transition")]
+    fn test_dynamic_transition_out_of_final_state() {
+        let mut state_machine = Job.state_machine();
+
+        state_machine.handle(&Event::Finish);
+        state_machine.handle(&Event::Restart(State::running()));
+    }
+}
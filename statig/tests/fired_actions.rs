@@ -0,0 +1,94 @@
+#[cfg(test)]
+#[cfg(feature = "instrumentation")]
+mod tests {
+
+    use statig::prelude::*;
+    use statig::{FiredActions, Outcome};
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+        Stay,
+    }
+
+    #[derive(Default)]
+    struct Machine {
+        fired: FiredActions,
+    }
+
+    #[state_machine(initial = "State::a()", fired_actions = "fired")]
+    impl Machine {
+        #[state(superstate = "left", entry_action = "enter_a", exit_action = "exit_a")]
+        fn a(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::b()),
+                Event::Stay => Handled,
+            }
+        }
+
+        #[superstate(entry_action = "enter_left", exit_action = "exit_left")]
+        fn left() -> Response {
+            Handled
+        }
+
+        #[state(superstate = "right", entry_action = "enter_b")]
+        fn b(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::a()),
+                Event::Stay => Handled,
+            }
+        }
+
+        #[superstate(entry_action = "enter_right")]
+        fn right() -> Response {
+            Handled
+        }
+
+        #[action]
+        fn enter_a(&mut self) {}
+
+        #[action]
+        fn exit_a(&mut self) {}
+
+        #[action]
+        fn enter_left(&mut self) {}
+
+        #[action]
+        fn exit_left(&mut self) {}
+
+        #[action]
+        fn enter_b(&mut self) {}
+
+        #[action]
+        fn enter_right(&mut self) {}
+    }
+
+    #[test]
+    fn test_fired_actions_across_superstates() {
+        let mut state_machine = Machine::default().state_machine();
+
+        assert_eq!(
+            state_machine.handle_recording(&Event::Next),
+            (
+                Outcome::Transition,
+                vec!["exit_a", "exit_left", "enter_right", "enter_b"]
+            )
+        );
+        assert_eq!(
+            state_machine.handle_recording(&Event::Next),
+            (Outcome::Transition, vec!["enter_left", "enter_a"])
+        );
+    }
+
+    #[test]
+    fn test_handled_fires_no_actions() {
+        let mut state_machine = Machine::default().state_machine();
+
+        // The entry actions of the initialization are not included.
+        assert_eq!(
+            state_machine.handle_recording(&Event::Stay),
+            (Outcome::Handled, vec![])
+        );
+    }
+}
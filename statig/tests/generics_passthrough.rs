@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event<T> {
+        Push(T),
+        Lock,
+    }
+
+    #[derive(Default)]
+    struct Buffer<T> {
+        items: Vec<T>,
+    }
+
+    #[state_machine(
+        initial = "State::open()",
+        generics_passthrough,
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl<T> Buffer<T>
+    where
+        T: Clone,
+    {
+        #[state]
+        fn open(&mut self, event: &Event<T>) -> Response<State<T>> {
+            match event {
+                Event::Push(item) => {
+                    self.items.push(item.clone());
+                    Handled
+                }
+                Event::Lock => Transition(State::locked()),
+            }
+        }
+
+        #[state]
+        fn locked(event: &Event<T>) -> Response<State<T>> {
+            match event {
+                Event::Push(_) => Handled,
+                Event::Lock => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_handle_generic_machine() {
+        let mut state_machine = Buffer::default().state_machine();
+
+        state_machine.handle(&Event::Push(1u8));
+        state_machine.handle(&Event::Lock);
+        state_machine.handle(&Event::Push(2));
+
+        assert_eq!(state_machine.items, [1]);
+        assert_eq!(state_machine.state(), &State::locked());
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Player;
+
+    #[state_machine(initial = "State::idle()", export_graph)]
+    impl Player {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::playing()),
+            }
+        }
+
+        #[state(superstate = "running")]
+        fn playing(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::paused()),
+            }
+        }
+
+        #[state(superstate = "active")]
+        fn paused(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::idle()),
+            }
+        }
+
+        #[superstate(superstate = "active")]
+        fn running(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Super,
+            }
+        }
+
+        #[superstate]
+        fn active(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Handled,
+            }
+        }
+    }
+
+    const GRAPH: &str = Player::graphviz();
+
+    #[test]
+    fn test_graphviz() {
+        assert_eq!(
+            GRAPH,
+            concat!(
+                "digraph \"Player\" {\n",
+                "    subgraph \"cluster_active\" {\n",
+                "        label=\"active\";\n",
+                "        \"active\" [shape=box];\n",
+                "        subgraph \"cluster_running\" {\n",
+                "            label=\"running\";\n",
+                "            \"running\" [shape=box];\n",
+                "            \"playing\";\n",
+                "        }\n",
+                "        \"paused\";\n",
+                "    }\n",
+                "    \"idle\";\n",
+                "    \"paused\" -> \"active\" [style=dashed];\n",
+                "    \"playing\" -> \"running\" [style=dashed];\n",
+                "    \"running\" -> \"active\" [style=dashed];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_graphviz_is_balanced() {
+        assert_eq!(GRAPH.matches('{').count(), GRAPH.matches('}').count());
+        assert_eq!(GRAPH.matches('"').count() % 2, 0);
+    }
+}
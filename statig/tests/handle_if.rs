@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Traffic;
+
+    #[state_machine(initial = "State::red()")]
+    impl Traffic {
+        #[state]
+        fn red(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::green()),
+            }
+        }
+
+        #[state]
+        fn green(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::amber()),
+            }
+        }
+
+        #[state]
+        fn amber(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::red()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_handle_if() {
+        let mut state_machine = Traffic.state_machine();
+
+        let handled =
+            state_machine.handle_if(|kind: StateKind| kind == StateKind::Green, &Event::Next);
+        assert!(!handled);
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Red);
+
+        let handled =
+            state_machine.handle_if(|kind: StateKind| kind == StateKind::Red, &Event::Next);
+        assert!(handled);
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Green);
+    }
+
+    #[test]
+    fn test_initialized_handle_if() {
+        let mut state_machine = Traffic.uninitialized_state_machine().init();
+
+        let mut checked = Vec::new();
+        for _ in 0..4 {
+            state_machine.handle_if(
+                |kind: StateKind| {
+                    checked.push(kind);
+                    kind != StateKind::Amber
+                },
+                &Event::Next,
+            );
+        }
+
+        assert_eq!(
+            checked,
+            [
+                StateKind::Red,
+                StateKind::Green,
+                StateKind::Amber,
+                StateKind::Amber
+            ]
+        );
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+    }
+
+    struct Switch;
+
+    #[state_machine(initial = "State::off()", state(derive(Eq, PartialEq, Debug)))]
+    impl Switch {
+        #[state]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::on()),
+            }
+        }
+
+        #[state]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_owned_and_borrowed_events() {
+        let mut state_machine = Switch.state_machine();
+
+        state_machine.handle_ref_event(Event::Toggle);
+        assert_eq!(state_machine.state(), &State::on());
+
+        let event = Event::Toggle;
+        state_machine.handle_ref_event(&event);
+        assert_eq!(state_machine.state(), &State::off());
+
+        let mut state_machine = Switch.uninitialized_state_machine().init();
+
+        state_machine.handle_ref_event(&event);
+        assert_eq!(state_machine.state(), &State::on());
+
+        state_machine.handle_ref_event(event);
+        assert_eq!(state_machine.state(), &State::off());
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Play,
+        Pause,
+        Stop,
+    }
+
+    #[derive(Default)]
+    struct Player;
+
+    #[state_machine(initial = "State::stopped()")]
+    impl Player {
+        #[state]
+        fn stopped(event: &Event) -> Response {
+            match event {
+                Event::Play => Transition(State::playing()),
+                Event::Pause | Event::Stop => Handled,
+            }
+        }
+
+        #[state]
+        fn playing(event: &Event) -> Response {
+            match event {
+                Event::Pause => Transition(State::paused()),
+                Event::Stop => Transition(State::stopped()),
+                _ => Handled,
+            }
+        }
+
+        #[state]
+        fn paused(event: &Event) -> Response {
+            #[allow(clippy::match_single_binding)]
+            match event {
+                _ => Transition(State::playing()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_handled_events() {
+        assert_eq!(
+            State::handled_events(StateKind::Stopped),
+            ["Play", "Pause", "Stop"]
+        );
+        assert_eq!(
+            State::handled_events(StateKind::Playing),
+            ["Pause", "Stop", "_"]
+        );
+    }
+
+    #[test]
+    fn test_handled_events_wildcard_only() {
+        assert_eq!(State::handled_events(StateKind::Paused), ["_"]);
+    }
+}
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        TimerElapsed,
+    }
+
+    #[derive(Default)]
+    struct Blinky {
+        led: bool,
+        resets: usize,
+    }
+
+    #[state_machine(
+        initial = "State::led_on()",
+        actions(reset_led = "self.led = false; self.resets += 1")
+    )]
+    impl Blinky {
+        #[state(entry_action = "reset_led")]
+        fn led_on(&mut self, event: &Event) -> Response {
+            self.led = true;
+            match event {
+                Event::TimerElapsed => Transition(State::led_off()),
+            }
+        }
+
+        #[state(entry_action = "reset_led")]
+        fn led_off(event: &Event) -> Response {
+            match event {
+                Event::TimerElapsed => Transition(State::led_on()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_inline_action_shared_by_states() {
+        let mut state_machine = Blinky::default().state_machine();
+
+        state_machine.handle(&Event::TimerElapsed);
+        assert!(!state_machine.led);
+        assert_eq!(state_machine.resets, 2);
+
+        state_machine.handle(&Event::TimerElapsed);
+        assert_eq!(state_machine.resets, 3);
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Byte(u8),
+        End,
+    }
+
+    #[derive(Default)]
+    struct Receiver {
+        buffer: Vec<u8>,
+    }
+
+    #[state_machine(initial = "State::receiving()")]
+    impl Receiver {
+        #[state]
+        fn receiving(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Byte(byte) => {
+                    self.buffer.push(*byte);
+                    Handled
+                }
+                Event::End => Transition(State::done()),
+            }
+        }
+
+        #[state]
+        fn done() -> Response<State> {
+            Handled
+        }
+    }
+
+    #[test]
+    fn test_into_storage() {
+        let mut state_machine = Receiver::default().state_machine();
+
+        state_machine.handle(&Event::Byte(1));
+        state_machine.handle(&Event::Byte(2));
+        state_machine.handle(&Event::End);
+
+        let receiver = state_machine.into_storage();
+        assert_eq!(receiver.buffer, [1, 2]);
+    }
+
+    #[test]
+    fn test_initialized_into_storage() {
+        let mut state_machine = Receiver::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Byte(3));
+
+        let receiver = state_machine.into_storage();
+        assert_eq!(receiver.buffer, [3]);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod awaitable {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Byte(u8),
+    }
+
+    #[derive(Default)]
+    struct Receiver {
+        buffer: Vec<u8>,
+    }
+
+    #[state_machine(initial = "State::receiving()", drop_guard)]
+    impl Receiver {
+        #[state]
+        async fn receiving(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Byte(byte) => {
+                    self.buffer.push(*byte);
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_into_storage() {
+        let mut state_machine = Receiver::default().state_machine();
+        futures::executor::block_on(state_machine.handle(&Event::Byte(1)));
+
+        let receiver = state_machine.into_storage();
+        assert_eq!(receiver.buffer, [1]);
+    }
+
+    #[test]
+    fn test_initialized_into_storage() {
+        let mut state_machine =
+            futures::executor::block_on(Receiver::default().uninitialized_state_machine().init());
+        futures::executor::block_on(state_machine.handle(&Event::Byte(2)));
+
+        let receiver = state_machine.into_storage();
+        assert_eq!(receiver.buffer, [2]);
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+
+    use serde_json::{json, Value};
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        TimerElapsed,
+        ButtonPressed,
+    }
+
+    #[derive(Default)]
+    struct Blinky;
+
+    #[state_machine(initial = "State::led_on()", json)]
+    impl Blinky {
+        #[state(superstate = "blinking", entry_action = "enter_led_on")]
+        fn led_on(event: &Event) -> Response {
+            match event {
+                Event::TimerElapsed => Transition(State::led_off()),
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "blinking")]
+        fn led_off(event: &Event) -> Response {
+            match event {
+                Event::TimerElapsed => Transition(State::led_on()),
+                _ => Super,
+            }
+        }
+
+        #[superstate]
+        fn blinking(event: &Event) -> Response {
+            match event {
+                Event::ButtonPressed => Transition(State::not_blinking()),
+                _ => Super,
+            }
+        }
+
+        #[state]
+        fn not_blinking(event: &Event) -> Response {
+            match event {
+                Event::ButtonPressed => Transition(State::led_on()),
+                _ => Super,
+            }
+        }
+
+        #[action]
+        fn enter_led_on() {}
+    }
+
+    #[test]
+    fn test_machine_json() {
+        let description: Value = serde_json::from_str(Blinky::MACHINE_JSON).unwrap();
+
+        let states: Vec<&str> = description["states"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|state| state["name"].as_str().unwrap())
+            .collect();
+
+        assert_eq!(states, ["led_off", "led_on", "not_blinking"]);
+        assert_eq!(description["initial"], json!("led_on"));
+        assert_eq!(description["actions"], json!(["enter_led_on"]));
+        assert_eq!(
+            description["states"][1],
+            json!({
+                "name": "led_on",
+                "superstate": "blinking",
+                "entry_action": "enter_led_on",
+                "exit_action": null,
+                "transitions": ["led_off"]
+            })
+        );
+        assert_eq!(
+            description["superstates"],
+            json!([{
+                "name": "blinking",
+                "superstate": null,
+                "initial": null,
+                "entry_action": null,
+                "exit_action": null,
+                "transitions": ["not_blinking"]
+            }])
+        );
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Tick,
+        Reset,
+    }
+
+    struct Counter;
+
+    #[state_machine(initial = "State::counting(2, 0)", state(derive(Debug, PartialEq, Eq)))]
+    impl Counter {
+        #[state(local_storage_by_value)]
+        fn counting(limit: u8, count: &mut u8, event: &Event) -> Response<State> {
+            match event {
+                Event::Tick if *count + 1 == limit => Transition(State::done(limit)),
+                Event::Tick => {
+                    *count += 1;
+                    Handled
+                }
+                Event::Reset => Handled,
+            }
+        }
+
+        #[state(local_storage_by_value)]
+        fn done(limit: u8, event: &Event) -> Response<State> {
+            match event {
+                Event::Reset => Transition(State::counting(limit, 0)),
+                Event::Tick => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed_by_value_and_by_reference_storage() {
+        let mut state_machine = Counter.state_machine();
+
+        state_machine.handle(&Event::Tick);
+        assert_eq!(state_machine.state(), &State::counting(2, 1));
+
+        state_machine.handle(&Event::Tick);
+        assert_eq!(state_machine.state(), &State::done(2));
+
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.state(), &State::counting(2, 0));
+    }
+}
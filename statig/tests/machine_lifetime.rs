@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Start,
+        Step,
+    }
+
+    struct Scanner<'sm> {
+        input: &'sm [u8],
+        scanned: usize,
+    }
+
+    #[state_machine(
+        initial = "State::idle()",
+        lifetime = "'sm",
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl<'sm> Scanner<'sm> {
+        #[state]
+        fn idle(&mut self, event: &Event) -> Response<State<'sm>> {
+            match event {
+                Event::Start => Transition(State::scanning(self.input)),
+                Event::Step => Handled,
+            }
+        }
+
+        // The local storage borrows the input, but the handler does not name the lifetime.
+        #[state(local_storage("rest: &'sm [u8]"))]
+        fn scanning(&mut self, rest: &mut &[u8]) -> Response<State<'sm>> {
+            match rest.split_first() {
+                Some((_, tail)) => {
+                    self.scanned += 1;
+                    *rest = tail;
+                    Handled
+                }
+                None => Transition(State::done()),
+            }
+        }
+
+        #[state]
+        fn done() -> Response<State<'sm>> {
+            Handled
+        }
+    }
+
+    #[test]
+    fn test_machine_borrows_external_slice() {
+        let buffer = [1, 2];
+        let mut state_machine = Scanner {
+            input: &buffer,
+            scanned: 0,
+        }
+        .state_machine();
+
+        state_machine.handle(&Event::Start);
+        assert_eq!(state_machine.state(), &State::scanning(&[1, 2]));
+
+        state_machine.handle(&Event::Step);
+        assert_eq!(state_machine.state(), &State::scanning(&[2]));
+
+        state_machine.handle(&Event::Step);
+        state_machine.handle(&Event::Step);
+        assert_eq!(state_machine.state(), &State::done());
+        assert_eq!(state_machine.scanned, 2);
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Foo;
+
+    #[state_machine(initial = "State::s111()")]
+    impl Foo {
+        #[state(superstate = "s11")]
+        fn s111(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::s12()),
+            }
+        }
+
+        #[state(superstate = "s1")]
+        fn s12(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::s2()),
+            }
+        }
+
+        #[superstate(superstate = "s1")]
+        fn s11() -> Response {
+            Super
+        }
+
+        #[superstate]
+        fn s1() -> Response {
+            Super
+        }
+
+        #[state]
+        fn s2(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::s111()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_depth() {
+        assert_eq!(State::MAX_DEPTH, 2);
+    }
+
+    #[test]
+    fn test_ancestors_fit_in_buffer() {
+        let state = State::s111();
+
+        let mut buffer = [SuperstateKind::S1; State::MAX_DEPTH];
+        let mut len = 0;
+        for (slot, ancestor) in buffer.iter_mut().zip(state.ancestors()) {
+            *slot = ancestor;
+            len += 1;
+        }
+
+        assert_eq!(&buffer[..len], [SuperstateKind::S11, SuperstateKind::S1]);
+    }
+}
+
+#[cfg(test)]
+mod flat {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Switch;
+
+    #[state_machine(initial = "State::off()")]
+    impl Switch {
+        #[state]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::on()),
+            }
+        }
+
+        #[state]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_depth_without_superstates() {
+        assert_eq!(State::MAX_DEPTH, 0);
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Blinky;
+
+    #[state_machine(initial = "State::on()", minimal, state(derive(Debug, PartialEq, Eq)))]
+    impl Blinky {
+        #[state(superstate = "blinking")]
+        fn on(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+
+        #[state(superstate = "blinking")]
+        fn off(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::on()),
+            }
+        }
+
+        #[superstate]
+        fn blinking() -> Response {
+            Super
+        }
+    }
+
+    #[test]
+    fn test_minimal_state_machine() {
+        let mut state_machine = Blinky::default().state_machine();
+
+        state_machine.handle(&Event::Toggle);
+
+        assert_eq!(state_machine.state(), &State::off());
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Tick,
+    }
+
+    trait Motor {
+        const STEPS: usize;
+
+        fn step(&mut self);
+    }
+
+    struct Stepper<M: Motor> {
+        motor: M,
+    }
+
+    #[state_machine(
+        initial = "State::moving(M::STEPS)",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl<M: Motor> Stepper<M> {
+        #[state]
+        fn moving(&mut self, remaining: &mut usize, event: &Event) -> Response {
+            match event {
+                Event::Tick => {
+                    self.motor.step();
+                    *remaining -= 1;
+                    match remaining {
+                        0 => Transition(State::stopped()),
+                        _ => Handled,
+                    }
+                }
+            }
+        }
+
+        #[state]
+        fn stopped(event: &Event) -> Response {
+            match event {
+                Event::Tick => Handled,
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct MockMotor {
+        steps: usize,
+    }
+
+    impl Motor for MockMotor {
+        const STEPS: usize = 2;
+
+        fn step(&mut self) {
+            self.steps += 1;
+        }
+    }
+
+    #[test]
+    fn test_mock_storage() {
+        let mut state_machine = Stepper {
+            motor: MockMotor::default(),
+        }
+        .state_machine();
+
+        assert_eq!(state_machine.state(), &State::moving(MockMotor::STEPS));
+
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Tick);
+
+        assert_eq!(state_machine.state(), &State::stopped());
+        assert_eq!(state_machine.motor.steps, 2);
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Blocking;
+
+    #[state_machine(
+        initial = "State::on()",
+        mode = "blocking",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Blocking {
+        #[state]
+        fn on(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+
+        #[state]
+        fn off(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::on()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_forced_blocking_mode() {
+        let mut state_machine: statig::blocking::StateMachine<Blocking> =
+            Blocking::default().state_machine();
+
+        state_machine.handle(&Event::Toggle);
+
+        assert_eq!(state_machine.state(), &State::off());
+    }
+
+    #[cfg(feature = "async")]
+    mod awaitable {
+        use statig::prelude::*;
+
+        type Response = statig::Response<State>;
+
+        enum Event {
+            Toggle,
+        }
+
+        #[derive(Default)]
+        struct Awaitable;
+
+        #[state_machine(
+            initial = "State::on()",
+            mode = "awaitable",
+            state(derive(Debug, PartialEq, Eq))
+        )]
+        impl Awaitable {
+            #[state]
+            fn on(event: &Event) -> Response {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                }
+            }
+
+            #[state]
+            fn off(event: &Event) -> Response {
+                match event {
+                    Event::Toggle => Transition(State::on()),
+                }
+            }
+        }
+
+        #[test]
+        fn test_forced_awaitable_mode() {
+            let future = async move {
+                let mut state_machine: statig::awaitable::StateMachine<Awaitable> =
+                    Awaitable::default().state_machine();
+
+                state_machine.handle(&Event::Toggle).await;
+
+                assert_eq!(state_machine.state(), &State::off());
+            };
+
+            futures::executor::block_on(future);
+        }
+    }
+}
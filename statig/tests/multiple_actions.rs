@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Machine {
+        calls: Vec<&'static str>,
+    }
+
+    #[state_machine(initial = "State::a()")]
+    impl Machine {
+        #[state(
+            superstate = "outer",
+            entry_action = "open_a",
+            entry_action = "start_a",
+            exit_actions(close_a, stop_a)
+        )]
+        fn a(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::b()),
+            }
+        }
+
+        #[superstate(
+            entry_actions(open_outer, start_outer),
+            exit_actions(close_outer, stop_outer)
+        )]
+        fn outer() -> Response {
+            Handled
+        }
+
+        #[state]
+        fn b() -> Response {
+            Handled
+        }
+
+        #[action]
+        fn open_a(&mut self) {
+            self.calls.push("open_a");
+        }
+
+        #[action]
+        fn start_a(&mut self) {
+            self.calls.push("start_a");
+        }
+
+        #[action]
+        fn close_a(&mut self) {
+            self.calls.push("close_a");
+        }
+
+        #[action]
+        fn stop_a(&mut self) {
+            self.calls.push("stop_a");
+        }
+
+        #[action]
+        fn open_outer(&mut self) {
+            self.calls.push("open_outer");
+        }
+
+        #[action]
+        fn start_outer(&mut self) {
+            self.calls.push("start_outer");
+        }
+
+        #[action]
+        fn close_outer(&mut self) {
+            self.calls.push("close_outer");
+        }
+
+        #[action]
+        fn stop_outer(&mut self) {
+            self.calls.push("stop_outer");
+        }
+    }
+
+    #[test]
+    fn test_entry_actions_run_in_order() {
+        let mut state_machine = Machine::default().state_machine();
+        state_machine.init();
+
+        assert_eq!(
+            state_machine.calls,
+            ["open_outer", "start_outer", "open_a", "start_a"]
+        );
+    }
+
+    #[test]
+    fn test_exit_actions_run_in_reverse_order() {
+        let mut state_machine = Machine::default().state_machine();
+        state_machine.init();
+        state_machine.calls.clear();
+
+        state_machine.handle(&Event::Next);
+
+        assert_eq!(
+            state_machine.calls,
+            ["stop_a", "close_a", "stop_outer", "close_outer"]
+        );
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Append(char),
+        Submit,
+    }
+
+    #[derive(Default)]
+    struct Form {
+        submitted: Vec<String>,
+    }
+
+    #[state_machine(
+        initial = "State::editing(String::new())",
+        state(derive(Clone, Eq, PartialEq, Debug))
+    )]
+    impl Form {
+        #[state]
+        fn editing(&mut self, text: &mut String, event: &Event) -> Response<State> {
+            match event {
+                Event::Append(c) => {
+                    text.push(*c);
+                    Handled
+                }
+                Event::Submit => {
+                    self.submitted.push(text.clone());
+                    Transition(State::editing(String::new()))
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_clone_state_with_string_field() {
+        let mut state_machine = Form::default().state_machine();
+
+        state_machine.handle(&Event::Append('h'));
+        state_machine.handle(&Event::Append('i'));
+        let snapshot = state_machine.state().clone();
+        assert_eq!(snapshot, State::editing(String::from("hi")));
+
+        state_machine.handle(&Event::Submit);
+        assert_eq!(state_machine.submitted, ["hi"]);
+        assert_eq!(state_machine.state(), &State::editing(String::new()));
+    }
+}
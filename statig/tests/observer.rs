@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+    use statig::Observer;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Toggle,
+        Check,
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<String>,
+    }
+
+    impl Observer<Light> for Recorder {
+        fn on_dispatch(
+            &mut self,
+            state_or_superstate: StateOrSuperstate<'_, '_, Light>,
+            _event: &Event,
+        ) {
+            match state_or_superstate {
+                StateOrSuperstate::State(state) => {
+                    self.calls.push(format!("dispatch {:?}", state.kind()))
+                }
+                StateOrSuperstate::Superstate(_) => self.calls.push("dispatch superstate".into()),
+            }
+        }
+
+        fn on_transition(&mut self, source: &State, target: &State) {
+            self.calls.push(format!(
+                "transition {:?} -> {:?}",
+                source.kind(),
+                target.kind()
+            ));
+        }
+    }
+
+    #[derive(Default)]
+    struct Light {
+        observer: Recorder,
+    }
+
+    #[state_machine(
+        initial = "State::off()",
+        observer = "observer",
+        on_transition = "Self::on_transition"
+    )]
+    impl Light {
+        #[state]
+        fn off(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::on()),
+                Event::Check => Handled,
+            }
+        }
+
+        #[state(superstate = "powered")]
+        fn on(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::off()),
+                Event::Check => Super,
+            }
+        }
+
+        #[superstate]
+        fn powered(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Super,
+                Event::Check => Handled,
+            }
+        }
+    }
+
+    impl Light {
+        fn on_transition(&mut self, _source: &State, _target: &State) {
+            self.observer.calls.push("callback".into());
+        }
+    }
+
+    #[test]
+    fn test_observer_call_order() {
+        let mut state_machine = Light::default().state_machine();
+
+        state_machine.handle(&Event::Toggle);
+        state_machine.handle(&Event::Check);
+
+        assert_eq!(
+            state_machine.observer.calls,
+            [
+                "dispatch Off",
+                "transition Off -> On",
+                "callback",
+                "dispatch On",
+                "dispatch superstate",
+            ]
+        );
+    }
+
+    mod defaults {
+        use statig::prelude::*;
+        use statig::Observer;
+
+        /// Observer that only cares about transitions.
+        #[derive(Default)]
+        struct Counter {
+            transitions: usize,
+        }
+
+        impl Observer<Switch> for Counter {
+            fn on_transition(&mut self, _source: &State, _target: &State) {
+                self.transitions += 1;
+            }
+        }
+
+        #[derive(Default)]
+        struct Switch {
+            counter: Counter,
+        }
+
+        #[state_machine(initial = "State::off()", observer = "counter")]
+        impl Switch {
+            #[state]
+            fn off() -> Response<State> {
+                Transition(State::on())
+            }
+
+            #[state]
+            fn on() -> Response<State> {
+                Transition(State::off())
+            }
+        }
+
+        #[test]
+        fn test_observer_defaults() {
+            let mut state_machine = Switch::default().state_machine();
+
+            state_machine.handle(&());
+            state_machine.handle(&());
+
+            assert_eq!(state_machine.counter.transitions, 2);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod tests {
+
+    pub enum Event {
+        Start,
+        Tick,
+        Connect,
+        Stop,
+    }
+
+    mod display_region {
+        use super::Event;
+        use statig::prelude::*;
+
+        pub struct Display;
+
+        #[state_machine(initial = "State::off()", state(derive(Clone, Eq, PartialEq, Debug)))]
+        impl Display {
+            #[state]
+            fn off(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::on()),
+                    _ => Handled,
+                }
+            }
+
+            #[state]
+            fn on(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::off()),
+                    _ => Handled,
+                }
+            }
+        }
+    }
+
+    mod network_region {
+        use super::Event;
+        use statig::prelude::*;
+
+        pub struct Network;
+
+        #[state_machine(
+            initial = "State::disconnected()",
+            state(derive(Clone, Eq, PartialEq, Debug))
+        )]
+        impl Network {
+            #[state]
+            fn disconnected(event: &Event) -> Response<State> {
+                match event {
+                    Event::Connect => Transition(State::connected()),
+                    _ => Handled,
+                }
+            }
+
+            #[state]
+            fn connected() -> Response<State> {
+                Handled
+            }
+        }
+    }
+
+    use display_region::Display;
+    use network_region::Network;
+    use statig::blocking::StateMachine;
+    use statig::prelude::*;
+
+    struct Device;
+
+    #[state_machine(initial = "State::idle()")]
+    impl Device {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Transition(State::operating(
+                    Display.state_machine(),
+                    Network.state_machine(),
+                )),
+                _ => Handled,
+            }
+        }
+
+        #[state(parallel)]
+        fn operating(
+            display: &mut StateMachine<Display>,
+            network: &mut StateMachine<Network>,
+            event: &Event,
+        ) -> Response<State> {
+            match event {
+                // Both regions have already handled the event at this point.
+                Event::Stop
+                    if display.state() == &display_region::State::off()
+                        && network.state() == &network_region::State::connected() =>
+                {
+                    Transition(State::idle())
+                }
+                _ => Handled,
+            }
+        }
+    }
+
+    fn region_states(
+        state_machine: &StateMachine<Device>,
+    ) -> (display_region::State, network_region::State) {
+        match state_machine.state() {
+            State::Operating { display, network } => {
+                (display.state().clone(), network.state().clone())
+            }
+            State::Idle {} => panic!("state machine is not operating"),
+        }
+    }
+
+    #[test]
+    fn test_regions_advance_independently() {
+        let mut state_machine = Device.state_machine();
+
+        state_machine.handle(&Event::Start);
+        assert_eq!(
+            region_states(&state_machine),
+            (
+                display_region::State::off(),
+                network_region::State::disconnected()
+            )
+        );
+
+        state_machine.handle(&Event::Tick);
+        assert_eq!(
+            region_states(&state_machine),
+            (
+                display_region::State::on(),
+                network_region::State::disconnected()
+            )
+        );
+
+        state_machine.handle(&Event::Connect);
+        assert_eq!(
+            region_states(&state_machine),
+            (
+                display_region::State::on(),
+                network_region::State::connected()
+            )
+        );
+
+        state_machine.handle(&Event::Tick);
+        assert_eq!(
+            region_states(&state_machine),
+            (
+                display_region::State::off(),
+                network_region::State::connected()
+            )
+        );
+    }
+
+    #[test]
+    fn test_leaving_parallel_state_drops_regions() {
+        let mut state_machine = Device.state_machine();
+
+        state_machine.handle(&Event::Start);
+        state_machine.handle(&Event::Stop);
+        assert_eq!(state_machine.state().kind(), StateKind::Operating);
+
+        state_machine.handle(&Event::Connect);
+        state_machine.handle(&Event::Stop);
+        assert_eq!(state_machine.state().kind(), StateKind::Idle);
+
+        state_machine.handle(&Event::Start);
+        assert_eq!(
+            region_states(&state_machine),
+            (
+                display_region::State::off(),
+                network_region::State::disconnected()
+            )
+        );
+    }
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+
+    pub enum Event {
+        Tick,
+        Stop,
+    }
+
+    /// The exit actions of all state machines are logged in the context they share.
+    pub type Log = Vec<&'static str>;
+
+    mod left_region {
+        use super::{Event, Log};
+        use statig::prelude::*;
+
+        pub struct Left;
+
+        #[state_machine(initial = "State::a()")]
+        impl Left {
+            #[state(exit_action = "exit_a")]
+            fn a(event: &Event, context: &mut Log) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::b()),
+                    Event::Stop => Handled,
+                }
+            }
+
+            #[state(exit_action = "exit_b")]
+            fn b(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::a()),
+                    Event::Stop => Handled,
+                }
+            }
+
+            #[action]
+            fn exit_a(context: &mut Log) {
+                context.push("a");
+            }
+
+            #[action]
+            fn exit_b(context: &mut Log) {
+                context.push("b");
+            }
+        }
+    }
+
+    mod right_region {
+        use super::{Event, Log};
+        use statig::prelude::*;
+
+        pub struct Right;
+
+        #[state_machine(initial = "State::x()")]
+        impl Right {
+            #[state(exit_action = "exit_x")]
+            fn x(event: &Event, context: &mut Log) -> Response<State> {
+                match event {
+                    Event::Tick | Event::Stop => Handled,
+                }
+            }
+
+            #[action]
+            fn exit_x(context: &mut Log) {
+                context.push("x");
+            }
+        }
+    }
+
+    use left_region::Left;
+    use right_region::Right;
+    use statig::blocking::StateMachine;
+    use statig::prelude::*;
+
+    struct Device;
+
+    #[state_machine(initial = "State::running(StateMachine::new(Left), StateMachine::new(Right))")]
+    impl Device {
+        #[state(parallel, exit_action = "exit_running")]
+        fn running(
+            left: &mut StateMachine<Left>,
+            right: &mut StateMachine<Right>,
+            event: &Event,
+            context: &mut Log,
+        ) -> Response<State> {
+            match event {
+                Event::Tick => Handled,
+                Event::Stop => Transition(State::stopped()),
+            }
+        }
+
+        #[state]
+        fn stopped(event: &Event) -> Response<State> {
+            match event {
+                Event::Tick | Event::Stop => Handled,
+            }
+        }
+
+        #[action]
+        fn exit_running(context: &mut Log) {
+            context.push("running");
+        }
+    }
+
+    #[test]
+    fn test_leaving_parallel_state_exits_regions() {
+        let mut log = Log::new();
+        let mut state_machine = Device.state_machine();
+
+        state_machine.handle_with_context(&Event::Tick, &mut log);
+        assert_eq!(log, ["a"]);
+        log.clear();
+
+        state_machine.handle_with_context(&Event::Stop, &mut log);
+        assert_eq!(log, ["b", "x", "running"]);
+    }
+}
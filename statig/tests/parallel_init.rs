@@ -0,0 +1,238 @@
+#[cfg(test)]
+mod tests {
+
+    pub enum Event {
+        Tick,
+    }
+
+    mod left_region {
+        use super::Event;
+        use statig::prelude::*;
+
+        #[derive(Default)]
+        pub struct Left {
+            pub entered: Vec<&'static str>,
+        }
+
+        impl Left {
+            pub const fn new() -> Self {
+                Self {
+                    entered: Vec::new(),
+                }
+            }
+        }
+
+        #[state_machine(initial = "State::a()", state(derive(Eq, PartialEq, Debug)))]
+        impl Left {
+            #[state(entry_action = "enter_a")]
+            fn a(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::b()),
+                }
+            }
+
+            #[state(entry_action = "enter_b")]
+            fn b(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::a()),
+                }
+            }
+
+            #[action]
+            fn enter_a(&mut self) {
+                self.entered.push("a");
+            }
+
+            #[action]
+            fn enter_b(&mut self) {
+                self.entered.push("b");
+            }
+        }
+    }
+
+    mod right_region {
+        use super::Event;
+        use statig::prelude::*;
+
+        #[derive(Default)]
+        pub struct Right {
+            pub entered: Vec<&'static str>,
+        }
+
+        impl Right {
+            pub const fn new() -> Self {
+                Self {
+                    entered: Vec::new(),
+                }
+            }
+        }
+
+        #[state_machine(initial = "State::x()", state(derive(Eq, PartialEq, Debug)))]
+        impl Right {
+            #[state(entry_action = "enter_x")]
+            fn x(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Handled,
+                }
+            }
+
+            #[action]
+            fn enter_x(&mut self) {
+                self.entered.push("x");
+            }
+        }
+    }
+
+    use left_region::Left;
+    use right_region::Right;
+    use statig::blocking::StateMachine;
+    use statig::prelude::*;
+
+    struct Device;
+
+    // The initial state of every region is the initial state of its own state machine.
+    #[state_machine(
+        initial = "State::running(StateMachine::new(Left::new()), StateMachine::new(Right::new()))"
+    )]
+    impl Device {
+        #[state(parallel)]
+        fn running(
+            left: &mut StateMachine<Left>,
+            right: &mut StateMachine<Right>,
+            event: &Event,
+        ) -> Response<State> {
+            match event {
+                Event::Tick => Handled,
+            }
+        }
+    }
+
+    fn regions(
+        state_machine: &StateMachine<Device>,
+    ) -> (&StateMachine<Left>, &StateMachine<Right>) {
+        match state_machine.state() {
+            State::Running { left, right } => (left, right),
+        }
+    }
+
+    #[test]
+    fn test_init_activates_all_regions() {
+        let mut state_machine = Device.state_machine();
+        state_machine.init();
+
+        let (left, right) = regions(&state_machine);
+        assert_eq!(left.entered, ["a"]);
+        assert_eq!(right.entered, ["x"]);
+
+        state_machine.handle(&Event::Tick);
+
+        let (left, right) = regions(&state_machine);
+        assert_eq!(left.state(), &left_region::State::b());
+        assert_eq!(left.entered, ["a", "b"]);
+        assert_eq!(right.entered, ["x"]);
+    }
+
+    mod aliased {
+        use super::left_region::Left;
+        use super::right_region::Right;
+        use super::Event;
+        use statig::prelude::*;
+
+        // The regions are recognized by their type, so aliases of the state machine types
+        // are initialized like the types themselves.
+        pub type LeftRegion = statig::blocking::StateMachine<Left>;
+        pub type RightRegion = statig::blocking::InitializedStateMachine<Right>;
+
+        pub struct Device;
+
+        #[state_machine(initial = "State::idle()")]
+        impl Device {
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::running(
+                        LeftRegion::new(Left::new()),
+                        Right::new().uninitialized_state_machine().init(),
+                    )),
+                }
+            }
+
+            #[state(parallel)]
+            fn running(
+                left: &mut LeftRegion,
+                right: &mut RightRegion,
+                event: &Event,
+            ) -> Response<State> {
+                match event {
+                    Event::Tick => Handled,
+                }
+            }
+        }
+    }
+
+    mod declared {
+        use super::left_region::{self, Left};
+        use super::right_region::Right;
+        use super::Event;
+        use statig::blocking::{InitializedStateMachine, StateMachine};
+        use statig::prelude::*;
+
+        pub struct Device;
+
+        // The left region starts in `b` instead of the initial state of its own state machine.
+        #[state_machine(initial = "State::idle()", initial(left = "left_region::State::b()"))]
+        impl Device {
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Transition(State::running(
+                        StateMachine::new(Left::new()),
+                        Right::new().uninitialized_state_machine().init(),
+                    )),
+                }
+            }
+
+            #[state(parallel)]
+            fn running(
+                left: &mut StateMachine<Left>,
+                right: &mut InitializedStateMachine<Right>,
+                event: &Event,
+            ) -> Response<State> {
+                match event {
+                    Event::Tick => Handled,
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_regions_start_in_declared_initial_states() {
+        let mut state_machine = declared::Device.state_machine();
+        state_machine.handle(&Event::Tick);
+
+        match state_machine.state() {
+            declared::State::Running { left, right } => {
+                assert_eq!(left.state(), &left_region::State::b());
+                assert_eq!(left.entered, ["b"]);
+                // Regions without a declared initial state are activated as usual.
+                assert_eq!(right.entered, ["x"]);
+            }
+            _ => panic!("expected the parallel state"),
+        }
+    }
+
+    #[test]
+    fn test_aliased_regions_are_activated() {
+        let mut state_machine = aliased::Device.state_machine();
+        state_machine.handle(&Event::Tick);
+
+        match state_machine.state() {
+            aliased::State::Running { left, right } => {
+                assert_eq!(left.entered, ["a"]);
+                // The region that was already initialized is not entered a second time.
+                assert_eq!(right.entered, ["x"]);
+            }
+            _ => panic!("expected the parallel state"),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Lock,
+        Unlock,
+        Press,
+    }
+
+    #[derive(Default)]
+    struct Keypad {
+        presses: usize,
+        blocked: usize,
+    }
+
+    #[state_machine(initial = "State::idle(false)", state(derive(Debug, PartialEq, Eq)))]
+    impl Keypad {
+        #[state(superstate = "guarded", local_storage("locked: bool"))]
+        fn idle(&mut self, event: &Event) -> Response {
+            match event {
+                Event::Press => {
+                    self.presses += 1;
+                    Handled
+                }
+                _ => Super,
+            }
+        }
+
+        #[superstate(pre_dispatch = "check_locked")]
+        fn guarded(locked: &mut bool, event: &Event) -> Response {
+            match event {
+                Event::Lock => {
+                    *locked = true;
+                    Handled
+                }
+                Event::Unlock => {
+                    *locked = false;
+                    Handled
+                }
+                Event::Press => Handled,
+            }
+        }
+
+        #[action]
+        fn check_locked(&mut self, locked: &bool, event: &Event) -> Response {
+            match (locked, event) {
+                (true, Event::Press) => {
+                    self.blocked += 1;
+                    Handled
+                }
+                _ => Super,
+            }
+        }
+    }
+
+    #[test]
+    fn test_pre_dispatch_blocks_child() {
+        let mut state_machine = Keypad::default().state_machine();
+
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.presses, 1);
+
+        state_machine.handle(&Event::Lock);
+        assert_eq!(state_machine.state(), &State::idle(true));
+
+        // The filter keeps the event from reaching the child.
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.presses, 1);
+        assert_eq!(state_machine.blocked, 1);
+
+        state_machine.handle(&Event::Unlock);
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.presses, 2);
+        assert_eq!(state_machine.blocked, 1);
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+    use statig::PreviousStateKind;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Toggle,
+        Check,
+    }
+
+    #[derive(Default)]
+    struct Light {
+        previous: PreviousStateKind<StateKind>,
+        seen: Vec<Option<StateKind>>,
+    }
+
+    #[state_machine(initial = "State::off()", previous_state_kind = "previous")]
+    impl Light {
+        #[state]
+        fn off(&mut self, event: &Event) -> Response {
+            self.seen.push(self.previous.get());
+            match event {
+                Event::Toggle => Transition(State::on()),
+                Event::Check => Handled,
+            }
+        }
+
+        #[state]
+        fn on(&mut self, event: &Event) -> Response {
+            self.seen.push(self.previous.get());
+            match event {
+                Event::Toggle => Transition(State::off()),
+                Event::Check => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_previous_state_kind() {
+        let mut state_machine = Light::default().state_machine();
+
+        state_machine.handle(&Event::Toggle);
+        state_machine.handle(&Event::Check);
+        state_machine.handle(&Event::Check);
+
+        assert_eq!(
+            state_machine.seen,
+            [None, Some(StateKind::Off), Some(StateKind::On)]
+        );
+        assert_eq!(state_machine.previous.get(), Some(StateKind::On));
+    }
+}
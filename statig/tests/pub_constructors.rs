@@ -0,0 +1,63 @@
+mod machine {
+    use statig::prelude::*;
+
+    pub enum Event {
+        Dim,
+    }
+
+    #[derive(Default)]
+    pub struct Lamp;
+
+    #[state_machine(initial = "State::off()", state(derive(PartialEq, Debug)))]
+    impl Lamp {
+        #[state]
+        fn off() -> Response<State> {
+            Handled
+        }
+
+        #[state(superstate = "lit")]
+        fn on(brightness: &mut u8, event: &Event) -> Response<State> {
+            match event {
+                Event::Dim if *brightness > 1 => {
+                    *brightness -= 1;
+                    Handled
+                }
+                Event::Dim => Transition(State::off()),
+            }
+        }
+
+        #[superstate(initial = "State::on(10)")]
+        fn lit() -> Response<State> {
+            Handled
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    use super::machine::{Event, Lamp, State};
+
+    const BRIGHT: State = State::on(2);
+
+    #[test]
+    fn test_construct_state_outside_module() {
+        let mut state_machine = Lamp
+            .uninitialized_state_machine()
+            .with_initial_state(BRIGHT)
+            .init();
+
+        state_machine.handle(&Event::Dim);
+        assert_eq!(state_machine.state(), &State::on(1));
+
+        state_machine.handle(&Event::Dim);
+        assert_eq!(state_machine.state(), &State::off());
+    }
+
+    #[test]
+    fn test_construct_superstate_outside_module() {
+        assert_eq!(State::lit(), State::on(10));
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Start,
+        Pause,
+        Stop,
+        Fail,
+    }
+
+    struct Player;
+
+    #[state_machine(initial = "State::idle()")]
+    impl Player {
+        #[state]
+        fn idle(event: &Event) -> Response {
+            match event {
+                Event::Start => Transition(State::active()),
+                _ => Handled,
+            }
+        }
+
+        #[superstate(initial = "State::playing()")]
+        fn active(event: &Event) -> Response {
+            match event {
+                Event::Stop => Transition(State::idle()),
+                Event::Fail => Transition(Player::failure()),
+                _ => Handled,
+            }
+        }
+
+        #[state(superstate = "active")]
+        fn playing(event: &Event) -> Response {
+            match event {
+                Event::Pause => Transition(State::paused()),
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "active")]
+        fn paused(event: &Event) -> Response {
+            match event {
+                Event::Start => Transition(State::playing()),
+                _ => Super,
+            }
+        }
+
+        /// Only targeted by a transition that is created at runtime.
+        #[state]
+        fn failed(event: &Event) -> Response {
+            match event {
+                Event::Stop => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+
+        /// Never targeted by any transition.
+        #[state]
+        fn orphaned() -> Response {
+            Transition(State::idle())
+        }
+    }
+
+    impl Player {
+        fn failure() -> State {
+            State::failed()
+        }
+    }
+
+    #[test]
+    fn test_reachable_states() {
+        assert_eq!(State::reachable_states(), ["idle", "playing", "paused"]);
+
+        // The states that are left out still exist, they are only not reachable through
+        // transitions that are known at compile time.
+        assert_eq!(Player::failure().kind(), StateKind::Failed);
+        assert_eq!(State::orphaned().kind(), StateKind::Orphaned);
+    }
+}
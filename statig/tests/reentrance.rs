@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+
+    use std::cell::RefCell;
+    use std::rc::{Rc, Weak};
+
+    use statig::blocking::StateMachine;
+    use statig::prelude::*;
+
+    enum Event {
+        Ping,
+    }
+
+    #[derive(Default)]
+    struct Node {
+        peer: Weak<RefCell<StateMachine<Node>>>,
+        forwarded: usize,
+        handled: usize,
+        rejected: usize,
+    }
+
+    #[state_machine(initial = "State::idle()")]
+    impl Node {
+        #[state]
+        fn idle(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Ping => {
+                    self.handled += 1;
+                    if let Some(peer) = self.peer.upgrade() {
+                        match peer.try_borrow_mut() {
+                            Ok(mut peer) => {
+                                self.forwarded += 1;
+                                peer.handle(&Event::Ping);
+                            }
+                            Err(_) => self.rejected += 1,
+                        }
+                    }
+                    Handled
+                }
+            }
+        }
+    }
+
+    fn node(peer: Weak<RefCell<StateMachine<Node>>>) -> Rc<RefCell<StateMachine<Node>>> {
+        Rc::new(RefCell::new(
+            Node {
+                peer,
+                ..Node::default()
+            }
+            .state_machine(),
+        ))
+    }
+
+    #[test]
+    fn test_reentrant_dispatch_is_rejected() {
+        let node = Rc::new_cyclic(|this| {
+            RefCell::new(
+                Node {
+                    peer: this.clone(),
+                    ..Node::default()
+                }
+                .state_machine(),
+            )
+        });
+
+        node.borrow_mut().handle(&Event::Ping);
+
+        let node = node.borrow();
+        assert_eq!(node.handled, 1);
+        assert_eq!(node.forwarded, 0);
+        assert_eq!(node.rejected, 1);
+    }
+
+    #[test]
+    fn test_dispatch_to_other_instance() {
+        let leaf = node(Weak::new());
+        let root = node(Rc::downgrade(&leaf));
+
+        root.borrow_mut().handle(&Event::Ping);
+
+        assert_eq!(root.borrow().forwarded, 1);
+        assert_eq!(root.borrow().rejected, 0);
+        assert_eq!(leaf.borrow().handled, 1);
+    }
+}
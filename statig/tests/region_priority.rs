@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+
+    pub enum Event {
+        Start,
+        Alarm,
+        Disarm,
+        Stop,
+    }
+
+    /// Where the regions would like the parallel state to go next. Only the first request
+    /// is kept, so the region that is dispatched to first decides.
+    #[derive(Default)]
+    pub struct Requests {
+        pub target: Option<&'static str>,
+    }
+
+    mod door_region {
+        use super::{Event, Requests};
+        use statig::prelude::*;
+
+        pub struct Door;
+
+        #[state_machine(initial = "State::closed()")]
+        impl Door {
+            #[state(priority = 1)]
+            fn closed(context: &mut Requests, event: &Event) -> Response<State> {
+                match event {
+                    Event::Alarm => {
+                        context.target.get_or_insert("lockdown");
+                        Handled
+                    }
+                    _ => Handled,
+                }
+            }
+        }
+    }
+
+    mod siren_region {
+        use super::{Event, Requests};
+        use statig::prelude::*;
+
+        pub struct Siren;
+
+        #[state_machine(initial = "State::armed()")]
+        impl Siren {
+            #[state(priority = 2)]
+            fn armed(context: &mut Requests, event: &Event) -> Response<State> {
+                match event {
+                    Event::Alarm => {
+                        context.target.get_or_insert("evacuate");
+                        Handled
+                    }
+                    Event::Disarm => Transition(State::disarmed()),
+                    _ => Handled,
+                }
+            }
+
+            #[state(priority = 1)]
+            fn disarmed(context: &mut Requests, event: &Event) -> Response<State> {
+                match event {
+                    Event::Alarm => {
+                        context.target.get_or_insert("evacuate");
+                        Handled
+                    }
+                    _ => Handled,
+                }
+            }
+        }
+    }
+
+    use door_region::Door;
+    use siren_region::Siren;
+    use statig::blocking::StateMachine;
+    use statig::prelude::*;
+
+    struct Building;
+
+    #[state_machine(initial = "State::idle()")]
+    impl Building {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Transition(State::operating(
+                    Door.state_machine(),
+                    Siren.state_machine(),
+                )),
+                _ => Handled,
+            }
+        }
+
+        #[state(parallel)]
+        fn operating(
+            door: &mut StateMachine<Door>,
+            siren: &mut StateMachine<Siren>,
+            event: &Event,
+        ) -> Response<State> {
+            match event {
+                // Once the siren is disarmed the regions are equally important.
+                Event::Stop if siren.state_priority() == door.state_priority() => {
+                    Transition(State::idle())
+                }
+                _ => Handled,
+            }
+        }
+    }
+
+    fn alarm_target(state_machine: &mut StateMachine<Building>) -> Option<&'static str> {
+        let mut requests = Requests::default();
+        state_machine.handle_with_context(&Event::Alarm, &mut requests);
+        requests.target
+    }
+
+    #[test]
+    fn test_higher_priority_region_is_dispatched_first() {
+        let mut state_machine = Building.state_machine();
+        state_machine.handle_with_context(&Event::Start, &mut Requests::default());
+
+        // The siren is declared after the door, but its current state has a higher priority.
+        assert_eq!(alarm_target(&mut state_machine), Some("evacuate"));
+    }
+
+    #[test]
+    fn test_equal_priorities_are_dispatched_in_declaration_order() {
+        let mut state_machine = Building.state_machine();
+        state_machine.handle_with_context(&Event::Start, &mut Requests::default());
+        state_machine.handle_with_context(&Event::Disarm, &mut Requests::default());
+
+        assert_eq!(alarm_target(&mut state_machine), Some("lockdown"));
+
+        state_machine.handle_with_context(&Event::Stop, &mut Requests::default());
+        assert_eq!(state_machine.state().kind(), StateKind::Idle);
+    }
+
+    #[test]
+    fn test_priority() {
+        assert_eq!(door_region::State::closed().priority(), 1);
+        assert_eq!(siren_region::State::armed().priority(), 2);
+
+        let mut state_machine = Building.state_machine();
+        state_machine.handle_with_context(&Event::Start, &mut Requests::default());
+        assert_eq!(state_machine.state_priority(), 0);
+    }
+}
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+
+    mod blocking {
+        use statig::prelude::*;
+
+        type Response = statig::Response<State>;
+
+        enum Event {
+            Coin,
+            Push,
+        }
+
+        #[derive(Default)]
+        struct Turnstile;
+
+        #[state_machine(initial = "State::locked()", replay, apply_event_log)]
+        impl Turnstile {
+            #[state]
+            fn locked(event: &Event) -> Response {
+                match event {
+                    Event::Coin => Transition(State::unlocked()),
+                    Event::Push => Handled,
+                }
+            }
+
+            #[state]
+            fn unlocked(event: &Event) -> Response {
+                match event {
+                    Event::Coin => Handled,
+                    Event::Push => Transition(State::locked()),
+                }
+            }
+        }
+
+        #[test]
+        fn test_replay() {
+            let events = [Event::Coin, Event::Push, Event::Coin];
+            assert_eq!(Turnstile.replay(&events), StateKind::Unlocked);
+
+            let events = [Event::Coin, Event::Push];
+            assert_eq!(Turnstile.replay(&events), StateKind::Locked);
+
+            assert_eq!(Turnstile.replay(core::iter::empty()), StateKind::Locked);
+        }
+
+        #[test]
+        fn test_apply_event_log() {
+            let log = [
+                (Event::Coin, StateKind::Unlocked),
+                (Event::Coin, StateKind::Unlocked),
+                (Event::Push, StateKind::Locked),
+            ];
+            assert_eq!(Turnstile.apply_event_log(&log), Ok(()));
+
+            let log = [
+                (Event::Coin, StateKind::Unlocked),
+                (Event::Push, StateKind::Unlocked),
+                (Event::Coin, StateKind::Unlocked),
+            ];
+            assert_eq!(Turnstile.apply_event_log(&log), Err((1, StateKind::Locked)));
+
+            assert_eq!(Turnstile.apply_event_log(&[]), Ok(()));
+        }
+    }
+
+    mod context {
+        use statig::prelude::*;
+
+        type Response = statig::Response<State>;
+
+        enum Event {
+            Coin,
+            Push,
+        }
+
+        #[derive(Default)]
+        struct Counter;
+
+        #[state_machine(initial = "State::idle()", replay)]
+        impl Counter {
+            #[state]
+            fn idle(event: &Event, context: &mut usize) -> Response {
+                match event {
+                    Event::Coin => Handled,
+                    Event::Push => {
+                        *context += 1;
+                        Handled
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn test_replay_with_context() {
+            let mut pushes = 0;
+            let events = [Event::Push, Event::Coin, Event::Push];
+            let kind = Counter.replay_with_context(&events, &mut pushes);
+
+            assert_eq!(kind, StateKind::Idle);
+            assert_eq!(pushes, 2);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    mod awaitable {
+        use statig::prelude::*;
+
+        type Response = statig::Response<State>;
+
+        enum Event {
+            Coin,
+            Push,
+        }
+
+        #[derive(Default)]
+        struct Turnstile;
+
+        #[state_machine(initial = "State::locked()", replay, apply_event_log)]
+        impl Turnstile {
+            #[state]
+            async fn locked(event: &Event) -> Response {
+                match event {
+                    Event::Coin => Transition(State::unlocked()),
+                    Event::Push => Handled,
+                }
+            }
+
+            #[state]
+            async fn unlocked(event: &Event) -> Response {
+                match event {
+                    Event::Coin => Handled,
+                    Event::Push => Transition(State::locked()),
+                }
+            }
+        }
+
+        #[test]
+        fn test_replay() {
+            let future = async move {
+                let events = [Event::Coin, Event::Push, Event::Coin];
+                assert_eq!(Turnstile.replay(&events).await, StateKind::Unlocked);
+            };
+
+            futures::executor::block_on(future);
+        }
+
+        #[test]
+        fn test_apply_event_log() {
+            let future = async move {
+                let log = [
+                    (Event::Coin, StateKind::Unlocked),
+                    (Event::Coin, StateKind::Locked),
+                ];
+                assert_eq!(
+                    Turnstile.apply_event_log(&log).await,
+                    Err((1, StateKind::Unlocked))
+                );
+            };
+
+            futures::executor::block_on(future);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Machine {
+        path: Vec<&'static str>,
+    }
+
+    #[state_machine(initial = "State::a()", state(derive(Eq, PartialEq, Debug)))]
+    impl Machine {
+        #[state(superstate = "left", entry_action = "enter_a", exit_action = "exit_a")]
+        fn a(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::b()),
+            }
+        }
+
+        #[state(superstate = "left", entry_action = "enter_b", exit_action = "exit_b")]
+        fn b(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::c(0)),
+            }
+        }
+
+        #[superstate(entry_action = "enter_left", exit_action = "exit_left")]
+        fn left(event: &Event) -> Response {
+            match event {
+                Event::Next => Handled,
+            }
+        }
+
+        #[state(superstate = "right", entry_action = "enter_c", exit_action = "exit_c")]
+        fn c(counter: &mut usize, event: &Event) -> Response {
+            match event {
+                Event::Next => {
+                    *counter += 1;
+                    Transition(State::a())
+                }
+            }
+        }
+
+        #[superstate(entry_action = "enter_right", exit_action = "exit_right")]
+        fn right(event: &Event) -> Response {
+            match event {
+                Event::Next => Handled,
+            }
+        }
+
+        #[action]
+        fn enter_a(&mut self) {
+            self.path.push("enter a");
+        }
+
+        #[action]
+        fn exit_a(&mut self) {
+            self.path.push("exit a");
+        }
+
+        #[action]
+        fn enter_b(&mut self) {
+            self.path.push("enter b");
+        }
+
+        #[action]
+        fn exit_b(&mut self) {
+            self.path.push("exit b");
+        }
+
+        #[action]
+        fn enter_left(&mut self) {
+            self.path.push("enter left");
+        }
+
+        #[action]
+        fn exit_left(&mut self) {
+            self.path.push("exit left");
+        }
+
+        #[action]
+        fn enter_c(&mut self) {
+            self.path.push("enter c");
+        }
+
+        #[action]
+        fn exit_c(&mut self) {
+            self.path.push("exit c");
+        }
+
+        #[action]
+        fn enter_right(&mut self) {
+            self.path.push("enter right");
+        }
+
+        #[action]
+        fn exit_right(&mut self) {
+            self.path.push("exit right");
+        }
+    }
+
+    #[test]
+    fn test_try_from_state_kind() {
+        assert_eq!(State::try_from(StateKind::A), Ok(State::a()));
+        assert_eq!(State::try_from(StateKind::B), Ok(State::b()));
+        assert_eq!(State::try_from(StateKind::C), Err(StateKind::C));
+    }
+
+    #[test]
+    fn test_reset_to_other_superstate() {
+        let mut state_machine = Machine::default().state_machine();
+
+        state_machine.handle(&Event::Next);
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::c(0));
+        let start = state_machine.path.len();
+
+        assert_eq!(state_machine.reset_to(StateKind::A), Ok(()));
+        assert_eq!(state_machine.state(), &State::a());
+        assert_eq!(
+            state_machine.path[start..],
+            ["exit c", "exit right", "enter left", "enter a"]
+        );
+    }
+
+    #[test]
+    fn test_reset_to_same_superstate() {
+        let mut state_machine = Machine::default().state_machine();
+        state_machine.init();
+        let start = state_machine.path.len();
+
+        assert_eq!(state_machine.reset_to(StateKind::B), Ok(()));
+        assert_eq!(state_machine.state(), &State::b());
+        assert_eq!(state_machine.path[start..], ["exit a", "enter b"]);
+    }
+
+    #[test]
+    fn test_reset_to_state_with_local_storage() {
+        let mut state_machine = Machine::default().state_machine();
+        state_machine.init();
+        let start = state_machine.path.len();
+
+        assert_eq!(state_machine.reset_to(StateKind::C), Err(StateKind::C));
+        assert_eq!(state_machine.state(), &State::a());
+        assert_eq!(state_machine.path.len(), start);
+    }
+
+    #[test]
+    fn test_reset_to_uninitialized() {
+        let mut state_machine = Machine::default().state_machine();
+
+        assert_eq!(state_machine.reset_to(StateKind::B), Ok(()));
+        assert_eq!(state_machine.state(), &State::b());
+        assert_eq!(
+            state_machine.path,
+            ["enter left", "enter a", "exit a", "enter b"]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+
+    mod machine {
+        use statig::prelude::*;
+
+        pub enum Event {
+            Toggle,
+            Ignore,
+            Other,
+        }
+
+        #[derive(Default)]
+        pub struct Switch;
+
+        #[state_machine(
+            initial = "State::off()",
+            response_identifier = "Response",
+            state(derive(Debug, PartialEq, Eq))
+        )]
+        impl Switch {
+            #[state(superstate = "powered")]
+            fn off(event: &Event) -> Response {
+                match event {
+                    Event::Toggle => Transition(State::on()),
+                    Event::Ignore => Handled,
+                    Event::Other => Super,
+                }
+            }
+
+            #[state(superstate = "powered")]
+            fn on(event: &Event) -> Response {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                    Event::Ignore => Handled,
+                    Event::Other => Super,
+                }
+            }
+
+            #[superstate]
+            fn powered() -> Response {
+                Handled
+            }
+        }
+    }
+
+    use machine::{Event, Response, State, Switch};
+    use statig::prelude::*;
+
+    #[test]
+    fn test_construct_response_externally() {
+        let responses: [Response; 3] = [
+            Response::Handled,
+            Response::Super,
+            Response::Transition(State::on()),
+        ];
+
+        assert!(matches!(responses[0], statig::Response::Handled));
+        assert!(matches!(responses[1], statig::Response::Super));
+        assert!(
+            matches!(&responses[2], statig::Response::Transition(state) if state == &State::on())
+        );
+    }
+
+    #[test]
+    fn test_handle_with_response_alias() {
+        let mut state_machine = Switch.state_machine();
+
+        state_machine.handle(&Event::Ignore);
+        assert_eq!(state_machine.state(), &State::off());
+
+        state_machine.handle(&Event::Other);
+        assert_eq!(state_machine.state(), &State::off());
+
+        state_machine.handle(&Event::Toggle);
+        assert_eq!(state_machine.state(), &State::on());
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Mutex;
+
+    use statig::blocking::StateMachine;
+    use statig::prelude::*;
+
+    enum Event {
+        Record(u32),
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        values: Mutex<Vec<u32>>,
+    }
+
+    #[state_machine(initial = "State::recording(0)", send_sync)]
+    impl Recorder {
+        #[state]
+        fn recording(&mut self, total: &mut u32, event: &Event) -> Response<State> {
+            match event {
+                Event::Record(value) => {
+                    *total += value;
+                    self.values.get_mut().unwrap().push(*value);
+                    Handled
+                }
+            }
+        }
+    }
+
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn test_send_sync_state_machine() {
+        let mut state_machine: StateMachine<Recorder> = Recorder::default().state_machine();
+        state_machine.handle(&Event::Record(3));
+        assert_send_sync(&state_machine);
+
+        let state_machine = std::thread::spawn(move || {
+            state_machine.handle(&Event::Record(4));
+            state_machine
+        })
+        .join()
+        .unwrap();
+        assert_eq!(*state_machine.values.lock().unwrap(), [3, 4]);
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Ping,
+    }
+
+    #[derive(Default)]
+    struct Pinger {
+        sequence: u64,
+        seen: Vec<u64>,
+    }
+
+    #[state_machine(initial = "State::waiting()", sequence = "sequence")]
+    impl Pinger {
+        #[state(superstate = "online")]
+        fn waiting(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Ping => {
+                    self.seen.push(self.sequence);
+                    Super
+                }
+            }
+        }
+
+        #[superstate]
+        fn online(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Ping => {
+                    // The sequence number is the same for every handler of a dispatch.
+                    self.seen.push(self.sequence);
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sequence_increments_per_dispatch() {
+        let mut state_machine = Pinger::default().state_machine();
+
+        state_machine.handle(&Event::Ping);
+        state_machine.handle(&Event::Ping);
+        state_machine.handle(&Event::Ping);
+
+        assert_eq!(state_machine.sequence, 3);
+        assert_eq!(state_machine.seen, [1, 1, 2, 2, 3, 3]);
+    }
+
+    #[test]
+    fn test_sequence_wraps_around() {
+        let mut state_machine = Pinger {
+            sequence: u64::MAX,
+            seen: Vec::new(),
+        }
+        .state_machine();
+
+        state_machine.handle(&Event::Ping);
+        assert_eq!(state_machine.sequence, 0);
+    }
+}
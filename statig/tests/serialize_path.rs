@@ -0,0 +1,83 @@
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+    }
+
+    struct Player;
+
+    #[state_machine(
+        initial = "State::playing()",
+        serialize_path,
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Player {
+        #[state(superstate = "running")]
+        fn playing(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::paused()),
+            }
+        }
+
+        #[state(superstate = "active")]
+        fn paused(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::stopped(0)),
+            }
+        }
+
+        #[superstate(superstate = "active")]
+        fn running() -> Response {
+            Super
+        }
+
+        #[superstate]
+        fn active() -> Response {
+            Super
+        }
+
+        #[state]
+        fn stopped(count: &mut u32, event: &Event) -> Response {
+            match event {
+                Event::Next => {
+                    *count += 1;
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for state in [State::playing(), State::paused()] {
+            let bytes = state.serialize_path();
+            assert_eq!(State::restore_path(&bytes), Ok(state));
+        }
+    }
+
+    #[test]
+    fn test_path_bytes() {
+        // The kinds are indexed by the sorted names of the states and superstates.
+        assert_eq!(State::playing().serialize_path(), [1, 1, 0]);
+        assert_eq!(State::paused().serialize_path(), [0, 0]);
+        assert_eq!(State::stopped(3).serialize_path(), [2]);
+    }
+
+    #[test]
+    fn test_restore_state_with_local_storage() {
+        let bytes = State::stopped(3).serialize_path();
+        assert_eq!(State::restore_path(&bytes), Err(Some(StateKind::Stopped)));
+    }
+
+    #[test]
+    fn test_restore_invalid_path() {
+        assert_eq!(State::restore_path(&[]), Err(None));
+        assert_eq!(State::restore_path(&[0, 1]), Err(None));
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Tick,
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        ticks: usize,
+        log: Vec<usize>,
+        label: &'static str,
+    }
+
+    #[state_machine(initial = "State::counting()")]
+    impl Counter {
+        #[state(shared_storage_fields("ticks", "log", "label"))]
+        fn counting(
+            ticks: &mut usize,
+            log: &mut Vec<usize>,
+            label: &&'static str,
+            event: &Event,
+        ) -> Response {
+            match event {
+                Event::Tick => {
+                    *ticks += 1;
+                    log.push(*ticks);
+                    assert_eq!(*label, "counter");
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_disjoint_shared_storage_fields() {
+        let counter = Counter {
+            label: "counter",
+            ..Default::default()
+        };
+        let mut state_machine = counter.state_machine();
+
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Tick);
+
+        assert_eq!(state_machine.ticks, 2);
+        assert_eq!(state_machine.log, [1, 2]);
+    }
+}
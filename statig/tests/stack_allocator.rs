@@ -0,0 +1,76 @@
+#[cfg(test)]
+#[cfg(all(feature = "allocator-api2", feature = "state_stack"))]
+mod tests {
+
+    use core::alloc::Layout;
+    use core::ptr::NonNull;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use statig::allocator_api2::alloc::{AllocError, Allocator, Global};
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    /// Allocator that counts its allocations and delegates them to the global allocator.
+    struct CountingAllocator {
+        allocations: AtomicUsize,
+    }
+
+    unsafe impl Allocator for CountingAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.allocations.fetch_add(1, Ordering::SeqCst);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    static ALLOCATOR: CountingAllocator = CountingAllocator {
+        allocations: AtomicUsize::new(0),
+    };
+
+    enum Event {
+        Open,
+        Close,
+    }
+
+    struct Ui;
+
+    #[state_machine(
+        initial = "State::main()",
+        stack_allocator = "ALLOCATOR",
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl Ui {
+        #[state]
+        fn main(event: &Event) -> Response {
+            match event {
+                Event::Open => Push(State::dialog()),
+                Event::Close => Handled,
+            }
+        }
+
+        #[state]
+        fn dialog(event: &Event) -> Response {
+            match event {
+                Event::Open => Handled,
+                Event::Close => Pop,
+            }
+        }
+    }
+
+    #[test]
+    fn test_state_stack_uses_allocator() {
+        let mut state_machine = Ui.uninitialized_state_machine().init();
+        assert_eq!(ALLOCATOR.allocations.load(Ordering::SeqCst), 0);
+
+        state_machine.handle(&Event::Open);
+        assert_eq!(state_machine.state(), &State::dialog());
+        assert_eq!(ALLOCATOR.allocations.load(Ordering::SeqCst), 1);
+
+        state_machine.handle(&Event::Close);
+        assert_eq!(state_machine.state(), &State::main());
+    }
+}
@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+
+    mod handled {
+        use statig::prelude::*;
+
+        type Response = statig::Response<State>;
+
+        enum Event {
+            Start,
+            Stop,
+        }
+
+        #[derive(Default)]
+        struct Engine {
+            log: Vec<&'static str>,
+        }
+
+        #[state_machine(
+            initial = "State::idle()",
+            startup_event = "Event::Start",
+            state(derive(Debug, PartialEq, Eq))
+        )]
+        impl Engine {
+            #[state]
+            fn idle(&mut self, event: &Event) -> Response {
+                match event {
+                    Event::Start => {
+                        self.log.push("start");
+                        Transition(State::running())
+                    }
+                    Event::Stop => Handled,
+                }
+            }
+
+            #[state]
+            fn running(&mut self, event: &Event) -> Response {
+                match event {
+                    Event::Start => Handled,
+                    Event::Stop => {
+                        self.log.push("stop");
+                        Transition(State::idle())
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn test_startup_event() {
+            let mut state_machine = Engine::default().state_machine();
+            state_machine.init();
+
+            assert_eq!(state_machine.log, ["start"]);
+            assert_eq!(state_machine.state(), &State::running());
+
+            // The startup event is only handled once.
+            state_machine.handle(&Event::Stop);
+            assert_eq!(state_machine.log, ["start", "stop"]);
+            assert_eq!(state_machine.state(), &State::idle());
+        }
+    }
+
+    mod unhandled {
+        use statig::prelude::*;
+
+        type Response = statig::Response<State>;
+
+        enum Event {
+            Start,
+            Stop,
+        }
+
+        #[derive(Default)]
+        struct Engine {
+            log: Vec<&'static str>,
+        }
+
+        #[state_machine(
+            initial = "State::idle()",
+            startup_event = "Event::Start",
+            state(derive(Debug, PartialEq, Eq))
+        )]
+        impl Engine {
+            #[state(superstate = "powered")]
+            fn idle(event: &Event) -> Response {
+                match event {
+                    Event::Start => Super,
+                    Event::Stop => Handled,
+                }
+            }
+
+            #[superstate]
+            fn powered(&mut self, event: &Event) -> Response {
+                match event {
+                    Event::Start => {
+                        self.log.push("powered");
+                        Super
+                    }
+                    Event::Stop => Handled,
+                }
+            }
+        }
+
+        #[test]
+        fn test_unhandled_startup_event() {
+            let mut state_machine = Engine::default().state_machine();
+            state_machine.handle(&Event::Stop);
+
+            assert_eq!(state_machine.log, ["powered"]);
+            assert_eq!(state_machine.state(), &State::idle());
+        }
+    }
+}
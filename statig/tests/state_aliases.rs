@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+    }
+
+    struct Lamp;
+
+    #[state_machine(initial = "State::led_off()", state(derive(Eq, PartialEq, Debug)))]
+    impl Lamp {
+        #[state(alias = "off", alias = "dark")]
+        fn led_off(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::led_on()),
+            }
+        }
+
+        #[state(alias = "on")]
+        fn led_on(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::led_off()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(StateKind::from_name("led_on"), Some(StateKind::LedOn));
+        assert_eq!(StateKind::from_name("on"), Some(StateKind::LedOn));
+        assert_eq!(StateKind::from_name("dark"), Some(StateKind::LedOff));
+        assert_eq!(StateKind::from_name("ON"), None);
+        assert_eq!(StateKind::from_name("dim"), None);
+    }
+
+    #[test]
+    fn test_from_name_ignore_case() {
+        assert_eq!(
+            StateKind::from_name_ignore_case("ON"),
+            Some(StateKind::LedOn)
+        );
+        assert_eq!(
+            StateKind::from_name_ignore_case("Led_Off"),
+            Some(StateKind::LedOff)
+        );
+        assert_eq!(StateKind::from_name_ignore_case("dim"), None);
+    }
+
+    #[test]
+    fn test_initial_state_from_config() {
+        let kind = StateKind::from_name("on").unwrap();
+        let mut state_machine = Lamp.state_machine();
+
+        assert_eq!(state_machine.reset_to(kind), Ok(()));
+        assert_eq!(state_machine.state(), &State::led_on());
+    }
+}
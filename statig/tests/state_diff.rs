@@ -0,0 +1,68 @@
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Tick,
+    }
+
+    struct Counter;
+
+    #[state_machine(initial = "State::idle()", diff)]
+    impl Counter {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Tick => Transition(State::counting(0, "tick")),
+            }
+        }
+
+        #[state]
+        fn counting(count: &mut u32, label: &mut &'static str, event: &Event) -> Response<State> {
+            match event {
+                Event::Tick => {
+                    *count += 1;
+                    *label = "tock";
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff_same_variant() {
+        let before = State::counting(1, "tick");
+        let after = State::counting(2, "tick");
+
+        assert_eq!(before.diff(&after), [("count", String::from("2"))]);
+        assert!(before.diff(&before).is_empty());
+        assert!(State::idle().diff(&State::idle()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_different_variant() {
+        let before = State::idle();
+        let after = State::counting(0, "tick");
+
+        assert_eq!(before.diff(&after), [("variant", String::from("Counting"))]);
+    }
+
+    #[test]
+    fn test_diff_after_dispatch() {
+        let mut state_machine = Counter.state_machine();
+        state_machine.handle(&Event::Tick);
+        let before = state_machine.state().diff(&State::counting(0, "tick"));
+        assert!(before.is_empty());
+
+        state_machine.handle(&Event::Tick);
+        assert_eq!(
+            State::counting(0, "tick").diff(state_machine.state()),
+            [
+                ("count", String::from("1")),
+                ("label", String::from("\"tock\""))
+            ]
+        );
+    }
+}
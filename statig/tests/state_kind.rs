@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Traffic;
+
+    #[state_machine(initial = "State::red()", state_index)]
+    impl Traffic {
+        #[state]
+        fn red(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::green(0)),
+            }
+        }
+
+        #[state]
+        fn green(passed: &mut usize, event: &Event) -> Response {
+            match event {
+                Event::Next => {
+                    *passed += 1;
+                    Transition(State::amber())
+                }
+            }
+        }
+
+        #[state]
+        fn amber(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::red()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_state_kind() {
+        assert_eq!(State::green(3).kind(), StateKind::Green);
+        assert_eq!(State::red().kind(), StateKind::Red);
+    }
+
+    #[test]
+    fn test_state_machine_state_kind() {
+        let mut state_machine = Traffic.state_machine();
+        state_machine.init();
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Red);
+
+        state_machine.handle(&Event::Next);
+        let kind: StateKind = state_machine.state_kind();
+        assert_eq!(kind, StateKind::Green);
+    }
+
+    #[test]
+    fn test_state_index() {
+        // Indices follow the sorted names of the states.
+        assert_eq!(u32::from(&State::amber()), 0);
+        assert_eq!(u32::from(&State::green(3)), 1);
+        assert_eq!(u32::from(&State::red()), 2);
+    }
+
+    #[test]
+    fn test_state_kind_ordering() {
+        // Kinds are ordered by the sorted names of the states.
+        let mut kinds = vec![StateKind::Red, StateKind::Amber, StateKind::Green];
+        kinds.sort();
+        assert_eq!(kinds, [StateKind::Amber, StateKind::Green, StateKind::Red]);
+    }
+
+    mod renamed {
+        use statig::prelude::*;
+
+        /// A type of the user that would clash with the default name of the kind enum.
+        #[allow(dead_code)]
+        pub struct StateKind;
+
+        pub struct Lamp;
+
+        #[state_machine(
+            initial = "State::on()",
+            state(kind = "LampMode"),
+            superstate(kind = "LampGroup")
+        )]
+        impl Lamp {
+            #[state(superstate = "powered")]
+            fn on() -> Response<State> {
+                Super
+            }
+
+            #[superstate]
+            fn powered() -> Response<State> {
+                Handled
+            }
+        }
+
+        #[test]
+        fn test_renamed_kinds() {
+            assert_eq!(State::on().kind(), LampMode::On);
+            assert_eq!(State::on().ancestors().next(), Some(LampGroup::Powered));
+        }
+    }
+}
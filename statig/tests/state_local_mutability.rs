@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Increment,
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        observed: Vec<(isize, isize)>,
+    }
+
+    #[state_machine(initial = "State::counting(0, 2)", state(derive(Debug, PartialEq, Eq)))]
+    impl Counter {
+        #[state(superstate = "active", entry_action = "enter_counting")]
+        fn counting(count: &mut isize, step: &isize, event: &Event) -> Response {
+            match event {
+                Event::Increment => {
+                    *count += *step;
+                    Super
+                }
+            }
+        }
+
+        #[action]
+        fn enter_counting(step: &mut isize) {
+            *step += 1;
+        }
+
+        #[superstate]
+        fn active(&mut self, count: &isize, step: &isize, event: &Event) -> Response {
+            match event {
+                Event::Increment => {
+                    self.observed.push((*count, *step));
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed_state_local_mutability() {
+        let mut state_machine = Counter::default().state_machine();
+
+        state_machine.handle(&Event::Increment);
+        state_machine.handle(&Event::Increment);
+
+        assert_eq!(state_machine.state(), &State::counting(6, 3));
+        assert_eq!(state_machine.observed, [(3, 3), (6, 3)]);
+    }
+}
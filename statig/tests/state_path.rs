@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Foo;
+
+    #[state_machine(initial = "State::s111()")]
+    impl Foo {
+        #[state(superstate = "s11")]
+        fn s111(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::s2()),
+            }
+        }
+
+        #[superstate(superstate = "s1")]
+        fn s11() -> Response {
+            Super
+        }
+
+        #[superstate]
+        fn s1() -> Response {
+            Super
+        }
+
+        #[state]
+        fn s2(event: &Event) -> Response {
+            match event {
+                Event::Next => Transition(State::s111()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_state_path() {
+        let mut state_machine = Foo::default().state_machine();
+
+        assert_eq!(state_machine.state().state_path(), ["s1", "s11", "s111"]);
+
+        state_machine.handle(&Event::Next);
+
+        assert_eq!(state_machine.state().state_path(), ["s2"]);
+    }
+
+    #[test]
+    fn test_handler_name() {
+        let mut state_machine = Foo::default().state_machine();
+
+        // The variant is `S111`, while the handler keeps its original name.
+        assert_eq!(state_machine.state().kind(), StateKind::S111);
+        assert_eq!(state_machine.state().handler_name(), "s111");
+
+        state_machine.handle(&Event::Next);
+
+        assert_eq!(state_machine.state().handler_name(), "s2");
+    }
+
+    #[test]
+    fn test_name() {
+        let mut state = State::s111();
+        assert_eq!(state.name(), "S111");
+
+        let superstate = statig::blocking::State::superstate(&mut state).unwrap();
+        assert_eq!(superstate.name(), "S11");
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let mut state_machine = Foo::default().state_machine();
+
+        let ancestors: Vec<SuperstateKind> = state_machine.state().ancestors().collect();
+        assert_eq!(ancestors, [SuperstateKind::S11, SuperstateKind::S1]);
+
+        state_machine.handle(&Event::Next);
+
+        assert_eq!(state_machine.state().ancestors().next(), None);
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+
+    use core::mem::{align_of, size_of};
+
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+    }
+
+    struct Lamp;
+
+    #[state_machine(initial = "State::off()", state(repr = "C, u8"))]
+    impl Lamp {
+        #[state]
+        fn on(brightness: &mut u32, event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+
+        #[state]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::on(100)),
+            }
+        }
+    }
+
+    fn discriminant(state: &State) -> u8 {
+        // With a primitive representation the discriminant is stored first.
+        unsafe { *(state as *const State).cast::<u8>() }
+    }
+
+    #[test]
+    fn test_state_repr_layout() {
+        // A `u8` tag followed by a union that holds a `u32`.
+        assert_eq!(size_of::<State>(), 8);
+        assert_eq!(align_of::<State>(), 4);
+    }
+
+    #[test]
+    fn test_state_repr_discriminants() {
+        assert_eq!(discriminant(&State::off()), StateKind::Off as u8);
+        assert_eq!(discriminant(&State::on(5)), StateKind::On as u8);
+
+        let mut state_machine = Lamp.state_machine();
+        state_machine.handle(&Event::Toggle);
+        assert_eq!(discriminant(state_machine.state()), 1);
+    }
+}
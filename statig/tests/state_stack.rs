@@ -0,0 +1,169 @@
+#[cfg(test)]
+#[cfg(feature = "state_stack")]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        OpenMenu,
+        OpenDialog,
+        Close,
+    }
+
+    #[derive(Default)]
+    struct Ui {
+        path: Vec<&'static str>,
+    }
+
+    #[state_machine(initial = "State::main()", state(derive(Eq, PartialEq, Debug)))]
+    impl Ui {
+        #[state(entry_action = "enter_main", exit_action = "exit_main")]
+        fn main(event: &Event) -> Response {
+            match event {
+                Event::OpenMenu => Push(State::menu()),
+                Event::Close => Pop,
+                _ => Handled,
+            }
+        }
+
+        #[state(entry_action = "enter_menu", exit_action = "exit_menu")]
+        fn menu(event: &Event) -> Response {
+            match event {
+                Event::OpenDialog => Push(State::dialog()),
+                Event::Close => Pop,
+                _ => Handled,
+            }
+        }
+
+        #[state(entry_action = "enter_dialog", exit_action = "exit_dialog")]
+        fn dialog(event: &Event) -> Response {
+            match event {
+                Event::Close => Pop,
+                _ => Handled,
+            }
+        }
+
+        #[action]
+        fn enter_main(&mut self) {
+            self.path.push("enter main");
+        }
+
+        #[action]
+        fn exit_main(&mut self) {
+            self.path.push("exit main");
+        }
+
+        #[action]
+        fn enter_menu(&mut self) {
+            self.path.push("enter menu");
+        }
+
+        #[action]
+        fn exit_menu(&mut self) {
+            self.path.push("exit menu");
+        }
+
+        #[action]
+        fn enter_dialog(&mut self) {
+            self.path.push("enter dialog");
+        }
+
+        #[action]
+        fn exit_dialog(&mut self) {
+            self.path.push("exit dialog");
+        }
+    }
+
+    #[test]
+    fn test_push_and_pop() {
+        let mut state_machine = Ui::default().state_machine();
+
+        state_machine.handle(&Event::OpenMenu);
+        state_machine.handle(&Event::OpenDialog);
+        assert_eq!(state_machine.state(), &State::dialog());
+
+        state_machine.handle(&Event::Close);
+        assert_eq!(state_machine.state(), &State::menu());
+
+        state_machine.handle(&Event::Close);
+        assert_eq!(state_machine.state(), &State::main());
+
+        assert_eq!(
+            state_machine.path,
+            [
+                "enter main",
+                "exit main",
+                "enter menu",
+                "exit menu",
+                "enter dialog",
+                "exit dialog",
+                "enter menu",
+                "exit menu",
+                "enter main",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pop_on_empty_stack() {
+        let mut state_machine = Ui::default().state_machine();
+        state_machine.init();
+        let start = state_machine.path.len();
+
+        state_machine.handle(&Event::Close);
+        assert_eq!(state_machine.state(), &State::main());
+        assert_eq!(state_machine.path.len(), start);
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "state_stack", feature = "serde"))]
+mod serialized_stack {
+
+    use serde::{Deserialize, Serialize};
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+        Back,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Wizard;
+
+    #[state_machine(
+        initial = "State::step(0)",
+        state(derive(Debug, Serialize, Deserialize, PartialEq))
+    )]
+    impl Wizard {
+        #[state]
+        fn step(index: &mut usize, event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Push(State::step(*index + 1)),
+                Event::Back => Pop,
+            }
+        }
+    }
+
+    #[test]
+    fn test_stack_is_serialized() {
+        let mut state_machine = Wizard.uninitialized_state_machine().init();
+        state_machine.handle(&Event::Next);
+        state_machine.handle(&Event::Next);
+
+        let ser = serde_json::to_string(&state_machine).unwrap();
+        let de: statig::blocking::UninitializedStateMachine<Wizard> =
+            serde_json::from_str(&ser).unwrap();
+        let mut de = de.init();
+        assert_eq!(de, state_machine);
+
+        de.handle(&Event::Back);
+        assert_eq!(de.state(), &State::step(1));
+        assert_ne!(de, state_machine);
+
+        de.handle(&Event::Back);
+        assert_eq!(de.state(), &State::step(0));
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Play,
+        Stop,
+    }
+
+    #[derive(Default)]
+    struct Player {
+        stops: usize,
+    }
+
+    #[state_machine(initial = "State::paused()")]
+    impl Player {
+        /// Ignores the event, so everything is deferred to the superstate.
+        #[state(superstate = "active")]
+        fn paused() -> Response {
+            Super
+        }
+
+        #[state(superstate = "active")]
+        fn playing(event: &Event) -> Response {
+            match event {
+                Event::Play => Handled,
+                Event::Stop => Super,
+            }
+        }
+
+        #[superstate]
+        fn active(&mut self, event: &Event) -> Response {
+            match event {
+                Event::Play => Transition(State::playing()),
+                Event::Stop => {
+                    self.stops += 1;
+                    Transition(State::paused())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_is_handled_by_superstate() {
+        let mut state_machine = Player::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Play);
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Playing);
+
+        state_machine.handle(&Event::Play);
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Playing);
+
+        state_machine.handle(&Event::Stop);
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Paused);
+
+        state_machine.handle(&Event::Stop);
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Paused);
+        assert_eq!(state_machine.stops, 2);
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Mutex;
+
+    use statig::blocking::{StateMachine, UninitializedStateMachine};
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Toggle,
+    }
+
+    struct Blinky {
+        toggles: usize,
+    }
+
+    const MACHINE: StateMachine<Blinky> = StateMachine::new(Blinky { toggles: 0 });
+
+    const UNINIT: UninitializedStateMachine<Blinky> =
+        UninitializedStateMachine::new(Blinky { toggles: 0 });
+
+    static MACHINES: Mutex<[StateMachine<Blinky>; 4]> = Mutex::new([MACHINE; 4]);
+
+    static UNINIT_MACHINES: [UninitializedStateMachine<Blinky>; 2] = [UNINIT; 2];
+
+    #[state_machine(initial = "State::off()", state(derive(Eq, PartialEq, Debug)))]
+    impl Blinky {
+        #[state(entry_action = "count")]
+        fn off(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::on()),
+            }
+        }
+
+        #[state(entry_action = "count")]
+        fn on(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+
+        #[action]
+        fn count(&mut self) {
+            self.toggles += 1;
+        }
+    }
+
+    #[test]
+    fn test_static_array_of_machines() {
+        let mut machines = MACHINES.lock().unwrap();
+
+        machines[1].handle(&Event::Toggle);
+        machines[3].handle(&Event::Toggle);
+        machines[3].handle(&Event::Toggle);
+
+        assert_eq!(machines[0].state(), &State::off());
+        assert_eq!(machines[1].state(), &State::on());
+        assert_eq!(machines[2].state(), &State::off());
+        assert_eq!(machines[3].state(), &State::off());
+
+        // Entering the initial state on the lazy initialization counts as well.
+        assert_eq!(machines[0].toggles, 0);
+        assert_eq!(machines[1].toggles, 2);
+        assert_eq!(machines[3].toggles, 3);
+    }
+
+    #[test]
+    fn test_static_array_of_uninitialized_machines() {
+        assert!(UNINIT_MACHINES.iter().all(|machine| machine.toggles == 0));
+    }
+}
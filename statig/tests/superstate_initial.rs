@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Start,
+        Pause,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Player {
+        entered: Vec<&'static str>,
+    }
+
+    #[state_machine(
+        initial = "State::idle()",
+        event_identifier = "event",
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl Player {
+        #[state]
+        fn idle(event: &Event) -> Response {
+            match event {
+                // Transition to the superstate, which resolves to its initial state.
+                Event::Start => Transition(State::active()),
+                _ => Handled,
+            }
+        }
+
+        #[superstate(initial = "State::playing()")]
+        fn active(event: &Event) -> Response {
+            match event {
+                Event::Reset => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+
+        #[superstate(superstate = "active", initial = "State::playing()")]
+        fn running(event: &Event) -> Response {
+            match event {
+                Event::Pause => Transition(State::paused()),
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "running", entry_action = "enter_playing")]
+        fn playing(event: &Event) -> Response {
+            match event {
+                Event::Start => Handled,
+                _ => Super,
+            }
+        }
+
+        #[action]
+        fn enter_playing(&mut self) {
+            self.entered.push("playing");
+        }
+
+        #[state(superstate = "active")]
+        fn paused(event: &Event) -> Response {
+            match event {
+                Event::Start => Transition(State::running()),
+                _ => Super,
+            }
+        }
+    }
+
+    #[test]
+    fn test_transition_to_superstate_initial_state() {
+        let mut state_machine = Player::default().state_machine();
+
+        state_machine.handle(&Event::Start);
+        assert_eq!(state_machine.state(), &State::playing());
+
+        state_machine.handle(&Event::Pause);
+        assert_eq!(state_machine.state(), &State::paused());
+
+        // Nested superstate resolves to its own initial state.
+        state_machine.handle(&Event::Start);
+        assert_eq!(state_machine.state(), &State::playing());
+
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.state(), &State::idle());
+
+        assert_eq!(state_machine.entered, ["playing", "playing"]);
+    }
+
+    #[test]
+    fn test_superstate_initial_state_constructor() {
+        assert_eq!(State::active(), State::playing());
+        assert_eq!(State::running(), State::playing());
+    }
+}
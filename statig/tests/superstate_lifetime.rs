@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Step,
+    }
+
+    // The lifetime has the same name as the one of the superstate enum.
+    struct Reader<'sub> {
+        input: &'sub [u8],
+        read: usize,
+    }
+
+    #[state_machine(
+        initial = "State::reading(0)",
+        lifetime = "'sub",
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl<'sub> Reader<'sub> {
+        #[state(superstate = "active")]
+        fn reading(&mut self, position: &mut usize, event: &Event) -> Response<State<'sub>> {
+            match event {
+                Event::Step if *position < self.input.len() => {
+                    *position += 1;
+                    Super
+                }
+                Event::Step => Transition(State::done()),
+            }
+        }
+
+        #[superstate]
+        fn active(&mut self, position: &usize) -> Response<State<'sub>> {
+            self.read = *position;
+            Handled
+        }
+
+        #[state]
+        fn done() -> Response<State<'sub>> {
+            Handled
+        }
+    }
+
+    #[test]
+    fn test_superstate_lifetime_is_renamed() {
+        let buffer = [1, 2];
+        let mut state_machine = Reader {
+            input: &buffer,
+            read: 0,
+        }
+        .state_machine();
+
+        state_machine.handle(&Event::Step);
+        state_machine.handle(&Event::Step);
+        assert_eq!(state_machine.read, 2);
+
+        state_machine.handle(&Event::Step);
+        assert_eq!(state_machine.state(), &State::done());
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Enter,
+        Tick,
+        Swap,
+        Leave,
+    }
+
+    struct Counter;
+
+    #[state_machine(initial = "State::idle()", state(derive(Eq, PartialEq, Debug)))]
+    impl Counter {
+        #[state]
+        fn idle(event: &Event) -> Response {
+            match event {
+                Event::Enter => Transition(State::a()),
+                _ => Handled,
+            }
+        }
+
+        #[state(superstate = "counting")]
+        fn a(event: &Event) -> Response {
+            match event {
+                Event::Swap => Transition(State::b()),
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "counting")]
+        fn b(event: &Event) -> Response {
+            match event {
+                Event::Swap => Transition(State::a()),
+                _ => Super,
+            }
+        }
+
+        #[superstate(owned_storage("ticks: usize = 0"))]
+        fn counting(ticks: &mut usize, event: &Event) -> Response {
+            match event {
+                Event::Tick => {
+                    *ticks += 1;
+                    Handled
+                }
+                Event::Leave => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_owned_storage_persists_across_child_transitions() {
+        let mut state_machine = Counter.state_machine();
+
+        state_machine.handle(&Event::Enter);
+        assert_eq!(state_machine.state(), &State::A { ticks: 0 });
+
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Swap);
+        assert_eq!(state_machine.state(), &State::B { ticks: 1 });
+
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Swap);
+        state_machine.handle(&Event::Tick);
+        assert_eq!(state_machine.state(), &State::A { ticks: 3 });
+    }
+
+    #[test]
+    fn test_owned_storage_is_cleared_when_leaving_superstate() {
+        let mut state_machine = Counter.state_machine();
+
+        state_machine.handle(&Event::Enter);
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Leave);
+        assert_eq!(state_machine.state(), &State::idle());
+
+        state_machine.handle(&Event::Enter);
+        assert_eq!(state_machine.state(), &State::A { ticks: 0 });
+    }
+}
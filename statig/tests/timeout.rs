@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+    use statig::Timeout;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Connect,
+        Retry,
+        TimedOut,
+    }
+
+    #[derive(Default)]
+    struct Link {
+        now: u64,
+        timeout: Timeout<StateKind, u64>,
+    }
+
+    #[state_machine(
+        initial = "State::idle()",
+        timeout = "timeout",
+        deref_storage,
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl Link {
+        #[state]
+        fn idle(&mut self, event: &Event) -> Response {
+            match event {
+                Event::Connect => {
+                    // Armed for `idle`, so it is disarmed again on the transition.
+                    self.timeout.arm(self.now + 100);
+                    Transition(State::connecting())
+                }
+                _ => Handled,
+            }
+        }
+
+        #[state(entry_action = "enter_connecting")]
+        fn connecting(event: &Event) -> Response {
+            match event {
+                Event::Retry => Transition(State::connecting()),
+                Event::TimedOut => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+
+        #[action]
+        fn enter_connecting(&mut self) {
+            self.timeout.arm(self.now + 5);
+        }
+    }
+
+    #[test]
+    fn test_timeout_fires_after_deadline() {
+        let mut state_machine = Link::default().state_machine();
+        state_machine.handle(&Event::Connect);
+        assert_eq!(
+            state_machine.timeout.armed(),
+            Some((StateKind::Connecting, &5))
+        );
+
+        assert_eq!(state_machine.timeout.expired(&4), None);
+        assert_eq!(
+            state_machine.timeout.expired(&5),
+            Some(StateKind::Connecting)
+        );
+    }
+
+    #[test]
+    fn test_timeout_disarmed_on_exit() {
+        let mut state_machine = Link::default().state_machine();
+        state_machine.handle(&Event::Connect);
+        state_machine.handle(&Event::TimedOut);
+
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.timeout.armed(), None);
+        assert_eq!(state_machine.timeout.expired(&10), None);
+    }
+
+    #[test]
+    fn test_timeout_rearmed_on_reentry() {
+        let mut state_machine = Link::default().state_machine();
+        state_machine.handle(&Event::Connect);
+
+        state_machine.now = 3;
+        state_machine.handle(&Event::Retry);
+        assert_eq!(
+            state_machine.timeout.armed(),
+            Some((StateKind::Connecting, &8))
+        );
+        assert_eq!(state_machine.timeout.expired(&5), None);
+    }
+
+    #[test]
+    fn test_poll_calls_back_once() {
+        let mut state_machine = Link::default().state_machine();
+        state_machine.handle(&Event::Connect);
+
+        let mut fired = Vec::new();
+        for now in [4, 5, 6] {
+            state_machine.timeout.poll(&now, |kind| fired.push(kind));
+        }
+        assert_eq!(fired, [StateKind::Connecting]);
+
+        state_machine.handle(&Event::TimedOut);
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+#[cfg(feature = "tracing")]
+mod tests {
+
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event as TracingEvent, Metadata, Subscriber};
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    #[derive(Debug)]
+    enum Event {
+        TimerElapsed,
+        ButtonPressed,
+    }
+
+    #[derive(Default)]
+    struct Blinky;
+
+    #[state_machine(initial = "State::led_on()", tracing, state(derive(Debug)))]
+    impl Blinky {
+        #[state(superstate = "blinking")]
+        fn led_on(event: &Event) -> Response {
+            match event {
+                Event::TimerElapsed => Transition(State::led_off()),
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "blinking")]
+        fn led_off(event: &Event) -> Response {
+            match event {
+                Event::TimerElapsed => Transition(State::led_on()),
+                _ => Super,
+            }
+        }
+
+        #[superstate]
+        fn blinking(event: &Event) -> Response {
+            match event {
+                Event::ButtonPressed => Handled,
+                _ => Super,
+            }
+        }
+    }
+
+    /// Subscriber that records the names of the spans that are entered.
+    #[derive(Default)]
+    struct Recorder {
+        next_id: AtomicU64,
+        names: Mutex<Vec<&'static str>>,
+        entered: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            self.names.lock().unwrap().push(span.metadata().name());
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &TracingEvent<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            let name = self.names.lock().unwrap()[span.into_u64() as usize - 1];
+            self.entered.lock().unwrap().push(name);
+        }
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn test_span_per_dispatch() {
+        let recorder = Recorder::default();
+        let entered = recorder.entered.clone();
+
+        tracing::subscriber::with_default(recorder, || {
+            let mut state_machine = Blinky.state_machine();
+            state_machine.handle(&Event::TimerElapsed);
+            state_machine.handle(&Event::ButtonPressed);
+        });
+
+        assert_eq!(
+            entered.lock().unwrap()[..],
+            ["led_on", "led_off", "blinking"]
+        );
+    }
+}
@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Connect,
+        Drop,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Client {
+        calls: Vec<&'static str>,
+    }
+
+    #[state_machine(
+        initial = "State::disconnected()",
+        on_transition = "Self::on_transition"
+    )]
+    #[transition(from = "connecting", to = "connected", call = "on_connected")]
+    #[transition(from = "connected", to = "disconnected", call = "on_reset")]
+    impl Client {
+        #[state]
+        fn disconnected(event: &Event) -> Response {
+            match event {
+                Event::Connect => Transition(State::connecting()),
+                _ => Handled,
+            }
+        }
+
+        #[state(superstate = "online")]
+        fn connecting(event: &Event) -> Response {
+            match event {
+                Event::Connect => Transition(State::connected()),
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "online")]
+        fn connected(event: &Event) -> Response {
+            match event {
+                Event::Drop => Transition(State::connecting()),
+                _ => Super,
+            }
+        }
+
+        #[superstate]
+        fn online(event: &Event) -> Response {
+            match event {
+                Event::Reset => Transition(State::disconnected()),
+                _ => Handled,
+            }
+        }
+    }
+
+    impl Client {
+        fn on_transition(&mut self, _source: &State, _target: &State) {
+            self.calls.push("transition");
+        }
+
+        fn on_connected(&mut self) {
+            self.calls.push("connected");
+        }
+
+        fn on_reset(&mut self) {
+            self.calls.push("reset");
+        }
+    }
+
+    #[test]
+    fn test_hook_fires_only_for_its_transition() {
+        let mut state_machine = Client::default().state_machine();
+
+        state_machine.handle(&Event::Connect);
+        state_machine.handle(&Event::Connect);
+        state_machine.handle(&Event::Drop);
+        state_machine.handle(&Event::Reset);
+
+        assert_eq!(
+            state_machine.calls,
+            [
+                "transition",
+                "transition",
+                "connected",
+                "transition",
+                "transition",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hook_fires_for_transition_of_superstate() {
+        let mut state_machine = Client::default().state_machine();
+
+        state_machine.handle(&Event::Connect);
+        state_machine.handle(&Event::Connect);
+        state_machine.handle(&Event::Reset);
+
+        assert_eq!(
+            state_machine.calls,
+            [
+                "transition",
+                "transition",
+                "connected",
+                "transition",
+                "reset"
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Switch {
+        toggles: usize,
+        exited_off: bool,
+    }
+
+    #[state_machine(
+        initial = "State::off()",
+        transition_to,
+        state(derive(Eq, PartialEq, Debug))
+    )]
+    impl Switch {
+        #[state(exit_action = "exit_off")]
+        fn off(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => {
+                    let response = self.transition_to(State::on());
+                    // The transition is deferred until the handler returns.
+                    assert!(!self.exited_off);
+                    self.toggles += 1;
+                    response
+                }
+            }
+        }
+
+        #[state]
+        fn on(&self, event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => self.transition_to(State::off()),
+            }
+        }
+
+        #[action]
+        fn exit_off(&mut self) {
+            self.exited_off = true;
+        }
+    }
+
+    #[test]
+    fn test_transition_to() {
+        let mut state_machine = Switch::default().state_machine();
+
+        state_machine.handle(&Event::Toggle);
+        assert_eq!(state_machine.state(), &State::on());
+        assert_eq!(state_machine.toggles, 1);
+        assert!(state_machine.exited_off);
+
+        state_machine.handle(&Event::Toggle);
+        assert_eq!(state_machine.state(), &State::off());
+    }
+
+    #[test]
+    fn test_transition_to_response() {
+        let switch = Switch::default();
+
+        assert!(matches!(
+            switch.transition_to(State::on()),
+            Transition(State::On {})
+        ));
+    }
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Packet,
+        Timeout,
+    }
+
+    #[derive(Default)]
+    struct Link;
+
+    #[state_machine(initial = "State::idle()")]
+    impl Link {
+        #[state]
+        #[transition(to = "receiving", weight = 0.9)]
+        #[transition(to = "error", weight = 0.05)]
+        fn idle(event: &Event) -> Response {
+            match event {
+                Event::Packet => Transition(State::receiving()),
+                Event::Timeout => Transition(State::error()),
+            }
+        }
+
+        #[state]
+        #[transition(to = "idle", weight = 0.99)]
+        #[transition(to = "error", weight = 0.01)]
+        fn receiving(event: &Event) -> Response {
+            match event {
+                Event::Packet => Transition(State::idle()),
+                Event::Timeout => Transition(State::error()),
+            }
+        }
+
+        #[state]
+        fn error() -> Response {
+            Handled
+        }
+    }
+
+    #[test]
+    fn test_transition_weights() {
+        assert_eq!(
+            Link::transition_weights(),
+            [
+                ("idle", "receiving", 0.9),
+                ("idle", "error", 0.05),
+                ("receiving", "idle", 0.99),
+                ("receiving", "error", 0.01),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_weights_are_not_normalized() {
+        let idle: f64 = Link::transition_weights()
+            .iter()
+            .filter(|(from, _, _)| *from == "idle")
+            .map(|(_, _, weight)| weight)
+            .sum();
+
+        assert!((idle - 0.95).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weights_do_not_change_transitions() {
+        let mut state_machine = Link.state_machine();
+        state_machine.handle(&Event::Timeout);
+
+        assert_eq!(state_machine.state_kind::<StateKind>(), StateKind::Error);
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Connect,
+        Connected,
+        Fail,
+        Reset,
+    }
+
+    struct Link;
+
+    #[state_machine(initial = "State::idle()")]
+    impl Link {
+        #[state]
+        fn idle(event: &Event) -> Response {
+            match event {
+                Event::Connect => Transition(State::connecting()),
+                _ => Handled,
+            }
+        }
+
+        #[state(superstate = "online")]
+        fn connecting(event: &Event) -> Response {
+            match event {
+                Event::Connected => Transition(State::connected()),
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "online")]
+        fn connected(event: &Event) -> Response {
+            match event {
+                Event::Reset => Transition(State::idle()),
+                _ => Super,
+            }
+        }
+
+        #[superstate]
+        fn online(event: &Event) -> Response {
+            match event {
+                Event::Fail => Transition(State::error()),
+                _ => Handled,
+            }
+        }
+
+        #[state]
+        fn error(event: &Event) -> Response {
+            match event {
+                Event::Reset => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn test_transitions_into() {
+        // Transitions of the superstate count for the states nested within it.
+        assert_eq!(
+            StateKind::Error.transitions_into(),
+            ["connected", "connecting"]
+        );
+        assert_eq!(StateKind::Connected.transitions_into(), ["connecting"]);
+        assert_eq!(StateKind::Connecting.transitions_into(), ["idle"]);
+    }
+
+    #[test]
+    fn test_transitions_into_initial_state() {
+        assert_eq!(
+            StateKind::Idle.transitions_into(),
+            ["<start>", "connected", "error"]
+        );
+    }
+
+    mod dynamic {
+        use statig::prelude::*;
+
+        type Response = statig::Response<State>;
+
+        pub struct Jumper;
+
+        #[state_machine(initial = "State::idle()")]
+        impl Jumper {
+            #[state]
+            fn idle() -> Response {
+                Transition(State::running())
+            }
+
+            #[state]
+            fn running(event: &bool) -> Response {
+                let target = if *event {
+                    State::stopped()
+                } else {
+                    State::idle()
+                };
+                Transition(target)
+            }
+
+            #[state]
+            fn stopped() -> Response {
+                Handled
+            }
+        }
+    }
+
+    #[test]
+    fn test_transitions_into_from_dynamic_source() {
+        use dynamic::StateKind;
+
+        assert_eq!(StateKind::Stopped.transitions_into(), ["<dynamic>"]);
+        assert_eq!(StateKind::Running.transitions_into(), ["idle", "<dynamic>"]);
+        assert_eq!(StateKind::Idle.transitions_into(), ["<start>", "<dynamic>"]);
+    }
+}
@@ -0,0 +1,10 @@
+#[cfg(test)]
+mod tests {
+
+    /// Check the diagnostics that the `state_machine` macro emits for invalid state machines.
+    #[test]
+    fn test_ui() {
+        let t = trybuild::TestCases::new();
+        t.compile_fail("tests/ui/*.rs");
+    }
+}
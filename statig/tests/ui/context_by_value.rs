@@ -0,0 +1,23 @@
+use statig::prelude::*;
+
+enum Event {
+    Toggle,
+}
+
+struct Context;
+
+struct Blinky;
+
+#[state_machine(initial = "State::on()")]
+impl Blinky {
+    #[state]
+    fn on(event: &Event, context: Context) -> Response<State> {
+        match event {
+            Event::Toggle => Handled,
+        }
+    }
+}
+
+fn main() {
+    let _ = (Blinky, Event::Toggle, Context);
+}
@@ -0,0 +1,24 @@
+use statig::prelude::*;
+
+enum Event {
+    Toggle,
+}
+
+struct Blinky;
+
+#[state_machine(initial = "State::on()")]
+impl Blinky {
+    #[state(entry_actions(enter_on, enter_missing))]
+    fn on(event: &Event) -> Response<State> {
+        match event {
+            Event::Toggle => Handled,
+        }
+    }
+
+    #[action]
+    fn enter_on() {}
+}
+
+fn main() {
+    let _ = (Blinky, Event::Toggle);
+}
@@ -0,0 +1,21 @@
+use statig::prelude::*;
+
+enum Event {
+    Toggle,
+}
+
+struct Blinky;
+
+#[state_machine(initial = "State::on()")]
+impl Blinky {
+    #[state]
+    fn on(event: Event) -> Response<State> {
+        match event {
+            Event::Toggle => Handled,
+        }
+    }
+}
+
+fn main() {
+    let _ = (Blinky, Event::Toggle);
+}
@@ -0,0 +1,24 @@
+use statig::prelude::*;
+
+enum Event {
+    Toggle,
+}
+
+struct Blinky;
+
+#[state_machine(initial = "State::on()")]
+impl Blinky {
+    #[state(exit_action = "exit_on", exit_action = "exit_missing")]
+    fn on(event: &Event) -> Response<State> {
+        match event {
+            Event::Toggle => Handled,
+        }
+    }
+
+    #[action]
+    fn exit_on() {}
+}
+
+fn main() {
+    let _ = (Blinky, Event::Toggle);
+}
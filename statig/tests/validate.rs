@@ -0,0 +1,42 @@
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Blinky;
+
+    #[state_machine(initial = "State::on()", validate)]
+    impl Blinky {
+        #[state(superstate = "blinking")]
+        fn on(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+
+        #[state(superstate = "blinking")]
+        fn off(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::on()),
+            }
+        }
+
+        #[superstate(initial = "State::off()")]
+        fn blinking() -> Response {
+            Super
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(Blinky::validate(), Ok(()));
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::prelude::*;
+
+    type Response = statig::Response<State>;
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Lamp {
+        entered: Vec<&'static str>,
+    }
+
+    #[state_machine(initial = "State::off()", state(derive(PartialEq, Debug)))]
+    impl Lamp {
+        #[state(entry_action = "enter_off")]
+        fn off(event: &Event) -> Response {
+            match event {
+                Event::Toggle => Transition(State::on(1)),
+            }
+        }
+
+        #[state(entry_action = "enter_on")]
+        fn on(brightness: &mut u8, event: &Event) -> Response {
+            match event {
+                Event::Toggle if *brightness < 3 => {
+                    *brightness += 1;
+                    Handled
+                }
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+
+        #[action]
+        fn enter_off(&mut self) {
+            self.entered.push("off");
+        }
+
+        #[action]
+        fn enter_on(&mut self) {
+            self.entered.push("on");
+        }
+    }
+
+    #[test]
+    fn test_default_initial_state() {
+        let state_machine = Lamp::default().uninitialized_state_machine().init();
+
+        assert_eq!(state_machine.state(), &State::off());
+        assert_eq!(state_machine.entered, ["off"]);
+    }
+
+    #[test]
+    fn test_overridden_initial_state() {
+        let mut state_machine = Lamp::default()
+            .uninitialized_state_machine()
+            .with_initial_state(State::on(3))
+            .init();
+
+        assert_eq!(state_machine.state(), &State::on(3));
+        assert_eq!(state_machine.entered, ["on"]);
+
+        state_machine.handle(&Event::Toggle);
+
+        assert_eq!(state_machine.state(), &State::off());
+        assert_eq!(state_machine.entered, ["on", "off"]);
+    }
+}
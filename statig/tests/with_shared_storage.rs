@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+
+    use statig::blocking::UninitializedStateMachine;
+    use statig::prelude::*;
+
+    enum Event {
+        Drain,
+    }
+
+    struct Queue {
+        items: Vec<u32>,
+        drained: Vec<u32>,
+    }
+
+    #[state_machine(initial = "State::filled()", state(derive(Eq, PartialEq, Debug)))]
+    impl Queue {
+        #[state]
+        fn filled(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Drain => {
+                    self.drained.append(&mut self.items);
+                    Transition(State::empty())
+                }
+            }
+        }
+
+        #[state]
+        fn empty(event: &Event) -> Response<State> {
+            match event {
+                Event::Drain => Handled,
+            }
+        }
+    }
+
+    mod unit {
+        use statig::prelude::*;
+
+        pub struct Toggle;
+
+        #[state_machine(initial = "State::off()", state(derive(Eq, PartialEq, Debug)))]
+        impl Toggle {
+            #[state]
+            fn off() -> Response<State> {
+                Transition(State::on())
+            }
+
+            #[state]
+            fn on() -> Response<State> {
+                Transition(State::off())
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_shared_storage() {
+        let queue = Queue {
+            items: vec![1, 2, 3],
+            drained: Vec::new(),
+        };
+        let mut state_machine = UninitializedStateMachine::new(queue).init();
+        assert_eq!(state_machine.state(), &State::filled());
+
+        state_machine.handle(&Event::Drain);
+        assert_eq!(state_machine.state(), &State::empty());
+        assert!(state_machine.items.is_empty());
+        assert_eq!(state_machine.drained, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_unit_shared_storage() {
+        let mut state_machine = UninitializedStateMachine::new(unit::Toggle).init();
+        assert_eq!(state_machine.state(), &unit::State::off());
+
+        state_machine.step();
+        assert_eq!(state_machine.state(), &unit::State::on());
+    }
+}